@@ -99,7 +99,7 @@ impl CpalBackend {
         };
         let channel_count = self.channel_count;
         let data_callback = move |buffer: &mut [i16], _info: &cpal::OutputCallbackInfo| {
-            assert!(buffer.len() % channel_count as usize == 0);
+            assert!(buffer.len().is_multiple_of(channel_count as usize));
             renderer.on_start_of_batch();
 
             buffer.fill_with(|| {
@@ -12,8 +12,10 @@ mod sound;
 mod tests;
 
 pub use error::Error;
+pub use sound::CostHint;
 pub use sound::NextSample;
 pub use sound::Sound;
+pub use sound::UnderrunStats;
 
 /// Start outputting audio with the default backend, device, and configs.
 ///
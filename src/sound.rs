@@ -6,8 +6,8 @@ use std::{
 use crate::{
     sounds::{
         wrappers::{
-            AdjustableSpeed, AdjustableVolume, Controllable, Controller, FinishAfter, Pausable,
-            SetPaused,
+            AdjustableSpeed, AdjustableVolume, Agc, Controllable, Controller, FadeCurve, FadeIn,
+            FinishAfter, Pausable, Prebuffer, RealtimeThrottle, SetPaused, SkipSilence, TailFade,
         },
         MemorySound,
     },
@@ -47,6 +47,18 @@ pub trait Sound: Send {
     /// and `SoundList`).
     fn next_sample(&mut self) -> Result<NextSample, crate::Error>;
 
+    /// Whether the next call to `next_sample` will return
+    /// `MetadataChanged`, so buffers for the new format can be prepared
+    /// before any of its samples are pulled. The channel count and sample
+    /// rate keep describing the old format until then.
+    ///
+    /// Answering requires looking ahead one value so it is only implemented
+    /// by [Peekable][crate::sounds::wrappers::Peekable]. Wrap a sound in it
+    /// to use this. Other sounds return `None` since they can not tell.
+    fn next_is_metadata_change(&mut self) -> Option<bool> {
+        None
+    }
+
     /// Called whenever a new batch of audio samples is requested by the
     /// backend.
     ///
@@ -91,6 +103,30 @@ pub trait Sound: Send {
         Ok(())
     }
 
+    /// Fill `out` with interleaved samples as f32 where full scale is
+    /// `-1.0..1.0`, for backends that use float samples.
+    ///
+    /// Returns the number of samples written. Fewer than `out.len()` are
+    /// written if the sound returns something other than a sample, which is
+    /// consumed. If its channel count or sample rate differ afterward the
+    /// metadata changed and the samples written were in the previous format.
+    /// Otherwise the sound is paused or finished.
+    ///
+    /// The default converts each sample from `next_sample`.
+    /// [SymphoniaDecoder][crate::sounds::decoders::SymphoniaDecoder] copies
+    /// decoded float samples directly so they are not quantized to i16.
+    fn next_samples_f32(&mut self, out: &mut [f32]) -> Result<usize, crate::Error> {
+        for (written, out_sample) in out.iter_mut().enumerate() {
+            match self.next_sample()? {
+                NextSample::Sample(s) => *out_sample = s as f32 / 32768.0,
+                NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
+                    return Ok(written)
+                }
+            }
+        }
+        Ok(out.len())
+    }
+
     /// Read the entire sound into memory. MemorySound can be cloned for
     /// efficient reuse. See [MemorySound::from_sound].
     fn into_memory_sound(self) -> Result<MemorySound, crate::Error>
@@ -153,6 +189,19 @@ pub trait Sound: Send {
         crate::sounds::wrappers::CompletionNotifier::new(self)
     }
 
+    /// Call `callback` once when this sound has Finished.
+    ///
+    /// See [OnFinish][crate::sounds::wrappers::OnFinish].
+    fn on_finished(
+        self,
+        callback: impl FnOnce() + Send + 'static,
+    ) -> crate::sounds::wrappers::OnFinish<Self>
+    where
+        Self: Sized,
+    {
+        crate::sounds::wrappers::OnFinish::new(self, callback)
+    }
+
     /// Allow the volume of the sound to be adjustable with `set_volume`.
     fn with_adjustable_volume(self) -> AdjustableVolume<Self>
     where
@@ -170,6 +219,17 @@ pub trait Sound: Send {
         AdjustableVolume::new_with_volume(self, volume_adjustment)
     }
 
+    /// Continuously adjust the volume so the short-term RMS level stays near
+    /// `target_rms` (a fraction of full scale).
+    ///
+    /// See [Agc].
+    fn with_auto_gain_control(self, target_rms: f32) -> Agc<Self>
+    where
+        Self: Sized,
+    {
+        Agc::new(self, target_rms)
+    }
+
     /// Allow the speed of the sound to be adjustable with `set_speed`.
     ///
     /// This adjusts both speed and pitch.
@@ -199,6 +259,17 @@ pub trait Sound: Send {
         Pausable::new(self)
     }
 
+    /// Allow for the sound to be pausable with `set_paused`, fading out over
+    /// `fade_duration` when paused and back in when resumed. Starts unpaused.
+    ///
+    /// See [Pausable::with_fade].
+    fn pausable_with_fade(self, fade_duration: Duration) -> Pausable<Self>
+    where
+        Self: Sized,
+    {
+        Pausable::with_fade(self, fade_duration)
+    }
+
     /// Allow for the sound to be pausable with `set_paused`. Starts paused.
     fn paused(self) -> Pausable<Self>
     where
@@ -220,6 +291,69 @@ pub trait Sound: Send {
         FinishAfter::new(self, duration)
     }
 
+    /// Fade in from silence over `duration` using a linear curve.
+    ///
+    /// See [FadeIn].
+    fn fade_in(self, duration: Duration) -> FadeIn<Self>
+    where
+        Self: Sized,
+    {
+        FadeIn::new(self, duration, FadeCurve::Linear)
+    }
+
+    /// Fade in from silence over `duration` using the gain shape of `curve`.
+    ///
+    /// See [FadeIn].
+    fn fade_in_with_curve(self, duration: Duration, curve: FadeCurve) -> FadeIn<Self>
+    where
+        Self: Sized,
+    {
+        FadeIn::new(self, duration, curve)
+    }
+
+    /// Fade out the last `duration` of this sound using a linear curve so it
+    /// ends without a click.
+    ///
+    /// See [TailFade].
+    fn with_tail_fade(self, duration: Duration) -> TailFade<Self>
+    where
+        Self: Sized,
+    {
+        TailFade::new(self, duration, FadeCurve::Linear)
+    }
+
+    /// Buffer `duration` of this sound before playback starts so a slow
+    /// first decode does not cause an underrun.
+    ///
+    /// See [Prebuffer].
+    fn prebuffered(self, duration: Duration) -> Prebuffer<Self>
+    where
+        Self: Sized,
+    {
+        Prebuffer::new(self, duration)
+    }
+
+    /// Skip silent regions lasting longer than `min_silence`.
+    ///
+    /// See [SkipSilence].
+    fn skip_silence(self, min_silence: Duration) -> SkipSilence<Self>
+    where
+        Self: Sized,
+    {
+        SkipSilence::new(self, min_silence)
+    }
+
+    /// Sleep in `next_sample` as needed so samples are not pulled faster than
+    /// real time.
+    ///
+    /// See [RealtimeThrottle].
+    fn throttled_to_realtime(self) -> RealtimeThrottle<Self>
+    where
+        Self: Sized,
+    {
+        RealtimeThrottle::new(self)
+    }
+
     /// Skip the next `duration` of samples.
     ///
     /// This is done by calling next_sample repeatedly.
@@ -264,13 +398,179 @@ pub trait Sound: Send {
     }
 
     /// seek to specified time in the audio stream
-    /// only implemented for [SymphoniaDecoder]
-    fn seek(&mut self, _seek_to: Duration) -> Result<Duration, symphonia_core::errors::Error> {
+    /// only implemented for [SymphoniaDecoder], [MultiFileSound] and
+    /// [MemorySound]
+    fn seek(&mut self, _seek_to: Duration) -> Result<Duration, crate::Error> {
         println!("default seek");
         Ok(Duration::from_secs(0))
     }
 
-    fn set_sample_mult(&mut self, mult: f32) {
+    /// The precision of [seek][Sound::seek]. A seek may land up to this much
+    /// before the requested position.
+    ///
+    /// `None` if unknown or seeking is not supported.
+    fn seek_granularity(&self) -> Option<Duration> {
+        None
+    }
+
+    /// The number of frames the codec encodes together (e.g. the block size
+    /// of FLAC), so joins between sounds played back to back can be placed
+    /// on a boundary to avoid artifacts. See
+    /// [SoundList::add_trimmed][crate::sounds::SoundList::add_trimmed].
+    ///
+    /// Uncompressed sources return `Some(1)`. `None` if unknown. Only
+    /// implemented for
+    /// [SymphoniaDecoder][crate::sounds::decoders::SymphoniaDecoder],
+    /// [WavDecoder][crate::sounds::decoders::WavDecoder],
+    /// [RawPcmDecoder][crate::sounds::decoders::RawPcmDecoder] and
+    /// [MemorySound].
+    fn frame_alignment(&self) -> Option<u64> {
+        None
+    }
+
+    /// The number of complete frames (one sample for every channel) returned
+    /// since the start of the stream, for logging and exact assertions in
+    /// tests.
+    ///
+    /// A frame is only counted once its last channel has been returned. A
+    /// seek or loop moves the index to the frame played next. Unlike a
+    /// container timestamp this always counts the frames actually returned.
+    ///
+    /// Only implemented for
+    /// [SymphoniaDecoder][crate::sounds::decoders::SymphoniaDecoder] and
+    /// [MemorySound]. Defaults to 0.
+    fn emitted_frame_index(&self) -> u64 {
+        0
+    }
+
+    /// How far through the sound playback is, from 0.0 at the start to 1.0
+    /// at the end, e.g. for drawing a seek bar.
+    ///
+    /// This is the current position divided by the total duration so it does
+    /// not depend on the sample rate. `None` if the total duration is unknown
+    /// or the source can not seek. Only implemented for
+    /// [SymphoniaDecoder][crate::sounds::decoders::SymphoniaDecoder] and
+    /// [MemorySound].
+    fn progress(&self) -> Option<f32> {
+        None
+    }
+
+    /// The exact number of samples (counting every channel) left to be
+    /// returned before `Finished`, e.g. to start a fade out so it ends with
+    /// the sound.
+    ///
+    /// Includes an end set with [set_end][Sound::set_end] and stays correct
+    /// after a seek. `None` if the length is unknown or the sound is looping.
+    /// Only implemented for
+    /// [SymphoniaDecoder][crate::sounds::decoders::SymphoniaDecoder] when the
+    /// track reports its number of frames,
+    /// [WavDecoder][crate::sounds::decoders::WavDecoder] and [MemorySound].
+    fn samples_remaining(&self) -> Option<u64> {
+        None
+    }
+
+    /// Codec specific configuration (e.g. setup headers) needed to decode the
+    /// encoded stream, for passing to another decoder or remuxing.
+    ///
+    /// `None` if the source has none or does not expose it. Only implemented
+    /// for [SymphoniaDecoder][crate::sounds::decoders::SymphoniaDecoder].
+    fn codec_extra_data(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Instead of finishing, start back at the beginning and continue. Can be
+    /// changed while playing.
+    ///
+    /// This is cheaper than rebuilding the sound with a looping wrapper but is
+    /// only implemented for seekable sources:
+    /// [SymphoniaDecoder][crate::sounds::decoders::SymphoniaDecoder] and
+    /// [MemorySound]. Does nothing for other sounds.
+    fn set_looping(&mut self, _enabled: bool) {}
+
+    /// Whether [set_looping][Sound::set_looping] has enabled looping.
+    fn is_looping(&self) -> bool {
+        false
+    }
+
+    /// Finish once playback reaches `at`, measured from the start of the
+    /// sound, instead of at the end of the data. `None` plays to the end.
+    /// Can be changed while playing.
+    ///
+    /// If the position is already past `at` the sound finishes at the next
+    /// frame. A looping sound starts back at the beginning at `at` instead of
+    /// finishing. Only implemented for
+    /// [SymphoniaDecoder][crate::sounds::decoders::SymphoniaDecoder] and
+    /// [MemorySound]. Does nothing for other sounds.
+    fn set_end(&mut self, _at: Option<Duration>) {}
+
+    /// The end point set with [set_end][Sound::set_end].
+    fn end(&self) -> Option<Duration> {
+        None
+    }
+
+    /// How much audio has been received or decoded ahead and is ready to
+    /// play, e.g. to show a buffering indicator when it runs low.
+    ///
+    /// Only implemented for [QueueSound][crate::sounds::QueueSound],
+    /// [SharedMemorySound][crate::sounds::SharedMemorySound] and
+    /// [Prebuffer][crate::sounds::wrappers::Prebuffer]. Other sounds return
+    /// zero.
+    fn buffered_duration(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// How often and for how long silence has been played because audio was
+    /// not received in time, e.g. to flag a problematic session in
+    /// telemetry.
+    ///
+    /// Only implemented for [QueueSound][crate::sounds::QueueSound],
+    /// [SharedMemorySound][crate::sounds::SharedMemorySound] and
+    /// [AsyncResampler][crate::sounds::wrappers::AsyncResampler]. Other
+    /// sounds return empty stats.
+    fn underrun_stats(&self) -> UnderrunStats {
+        UnderrunStats::default()
+    }
+
+    /// A coarse estimate of the processing needed to produce each second of
+    /// audio, e.g. for a scheduler to limit how many sounds play at once.
+    ///
+    /// Wrappers that add significant work such as
+    /// [SincSampleRateConverter][crate::sounds::wrappers::SincSampleRateConverter]
+    /// and [ConvolutionReverb][crate::sounds::wrappers::ConvolutionReverb]
+    /// return the higher of their own cost and the cost of the sound they
+    /// wrap. Every wrapper in [crate::sounds::wrappers] returns at least the
+    /// cost of the sound it wraps so the cost is composed up the wrapper
+    /// chain. Defaults to [CostHint::Low] for sounds that do not give an
+    /// estimate.
+    fn estimated_cost(&self) -> CostHint {
+        CostHint::Low
+    }
+
+    /// Whether `next_sample` may block, e.g. by reading from disk or calling
+    /// into a codec library, so real-time code can decide to pull the sound
+    /// on a worker thread instead.
+    ///
+    /// Defaults to `true` since it is not known. Sources that only read from
+    /// memory or generate samples such as [MemorySound] return `false`.
+    /// Wrappers return the default regardless of the sound they contain.
+    fn may_block(&self) -> bool {
+        true
+    }
+
+    /// A new independent instance of this sound starting from the beginning,
+    /// for sources that can be duplicated cheaply such as sounds stored in
+    /// memory and generators. This allows a bank of `Box<dyn Sound>`
+    /// templates to be played any number of times simultaneously without
+    /// reopening files.
+    ///
+    /// Defaults to `None`. Streaming decoders and wrappers return `None`.
+    fn try_clone_boxed(&self) -> Option<Box<dyn Sound>> {
+        None
+    }
+
+    /// Set a multiplier applied to every decoded sample.
+    /// only implemented for [SymphoniaDecoder]
+    fn set_sample_mult(&mut self, _mult: f32) {
         println!("Unimplemented set_sample_mult");
     }
 }
@@ -296,6 +596,37 @@ pub enum NextSample {
     Finished,
 }
 
+/// Silence played because audio was not available in time. See
+/// [Sound::underrun_stats].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct UnderrunStats {
+    /// The number of times playback ran out of audio. Consecutive silent
+    /// samples count as a single underrun.
+    pub underruns: u64,
+    /// The total number of silent samples played.
+    pub silent_samples: u64,
+    /// How long the silent samples took to play.
+    pub silence: Duration,
+}
+
+/// A coarse relative cost of producing audio. See [Sound::estimated_cost].
+///
+/// Costs are ordered from cheapest to most expensive.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CostHint {
+    /// Copying already decoded samples, e.g. a
+    /// [MemorySound][crate::sounds::MemorySound].
+    Trivial,
+    /// Simple per sample processing, e.g. linear resampling or unpacking PCM.
+    #[default]
+    Low,
+    /// Decoding a compressed format such as MP3 or Opus or filtering with
+    /// several stages.
+    Moderate,
+    /// Long filters, e.g. sinc resampling or convolution.
+    High,
+}
+
 impl Sound for Box<dyn Sound> {
     fn on_start_of_batch(&mut self) {
         self.deref_mut().on_start_of_batch()
@@ -312,6 +643,82 @@ impl Sound for Box<dyn Sound> {
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
         self.deref_mut().next_sample()
     }
+
+    fn next_samples_f32(&mut self, out: &mut [f32]) -> Result<usize, crate::Error> {
+        self.deref_mut().next_samples_f32(out)
+    }
+
+    fn next_is_metadata_change(&mut self) -> Option<bool> {
+        self.deref_mut().next_is_metadata_change()
+    }
+
+    fn seek(&mut self, seek_to: Duration) -> Result<Duration, crate::Error> {
+        self.deref_mut().seek(seek_to)
+    }
+
+    fn seek_granularity(&self) -> Option<Duration> {
+        self.deref().seek_granularity()
+    }
+
+    fn frame_alignment(&self) -> Option<u64> {
+        self.deref().frame_alignment()
+    }
+
+    fn emitted_frame_index(&self) -> u64 {
+        self.deref().emitted_frame_index()
+    }
+
+    fn progress(&self) -> Option<f32> {
+        self.deref().progress()
+    }
+
+    fn samples_remaining(&self) -> Option<u64> {
+        self.deref().samples_remaining()
+    }
+
+    fn codec_extra_data(&self) -> Option<&[u8]> {
+        self.deref().codec_extra_data()
+    }
+
+    fn set_looping(&mut self, enabled: bool) {
+        self.deref_mut().set_looping(enabled)
+    }
+
+    fn is_looping(&self) -> bool {
+        self.deref().is_looping()
+    }
+
+    fn set_end(&mut self, at: Option<Duration>) {
+        self.deref_mut().set_end(at)
+    }
+
+    fn end(&self) -> Option<Duration> {
+        self.deref().end()
+    }
+
+    fn buffered_duration(&self) -> Duration {
+        self.deref().buffered_duration()
+    }
+
+    fn underrun_stats(&self) -> UnderrunStats {
+        self.deref().underrun_stats()
+    }
+
+    fn estimated_cost(&self) -> CostHint {
+        self.deref().estimated_cost()
+    }
+
+    fn may_block(&self) -> bool {
+        self.deref().may_block()
+    }
+
+    fn try_clone_boxed(&self) -> Option<Box<dyn Sound>> {
+        self.deref().try_clone_boxed()
+    }
+
+    fn set_sample_mult(&mut self, mult: f32) {
+        self.deref_mut().set_sample_mult(mult)
+    }
 }
 
 #[cfg(test)]
@@ -2,20 +2,73 @@
 pub mod decoders;
 pub mod wrappers;
 
+mod ab_compare;
+mod ambient_noise;
+mod beat_switch;
+mod binaural_beat;
+mod biquad;
+#[cfg(feature = "symphonia")]
+mod chunked_dir_sound;
+mod dtmf;
+mod granular;
+mod intensity_layers;
+mod measurement_sweep;
 mod memory_sound;
+mod metronome;
+#[cfg(feature = "symphonia")]
+mod multi_file_sound;
+mod noise_bed;
 mod open_file;
+mod queue_sound;
+mod shared_memory_sound;
+mod shared_sound;
 mod silence;
 mod sine_wav;
 mod sound_list;
 mod sound_mixer;
 mod sounds_from_fn;
+#[cfg(feature = "spectrum")]
+mod spectrogram;
+mod sync_group;
+mod tail_sound;
+mod timeline;
+mod vocoder;
 
+pub use ab_compare::{ABChoice, ABCompare, ABCompareHandle};
+pub use ambient_noise::{AmbientNoise, NoisePreset};
+pub use beat_switch::{BeatSwitch, BeatSwitchHandle};
+pub use binaural_beat::BinauralBeat;
+#[cfg(feature = "symphonia")]
+pub use chunked_dir_sound::ChunkedDirSound;
+pub use dtmf::Dtmf;
+pub use granular::Granular;
+pub use intensity_layers::{IntensityLayers, IntensityLayersHandle};
+pub use measurement_sweep::MeasurementSweep;
 pub use memory_sound::MemorySound;
 pub use memory_sound::UnsupportedMetadataChangeError;
+pub use metronome::Metronome;
+#[cfg(feature = "symphonia")]
+pub use multi_file_sound::MultiFileSound;
+pub use noise_bed::{NoiseBed, NoiseColor};
+pub use open_file::decode_range;
+#[cfg(feature = "symphonia")]
+pub use open_file::open_asset;
 pub use open_file::open_file;
+pub use open_file::open_file_resampled;
 pub use open_file::open_file_with_buffer_capacity;
+pub use open_file::DecodeChainError;
+pub use queue_sound::QueueSound;
+pub use queue_sound::QueueSoundHandle;
+pub use shared_memory_sound::{SharedMemorySound, SharedMemoryWriter};
+pub use shared_sound::SharedSound;
 pub use silence::Silence;
 pub use sine_wav::SineWav;
 pub use sound_list::SoundList;
 pub use sound_mixer::SoundMixer;
 pub use sounds_from_fn::SoundsFromFn;
+#[cfg(feature = "spectrum")]
+pub use spectrogram::spectrogram;
+pub use sync_group::SyncGroup;
+pub use tail_sound::{TailSound, TailSoundHandle};
+pub use timeline::Timeline;
+pub use vocoder::Vocoder;
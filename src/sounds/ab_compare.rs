@@ -0,0 +1,344 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::sounds::UnsupportedMetadataChangeError;
+use crate::{utils, NextSample, Sound};
+
+/// One of the two processing chains of an [ABCompare].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ABChoice {
+    /// The first chain.
+    A,
+    /// The second chain.
+    B,
+}
+
+/// Compare two processing chains (e.g. two effect settings) on the same
+/// source, switching between them instantly while playing.
+///
+/// Each frame of the source is pulled once and fed to both chains which are
+/// played in lockstep so they stay in sync. Only the selected chain is heard.
+/// A selection made with [ABCompareHandle::select] crossfades between the
+/// chains over 5ms by default so the switch does not click.
+///
+/// The chains must return one frame for every frame they are given and have
+/// the same channel count and sample rate as the source. While either chain
+/// is paused Paused is returned and the other chain's frame is kept so the
+/// chains stay in sync. Once the source finishes the chains are played until
+/// they finish too, e.g. to play out a reverb tail.
+///
+/// If the source or a chain changes its channel count or sample rate an
+/// IoError of ErrorKind::Other with a UnsupportedMetadataChangeError is
+/// returned.
+pub struct ABCompare {
+    source: Box<dyn Sound>,
+    source_finished: bool,
+    taps: [Arc<Mutex<TapState>>; 2],
+    chains: [Box<dyn Sound>; 2],
+    finished: [bool; 2],
+    channel_count: u16,
+    sample_rate: u32,
+    selected_b: Arc<AtomicBool>,
+    /// How far the output has crossfaded from A (0.0) to B (1.0).
+    mix: f32,
+    /// How much `mix` moves each frame while crossfading.
+    mix_step: f32,
+    /// The current output frame.
+    output: Vec<i16>,
+    next_channel_idx: usize,
+    scratch: Vec<i16>,
+    /// Whether the taps have been fed the source frame for the next output
+    /// frame.
+    fed: bool,
+    /// The next frame of each chain.
+    chain_frames: [Vec<i16>; 2],
+    /// Whether `chain_frames` holds the next frame of the chain.
+    chain_ready: [bool; 2],
+}
+
+/// Selects the chain heard from an [ABCompare].
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct ABCompareHandle {
+    selected_b: Arc<AtomicBool>,
+}
+
+/// The frames of the source not yet read by a chain.
+struct TapState {
+    samples: VecDeque<i16>,
+    finished: bool,
+}
+
+/// Feeds the source to a chain.
+struct Tap {
+    state: Arc<Mutex<TapState>>,
+    channel_count: u16,
+    sample_rate: u32,
+}
+
+impl ABCompare {
+    /// Feed `source` to the chains built by `chain_a` and `chain_b`. Chain A
+    /// is heard initially.
+    ///
+    /// An error is returned if either chain does not have the same channel
+    /// count and sample rate as `source`.
+    pub fn new(
+        source: Box<dyn Sound>,
+        chain_a: impl FnOnce(Box<dyn Sound>) -> Box<dyn Sound>,
+        chain_b: impl FnOnce(Box<dyn Sound>) -> Box<dyn Sound>,
+    ) -> Result<(ABCompare, ABCompareHandle), crate::Error> {
+        let channel_count = source.channel_count();
+        let sample_rate = source.sample_rate();
+        let new_tap = || {
+            Arc::new(Mutex::new(TapState {
+                samples: VecDeque::new(),
+                finished: false,
+            }))
+        };
+        let taps = [new_tap(), new_tap()];
+        let tap = |state: &Arc<Mutex<TapState>>| -> Box<dyn Sound> {
+            Box::new(Tap {
+                state: state.clone(),
+                channel_count,
+                sample_rate,
+            })
+        };
+        let chains = [chain_a(tap(&taps[0])), chain_b(tap(&taps[1]))];
+        if chains
+            .iter()
+            .any(|c| c.channel_count() != channel_count || c.sample_rate() != sample_rate)
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "both ABCompare chains must have the same channel count and sample rate as the source",
+            )
+            .into());
+        }
+        let selected_b = Arc::new(AtomicBool::new(false));
+        let handle = ABCompareHandle {
+            selected_b: selected_b.clone(),
+        };
+        let mut compare = ABCompare {
+            source,
+            source_finished: false,
+            taps,
+            chains,
+            finished: [false; 2],
+            channel_count,
+            sample_rate,
+            selected_b,
+            mix: 0.0,
+            mix_step: 1.0,
+            output: Vec::new(),
+            next_channel_idx: 0,
+            scratch: Vec::new(),
+            fed: false,
+            chain_frames: [Vec::new(), Vec::new()],
+            chain_ready: [false; 2],
+        };
+        compare.set_crossfade(Duration::from_millis(5));
+        Ok((compare, handle))
+    }
+
+    /// Set how long switching between the chains crossfades for. Zero
+    /// switches at the next frame.
+    pub fn set_crossfade(&mut self, crossfade: Duration) {
+        let frames = utils::duration_to_num_samples(crossfade, 1, self.sample_rate);
+        self.mix_step = 1.0 / frames.max(1) as f32;
+    }
+
+    /// Pull the next frame of the source into both taps. Returns what to
+    /// return instead if the source is paused.
+    fn feed_taps(&mut self) -> Result<Option<NextSample>, crate::Error> {
+        if self.source_finished {
+            return Ok(None);
+        }
+        self.scratch.clear();
+        loop {
+            match self.source.append_next_frame_to(&mut self.scratch) {
+                Ok(()) => break,
+                Err(Ok(NextSample::MetadataChanged)) => {
+                    if self.source.channel_count() != self.channel_count
+                        || self.source.sample_rate() != self.sample_rate
+                    {
+                        return Err(crate::Error::IoError(std::io::Error::other(
+                            UnsupportedMetadataChangeError {},
+                        )));
+                    }
+                    self.scratch.clear();
+                }
+                Err(Ok(NextSample::Finished)) => {
+                    self.source_finished = true;
+                    break;
+                }
+                Err(Ok(NextSample::Paused)) => return Ok(Some(NextSample::Paused)),
+                Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        for tap in &self.taps {
+            let mut state = tap.lock().unwrap();
+            state.samples.extend(&self.scratch);
+            state.finished = self.source_finished;
+        }
+        Ok(None)
+    }
+
+    /// Read the next frame of each chain that does not have one yet. Returns
+    /// Paused if a chain is paused.
+    fn read_chains(&mut self) -> Result<Option<NextSample>, crate::Error> {
+        for (idx, chain) in self.chains.iter_mut().enumerate() {
+            if self.chain_ready[idx] || self.finished[idx] {
+                continue;
+            }
+            let frame = &mut self.chain_frames[idx];
+            frame.clear();
+            loop {
+                match chain.append_next_frame_to(frame) {
+                    Ok(()) => break,
+                    Err(Ok(NextSample::MetadataChanged)) => {
+                        if chain.channel_count() != self.channel_count
+                            || chain.sample_rate() != self.sample_rate
+                        {
+                            return Err(crate::Error::IoError(std::io::Error::other(
+                                UnsupportedMetadataChangeError {},
+                            )));
+                        }
+                        frame.clear();
+                    }
+                    Err(Ok(NextSample::Finished)) => {
+                        self.finished[idx] = true;
+                        break;
+                    }
+                    Err(Ok(NextSample::Paused)) => return Ok(Some(NextSample::Paused)),
+                    Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                    Err(Err(e)) => return Err(e),
+                }
+            }
+            self.chain_ready[idx] = true;
+        }
+        Ok(None)
+    }
+
+    /// Crossfade the frames of both chains into `output`.
+    fn mix_frame(&mut self) {
+        let channel_count = self.channel_count as usize;
+        self.output.clear();
+        self.output.resize(channel_count, 0);
+        let target = if self.selected_b.load(Ordering::Relaxed) {
+            1.0
+        } else {
+            0.0
+        };
+        if self.mix < target {
+            self.mix = (self.mix + self.mix_step).min(target);
+        } else {
+            self.mix = (self.mix - self.mix_step).max(target);
+        }
+        for (idx, frame) in self.chain_frames.iter_mut().enumerate() {
+            self.chain_ready[idx] = false;
+            let gain = if idx == 0 { 1.0 - self.mix } else { self.mix };
+            if gain == 0.0 || self.finished[idx] {
+                continue;
+            }
+            for (out, sample) in self.output.iter_mut().zip(frame.iter()) {
+                *out = (*out as f32 + *sample as f32 * gain)
+                    .round()
+                    .clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            }
+        }
+    }
+}
+
+impl ABCompareHandle {
+    /// Crossfade to `choice`.
+    pub fn select(&self, choice: ABChoice) {
+        self.selected_b
+            .store(choice == ABChoice::B, Ordering::Relaxed);
+    }
+
+    /// The most recently selected chain. It may still be crossfading in.
+    pub fn selected(&self) -> ABChoice {
+        if self.selected_b.load(Ordering::Relaxed) {
+            ABChoice::B
+        } else {
+            ABChoice::A
+        }
+    }
+
+    /// Crossfade to the chain not currently selected.
+    pub fn toggle(&self) {
+        self.selected_b.fetch_xor(true, Ordering::Relaxed);
+    }
+}
+
+impl Sound for ABCompare {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output.get(self.next_channel_idx) {
+            self.next_channel_idx += 1;
+            return Ok(NextSample::Sample(*sample));
+        }
+        if self.finished.iter().all(|f| *f) {
+            return Ok(NextSample::Finished);
+        }
+        if !self.fed {
+            if let Some(next) = self.feed_taps()? {
+                return Ok(next);
+            }
+            self.fed = true;
+        }
+        if let Some(next) = self.read_chains()? {
+            return Ok(next);
+        }
+        self.fed = false;
+        self.mix_frame();
+        if self.finished.iter().all(|f| *f) {
+            return Ok(NextSample::Finished);
+        }
+        self.next_channel_idx = 1;
+        Ok(NextSample::Sample(self.output[0]))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.source.on_start_of_batch();
+        for chain in &mut self.chains {
+            chain.on_start_of_batch();
+        }
+    }
+}
+
+impl Sound for Tap {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let mut state = self.state.lock().unwrap();
+        Ok(match state.samples.pop_front() {
+            Some(sample) => NextSample::Sample(sample),
+            None if state.finished => NextSample::Finished,
+            None => NextSample::Paused,
+        })
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+#[cfg(test)]
+#[path = "./tests/ab_compare.rs"]
+mod tests;
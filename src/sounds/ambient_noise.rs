@@ -0,0 +1,218 @@
+use std::f64::consts::PI;
+
+use crate::utils::Xorshift64;
+use crate::{NextSample, Sound};
+
+/// The kind of noise texture played by [AmbientNoise].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoisePreset {
+    /// A resonant band of noise that drifts up and down in pitch and level
+    /// like gusts of wind.
+    Wind,
+    /// Low rumbling noise that swells and brightens every few seconds like
+    /// waves breaking on a shore.
+    Ocean,
+    /// Bright hiss with small variations in level like steady rain.
+    Rain,
+}
+
+/// How a preset filters and modulates the noise.
+struct PresetConfig {
+    mode: FilterMode,
+    /// The cutoff (or center) frequency range in Hz the modulation moves
+    /// between.
+    min_cutoff: f64,
+    max_cutoff: f64,
+    q: f64,
+    /// Output level before modulation.
+    gain: f64,
+    /// How much of the modulation comes from the periodic wave rather than
+    /// the random drift.
+    wave_depth: f64,
+    /// The frequency in Hz of the periodic wave.
+    wave_rate: f64,
+    /// How much the level follows the modulation.
+    level_depth: f64,
+}
+
+/// Which output of the state variable filter is used.
+enum FilterMode {
+    Low,
+    Band,
+    High,
+}
+
+impl NoisePreset {
+    fn config(self) -> PresetConfig {
+        match self {
+            NoisePreset::Wind => PresetConfig {
+                mode: FilterMode::Band,
+                min_cutoff: 250.0,
+                max_cutoff: 1200.0,
+                q: 2.5,
+                gain: 1.2,
+                wave_depth: 0.3,
+                wave_rate: 0.07,
+                level_depth: 0.6,
+            },
+            NoisePreset::Ocean => PresetConfig {
+                mode: FilterMode::Low,
+                min_cutoff: 120.0,
+                max_cutoff: 1000.0,
+                q: 0.7,
+                gain: 0.8,
+                wave_depth: 0.8,
+                wave_rate: 0.1,
+                level_depth: 0.8,
+            },
+            NoisePreset::Rain => PresetConfig {
+                mode: FilterMode::High,
+                min_cutoff: 2000.0,
+                max_cutoff: 5000.0,
+                q: 0.7,
+                gain: 0.3,
+                wave_depth: 0.0,
+                wave_rate: 0.0,
+                level_depth: 0.2,
+            },
+        }
+    }
+}
+
+/// Seconds between new random drift targets.
+const DRIFT_INTERVAL: f64 = 1.5;
+
+/// A procedurally generated ambient noise texture such as wind, ocean waves
+/// or rain that plays forever.
+///
+/// White noise from a seeded generator is passed through a state variable
+/// filter whose cutoff and output level are slowly modulated by a mix of a
+/// periodic wave and a smoothed random drift, so the output never repeats.
+/// Only a few values of state are kept regardless of how long it plays.
+///
+/// The output is mono with a default sample rate of 44,100. Random values are
+/// seeded with a fixed value by default so output is reproducible; use
+/// [set_seed][AmbientNoise::set_seed] to vary it.
+pub struct AmbientNoise {
+    preset: NoisePreset,
+    config: PresetConfig,
+    sample_rate: u32,
+    rng: Xorshift64,
+    /// The phase of the periodic wave in cycles in the range `0.0..1.0`.
+    wave_phase: f64,
+    drift: f64,
+    drift_target: f64,
+    /// Frames until a new drift target is picked.
+    until_drift_target: u64,
+    /// The two integrator states of the filter.
+    ic1eq: f64,
+    ic2eq: f64,
+}
+
+impl AmbientNoise {
+    /// An ambient noise texture with a sample rate of 44,100.
+    pub fn new(preset: NoisePreset) -> AmbientNoise {
+        Self::with_sample_rate(preset, 44100)
+    }
+
+    /// An ambient noise texture with `sample_rate`.
+    pub fn with_sample_rate(preset: NoisePreset, sample_rate: u32) -> AmbientNoise {
+        AmbientNoise {
+            preset,
+            config: preset.config(),
+            sample_rate,
+            rng: Xorshift64::new(0x853c_49e6_748f_ea9b),
+            wave_phase: 0.0,
+            drift: 0.5,
+            drift_target: 0.5,
+            until_drift_target: 0,
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+        }
+    }
+
+    /// The preset being played.
+    pub fn preset(&self) -> NoisePreset {
+        self.preset
+    }
+
+    /// Change the preset. The filter state is kept so the change is smooth.
+    pub fn set_preset(&mut self, preset: NoisePreset) {
+        self.preset = preset;
+        self.config = preset.config();
+    }
+
+    /// Seed the random number generator.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Xorshift64::new(seed);
+    }
+
+    /// The modulation for the next frame in the range `0.0..=1.0`.
+    fn next_modulation(&mut self) -> f64 {
+        let sample_rate = self.sample_rate as f64;
+        if self.until_drift_target == 0 {
+            self.drift_target = (self.rng.next_f64() + 1.0) / 2.0;
+            self.until_drift_target = (DRIFT_INTERVAL * sample_rate) as u64;
+        }
+        self.until_drift_target -= 1;
+        // Reaches most of the way to the target within the interval
+        self.drift += (self.drift_target - self.drift) * 2.0 / (DRIFT_INTERVAL * sample_rate);
+
+        let wave = (self.wave_phase * PI).sin().powi(2);
+        self.wave_phase = (self.wave_phase + self.config.wave_rate / sample_rate).rem_euclid(1.0);
+
+        let depth = self.config.wave_depth;
+        (depth * wave + (1.0 - depth) * self.drift).clamp(0.0, 1.0)
+    }
+
+    /// Filter `x` with the state variable filter at `cutoff` Hz.
+    fn filter(&mut self, x: f64, cutoff: f64) -> f64 {
+        let cutoff = cutoff.min(self.sample_rate as f64 * 0.45);
+        let g = (PI * cutoff / self.sample_rate as f64).tan();
+        let k = 1.0 / self.config.q;
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+        let v3 = x - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+        match self.config.mode {
+            FilterMode::Low => v2,
+            FilterMode::Band => k * v1,
+            FilterMode::High => x - k * v1 - v2,
+        }
+    }
+}
+
+impl Sound for AmbientNoise {
+    fn channel_count(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let modulation = self.next_modulation();
+        let cutoff =
+            self.config.min_cutoff + (self.config.max_cutoff - self.config.min_cutoff) * modulation;
+        let noise = self.rng.next_f64();
+        let filtered = self.filter(noise, cutoff);
+        let level = 1.0 - self.config.level_depth * (1.0 - modulation);
+        let value = (filtered * level * self.config.gain).clamp(-1.0, 1.0);
+        Ok(NextSample::Sample((value * i16::MAX as f64) as i16))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn may_block(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/ambient_noise.rs"]
+mod tests;
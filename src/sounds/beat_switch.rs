@@ -0,0 +1,215 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::sounds::UnsupportedMetadataChangeError;
+use crate::{NextSample, Sound};
+
+/// Switch between variations of a loop only on beat boundaries so playback
+/// stays in time.
+///
+/// All loops must have the same channel count and sample rate. They are all
+/// played in lockstep but only the selected loop is heard so a newly selected
+/// loop starts at the same position within the beat as the one it replaces.
+/// A selection made with [BeatSwitchHandle::select] takes effect at the start
+/// of the next beat. Beats are counted from the start of the BeatSwitch.
+///
+/// A loop that is paused or has finished is treated as silence. `Finished` is
+/// returned once all loops have finished. Loops are not restarted, use
+/// looping sounds (e.g. [MemorySound::set_looping][crate::sounds::MemorySound::set_looping])
+/// to play them indefinitely.
+///
+/// If a loop changes its channel count or sample rate an IoError of
+/// ErrorKind::Other with a UnsupportedMetadataChangeError is returned.
+pub struct BeatSwitch {
+    loops: Vec<Box<dyn Sound>>,
+    finished: Vec<bool>,
+    beat_frames: u64,
+    channel_count: u16,
+    sample_rate: u32,
+    frame_num: u64,
+    current: usize,
+    selected: Arc<AtomicUsize>,
+    /// The current output frame.
+    output: Vec<i16>,
+    next_channel_idx: usize,
+    scratch: Vec<i16>,
+}
+
+/// Selects the loop played by a [BeatSwitch].
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct BeatSwitchHandle {
+    selected: Arc<AtomicUsize>,
+    loop_count: usize,
+}
+
+impl BeatSwitch {
+    /// Switch between `loops` on boundaries of `beat_frames` frames. The first
+    /// loop is played initially.
+    ///
+    /// An error is returned if `loops` is empty, `beat_frames` is 0 or the
+    /// loops do not all have the same channel count and sample rate.
+    pub fn new(
+        loops: Vec<Box<dyn Sound>>,
+        beat_frames: u64,
+    ) -> Result<(BeatSwitch, BeatSwitchHandle), crate::Error> {
+        let invalid = |msg: &'static str| -> crate::Error {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, msg).into()
+        };
+        let Some(first) = loops.first() else {
+            return Err(invalid("BeatSwitch requires at least one loop"));
+        };
+        if beat_frames == 0 {
+            return Err(invalid("beat_frames must be at least 1"));
+        }
+        let channel_count = first.channel_count();
+        let sample_rate = first.sample_rate();
+        if loops
+            .iter()
+            .any(|l| l.channel_count() != channel_count || l.sample_rate() != sample_rate)
+        {
+            return Err(invalid(
+                "all BeatSwitch loops must have the same channel count and sample rate",
+            ));
+        }
+        let selected = Arc::new(AtomicUsize::new(0));
+        let handle = BeatSwitchHandle {
+            selected: selected.clone(),
+            loop_count: loops.len(),
+        };
+        let switch = BeatSwitch {
+            finished: vec![false; loops.len()],
+            loops,
+            beat_frames,
+            channel_count,
+            sample_rate,
+            frame_num: 0,
+            current: 0,
+            selected,
+            output: Vec::new(),
+            next_channel_idx: 0,
+            scratch: Vec::new(),
+        };
+        Ok((switch, handle))
+    }
+
+    /// Same as [new][BeatSwitch::new] but the beat length is derived from a
+    /// tempo in beats per minute and the sample rate of the loops.
+    ///
+    /// An error is also returned if `bpm` is not positive.
+    pub fn with_bpm(
+        loops: Vec<Box<dyn Sound>>,
+        bpm: f32,
+    ) -> Result<(BeatSwitch, BeatSwitchHandle), crate::Error> {
+        let sample_rate = loops.first().map(|l| l.sample_rate()).unwrap_or(0);
+        let beat_frames = if bpm > 0.0 {
+            (sample_rate as f64 * 60.0 / bpm as f64).round() as u64
+        } else {
+            0
+        };
+        BeatSwitch::new(loops, beat_frames)
+    }
+
+    /// The number of frames in each beat.
+    pub fn beat_frames(&self) -> u64 {
+        self.beat_frames
+    }
+
+    /// The index of the loop currently being played.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Read the next frame of every loop keeping the current loop's frame.
+    fn read_frame(&mut self) -> Result<(), crate::Error> {
+        let channel_count = self.channel_count as usize;
+        self.output.clear();
+        for (idx, sound) in self.loops.iter_mut().enumerate() {
+            self.scratch.clear();
+            if !self.finished[idx] {
+                loop {
+                    match sound.append_next_frame_to(&mut self.scratch) {
+                        Ok(()) => break,
+                        Err(Ok(NextSample::MetadataChanged)) => {
+                            if sound.channel_count() != self.channel_count
+                                || sound.sample_rate() != self.sample_rate
+                            {
+                                return Err(crate::Error::IoError(std::io::Error::other(
+                                    UnsupportedMetadataChangeError {},
+                                )));
+                            }
+                            self.scratch.clear();
+                        }
+                        Err(Ok(NextSample::Finished)) => {
+                            self.finished[idx] = true;
+                            break;
+                        }
+                        Err(Ok(NextSample::Paused)) | Err(Ok(NextSample::Sample(_))) => break,
+                        Err(Err(e)) => return Err(e),
+                    }
+                }
+            }
+            if idx == self.current {
+                self.output.extend_from_slice(&self.scratch);
+                self.output.resize(channel_count, 0);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BeatSwitchHandle {
+    /// Play the loop at `idx` starting at the next beat boundary.
+    ///
+    /// Panics if `idx` is not the index of a loop.
+    pub fn select(&self, idx: usize) {
+        assert!(idx < self.loop_count, "loop index out of range");
+        self.selected.store(idx, Ordering::Relaxed);
+    }
+
+    /// The most recently selected loop. It may not be playing yet.
+    pub fn selected(&self) -> usize {
+        self.selected.load(Ordering::Relaxed)
+    }
+}
+
+impl Sound for BeatSwitch {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output.get(self.next_channel_idx) {
+            self.next_channel_idx += 1;
+            return Ok(NextSample::Sample(*sample));
+        }
+        if self.finished.iter().all(|f| *f) {
+            return Ok(NextSample::Finished);
+        }
+        if self.frame_num.is_multiple_of(self.beat_frames) {
+            self.current = self.selected.load(Ordering::Relaxed);
+        }
+        self.read_frame()?;
+        if self.finished.iter().all(|f| *f) {
+            return Ok(NextSample::Finished);
+        }
+        self.frame_num += 1;
+        self.next_channel_idx = 1;
+        Ok(NextSample::Sample(self.output[0]))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        for sound in &mut self.loops {
+            sound.on_start_of_batch();
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/beat_switch.rs"]
+mod tests;
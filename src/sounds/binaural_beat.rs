@@ -0,0 +1,130 @@
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+/// Binaural beats: a stereo sound with a tone of the base frequency in the
+/// left channel and the base frequency plus the beat frequency in the right
+/// channel.
+///
+/// Both tones are full scale and phase continuous. The sound is infinite
+/// unless a duration is set with [set_duration][BinauralBeat::set_duration].
+pub struct BinauralBeat {
+    base_frequency: f32,
+    beat_frequency: f32,
+    sample_rate: u32,
+    /// The phase of each tone in cycles in the range `0.0..1.0`.
+    left_phase: f64,
+    right_phase: f64,
+    /// The right channel sample of the current frame if not yet returned.
+    right_sample: Option<i16>,
+    /// The duration last set, for clones.
+    duration_frames: Option<u64>,
+    remaining_frames: Option<u64>,
+}
+
+impl BinauralBeat {
+    /// Binaural beats with a default sample rate of 44,100.
+    pub fn new(base_frequency: f32, beat_frequency: f32) -> BinauralBeat {
+        Self::with_sample_rate(base_frequency, beat_frequency, 44100)
+    }
+
+    /// Binaural beats with `sample_rate`.
+    pub fn with_sample_rate(
+        base_frequency: f32,
+        beat_frequency: f32,
+        sample_rate: u32,
+    ) -> BinauralBeat {
+        BinauralBeat {
+            base_frequency,
+            beat_frequency,
+            sample_rate,
+            left_phase: 0.0,
+            right_phase: 0.0,
+            right_sample: None,
+            duration_frames: None,
+            remaining_frames: None,
+        }
+    }
+
+    /// Finish after `duration` from now. `None` plays forever. Clones made
+    /// with [try_clone_boxed][Sound::try_clone_boxed] play for the duration
+    /// last set.
+    pub fn set_duration(&mut self, duration: Option<Duration>) {
+        self.duration_frames =
+            duration.map(|d| utils::duration_to_num_samples(d, 1, self.sample_rate));
+        self.remaining_frames = self.duration_frames;
+    }
+
+    /// The frequency in Hz of the left channel.
+    pub fn base_frequency(&self) -> f32 {
+        self.base_frequency
+    }
+
+    /// The difference in Hz between the right and left channels.
+    pub fn beat_frequency(&self) -> f32 {
+        self.beat_frequency
+    }
+
+    /// Change the frequencies. The tones stay phase continuous.
+    pub fn set_frequencies(&mut self, base_frequency: f32, beat_frequency: f32) {
+        self.base_frequency = base_frequency;
+        self.beat_frequency = beat_frequency;
+    }
+}
+
+/// Return the sample for `phase` and advance it by one frame of `frequency`.
+fn advance(phase: &mut f64, frequency: f32, sample_rate: u32) -> i16 {
+    let sample = ((*phase * TAU).sin() * i16::MAX as f64) as i16;
+    *phase = (*phase + frequency as f64 / sample_rate as f64).rem_euclid(1.0);
+    sample
+}
+
+impl Sound for BinauralBeat {
+    fn channel_count(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.right_sample.take() {
+            return Ok(NextSample::Sample(sample));
+        }
+        if let Some(remaining) = &mut self.remaining_frames {
+            if *remaining == 0 {
+                return Ok(NextSample::Finished);
+            }
+            *remaining -= 1;
+        }
+        let left = advance(&mut self.left_phase, self.base_frequency, self.sample_rate);
+        self.right_sample = Some(advance(
+            &mut self.right_phase,
+            self.base_frequency + self.beat_frequency,
+            self.sample_rate,
+        ));
+        Ok(NextSample::Sample(left))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn may_block(&self) -> bool {
+        false
+    }
+
+    fn try_clone_boxed(&self) -> Option<Box<dyn Sound>> {
+        Some(Box::new(BinauralBeat {
+            left_phase: 0.0,
+            right_phase: 0.0,
+            right_sample: None,
+            remaining_frames: self.duration_frames,
+            ..*self
+        }))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/binaural_beat.rs"]
+mod tests;
@@ -0,0 +1,162 @@
+//! A second order IIR filter and the coefficients of common filter shapes,
+//! from the Audio EQ Cookbook by Robert Bristow-Johnson.
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+#[derive(Clone, Copy)]
+pub(crate) enum Shelf {
+    Low,
+    High,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct BiquadCoefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl BiquadCoefficients {
+    /// A Butterworth low pass filter. Two in series are a Linkwitz-Riley
+    /// filter.
+    pub(crate) fn low_pass(frequency: f32, sample_rate: f32) -> BiquadCoefficients {
+        let (cos, alpha) = Self::prepare(frequency, FRAC_1_SQRT_2, sample_rate);
+        Self::normalize(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            cos,
+            alpha,
+        )
+    }
+
+    /// A Butterworth high pass filter.
+    pub(crate) fn high_pass(frequency: f32, sample_rate: f32) -> BiquadCoefficients {
+        let (cos, alpha) = Self::prepare(frequency, FRAC_1_SQRT_2, sample_rate);
+        Self::normalize(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            cos,
+            alpha,
+        )
+    }
+
+    /// An all-pass filter with the same phase response as the sum of a
+    /// Linkwitz-Riley low pass and high pass at `frequency`.
+    pub(crate) fn all_pass(frequency: f32, sample_rate: f32) -> BiquadCoefficients {
+        let (cos, alpha) = Self::prepare(frequency, FRAC_1_SQRT_2, sample_rate);
+        Self::normalize([1.0 - alpha, -2.0 * cos, 1.0 + alpha], cos, alpha)
+    }
+
+    /// A band pass filter with a gain of 1 at the center frequency.
+    pub(crate) fn band_pass(frequency: f32, q: f32, sample_rate: f32) -> BiquadCoefficients {
+        let (cos, alpha) = Self::prepare(frequency, q, sample_rate);
+        Self::normalize([alpha, 0.0, -alpha], cos, alpha)
+    }
+
+    /// A peaking EQ filter boosting or cutting by `gain_db` around
+    /// `frequency`.
+    pub(crate) fn peaking(
+        frequency: f32,
+        gain_db: f32,
+        q: f32,
+        sample_rate: f32,
+    ) -> BiquadCoefficients {
+        let a = 10f32.powf(gain_db / 40.0);
+        let (cos, alpha) = Self::prepare(frequency, q, sample_rate);
+        let a0 = 1.0 + alpha / a;
+        BiquadCoefficients {
+            b0: (1.0 + alpha * a) / a0,
+            b1: -2.0 * cos / a0,
+            b2: (1.0 - alpha * a) / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha / a) / a0,
+        }
+    }
+
+    /// A shelf with a slope of 1 boosting frequencies beyond `frequency` by
+    /// `gain_db`.
+    pub(crate) fn shelf(
+        shelf: Shelf,
+        frequency: f32,
+        gain_db: f32,
+        sample_rate: f32,
+    ) -> BiquadCoefficients {
+        let a = 10f32.powf(gain_db / 40.0);
+        let (cos, alpha) = Self::prepare(frequency, FRAC_1_SQRT_2, sample_rate);
+        let beta = 2.0 * a.sqrt() * alpha;
+        // The high shelf is the low shelf with the sign of cos flipped and b1
+        // and a1 negated
+        let s = match shelf {
+            Shelf::Low => 1.0,
+            Shelf::High => -1.0,
+        };
+        let b0 = a * ((a + 1.0) - s * (a - 1.0) * cos + beta);
+        let b1 = s * 2.0 * a * ((a - 1.0) - s * (a + 1.0) * cos);
+        let b2 = a * ((a + 1.0) - s * (a - 1.0) * cos - beta);
+        let a0 = (a + 1.0) + s * (a - 1.0) * cos + beta;
+        let a1 = -s * 2.0 * ((a - 1.0) + s * (a + 1.0) * cos);
+        let a2 = (a + 1.0) + s * (a - 1.0) * cos - beta;
+        BiquadCoefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    /// The cosine of the angular frequency and alpha for `q`.
+    fn prepare(frequency: f32, q: f32, sample_rate: f32) -> (f32, f32) {
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        (w0.cos(), w0.sin() / (2.0 * q))
+    }
+
+    /// Divide by a0 of the filters whose feedback coefficients only depend
+    /// on the frequency and q.
+    fn normalize([b0, b1, b2]: [f32; 3], cos: f32, alpha: f32) -> BiquadCoefficients {
+        let a0 = 1.0 + alpha;
+        BiquadCoefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+}
+
+/// A biquad filter in Direct Form I.
+#[derive(Clone)]
+pub(crate) struct Biquad {
+    coefficients: BiquadCoefficients,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    pub(crate) fn new(coefficients: BiquadCoefficients) -> Biquad {
+        Biquad {
+            coefficients,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    /// Change the filter shape keeping its state so there is no click.
+    pub(crate) fn set_coefficients(&mut self, coefficients: BiquadCoefficients) {
+        self.coefficients = coefficients;
+    }
+
+    pub(crate) fn process(&mut self, x: f32) -> f32 {
+        let c = &self.coefficients;
+        let y = c.b0 * x + c.b1 * self.x1 + c.b2 * self.x2 - c.a1 * self.y1 - c.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{NextSample, Sound};
+
+use super::MultiFileSound;
+
+/// Play the numbered chunk files of a directory in order as one continuous
+/// Sound.
+///
+/// Files are matched against a pattern containing a single `%d` (or a zero
+/// padded `%0Nd`) in place of the chunk number, e.g. `chunk_%04d.wav`.
+/// Matching files are played in numeric order using a [MultiFileSound] so
+/// each chunk is opened lazily and converted to the channel count and sample
+/// rate of the first chunk.
+pub struct ChunkedDirSound {
+    inner: MultiFileSound,
+    paths: Vec<PathBuf>,
+}
+
+impl ChunkedDirSound {
+    /// Play the files in `dir` whose names match `pattern` in numeric order.
+    ///
+    /// Returns an error if `pattern` does not contain a chunk number
+    /// placeholder, if no files match or if the first chunk can not be
+    /// opened.
+    pub fn new<P: AsRef<Path>>(dir: P, pattern: &str) -> Result<ChunkedDirSound, crate::Error> {
+        let pattern = ChunkPattern::parse(pattern).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("chunk pattern {pattern:?} must contain %d or %0Nd"),
+            )
+        })?;
+        let mut chunks = Vec::new();
+        for entry in std::fs::read_dir(dir.as_ref())? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if let Some(number) = name.to_str().and_then(|n| pattern.chunk_number(n)) {
+                chunks.push((number, entry.path()));
+            }
+        }
+        if chunks.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no files in {:?} match the chunk pattern", dir.as_ref()),
+            )
+            .into());
+        }
+        chunks.sort();
+        let paths: Vec<PathBuf> = chunks.into_iter().map(|(_, path)| path).collect();
+        Ok(ChunkedDirSound {
+            inner: MultiFileSound::new(&paths)?,
+            paths,
+        })
+    }
+
+    /// The matched chunk files in the order they are played.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// The combined duration of all chunks.
+    ///
+    /// See [MultiFileSound::total_duration].
+    pub fn total_duration(&mut self) -> Result<Duration, crate::Error> {
+        self.inner.total_duration()
+    }
+
+    /// The position of the next sample measured from the start of the first
+    /// chunk.
+    pub fn current_position(&self) -> Duration {
+        self.inner.current_position()
+    }
+}
+
+/// A file name pattern split around its chunk number placeholder.
+struct ChunkPattern<'a> {
+    prefix: &'a str,
+    suffix: &'a str,
+    /// The minimum number of digits from a `%0Nd` placeholder.
+    min_digits: usize,
+}
+
+impl<'a> ChunkPattern<'a> {
+    fn parse(pattern: &'a str) -> Option<ChunkPattern<'a>> {
+        let start = pattern.find('%')?;
+        let (prefix, rest) = pattern.split_at(start);
+        let rest = &rest[1..];
+        let end = rest.find('d')?;
+        let width = &rest[..end];
+        let min_digits = match width.strip_prefix('0') {
+            Some(digits) => digits.parse().ok()?,
+            None if width.is_empty() => 1,
+            None => return None,
+        };
+        Some(ChunkPattern {
+            prefix,
+            suffix: &rest[end + 1..],
+            min_digits,
+        })
+    }
+
+    fn chunk_number(&self, name: &str) -> Option<u64> {
+        let digits = name.strip_prefix(self.prefix)?.strip_suffix(self.suffix)?;
+        if digits.len() < self.min_digits || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    }
+}
+
+impl Sound for ChunkedDirSound {
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        self.inner.next_sample()
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn seek(&mut self, seek_to: Duration) -> Result<Duration, crate::Error> {
+        self.inner.seek(seek_to)
+    }
+
+    fn seek_granularity(&self) -> Option<Duration> {
+        self.inner.seek_granularity()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/chunked_dir_sound.rs"]
+mod tests;
@@ -4,12 +4,13 @@
 //! [sounds::open_file][crate::sounds::open_file()].
 #[cfg(feature = "rmp3-mp3")]
 mod mp3;
+#[cfg(feature = "symphonia")]
+mod opus;
 #[cfg(feature = "qoa")]
 mod qoa;
+mod raw_pcm;
 #[cfg(feature = "symphonia")]
 mod symphonia;
-#[cfg(feature = "symphonia")]
-mod opus;
 #[cfg(feature = "hound-wav")]
 mod wav;
 
@@ -19,6 +20,8 @@ pub use mp3::Mp3Decoder;
 pub use qoa::QoaDecoder;
 #[cfg(feature = "qoa")]
 pub use qoaudio::DecodeError as QoaDecodeError;
+pub(crate) use raw_pcm::decode_sample;
+pub use raw_pcm::{Endianness, PcmFormat, RawPcmDecoder};
 
 use once_cell::sync::Lazy;
 
@@ -32,11 +35,11 @@ pub static CODEC_REGISTRY: Lazy<CodecRegistry> = Lazy::new(|| {
 
 use ::symphonia::default::register_enabled_codecs;
 #[cfg(feature = "symphonia")]
-pub use symphonia::SymphoniaDecoder;
+pub use symphonia::{DecodeDiagnostics, SymphoniaDecoder};
 use symphonia_core::codecs::CodecRegistry;
+pub use symphonia_core::io::MediaSource;
 #[cfg(feature = "hound-wav")]
 pub use wav::WavDecoder;
 
 #[cfg(feature = "symphonia")]
-use self::opus::OpusDecoder;
-
+pub use self::opus::OpusDecoder;
@@ -88,6 +88,10 @@ where
     }
 
     fn on_start_of_batch(&mut self) {}
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Moderate
+    }
 }
 
 impl<R> Mp3Decoder<R>
@@ -1,15 +1,12 @@
 use audiopus::{
-    coder::{Decoder as AudiopusDecoder, GenericCtl}, Channels, Error as OpusError, ErrorCode, SampleRate
+    coder::{Decoder as AudiopusDecoder, GenericCtl},
+    packet::Packet as OpusPacket,
+    Channels, Error as OpusError, ErrorCode, SampleRate,
 };
 use symphonia_core::{
     audio::{AsAudioBufferRef, AudioBuffer, AudioBufferRef, Layout, Signal, SignalSpec},
     codecs::{
-        CodecDescriptor,
-        CodecParameters,
-        Decoder,
-        DecoderOptions,
-        FinalizeResult,
-        CODEC_TYPE_OPUS,
+        CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult, CODEC_TYPE_OPUS,
     },
     errors::{decode_error, Result as SymphResult},
     formats::Packet,
@@ -24,6 +21,10 @@ pub struct OpusDecoder {
     buf: AudioBuffer<f32>,
     rawbuf: Vec<f32>,
     sample_rate: u32,
+    opus_sample_rate: SampleRate,
+    packet_lost: bool,
+    /// The output gain from the identification header in Q7.8 dB.
+    output_gain: i16,
 }
 
 pub const AUDIO_FRAME_RATE: usize = 50;
@@ -38,79 +39,163 @@ pub const AUDIO_FRAME_RATE: usize = 50;
 unsafe impl Sync for OpusDecoder {}
 
 impl OpusDecoder {
+    /// Signal that the packet before the next packet passed to `decode` was
+    /// lost.
+    ///
+    /// The next call to `decode` will first recover the lost frame using the
+    /// forward error correction (FEC) data of the new packet, falling back to
+    /// packet loss concealment if the packet has no FEC data, and then decode
+    /// the new packet itself. The returned buffer contains both frames. The
+    /// lost frame is assumed to have the same duration as the new packet.
+    ///
+    /// If the lost packet is not followed by another packet, decode an empty
+    /// packet instead which only conceals the loss.
+    pub fn signal_packet_loss(&mut self) {
+        self.packet_lost = true;
+    }
+
+    /// The output gain in dB from the Opus identification header (`OpusHead`)
+    /// in the codec extra data, or 0.0 if there is none.
+    ///
+    /// The gain is applied to the decoded output as players are required to
+    /// do.
+    pub fn output_gain_db(&self) -> f32 {
+        self.output_gain as f32 / 256.0
+    }
+
     fn decode_inner(&mut self, packet: &Packet) -> SymphResult<()> {
-        let s_ct = loop {
+        let mut s_ct = 0;
+        if std::mem::take(&mut self.packet_lost) && !packet.buf().is_empty() {
+            let lost_ct = match opus_packet(packet.buf())
+                .and_then(|pkt| audiopus::packet::nb_samples(pkt, self.opus_sample_rate))
+            {
+                Ok(ct) => ct,
+                Err(e) => {
+                    log::warn!("Opus packet error: {:?}", e);
+                    return decode_error("Opus packet error: see 'tracing' logs.");
+                }
+            };
+            // Make room for the recovered frame and the frame of this packet.
+            self.reserve(lost_ct * 2);
+            s_ct = self.decode_at(packet, 0, Some(lost_ct), true)?;
+        }
+        s_ct += self.decode_at(packet, s_ct, None, false)?;
+
+        self.buf.clear();
+        self.buf.render_reserved(Some(s_ct));
+
+        // Only the frames decoded for this packet are valid. Anything after
+        // them is left over from earlier, longer packets.
+        let decoded = &self.rawbuf[..s_ct * 2];
+        // Forcibly assuming stereo, for now.
+        for ch in 0..2 {
+            let iter = decoded.chunks_exact(2).map(|chunk| chunk[ch]);
+            for (tgt, src) in self.buf.chan_mut(ch).iter_mut().zip(iter) {
+                *tgt = src;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decode into `rawbuf` starting at frame `offset` and return the number of
+    /// frames decoded. With `fec`, exactly `frames` frames are recovered which
+    /// opus requires to match the duration of the lost packet. Otherwise the
+    /// buffer is grown as needed.
+    fn decode_at(
+        &mut self,
+        packet: &Packet,
+        offset: usize,
+        frames: Option<usize>,
+        fec: bool,
+    ) -> SymphResult<usize> {
+        loop {
             let pkt = if packet.buf().is_empty() {
                 None
-            } else if let Ok(checked_pkt) = packet.buf().try_into() {
+            } else if let Ok(checked_pkt) = opus_packet(packet.buf()) {
                 Some(checked_pkt)
             } else {
                 return decode_error("Opus packet was too large (greater than i32::MAX bytes).");
             };
-            let out_space = (&mut self.rawbuf[..]).try_into().expect("The following logic expands this buffer safely below i32::MAX, and we throw our own error.");
+            let out_end = frames.map_or(self.rawbuf.len(), |f| (offset + f) * 2);
+            // The buffer is never grown past i32::MAX samples so this only
+            // fails if that invariant is broken.
+            let out_space = match (&mut self.rawbuf[offset * 2..out_end]).try_into() {
+                Ok(out_space) => out_space,
+                Err(_) => {
+                    return decode_error("Opus decode buffer is larger than i32::MAX samples.")
+                }
+            };
 
-            match self.inner.decode_float(pkt, out_space, false) {
-                Ok(v) => break v,
-                Err(OpusError::Opus(ErrorCode::BufferTooSmall)) => {
+            match self.inner.decode_float(pkt, out_space, fec) {
+                Ok(v) => return Ok(v),
+                Err(OpusError::Opus(ErrorCode::BufferTooSmall)) if frames.is_none() => {
                     // double the buffer size
                     // correct behav would be to mirror the decoder logic in the udp_rx set.
                     let new_size = (self.rawbuf.len() * 2).min(i32::MAX as usize);
                     if new_size == self.rawbuf.len() {
-                        return decode_error("Opus frame too big: cannot expand opus frame decode buffer any further.");
+                        return decode_error(
+                            "Opus frame too big: cannot expand opus frame decode buffer any further.",
+                        );
                     }
-
-                    self.rawbuf.resize(new_size, 0.0);
-                    self.buf = AudioBuffer::new(
-                        self.rawbuf.len() as u64 / 2,
-                        SignalSpec::new_with_layout(self.sample_rate, Layout::Stereo),
-                    );
-                },
+                    self.reserve(new_size / 2);
+                }
                 Err(e) => {
-                    println!("Opus decode error: {:?}", e);
-                    return decode_error("Opus decode error: see 'tracing' logs.");
-                },
+                    log::error!("Opus decode error: {:?}", e);
+                    return decode_error("Opus decode error: see logs.");
+                }
             }
-        };
-
-        self.buf.clear();
-        self.buf.render_reserved(Some(s_ct));
+        }
+    }
 
-        // Forcibly assuming stereo, for now.
-        for ch in 0..2 {
-            let iter = self.rawbuf.chunks_exact(2).map(|chunk| chunk[ch]);
-            for (tgt, src) in self.buf.chan_mut(ch).iter_mut().zip(iter) {
-                *tgt = src;
-            }
+    /// Ensure both buffers can hold at least `frames` stereo frames.
+    fn reserve(&mut self, frames: usize) {
+        if self.rawbuf.len() >= frames * 2 {
+            return;
         }
+        self.rawbuf.resize(frames * 2, 0.0);
+        self.buf = AudioBuffer::new(
+            frames as u64,
+            SignalSpec::new_with_layout(self.sample_rate, Layout::Stereo),
+        );
+    }
+}
 
-        Ok(())
+/// The output gain in Q7.8 dB from an Opus identification header.
+fn output_gain(head: &[u8]) -> Option<i16> {
+    if !head.starts_with(b"OpusHead") {
+        return None;
     }
+    Some(i16::from_le_bytes(head.get(16..18)?.try_into().ok()?))
+}
+
+fn opus_packet(buf: &[u8]) -> Result<OpusPacket<'_>, OpusError> {
+    buf.try_into()
 }
 
 impl Decoder for OpusDecoder {
     fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> SymphResult<Self> {
         let (sample_rate, sample_rate_raw) = match params.sample_rate {
-            Some(48000) => {
-                (SampleRate::Hz48000, 48000)
-            },
-            Some(24000) => {
-                (SampleRate::Hz24000, 24000)
-            },
-            Some(16000) => {
-                (SampleRate::Hz16000, 16000)
-            },
-            Some(12000) => {
-                (SampleRate::Hz12000, 12000)
-            },
-            Some(8000) => {
-                (SampleRate::Hz8000, 8000)
-            },
+            Some(48000) => (SampleRate::Hz48000, 48000),
+            Some(24000) => (SampleRate::Hz24000, 24000),
+            Some(16000) => (SampleRate::Hz16000, 16000),
+            Some(12000) => (SampleRate::Hz12000, 12000),
+            Some(8000) => (SampleRate::Hz8000, 8000),
             e => {
                 println!("No sample rate provided {:?}", e);
                 panic!()
-            },
+            }
         };
         let inner = AudiopusDecoder::new(sample_rate, Channels::Stereo).unwrap();
+        let output_gain = params
+            .extra_data
+            .as_deref()
+            .and_then(output_gain)
+            .unwrap_or(0);
+        // libopus applies the gain while decoding and keeps it across resets
+        if let Err(e) = inner.set_gain(output_gain as i32) {
+            log::warn!("Could not apply Opus output gain: {:?}", e);
+        }
 
         let mut params = params.clone();
         params.with_sample_rate(sample_rate_raw);
@@ -122,11 +207,14 @@ impl Decoder for OpusDecoder {
             inner,
             params,
             buf: AudioBuffer::new(
-                mono_frame_size as  u64,
+                mono_frame_size as u64,
                 SignalSpec::new_with_layout(sample_rate_raw, Layout::Stereo),
             ),
             rawbuf: vec![0.0f32; stereo_frame_size],
-            sample_rate: sample_rate_raw
+            sample_rate: sample_rate_raw,
+            opus_sample_rate: sample_rate,
+            packet_lost: false,
+            output_gain,
         })
     }
 
@@ -152,6 +240,7 @@ impl Decoder for OpusDecoder {
     }
 
     fn reset(&mut self) {
+        self.packet_lost = false;
         _ = self.inner.reset_state();
     }
 
@@ -164,3 +253,6 @@ impl Decoder for OpusDecoder {
     }
 }
 
+#[cfg(test)]
+#[path = "./tests/opus.rs"]
+mod tests;
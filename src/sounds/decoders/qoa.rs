@@ -80,6 +80,10 @@ where
     }
 
     fn on_start_of_batch(&mut self) {}
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Low
+    }
 }
 
 impl From<DecodeError> for crate::Error {
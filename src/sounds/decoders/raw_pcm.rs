@@ -0,0 +1,173 @@
+use std::io::{ErrorKind, Read};
+
+use crate::sound::NextSample;
+use crate::Sound;
+
+/// Byte order of multi-byte samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// Least significant byte first.
+    Little,
+    /// Most significant byte first.
+    Big,
+}
+
+/// How raw integer PCM samples are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcmFormat {
+    /// The number of bits in each sample. Must be 8, 16, 24 or 32.
+    pub bits_per_sample: u16,
+    /// Whether samples are signed (two's complement) or unsigned (offset
+    /// binary where the midpoint is silence).
+    pub signed: bool,
+    /// Byte order of each sample. Ignored for 8 bit samples.
+    pub endianness: Endianness,
+}
+
+impl PcmFormat {
+    /// Signed 16 bit little-endian samples.
+    pub const S16LE: PcmFormat = PcmFormat {
+        bits_per_sample: 16,
+        signed: true,
+        endianness: Endianness::Little,
+    };
+    /// Signed 16 bit big-endian samples.
+    pub const S16BE: PcmFormat = PcmFormat {
+        bits_per_sample: 16,
+        signed: true,
+        endianness: Endianness::Big,
+    };
+    /// Unsigned 8 bit samples as used by 8 bit WAV files.
+    pub const U8: PcmFormat = PcmFormat {
+        bits_per_sample: 8,
+        signed: false,
+        endianness: Endianness::Little,
+    };
+}
+
+/// Decoder for headerless interleaved integer PCM data.
+///
+/// Since there is no header the channel count, sample rate and sample
+/// encoding must be provided. Samples of every width are converted to i16 by
+/// keeping the most significant bits.
+pub struct RawPcmDecoder<R>
+where
+    R: Read + Send,
+{
+    reader: R,
+    sample_rate: u32,
+    channel_count: u16,
+    format: PcmFormat,
+    buffer: [u8; 4],
+}
+
+impl<R> RawPcmDecoder<R>
+where
+    R: Read + Send,
+{
+    /// Decode `data` as samples encoded as `format`.
+    ///
+    /// Returns an InvalidInput error if `format.bits_per_sample` is not 8,
+    /// 16, 24 or 32 or if `channel_count` or `sample_rate` are 0.
+    pub fn new(
+        data: R,
+        channel_count: u16,
+        sample_rate: u32,
+        format: PcmFormat,
+    ) -> Result<RawPcmDecoder<R>, crate::Error> {
+        if !matches!(format.bits_per_sample, 8 | 16 | 24 | 32) {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("unsupported bits per sample: {}", format.bits_per_sample),
+            )
+            .into());
+        }
+        if channel_count == 0 || sample_rate == 0 {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "channel_count and sample_rate must be at least 1",
+            )
+            .into());
+        }
+        Ok(RawPcmDecoder {
+            reader: data,
+            sample_rate,
+            channel_count,
+            format,
+            buffer: [0; 4],
+        })
+    }
+
+    /// The encoding of the samples.
+    pub fn format(&self) -> PcmFormat {
+        self.format
+    }
+
+    /// Return the wrapped Reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> Sound for RawPcmDecoder<R>
+where
+    R: Read + Send,
+{
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let num_bytes = self.format.bits_per_sample as usize / 8;
+        let bytes = &mut self.buffer[..num_bytes];
+        match self.reader.read_exact(bytes) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(NextSample::Finished),
+            Err(e) => return Err(e.into()),
+        }
+        Ok(NextSample::Sample(decode_sample(bytes, self.format)))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Trivial
+    }
+
+    fn frame_alignment(&self) -> Option<u64> {
+        Some(1)
+    }
+}
+
+/// Convert one encoded sample to i16.
+pub(crate) fn decode_sample(bytes: &[u8], format: PcmFormat) -> i16 {
+    let mut value: u32 = 0;
+    match format.endianness {
+        Endianness::Little => {
+            for byte in bytes.iter().rev() {
+                value = (value << 8) | *byte as u32;
+            }
+        }
+        Endianness::Big => {
+            for byte in bytes {
+                value = (value << 8) | *byte as u32;
+            }
+        }
+    }
+    // Move the most significant bit of the sample to the most significant bit
+    // of the u32.
+    value <<= 32 - format.bits_per_sample as u32;
+    if !format.signed {
+        // Offset binary to two's complement
+        value ^= 0x8000_0000;
+    }
+    ((value as i32) >> 16) as i16
+}
+
+#[cfg(test)]
+#[path = "./tests/raw_pcm.rs"]
+mod tests;
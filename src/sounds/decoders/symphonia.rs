@@ -1,14 +1,20 @@
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use crate::utils;
 use crate::NextSample;
 use crate::Sound;
 use symphonia::core::audio::{AudioBuffer, AudioBufferRef, Channels, Signal};
-use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::codecs::{
+    CodecParameters, Decoder, DecoderOptions, CODEC_TYPE_AAC, CODEC_TYPE_MP1, CODEC_TYPE_MP2,
+    CODEC_TYPE_MP3, CODEC_TYPE_NULL, CODEC_TYPE_OPUS,
+};
 use symphonia::core::conv::FromSample;
 use symphonia::core::errors::Error;
-use symphonia::core::formats::FormatOptions;
+use symphonia::core::formats::{FormatOptions, Packet};
 use symphonia::core::io::{MediaSource, MediaSourceStream};
-use symphonia::core::meta::{Limit, MetadataOptions};
+use symphonia::core::meta::{Limit, MetadataOptions, StandardTagKey};
 use symphonia::core::probe::Hint;
 use symphonia::core::sample::Sample;
 use symphonia_core::formats::SeekMode;
@@ -16,22 +22,162 @@ use symphonia_core::formats::SeekTo;
 use symphonia_core::probe::ProbeResult;
 use symphonia_core::units::Time;
 
-use super::CODEC_REGISTRY;
+use super::{OpusDecoder, CODEC_REGISTRY};
 
 /// Decode formats using the Symphonia crate decoders.
 pub struct SymphoniaDecoder {
     sample_rate: u32,
 
-    decoder: Box<dyn Decoder>,
+    decoder: TrackDecoder,
 
     channels: Channels,
     /// currently playing track id
     pub track_id: u32,
     next_channel_idx: u16,
     next_sample_idx: usize,
+    /// see [Sound::emitted_frame_index]
+    emitted_frames: u64,
     /// probe result of currently playing stream
     pub probed: ProbeResult,
+    /// multiplier applied to every sample, see [Sound::set_sample_mult]
     pub sample_mult: f32,
+    metadata_changed: bool,
+    /// see [Sound::set_looping]
+    looping: bool,
+    /// see [Sound::set_end]
+    end: Option<Duration>,
+    /// Whether a sample has been returned since the start of the track so an
+    /// empty track does not loop forever.
+    returned_sample: bool,
+    diagnostics: Option<DecodeDiagnostics>,
+    /// see [SymphoniaDecoder::recovered_errors]
+    recovered_errors: u64,
+    /// The encoder tag found in the metadata.
+    encoder: Option<String>,
+    /// The sum of the bitrate of each decoded packet multiplied by its number
+    /// of frames.
+    bitrate_sum: u64,
+    /// The number of frames included in `bitrate_sum`.
+    bitrate_frames: u64,
+    /// The bitrate and number of frames of the packets decoded in about the
+    /// last [RECENT_BITRATE_WINDOW] of audio.
+    recent_bitrates: VecDeque<(u64, u64)>,
+    /// The number of frames in `recent_bitrates`.
+    recent_frames: u64,
+    /// see [SymphoniaDecoder::source_is_seekable]
+    source_seekable: bool,
+    /// see [SymphoniaDecoder::source_byte_len]
+    source_byte_len: Option<u64>,
+    /// The packet most recently decoded.
+    last_packet: Option<Packet>,
+    /// Whether [SymphoniaDecoder::next_encoded_packet] has been called since
+    /// the data was opened or seeked.
+    passthrough_started: bool,
+    /// The timestamp of the packet last returned by
+    /// [SymphoniaDecoder::next_encoded_packet].
+    encoded_packet_ts: Option<u64>,
+    /// The most frames decoded from a single packet, 0 before the first
+    /// packet.
+    max_packet_frames: u64,
+}
+
+/// Timing of packet decodes by a [SymphoniaDecoder] to help tell if glitches
+/// are caused by slow decoding (e.g. slow storage or a slow codec) rather
+/// than the backend.
+///
+/// Created with
+/// [enable_decode_diagnostics][SymphoniaDecoder::enable_decode_diagnostics].
+/// Each packet decode includes reading the packet from the source. Can be
+/// cloned and sent to other threads.
+#[derive(Clone)]
+pub struct DecodeDiagnostics {
+    shared: Arc<Mutex<DecodeStats>>,
+}
+
+struct DecodeStats {
+    slow_threshold: Duration,
+    packet_count: u64,
+    worst: Duration,
+    average: Duration,
+    slow_count: u64,
+    slow: bool,
+}
+
+/// The amount of audio [SymphoniaDecoder::current_bitrate] is averaged over.
+const RECENT_BITRATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Weight of the latest decode time in the moving average.
+const AVERAGE_WEIGHT: f64 = 0.1;
+
+impl DecodeDiagnostics {
+    fn new(slow_threshold: Duration) -> DecodeDiagnostics {
+        DecodeDiagnostics {
+            shared: Arc::new(Mutex::new(DecodeStats {
+                slow_threshold,
+                packet_count: 0,
+                worst: Duration::ZERO,
+                average: Duration::ZERO,
+                slow_count: 0,
+                slow: false,
+            })),
+        }
+    }
+
+    fn record(&self, elapsed: Duration) {
+        let mut stats = self.shared.lock().unwrap();
+        stats.average = if stats.packet_count == 0 {
+            elapsed
+        } else {
+            stats.average.mul_f64(1.0 - AVERAGE_WEIGHT) + elapsed.mul_f64(AVERAGE_WEIGHT)
+        };
+        stats.packet_count += 1;
+        stats.worst = stats.worst.max(elapsed);
+        if elapsed > stats.slow_threshold {
+            log::debug!("Decoding a packet took {:?}", elapsed);
+            stats.slow_count += 1;
+            stats.slow = true;
+        }
+    }
+
+    /// The number of packet decodes timed.
+    pub fn packet_count(&self) -> u64 {
+        self.shared.lock().unwrap().packet_count
+    }
+
+    /// The longest time a packet decode took.
+    pub fn worst_decode_time(&self) -> Duration {
+        self.shared.lock().unwrap().worst
+    }
+
+    /// An exponential moving average of packet decode times.
+    pub fn average_decode_time(&self) -> Duration {
+        self.shared.lock().unwrap().average
+    }
+
+    /// The number of packet decodes that took longer than the slow threshold.
+    pub fn slow_decode_count(&self) -> u64 {
+        self.shared.lock().unwrap().slow_count
+    }
+
+    /// Returns true if a packet decode took longer than the slow threshold
+    /// since the last call and resets the flag. A slow decode is a potential
+    /// cause of an underrun.
+    pub fn take_slow(&self) -> bool {
+        std::mem::take(&mut self.shared.lock().unwrap().slow)
+    }
+
+    /// Clear all recorded timings.
+    pub fn reset(&self) {
+        let mut stats = self.shared.lock().unwrap();
+        *stats = DecodeStats {
+            slow_threshold: stats.slow_threshold,
+            packet_count: 0,
+            worst: Duration::ZERO,
+            average: Duration::ZERO,
+            slow_count: 0,
+            slow: false,
+        };
+    }
 }
 
 impl SymphoniaDecoder {
@@ -42,46 +188,509 @@ impl SymphoniaDecoder {
         data: Box<dyn MediaSource>,
         extension: Option<&str>,
     ) -> Result<SymphoniaDecoder, Error> {
-        let mss = MediaSourceStream::new(data, Default::default());
-
-        let mut hint = Hint::new();
-        if let Some(extension) = extension {
-            hint.with_extension(extension);
-        }
-        let meta_opts: MetadataOptions = MetadataOptions {
-            limit_metadata_bytes: Limit::Maximum(1),
-            limit_visual_bytes: Limit::Maximum(1),
-        };
-        let fmt_opts: FormatOptions = Default::default();
-        let probed = symphonia::default::get_probe().format(&hint, mss, &fmt_opts, &meta_opts)?;
-
-        // Find the first audio track with a known (decodable) codec.
-        let track = probed.format
-            .tracks()
-            .iter()
-            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
-            .ok_or(Error::Unsupported(
-                "No track with a supported codec was found",
-            ))?;
-        let track_id = track.id;
-
-        let dec_opts: DecoderOptions = Default::default();
-        let decoder = CODEC_REGISTRY.make(&track.codec_params, &dec_opts)?;
+        let source_seekable = data.is_seekable();
+        let source_byte_len = data.byte_len();
+        let (mut probed, track_id, decoder) = probe(data, extension)?;
 
         let mut decoder = SymphoniaDecoder {
+            encoder: find_encoder(&mut probed),
             sample_rate: 1000,
             decoder,
             channels: Channels::empty(),
             track_id,
             next_channel_idx: 0,
             next_sample_idx: 0,
+            emitted_frames: 0,
             probed,
             sample_mult: 1.0,
+            metadata_changed: false,
+            looping: false,
+            end: None,
+            returned_sample: false,
+            diagnostics: None,
+            recovered_errors: 0,
+            bitrate_sum: 0,
+            bitrate_frames: 0,
+            recent_bitrates: VecDeque::new(),
+            recent_frames: 0,
+            source_seekable,
+            source_byte_len,
+            last_packet: None,
+            passthrough_started: false,
+            encoded_packet_ts: None,
+            max_packet_frames: 0,
         };
         // Ignore metadata changed since no one has seen the old values
         let _ = decoder.decode_next_packet();
         Ok(decoder)
     }
+
+    /// Replace the data being decoded with `data` without needing to recreate
+    /// any wrappers around this decoder.
+    ///
+    /// The new data is probed in the same way as [SymphoniaDecoder::new] and
+    /// all decoding state is reset. The next call to next_sample will return
+    /// `MetadataChanged` followed by samples of the new data. A stop position
+    /// set with [set_end][Sound::set_end] is cleared while the
+    /// [looping][Sound::set_looping] setting and
+    /// [sample_mult][SymphoniaDecoder::sample_mult] are kept. If an error is
+    /// returned the decoder is left unchanged.
+    pub fn reopen(
+        &mut self,
+        data: Box<dyn MediaSource>,
+        extension: Option<&str>,
+    ) -> Result<(), Error> {
+        let source_seekable = data.is_seekable();
+        let source_byte_len = data.byte_len();
+        let (mut probed, track_id, decoder) = probe(data, extension)?;
+        self.source_seekable = source_seekable;
+        self.source_byte_len = source_byte_len;
+        self.encoder = find_encoder(&mut probed);
+        self.probed = probed;
+        self.track_id = track_id;
+        self.decoder = decoder;
+        self.channels = Channels::empty();
+        self.sample_rate = 1000;
+        self.next_channel_idx = 0;
+        self.next_sample_idx = 0;
+        self.emitted_frames = 0;
+        self.end = None;
+        self.returned_sample = false;
+        self.bitrate_sum = 0;
+        self.bitrate_frames = 0;
+        self.recent_bitrates.clear();
+        self.recent_frames = 0;
+        self.recovered_errors = 0;
+        self.last_packet = None;
+        self.passthrough_started = false;
+        self.encoded_packet_ts = None;
+        self.max_packet_frames = 0;
+        // Errors will happen again on the next call to next_sample
+        let _ = self.decode_next_packet();
+        self.metadata_changed = true;
+        Ok(())
+    }
+
+    /// Decode the entire track and return the largest absolute sample value
+    /// normalized to `0.0..=1.0` where 1.0 is full scale.
+    ///
+    /// [sample_mult][SymphoniaDecoder::sample_mult] is not applied. Afterward
+    /// the decoder is returned to the start of the track with
+    /// `seek(Duration::ZERO)` regardless of its position beforehand.
+    pub fn scan_peak(&mut self) -> Result<f32, crate::Error> {
+        self.seek(Duration::ZERO)?;
+        let sample_mult = std::mem::replace(&mut self.sample_mult, 1.0);
+        let mut peak = 0;
+        let result = loop {
+            match self.next_sample() {
+                Ok(NextSample::Sample(s)) => peak = peak.max(s.unsigned_abs()),
+                Ok(NextSample::MetadataChanged) => (),
+                Ok(NextSample::Paused) | Ok(NextSample::Finished) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.sample_mult = sample_mult;
+        result?;
+        self.seek(Duration::ZERO)?;
+        Ok((peak as f32 / i16::MAX as f32).min(1.0))
+    }
+
+    /// Start timing each packet decode. Decodes taking longer than
+    /// `slow_threshold` are counted as slow.
+    ///
+    /// Returns the handle used to read the timings. If diagnostics were
+    /// already enabled the existing handle is returned and only its threshold
+    /// is changed. Timing adds a small overhead to each packet decode.
+    pub fn enable_decode_diagnostics(&mut self, slow_threshold: Duration) -> DecodeDiagnostics {
+        let diagnostics = self
+            .diagnostics
+            .get_or_insert_with(|| DecodeDiagnostics::new(slow_threshold));
+        diagnostics.shared.lock().unwrap().slow_threshold = slow_threshold;
+        diagnostics.clone()
+    }
+
+    /// Whether the track is HE-AAC (AAC-LC with SBR and possibly PS) which is
+    /// only partially supported.
+    ///
+    /// Symphonia does not support the SBR and PS extensions so only the
+    /// AAC-LC core is decoded. The sound then has half of the sample rate
+    /// stored in the container and lacks the high frequencies but otherwise
+    /// plays correctly. Only detected when signaled in the AudioSpecificConfig
+    /// of the container (the common case for M4A files).
+    pub fn aac_sbr_ignored(&self) -> bool {
+        is_he_aac(self.decoder.codec_params())
+    }
+
+    /// Signal that a packet of an Opus track was lost before the next packet
+    /// read from the source, e.g. by a network source that dropped it.
+    ///
+    /// The lost audio is recovered from the forward error correction data
+    /// of the next packet or concealed as described in
+    /// [OpusDecoder::signal_packet_loss]. Returns false and does nothing if
+    /// the track is not Opus.
+    pub fn signal_packet_loss(&mut self) -> bool {
+        match &mut self.decoder {
+            TrackDecoder::Opus(decoder) => {
+                decoder.signal_packet_loss();
+                true
+            }
+            TrackDecoder::Other(_) => false,
+        }
+    }
+
+    /// The output gain in dB from the Opus identification header of the
+    /// track, which is applied while decoding. See
+    /// [OpusDecoder::output_gain_db]. `None` if the track is not Opus.
+    pub fn opus_output_gain_db(&self) -> Option<f32> {
+        match &self.decoder {
+            TrackDecoder::Opus(decoder) => Some(decoder.output_gain_db()),
+            TrackDecoder::Other(_) => None,
+        }
+    }
+
+    /// The software or settings used to encode the file (e.g. `LAME3.100`) if
+    /// present in its metadata, such as the `TSSE` ID3v2 frame or the
+    /// `ENCODER` Vorbis comment.
+    pub fn encoder(&self) -> Option<&str> {
+        self.encoder.as_deref()
+    }
+
+    /// The average bitrate in bits per second of the packets decoded so far.
+    ///
+    /// For MPEG audio (MP1, MP2 and MP3) the bitrate in each frame header is
+    /// used so a constant bitrate file reports exactly its nominal bitrate
+    /// (e.g. 320,000). For other codecs it is calculated from the size of
+    /// each packet. Returns None if no packet has been decoded.
+    pub fn bitrate(&self) -> Option<u32> {
+        if self.bitrate_frames == 0 {
+            return None;
+        }
+        Some((self.bitrate_sum / self.bitrate_frames) as u32)
+    }
+
+    /// The bitrate in bits per second of the packets decoded in about the
+    /// last second of audio, for showing the current bitrate of variable
+    /// bitrate streams.
+    ///
+    /// Calculated in the same way as [bitrate][SymphoniaDecoder::bitrate].
+    /// Returns None for uncompressed (PCM) tracks and until a packet has been
+    /// decoded. Recent packets are forgotten when seeking.
+    pub fn current_bitrate(&self) -> Option<u32> {
+        if self.recent_frames == 0 || is_pcm(self.decoder.codec_params()) {
+            return None;
+        }
+        let sum: u64 = self
+            .recent_bitrates
+            .iter()
+            .map(|(bitrate, frames)| bitrate * frames)
+            .sum();
+        Some((sum / self.recent_frames) as u32)
+    }
+
+    /// The number of packets that could not be decoded and were skipped, or
+    /// required the decoder to be reset, since the data was opened.
+    ///
+    /// Each of these may have caused an audible glitch so this can be shown
+    /// as an indication of the health of the stream.
+    pub fn recovered_errors(&self) -> u64 {
+        self.recovered_errors
+    }
+
+    /// Return the next packet of the track as it is stored in the container
+    /// (e.g. an MP3 frame or an AAC access unit) without decoding it, for
+    /// passthrough to outputs or muxers that take compressed audio.
+    ///
+    /// Returns None at the end of the track. The timestamp of the returned
+    /// packet is available from
+    /// [encoded_packet_timestamp][SymphoniaDecoder::encoded_packet_timestamp].
+    ///
+    /// This can not be mixed with decoding on the same instance: once this
+    /// has been called `next_sample` skips the packets returned here and
+    /// calling this after samples have been returned skips the rest of the
+    /// packet being played. Use a separate decoder for each. Seeking is
+    /// supported and the next packet returned is the one containing the seek
+    /// position.
+    pub fn next_encoded_packet(&mut self) -> Result<Option<Vec<u8>>, crate::Error> {
+        if !std::mem::replace(&mut self.passthrough_started, true)
+            && self.next_channel_idx == 0
+            && self.next_sample_idx == 0
+        {
+            // The packet decoded when opening or seeking has not been played
+            if let Some(packet) = self.last_packet.take() {
+                self.encoded_packet_ts = Some(packet.ts());
+                return Ok(Some(packet.data.into_vec()));
+            }
+        }
+        loop {
+            let packet = match self.probed.format.next_packet() {
+                Ok(packet) => packet,
+                Err(e) if is_end_of_stream(&e) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+            self.pop_metadata();
+            if packet.track_id() == self.track_id {
+                self.encoded_packet_ts = Some(packet.ts());
+                return Ok(Some(packet.data.into_vec()));
+            }
+        }
+    }
+
+    /// The timestamp of the packet last returned by
+    /// [next_encoded_packet][SymphoniaDecoder::next_encoded_packet]
+    /// measured from the start of the track.
+    pub fn encoded_packet_timestamp(&self) -> Option<Duration> {
+        let time_base = self.decoder.codec_params().time_base?;
+        // Exact integer math so large timestamps do not overflow
+        let ticks = self.encoded_packet_ts? as u128 * time_base.numer as u128;
+        let denom = time_base.denom as u128;
+        let nanos = (ticks % denom) * 1_000_000_000 / denom;
+        Some(Duration::new((ticks / denom) as u64, nanos as u32))
+    }
+
+    /// Decode the next packet directly into the caller's per-channel buffers
+    /// without interleaving or converting to i16, for integration into other
+    /// DSP graphs.
+    ///
+    /// `out` must have one slice per channel of the packet and each slice must
+    /// be at least as long as the packet's number of frames (e.g. 1152 for
+    /// MP3). Returns the number of frames written to each slice or 0 at the
+    /// end of the track or the end set with [set_end][Sound::set_end]. If the
+    /// rest of a packet has not been played by `next_sample` those frames are
+    /// returned first. [sample_mult][SymphoniaDecoder::sample_mult] is
+    /// applied.
+    ///
+    /// Returns an error if the channel count or slice lengths do not fit the
+    /// packet. The packet is not consumed so the call can be retried with
+    /// larger buffers. Returns an error if called in the middle of a frame.
+    /// [channel_count][Sound::channel_count] and
+    /// [sample_rate][Sound::sample_rate] describe the returned frames.
+    pub fn decode_into(&mut self, out: &mut [&mut [f32]]) -> Result<usize, crate::Error> {
+        let channel_count = self.channels.count();
+        if self.next_channel_idx as usize >= channel_count {
+            self.next_channel_idx = 0;
+            self.next_sample_idx += 1;
+        }
+        if self.next_channel_idx != 0 {
+            return Err(invalid_input(
+                "decode_into must be called at the start of a frame".to_owned(),
+            ));
+        }
+        while self.next_sample_idx >= self.decoder.last_decoded().frames() {
+            match self.decode_next_packet() {
+                Ok(changed) => self.metadata_changed |= changed,
+                Err(e) if is_end_of_stream(&e) => return Ok(0),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        // The caller sees the new values in the returned frames
+        self.metadata_changed = false;
+
+        let channel_count = self.channels.count();
+        if out.len() != channel_count {
+            return Err(invalid_input(format!(
+                "decode_into got {} buffers for {} channels",
+                out.len(),
+                channel_count
+            )));
+        }
+        let buf_ref = self.decoder.last_decoded();
+        let start = self.next_sample_idx;
+        let mut frames = buf_ref.frames() - start;
+        if let Some(left) = self.frames_until_end() {
+            frames = frames.min(left as usize);
+        }
+        if let Some(short) = out.iter().find(|channel| channel.len() < frames) {
+            return Err(invalid_input(format!(
+                "decode_into got a buffer of {} frames for {} frames",
+                short.len(),
+                frames
+            )));
+        }
+        for (channel_idx, channel) in out.iter_mut().enumerate() {
+            let channel = &mut channel[..frames];
+            copy_f32_channel_from_ref(&buf_ref, channel_idx, start, channel);
+            for sample in channel {
+                *sample *= self.sample_mult;
+            }
+        }
+        self.next_sample_idx += frames;
+        self.emitted_frames += frames as u64;
+        self.returned_sample = true;
+        Ok(frames)
+    }
+
+    /// Whether the byte source the data is read from supports seeking, as
+    /// reported by its [MediaSource::is_seekable] when it was opened.
+    ///
+    /// This is independent of the container and codec, which may support
+    /// seeking (see [seek_granularity][Sound::seek_granularity]) even when
+    /// the source does not, in which case seeking fails.
+    pub fn source_is_seekable(&self) -> bool {
+        self.source_seekable
+    }
+
+    /// The length in bytes of the byte source the data is read from, as
+    /// reported by its [MediaSource::byte_len] when it was opened. `None` if
+    /// unknown (e.g. a network stream or pipe).
+    pub fn source_byte_len(&self) -> Option<u64> {
+        self.source_byte_len
+    }
+
+    /// The total duration of the track if known from its container.
+    pub fn duration(&self) -> Option<Duration> {
+        let par = self.decoder.codec_params();
+        let sample_rate = par.sample_rate.filter(|rate| *rate > 0)?;
+        Some(utils::num_samples_to_duration(
+            par.n_frames?,
+            1,
+            sample_rate,
+        ))
+    }
+}
+
+fn probe(
+    data: Box<dyn MediaSource>,
+    extension: Option<&str>,
+) -> Result<(ProbeResult, u32, TrackDecoder), Error> {
+    let mss = MediaSourceStream::new(data, Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+    let meta_opts: MetadataOptions = MetadataOptions {
+        limit_metadata_bytes: Limit::Maximum(1),
+        limit_visual_bytes: Limit::Maximum(1),
+    };
+    let fmt_opts: FormatOptions = Default::default();
+    let probed = symphonia::default::get_probe().format(&hint, mss, &fmt_opts, &meta_opts)?;
+
+    // Find the first audio track with a known (decodable) codec.
+    let track = probed
+        .format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or(Error::Unsupported(
+            "No track with a supported codec was found",
+        ))?;
+    let track_id = track.id;
+
+    let dec_opts: DecoderOptions = Default::default();
+    // Opus is made directly so its own functions can be called
+    let decoder = if track.codec_params.codec == CODEC_TYPE_OPUS {
+        TrackDecoder::Opus(Box::new(OpusDecoder::try_new(
+            &track.codec_params,
+            &dec_opts,
+        )?))
+    } else {
+        TrackDecoder::Other(CODEC_REGISTRY.make(&track.codec_params, &dec_opts)?)
+    };
+    if is_he_aac(&track.codec_params) {
+        log::warn!("HE-AAC SBR and PS are not supported, only decoding the AAC-LC core");
+    }
+    Ok((probed, track_id, decoder))
+}
+
+/// The decoder of the track being played.
+enum TrackDecoder {
+    Opus(Box<OpusDecoder>),
+    Other(Box<dyn Decoder>),
+}
+
+impl std::ops::Deref for TrackDecoder {
+    type Target = dyn Decoder;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            TrackDecoder::Opus(decoder) => decoder.as_ref(),
+            TrackDecoder::Other(decoder) => decoder.as_ref(),
+        }
+    }
+}
+
+impl std::ops::DerefMut for TrackDecoder {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            TrackDecoder::Opus(decoder) => decoder.as_mut(),
+            TrackDecoder::Other(decoder) => decoder.as_mut(),
+        }
+    }
+}
+
+/// The value of the first encoder tag in the metadata read while probing or
+/// the latest metadata of the container.
+fn find_encoder(probed: &mut ProbeResult) -> Option<String> {
+    let find = |tags: &[symphonia::core::meta::Tag]| {
+        tags.iter()
+            .find(|tag| tag.std_key == Some(StandardTagKey::Encoder))
+            .map(|tag| tag.value.to_string())
+    };
+    if let Some(encoder) = probed
+        .format
+        .metadata()
+        .current()
+        .and_then(|rev| find(rev.tags()))
+    {
+        return Some(encoder);
+    }
+    probed
+        .metadata
+        .get()
+        .and_then(|metadata| metadata.current().and_then(|rev| find(rev.tags())))
+}
+
+/// The bitrate in bits per second from the header of an MPEG audio frame or
+/// None if it is free format or invalid.
+fn mpeg_audio_bitrate(frame: &[u8]) -> Option<u32> {
+    const V1_L1: [u16; 14] = [
+        32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ];
+    const V1_L2: [u16; 14] = [
+        32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ];
+    const V1_L3: [u16; 14] = [
+        32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    const V2_L1: [u16; 14] = [
+        32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ];
+    const V2_L2_L3: [u16; 14] = [8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+    let header = u32::from_be_bytes(frame.get(..4)?.try_into().ok()?);
+    if header >> 21 != 0x7ff {
+        return None;
+    }
+    let is_v1 = match (header >> 19) & 0b11 {
+        0b11 => true,
+        0b00 | 0b10 => false,
+        _ => return None,
+    };
+    let table = match ((header >> 17) & 0b11, is_v1) {
+        (0b11, true) => &V1_L1,
+        (0b10, true) => &V1_L2,
+        (0b01, true) => &V1_L3,
+        (0b11, false) => &V2_L1,
+        (0b10 | 0b01, false) => &V2_L2_L3,
+        _ => return None,
+    };
+    let index = ((header >> 12) & 0b1111) as usize;
+    let kbps = table.get(index.checked_sub(1)?)?;
+    Some(*kbps as u32 * 1000)
+}
+
+/// Whether `params` are for AAC with an AudioSpecificConfig signaling SBR or
+/// PS.
+fn is_he_aac(params: &CodecParameters) -> bool {
+    const SBR_OBJECT_TYPE: u8 = 5;
+    const PS_OBJECT_TYPE: u8 = 29;
+    if params.codec != CODEC_TYPE_AAC {
+        return false;
+    }
+    match params.extra_data.as_deref() {
+        Some([first, ..]) => matches!(first >> 3, SBR_OBJECT_TYPE | PS_OBJECT_TYPE),
+        _ => false,
+    }
 }
 
 impl Sound for SymphoniaDecoder {
@@ -94,54 +703,72 @@ impl Sound for SymphoniaDecoder {
     }
 
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
-        if self.next_channel_idx >= self.channels.count().try_into().unwrap() {
-            self.next_channel_idx = 0;
-            self.next_sample_idx += 1;
-        }
-        let mut buf_ref = self.decoder.last_decoded();
-        if self.next_sample_idx >= buf_ref.frames() {
-            match self.decode_next_packet() {
-                Ok(true) => return Ok(NextSample::MetadataChanged),
-                Ok(false) => (),
-                Err(Error::IoError(err))
-                    if err.kind() == std::io::ErrorKind::UnexpectedEof
-                        && err.to_string() == "end of stream" =>
-                {
-                    // According to Symphonia this is the only way to detect an end of stream
-                    return Ok(NextSample::Finished);
-                }
-                // TODO: Handle errors better when awedio allows returning errors.
-                Err(e) => return Err(e.into()),
-            };
-            buf_ref = self.decoder.last_decoded();
+        if let Some(next) = self.prepare_next_sample()? {
+            return Ok(next);
         }
+        let buf_ref = self.decoder.last_decoded();
         let sample = extract_sample_from_ref(&buf_ref, self.next_channel_idx, self.next_sample_idx);
-        self.next_channel_idx += 1;
+        self.advance_channel();
         let sample = f32::from(sample);
         let sample = sample * self.sample_mult;
         let sample = sample as i32;
         let sample = i16::try_from(sample).unwrap();
+        self.returned_sample = true;
         Ok(NextSample::Sample(sample))
     }
 
+    /// Samples are copied from the decoded buffer without converting to i16
+    /// first so float codecs keep their full precision.
+    fn next_samples_f32(&mut self, out: &mut [f32]) -> Result<usize, crate::Error> {
+        for (written, out_sample) in out.iter_mut().enumerate() {
+            if self.prepare_next_sample()?.is_some() {
+                return Ok(written);
+            }
+            let buf_ref = self.decoder.last_decoded();
+            let sample =
+                extract_f32_sample_from_ref(&buf_ref, self.next_channel_idx, self.next_sample_idx);
+            self.advance_channel();
+            self.returned_sample = true;
+            *out_sample = sample * self.sample_mult;
+        }
+        Ok(out.len())
+    }
+
     fn on_start_of_batch(&mut self) {}
 
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Moderate
+    }
+
+    /// The seek is sample accurate. The format is seeked to a packet at or
+    /// before `seek_to` and the frames before `seek_to` are decoded and
+    /// dropped, so the next sample is the one at the returned position.
+    //
     // Thanks to
     // github.com/BonnyAD9/raplay/blob/master/src/source/symph.rs:153
-    fn seek(&mut self, seek_to: Duration) -> Result<Duration, symphonia_core::errors::Error> {
+    fn seek(&mut self, seek_to: Duration) -> Result<Duration, crate::Error> {
         let par = self.decoder.codec_params();
+        // Check before seeking so the position is not lost when it can not be
+        // reported
+        let sample_rate = match par.sample_rate {
+            Some(sample_rate) if sample_rate > 0 => sample_rate,
+            _ => {
+                return Err(Error::Unsupported(
+                    "unable to calculate the seek position without a sample rate",
+                )
+                .into())
+            }
+        };
         let time = Time::new(
             seek_to.as_secs(),
-            seek_to.as_secs_f64() - seek_to.as_secs_f64().trunc()
+            seek_to.as_secs_f64() - seek_to.as_secs_f64().trunc(),
         );
 
-        let seek_to = if let (Some(time_base), Some(max)) = 
-            (par.time_base, par.n_frames)
-        {
+        let seek_to = if let (Some(time_base), Some(max)) = (par.time_base, par.n_frames) {
             let ts = time_base.calc_timestamp(time);
             SeekTo::TimeStamp {
-                ts: ts.min(max - 1),
-                track_id: self.track_id, 
+                ts: ts.min(max.saturating_sub(1)),
+                track_id: self.track_id,
             }
         } else {
             SeekTo::Time {
@@ -150,10 +777,116 @@ impl Sound for SymphoniaDecoder {
             }
         };
 
-        let pos = self.probed.format.seek(SeekMode::Coarse, seek_to)?;
-        let current_duration = (pos.actual_ts * 1000) / par.sample_rate.unwrap() as u64;
+        let pos = self.probed.format.seek(SeekMode::Accurate, seek_to)?;
+
+        self.passthrough_started = false;
+        self.recent_bitrates.clear();
+        self.recent_frames = 0;
+        // Drop any samples decoded before the seek. The format may have seeked
+        // to before the requested position so decode and skip the frames in
+        // between.
+        self.decoder.reset();
+        let mut frames_to_skip = pos.required_ts.saturating_sub(pos.actual_ts);
+        loop {
+            match self.decode_next_packet() {
+                Ok(changed) => {
+                    self.metadata_changed |= changed;
+                    let frames = self.decoder.last_decoded().frames() as u64;
+                    if frames_to_skip < frames {
+                        self.next_sample_idx = frames_to_skip as usize;
+                        break;
+                    }
+                    frames_to_skip -= frames;
+                }
+                Err(e) => {
+                    self.next_channel_idx = 0;
+                    self.next_sample_idx = self.decoder.last_decoded().frames();
+                    self.emitted_frames = pos.required_ts;
+                    // The stream ending before the position leaves the decoder
+                    // finished
+                    if is_end_of_stream(&e) {
+                        break;
+                    }
+                    return Err(e.into());
+                }
+            }
+        }
+
+        self.emitted_frames = pos.required_ts;
+        Ok(utils::num_samples_to_duration(
+            pos.required_ts,
+            1,
+            sample_rate,
+        ))
+    }
+
+    /// PCM frames are decoded independently so seeking is sample exact.
+    /// Other codecs restart decoding at a packet boundary so the first packet
+    /// after a seek may not decode exactly as it would during continuous
+    /// playback.
+    fn seek_granularity(&self) -> Option<Duration> {
+        let frames = self.frames_per_packet()?;
+        let sample_rate = self
+            .decoder
+            .codec_params()
+            .sample_rate
+            .filter(|rate| *rate > 0)?;
+        Some(utils::num_samples_to_duration(frames, 1, sample_rate))
+    }
+
+    fn frame_alignment(&self) -> Option<u64> {
+        self.frames_per_packet()
+    }
 
-        Ok(Duration::from_millis(current_duration))
+    fn emitted_frame_index(&self) -> u64 {
+        self.emitted_frames
+    }
+
+    fn progress(&self) -> Option<f32> {
+        if !self.source_seekable {
+            return None;
+        }
+        let n_frames = self.decoder.codec_params().n_frames?;
+        if n_frames == 0 {
+            return None;
+        }
+        Some((self.emitted_frames as f64 / n_frames as f64).clamp(0.0, 1.0) as f32)
+    }
+
+    fn samples_remaining(&self) -> Option<u64> {
+        if self.looping {
+            return None;
+        }
+        let mut frames = self.decoder.codec_params().n_frames?;
+        if let Some(end) = self.end {
+            frames = frames.min(utils::duration_to_num_samples(end, 1, self.sample_rate));
+        }
+        let channel_count = self.channels.count();
+        // Channels of the current frame already returned. The frame is only
+        // counted in emitted_frames once all of its channels have been.
+        let returned_channels = self.next_channel_idx as usize % channel_count.max(1);
+        let samples = frames.saturating_sub(self.emitted_frames) * channel_count as u64;
+        Some(samples.saturating_sub(returned_channels as u64))
+    }
+
+    fn codec_extra_data(&self) -> Option<&[u8]> {
+        self.decoder.codec_params().extra_data.as_deref()
+    }
+
+    fn set_looping(&mut self, enabled: bool) {
+        self.looping = enabled;
+    }
+
+    fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    fn set_end(&mut self, at: Option<Duration>) {
+        self.end = at;
+    }
+
+    fn end(&self) -> Option<Duration> {
+        self.end
     }
 
     fn set_sample_mult(&mut self, mult: f32) {
@@ -163,32 +896,156 @@ impl Sound for SymphoniaDecoder {
 }
 
 impl SymphoniaDecoder {
-    fn decode_next_packet(&mut self) -> Result<bool, Error> {
-        loop {
-            let packet = self.probed.format.next_packet()?;
-            // We don't currently use the metadata but pop it off so it does not take
-            // memory.
+    /// Move to the next sample, decoding the next packet if needed. Returns
+    /// what to return instead if the next value is not a sample.
+    fn prepare_next_sample(&mut self) -> Result<Option<NextSample>, crate::Error> {
+        if self.metadata_changed {
+            self.metadata_changed = false;
+            return Ok(Some(NextSample::MetadataChanged));
+        }
+        if self.next_channel_idx >= self.channels.count().try_into().unwrap() {
+            self.next_channel_idx = 0;
+            self.next_sample_idx += 1;
+        }
+        if self.next_channel_idx == 0 && self.frames_until_end() == Some(0) {
+            if self.looping && self.returned_sample {
+                self.returned_sample = false;
+                self.seek(Duration::ZERO)?;
+                return self.prepare_next_sample();
+            }
+            return Ok(Some(NextSample::Finished));
+        }
+        if self.next_sample_idx >= self.decoder.last_decoded().frames() {
+            match self.decode_next_packet() {
+                Ok(true) => return Ok(Some(NextSample::MetadataChanged)),
+                Ok(false) => (),
+                Err(e) if is_end_of_stream(&e) => {
+                    if self.looping && self.returned_sample {
+                        self.returned_sample = false;
+                        self.seek(Duration::ZERO)?;
+                        return self.prepare_next_sample();
+                    }
+                    return Ok(Some(NextSample::Finished));
+                }
+                // TODO: Handle errors better when awedio allows returning errors.
+                Err(e) => return Err(e.into()),
+            };
+        }
+        Ok(None)
+    }
+
+    /// The number of frames left before the end set with [Sound::set_end] or
+    /// `None` if there is no end.
+    fn frames_until_end(&self) -> Option<u64> {
+        let end = utils::duration_to_num_samples(self.end?, 1, self.sample_rate);
+        Some(end.saturating_sub(self.emitted_frames))
+    }
+
+    /// The number of frames encoded together, 1 for PCM. If the container
+    /// does not give the maximum (e.g. Ogg Opus) it is the most frames decoded
+    /// from a single packet so far, `None` before the first packet.
+    fn frames_per_packet(&self) -> Option<u64> {
+        let params = self.decoder.codec_params();
+        if is_pcm(params) {
+            Some(1)
+        } else if let Some(frames) = params.max_frames_per_packet {
+            Some(frames)
+        } else {
+            Some(self.max_packet_frames).filter(|frames| *frames > 0)
+        }
+    }
+
+    /// Move past the channel just returned, counting the frame once its last
+    /// channel has been returned.
+    fn advance_channel(&mut self) {
+        self.next_channel_idx += 1;
+        if self.next_channel_idx as usize == self.channels.count() {
+            self.emitted_frames += 1;
+        }
+    }
+
+    /// We don't currently use the metadata other than the encoder but pop it
+    /// off so it does not take memory.
+    fn pop_metadata(&mut self) {
+        if !self.probed.format.metadata().is_latest() {
             while !self.probed.format.metadata().is_latest() {
                 self.probed.format.metadata().pop();
             }
+            if let Some(encoder) = find_encoder(&mut self.probed) {
+                self.encoder = Some(encoder);
+            }
+        }
+    }
+
+    fn decode_next_packet(&mut self) -> Result<bool, Error> {
+        if self.diagnostics.is_none() {
+            return self.decode_packet();
+        }
+        let start = Instant::now();
+        let result = self.decode_packet();
+        if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.record(start.elapsed());
+        }
+        result
+    }
+
+    fn decode_packet(&mut self) -> Result<bool, Error> {
+        loop {
+            let packet = self.probed.format.next_packet()?;
+            self.pop_metadata();
             if packet.track_id() != self.track_id {
                 continue;
             }
 
+            let codec = self.decoder.codec_params().codec;
+            let header_bitrate =
+                if [CODEC_TYPE_MP1, CODEC_TYPE_MP2, CODEC_TYPE_MP3].contains(&codec) {
+                    mpeg_audio_bitrate(packet.buf())
+                } else {
+                    None
+                };
+            let packet_bytes = packet.buf().len() as u64;
+
             // According to the Symphonia, some errors are indeed recoverable:
             let buf_ref = match self.decoder.decode(&packet) {
                 Ok(buf_ref) => buf_ref,
                 // Recoverable, but this packet is void. Expect weird noises!
                 Err(Error::DecodeError(e)) => {
                     log::warn!("DecodeError while decoding stream: {}", e);
+                    self.recovered_errors += 1;
                     continue;
                 }
                 // Reset required, which is handled correctly by this decoder
-                Err(Error::ResetRequired) => continue,
+                Err(Error::ResetRequired) => {
+                    self.recovered_errors += 1;
+                    continue;
+                }
                 // All other errors are unrecoverable
                 Err(e) => return Err(e),
             };
 
+            let frames = buf_ref.frames() as u64;
+            self.max_packet_frames = self.max_packet_frames.max(frames);
+            if frames > 0 {
+                let bitrate = header_bitrate
+                    .map(u64::from)
+                    .unwrap_or_else(|| packet_bytes * 8 * buf_ref.spec().rate as u64 / frames);
+                self.bitrate_sum += bitrate * frames;
+                self.bitrate_frames += frames;
+
+                let window_frames = RECENT_BITRATE_WINDOW.as_secs() * buf_ref.spec().rate as u64;
+                self.recent_bitrates.push_back((bitrate, frames));
+                self.recent_frames += frames;
+                while let Some((_, oldest_frames)) = self.recent_bitrates.front() {
+                    if self.recent_frames - oldest_frames < window_frames {
+                        break;
+                    }
+                    self.recent_frames -= oldest_frames;
+                    self.recent_bitrates.pop_front();
+                }
+            }
+
+            self.last_packet = Some(packet);
             self.next_channel_idx = 0;
             self.next_sample_idx = 0;
             let mut metadata_changed = false;
@@ -205,6 +1062,19 @@ impl SymphoniaDecoder {
     }
 }
 
+/// According to Symphonia this is the only way to detect an end of stream.
+fn is_end_of_stream(err: &Error) -> bool {
+    matches!(err, Error::IoError(err)
+        if err.kind() == std::io::ErrorKind::UnexpectedEof && err.to_string() == "end of stream")
+}
+
+/// Whether `params` are for uncompressed PCM audio.
+fn is_pcm(params: &CodecParameters) -> bool {
+    CODEC_REGISTRY
+        .get_codec(params.codec)
+        .is_some_and(|d| d.short_name.starts_with("pcm"))
+}
+
 pub fn extract_sample_from_ref(
     buffer: &AudioBufferRef,
     channel_idx: u16,
@@ -224,6 +1094,67 @@ pub fn extract_sample_from_ref(
     }
 }
 
+fn extract_f32_sample_from_ref(
+    buffer: &AudioBufferRef,
+    channel_idx: u16,
+    sample_idx: usize,
+) -> f32 {
+    let channel_idx = channel_idx as usize;
+    match buffer {
+        AudioBufferRef::U8(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+        AudioBufferRef::U16(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+        AudioBufferRef::U24(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+        AudioBufferRef::U32(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+        AudioBufferRef::S8(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+        AudioBufferRef::S16(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+        AudioBufferRef::S24(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+        AudioBufferRef::S32(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+        AudioBufferRef::F32(buffer) => buffer.chan(channel_idx)[sample_idx],
+        AudioBufferRef::F64(buffer) => f32::from_sample(buffer.chan(channel_idx)[sample_idx]),
+    }
+}
+
+/// Copy frames starting at `start` of `channel_idx` into `out`.
+fn copy_f32_channel_from_ref(
+    buffer: &AudioBufferRef,
+    channel_idx: usize,
+    start: usize,
+    out: &mut [f32],
+) {
+    match buffer {
+        AudioBufferRef::U8(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+        AudioBufferRef::U16(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+        AudioBufferRef::U24(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+        AudioBufferRef::U32(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+        AudioBufferRef::S8(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+        AudioBufferRef::S16(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+        AudioBufferRef::S24(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+        AudioBufferRef::S32(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+        AudioBufferRef::F32(buffer) => {
+            out.copy_from_slice(&buffer.chan(channel_idx)[start..start + out.len()])
+        }
+        AudioBufferRef::F64(buffer) => copy_f32_channel(buffer, channel_idx, start, out),
+    }
+}
+
+fn copy_f32_channel<S: Sample>(
+    buffer: &AudioBuffer<S>,
+    channel_idx: usize,
+    start: usize,
+    out: &mut [f32],
+) where
+    f32: FromSample<S>,
+{
+    let samples = &buffer.chan(channel_idx)[start..start + out.len()];
+    for (out_sample, sample) in out.iter_mut().zip(samples) {
+        *out_sample = f32::from_sample(*sample);
+    }
+}
+
+fn invalid_input(msg: String) -> crate::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, msg).into()
+}
+
 pub fn extract_sample<S: Sample>(
     buffer: &AudioBuffer<S>,
     channel_idx: u16,
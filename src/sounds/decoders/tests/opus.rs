@@ -0,0 +1,319 @@
+use std::time::Duration;
+
+use super::*;
+use audiopus::{coder::Encoder, Application, Bitrate};
+
+const FRAMES_PER_PACKET: usize = 48000 / AUDIO_FRAME_RATE;
+
+fn encode_sine(num_packets: usize) -> Vec<Vec<u8>> {
+    encode_sine_with_frames(num_packets, FRAMES_PER_PACKET)
+}
+
+fn encode_sine_with_frames(num_packets: usize, frames_per_packet: usize) -> Vec<Vec<u8>> {
+    let mut encoder =
+        Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Voip).unwrap();
+    encoder.set_bitrate(Bitrate::BitsPerSecond(32000)).unwrap();
+    encoder.set_inband_fec(true).unwrap();
+    encoder.set_packet_loss_perc(25).unwrap();
+    let mut packets = Vec::new();
+    for packet_idx in 0..num_packets {
+        let mut input = Vec::with_capacity(frames_per_packet * 2);
+        for i in 0..frames_per_packet {
+            let t = (packet_idx * frames_per_packet + i) as f32 / 48000.0;
+            let value = 0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+            input.push(value);
+            input.push(value);
+        }
+        let mut output = vec![0; 4000];
+        let len = encoder.encode_float(&input, &mut output).unwrap();
+        output.truncate(len);
+        packets.push(output);
+    }
+    packets
+}
+
+fn new_decoder() -> OpusDecoder {
+    let mut params = CodecParameters::new();
+    params.for_codec(CODEC_TYPE_OPUS).with_sample_rate(48000);
+    OpusDecoder::try_new(&params, &DecoderOptions::default()).unwrap()
+}
+
+fn decode(decoder: &mut OpusDecoder, packet: &[u8]) -> Vec<f32> {
+    let packet = Packet::new_from_slice(0, 0, FRAMES_PER_PACKET as u64, packet);
+    let AudioBufferRef::F32(buf) = decoder.decode(&packet).unwrap() else {
+        unreachable!()
+    };
+    buf.chan(0).to_vec()
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[test]
+fn fec_recovers_lost_packet() {
+    let packets = encode_sine(20);
+    let lost_idx = 10;
+
+    let mut reference = new_decoder();
+    let mut expected = Vec::new();
+    for packet in &packets[..=lost_idx] {
+        expected = decode(&mut reference, packet);
+    }
+
+    let mut decoder = new_decoder();
+    for packet in &packets[..lost_idx] {
+        assert_eq!(decode(&mut decoder, packet).len(), FRAMES_PER_PACKET);
+    }
+    decoder.signal_packet_loss();
+    let output = decode(&mut decoder, &packets[lost_idx + 1]);
+    assert_eq!(output.len(), FRAMES_PER_PACKET * 2);
+
+    let (recovered, current) = output.split_at(FRAMES_PER_PACKET);
+    assert!(recovered.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+    let recovered_rms = rms(recovered);
+    let expected_rms = rms(&expected);
+    assert!(
+        recovered_rms > expected_rms * 0.5 && recovered_rms < expected_rms * 1.5,
+        "recovered rms {recovered_rms}, expected rms {expected_rms}"
+    );
+    assert!(rms(current) > expected_rms * 0.5);
+
+    // Decoding continues normally afterward
+    assert_eq!(
+        decode(&mut decoder, &packets[lost_idx + 2]).len(),
+        FRAMES_PER_PACKET
+    );
+}
+
+#[test]
+fn empty_packet_conceals_loss() {
+    let packets = encode_sine(5);
+    let mut decoder = new_decoder();
+    for packet in &packets {
+        decode(&mut decoder, packet);
+    }
+    let concealed = decode(&mut decoder, &[]);
+    assert_eq!(concealed.len(), FRAMES_PER_PACKET);
+    assert!(concealed.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+}
+
+#[test]
+fn resize_during_decode_emits_only_decoded_frames() {
+    let long_frames = FRAMES_PER_PACKET * 3;
+    let encoder = Encoder::new(SampleRate::Hz48000, Channels::Stereo, Application::Audio).unwrap();
+    let mut input = Vec::with_capacity(long_frames * 2);
+    for i in 0..long_frames {
+        let t = i as f32 / 48000.0;
+        input.push(0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin());
+        input.push(0.25 * (2.0 * std::f32::consts::PI * 660.0 * t).sin());
+    }
+    let mut long_packet = vec![0; 4000];
+    let len = encoder.encode_float(&input, &mut long_packet).unwrap();
+    long_packet.truncate(len);
+    let short_packets = encode_sine(3);
+
+    let decode_stereo = |decoder: &mut OpusDecoder, packet: &[u8]| {
+        let packet = Packet::new_from_slice(0, 0, 0, packet);
+        let AudioBufferRef::F32(buf) = decoder.decode(&packet).unwrap() else {
+            unreachable!()
+        };
+        (buf.chan(0).to_vec(), buf.chan(1).to_vec())
+    };
+
+    // Already large enough so no resize happens
+    let mut reference = new_decoder();
+    reference.reserve(long_frames);
+    for packet in &short_packets {
+        decode_stereo(&mut reference, packet);
+    }
+    let expected = decode_stereo(&mut reference, &long_packet);
+    assert_eq!(expected.0.len(), long_frames);
+
+    let mut decoder = new_decoder();
+    for packet in &short_packets {
+        assert_eq!(
+            decode_stereo(&mut decoder, packet).0.len(),
+            FRAMES_PER_PACKET
+        );
+    }
+    let initial_len = decoder.rawbuf.len();
+    let output = decode_stereo(&mut decoder, &long_packet);
+    assert!(decoder.rawbuf.len() > initial_len);
+    assert_eq!(output.0.len(), long_frames);
+    assert_eq!(output.1.len(), long_frames);
+    assert_eq!(output, expected);
+
+    // A shorter packet after the resize must not include stale frames
+    let expected_short = decode_stereo(&mut reference, &short_packets[0]);
+    let output_short = decode_stereo(&mut decoder, &short_packets[0]);
+    assert_eq!(output_short.0.len(), FRAMES_PER_PACKET);
+    assert_eq!(output_short, expected_short);
+}
+
+/// CRC-32 of an Ogg page (polynomial 0x04c11db7, not reflected).
+fn ogg_crc(data: &[u8]) -> u32 {
+    let mut crc = 0_u32;
+    for byte in data {
+        crc ^= (*byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// An Ogg page containing a single packet.
+fn ogg_page(header_type: u8, granule: u64, sequence: u32, packet: &[u8]) -> Vec<u8> {
+    let mut lacing = vec![255; packet.len() / 255];
+    lacing.push((packet.len() % 255) as u8);
+    let mut page = b"OggS\0".to_vec();
+    page.push(header_type);
+    page.extend(granule.to_le_bytes());
+    page.extend(1_u32.to_le_bytes());
+    page.extend(sequence.to_le_bytes());
+    page.extend([0; 4]);
+    page.push(lacing.len() as u8);
+    page.extend(lacing);
+    page.extend(packet);
+    let crc = ogg_crc(&page);
+    page[22..26].copy_from_slice(&crc.to_le_bytes());
+    page
+}
+
+/// An Ogg Opus file of stereo 48kHz `packets` with `output_gain` in Q7.8 dB
+/// in its identification header.
+fn ogg_opus_bytes(output_gain: i16, packets: &[Vec<u8>]) -> Vec<u8> {
+    ogg_opus_bytes_with_frames(output_gain, packets, FRAMES_PER_PACKET)
+}
+
+/// Same as [ogg_opus_bytes] for packets of `frames_per_packet` frames.
+fn ogg_opus_bytes_with_frames(
+    output_gain: i16,
+    packets: &[Vec<u8>],
+    frames_per_packet: usize,
+) -> Vec<u8> {
+    let mut head = b"OpusHead\x01\x02".to_vec();
+    head.extend(0_u16.to_le_bytes());
+    head.extend(48000_u32.to_le_bytes());
+    head.extend(output_gain.to_le_bytes());
+    head.push(0);
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend(0_u32.to_le_bytes());
+    tags.extend(0_u32.to_le_bytes());
+
+    let mut file = ogg_page(0x02, 0, 0, &head);
+    file.extend(ogg_page(0, 0, 1, &tags));
+    for (idx, packet) in packets.iter().enumerate() {
+        let header_type = if idx + 1 == packets.len() { 0x04 } else { 0 };
+        let granule = ((idx + 1) * frames_per_packet) as u64;
+        file.extend(ogg_page(header_type, granule, idx as u32 + 2, packet));
+    }
+    file
+}
+
+#[test]
+fn output_gain_is_applied() {
+    let packets = encode_sine(20);
+    let decode_file = |output_gain: i16| {
+        let file = ogg_opus_bytes(output_gain, &packets);
+        let mut decoder = crate::sounds::decoders::SymphoniaDecoder::new(
+            Box::new(std::io::Cursor::new(file)),
+            Some("opus"),
+        )
+        .unwrap();
+        let gain_db = decoder.opus_output_gain_db().unwrap();
+        assert!((gain_db - output_gain as f32 / 256.0).abs() < 1e-6);
+        let mut samples = vec![0.0; 20 * FRAMES_PER_PACKET * 2];
+        let len = crate::Sound::next_samples_f32(&mut decoder, &mut samples).unwrap();
+        samples.truncate(len);
+        samples
+    };
+    let unchanged = decode_file(0);
+    // -6.02dB halves the amplitude
+    let quieter = decode_file(-1541);
+    assert_eq!(unchanged.len(), quieter.len());
+    assert!(unchanged.len() > 10 * FRAMES_PER_PACKET * 2);
+    let ratio = rms(&quieter) / rms(&unchanged);
+    assert!((ratio - 0.5).abs() < 0.01, "{ratio}");
+
+    // The 19 byte OpusHead after the 28 byte header of the first page
+    let head = &ogg_opus_bytes(-1541, &[])[28..47];
+    let mut params = CodecParameters::new();
+    params
+        .for_codec(CODEC_TYPE_OPUS)
+        .with_sample_rate(48000)
+        .with_extra_data(head.into());
+    let decoder = OpusDecoder::try_new(&params, &DecoderOptions::default()).unwrap();
+    assert!((decoder.output_gain_db() + 6.02).abs() < 0.01);
+    assert_eq!(new_decoder().output_gain_db(), 0.0);
+}
+
+#[test]
+fn packet_loss_signaled_through_symphonia_decoder() {
+    let mut packets = encode_sine(20);
+    // The source dropped packet 10
+    packets.remove(10);
+    let file = ogg_opus_bytes(0, &packets);
+    let decode_file = |signal_loss: bool| {
+        let mut decoder = crate::sounds::decoders::SymphoniaDecoder::new(
+            Box::new(std::io::Cursor::new(file.clone())),
+            Some("opus"),
+        )
+        .unwrap();
+        let mut samples = vec![0.0; 10 * FRAMES_PER_PACKET * 2];
+        // The first packet has been decoded when the decoder is created
+        let len = crate::Sound::next_samples_f32(&mut decoder, &mut samples).unwrap();
+        assert_eq!(len, samples.len());
+        if signal_loss {
+            assert!(decoder.signal_packet_loss());
+        }
+        let len = crate::Sound::next_samples_f32(&mut decoder, &mut samples).unwrap();
+        samples.truncate(len);
+        samples
+    };
+    let without = decode_file(false);
+    let recovered = decode_file(true);
+    // The lost packet's frames are played before the next packet
+    assert_eq!(recovered.len(), without.len() + FRAMES_PER_PACKET * 2);
+    assert!(recovered.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+    assert!(rms(&recovered[..FRAMES_PER_PACKET * 2]) > 0.1);
+}
+
+#[test]
+fn signal_packet_loss_of_wav_does_nothing() {
+    let wav = crate::tests::wav_bytes(1, 8000, &[0; 100]);
+    let mut decoder = crate::sounds::decoders::SymphoniaDecoder::new(
+        Box::new(std::io::Cursor::new(wav)),
+        Some("wav"),
+    )
+    .unwrap();
+    assert!(!decoder.signal_packet_loss());
+    assert_eq!(decoder.opus_output_gain_db(), None);
+}
+
+#[test]
+fn seek_granularity_is_packet_duration() {
+    use crate::sounds::decoders::SymphoniaDecoder;
+    use crate::Sound;
+
+    let wav = crate::tests::wav_bytes(1, 48000, &[0; 4800]);
+    let wav = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), Some("wav")).unwrap();
+    let wav_granularity = wav.seek_granularity().unwrap();
+    assert!(wav_granularity < Duration::from_micros(100));
+
+    for frames in [FRAMES_PER_PACKET, 2 * FRAMES_PER_PACKET] {
+        let packets = encode_sine_with_frames(5, frames);
+        let file = ogg_opus_bytes_with_frames(0, &packets, frames);
+        let decoder =
+            SymphoniaDecoder::new(Box::new(std::io::Cursor::new(file)), Some("opus")).unwrap();
+        let granularity = decoder.seek_granularity().unwrap();
+        assert_eq!(granularity, Duration::from_millis(frames as u64 / 48));
+        assert_eq!(decoder.frame_alignment(), Some(frames as u64));
+        assert!(granularity > wav_granularity);
+    }
+}
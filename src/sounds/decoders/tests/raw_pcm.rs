@@ -0,0 +1,83 @@
+use super::*;
+
+fn decode_all(bytes: &[u8], format: PcmFormat) -> Vec<i16> {
+    let mut decoder = RawPcmDecoder::new(bytes, 1, 8000, format).unwrap();
+    let mut samples = Vec::new();
+    while let NextSample::Sample(s) = decoder.next_sample().unwrap() {
+        samples.push(s);
+    }
+    samples
+}
+
+#[test]
+fn unsigned_8_bit() {
+    let samples = decode_all(&[0, 64, 128, 192, 255], PcmFormat::U8);
+    assert_eq!(samples, vec![-32768, -16384, 0, 16384, 32512]);
+}
+
+#[test]
+fn signed_8_bit() {
+    let format = PcmFormat {
+        signed: true,
+        ..PcmFormat::U8
+    };
+    let samples = decode_all(&[0x80, 0xC0, 0, 0x40, 0x7F], format);
+    assert_eq!(samples, vec![-32768, -16384, 0, 16384, 32512]);
+}
+
+#[test]
+fn big_endian_16_bit() {
+    let expected: Vec<i16> = vec![0, 1, -1, 256, i16::MAX, i16::MIN, 12345];
+    let bytes: Vec<u8> = expected.iter().flat_map(|s| s.to_be_bytes()).collect();
+    assert_eq!(decode_all(&bytes, PcmFormat::S16BE), expected);
+    let bytes: Vec<u8> = expected.iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_all(&bytes, PcmFormat::S16LE), expected);
+}
+
+#[test]
+fn unsigned_16_bit_big_endian() {
+    let format = PcmFormat {
+        signed: false,
+        ..PcmFormat::S16BE
+    };
+    let samples = decode_all(&[0x00, 0x00, 0x80, 0x00, 0xFF, 0xFF], format);
+    assert_eq!(samples, vec![i16::MIN, 0, i16::MAX]);
+}
+
+#[test]
+fn wide_samples_keep_most_significant_bits() {
+    let format = PcmFormat {
+        bits_per_sample: 24,
+        signed: true,
+        endianness: Endianness::Big,
+    };
+    assert_eq!(
+        decode_all(&[0x12, 0x34, 0x56, 0xFF, 0xFF, 0xFF], format),
+        vec![0x1234, -1]
+    );
+    let format = PcmFormat {
+        bits_per_sample: 32,
+        signed: true,
+        endianness: Endianness::Little,
+    };
+    assert_eq!(
+        decode_all(&(-0x1234_5678_i32).to_le_bytes(), format),
+        vec![(-0x1234_5678_i32 >> 16) as i16]
+    );
+}
+
+#[test]
+fn partial_sample_at_end_is_dropped() {
+    assert_eq!(decode_all(&[0, 1, 2], PcmFormat::S16LE), vec![256]);
+}
+
+#[test]
+fn invalid_parameters_are_errors() {
+    let format = PcmFormat {
+        bits_per_sample: 12,
+        ..PcmFormat::S16LE
+    };
+    assert!(RawPcmDecoder::new(&[][..], 1, 8000, format).is_err());
+    assert!(RawPcmDecoder::new(&[][..], 0, 8000, PcmFormat::S16LE).is_err());
+    assert!(RawPcmDecoder::new(&[][..], 1, 0, PcmFormat::S16LE).is_err());
+}
@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use super::*;
 use crate::NextSample;
 
@@ -48,3 +50,890 @@ fn samples_of_test_file() -> std::io::Result<()> {
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
     Ok(())
 }
+
+#[test]
+fn reopen_with_different_format() {
+    const WAV_FILE: &[u8] = include_bytes!("audiocheck.net_sin_1000Hz_0dBFS_0.1s.wav");
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(SINE_WAVE_FILE)), None).unwrap();
+    assert_eq!(
+        decoder.decoder.codec_params().codec,
+        symphonia::core::codecs::CODEC_TYPE_MP3
+    );
+    assert!(matches!(
+        decoder.next_sample().unwrap(),
+        NextSample::Sample(_)
+    ));
+
+    decoder
+        .reopen(Box::new(std::io::Cursor::new(WAV_FILE)), Some("wav"))
+        .unwrap();
+    assert_eq!(
+        decoder.decoder.codec_params().codec,
+        symphonia::core::codecs::CODEC_TYPE_PCM_S16LE
+    );
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(decoder.sample_rate(), 44100);
+    assert_eq!(decoder.channel_count(), 1);
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(4647));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(9201));
+}
+
+#[test]
+fn reopen_clears_end() {
+    let samples: Vec<i16> = (0..800).collect();
+    let wav = crate::tests::wav_bytes(1, 8000, &samples);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav.clone())), Some("wav")).unwrap();
+    decoder.set_end(Some(Duration::from_millis(10)));
+    assert_eq!(crate::tests::collect(&mut decoder).len(), 80);
+    decoder.set_looping(true);
+
+    decoder
+        .reopen(Box::new(std::io::Cursor::new(wav)), Some("wav"))
+        .unwrap();
+    assert_eq!(decoder.end(), None);
+    assert!(decoder.is_looping());
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::MetadataChanged);
+    decoder.set_looping(false);
+    assert_eq!(crate::tests::collect(&mut decoder), samples);
+}
+
+#[test]
+fn seek_returns_position() {
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(SINE_WAVE_FILE)), None).unwrap();
+    let position = decoder.seek(Duration::from_millis(50)).unwrap();
+    assert!(position <= Duration::from_millis(50));
+}
+
+#[test]
+fn seek_is_sample_accurate() {
+    // Symphonia reads WAV in packets of many frames so this lands within one
+    let samples: Vec<i16> = (0..8000).map(|i| i as i16).collect();
+    let wav = crate::tests::wav_bytes(2, 8000, &samples);
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    let position = decoder.seek(Duration::from_micros(312_500)).unwrap();
+    assert_eq!(position, Duration::from_micros(312_500));
+    assert_eq!(decoder.emitted_frame_index(), 2500);
+    assert_eq!(crate::tests::collect(&mut decoder), samples[5000..]);
+}
+
+#[test]
+fn seek_of_mp3_is_sample_accurate() {
+    let mut reference =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(SINE_WAVE_FILE)), None).unwrap();
+    let reference = crate::tests::collect(&mut reference);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(SINE_WAVE_FILE)), None).unwrap();
+    let position = decoder.seek(Duration::from_millis(50)).unwrap();
+    assert_eq!(position, Duration::from_millis(50));
+    assert_eq!(decoder.emitted_frame_index(), 2205);
+    let samples = crate::tests::collect(&mut decoder);
+    assert_eq!(samples.len(), reference.len() - 2205);
+    // The first packet decoded after the seek differs as the decoder starts
+    // without the overlap from the packet before it
+    assert_eq!(samples[1152..], reference[2205 + 1152..]);
+}
+
+#[test]
+fn samples_remaining_of_wav() {
+    let samples: Vec<i16> = (0..200).collect();
+    let wav = crate::tests::wav_bytes(2, 8000, &samples);
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    assert_eq!(decoder.samples_remaining(), Some(200));
+    // Partway through a frame
+    for _ in 0..31 {
+        decoder.next_sample().unwrap();
+    }
+    assert_eq!(decoder.samples_remaining(), Some(169));
+
+    let position = decoder.seek(Duration::from_millis(5)).unwrap();
+    assert_eq!(position, Duration::from_millis(5));
+    assert_eq!(decoder.samples_remaining(), Some(120));
+    decoder.set_end(Some(Duration::from_millis(10)));
+    assert_eq!(decoder.samples_remaining(), Some(80));
+    let mut count = 0;
+    while let NextSample::Sample(_) = decoder.next_sample().unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 80);
+    assert_eq!(decoder.samples_remaining(), Some(0));
+
+    decoder.set_looping(true);
+    assert_eq!(decoder.samples_remaining(), None);
+}
+
+#[test]
+fn seek_in_zero_length_stream() {
+    let wav = crate::tests::wav_bytes(1, 8000, &[]);
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    assert_eq!(decoder.decoder.codec_params().n_frames, Some(0));
+    assert_eq!(
+        decoder.seek(Duration::from_secs(1)).unwrap(),
+        Duration::ZERO
+    );
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+/// Hides the sample rate of the decoder it wraps.
+struct WithoutSampleRate {
+    inner: Box<dyn Decoder>,
+    params: CodecParameters,
+}
+
+impl Decoder for WithoutSampleRate {
+    fn try_new(_: &CodecParameters, _: &DecoderOptions) -> symphonia::core::errors::Result<Self> {
+        unimplemented!()
+    }
+
+    fn supported_codecs() -> &'static [symphonia::core::codecs::CodecDescriptor] {
+        &[]
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset()
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        &self.params
+    }
+
+    fn decode(&mut self, packet: &Packet) -> symphonia::core::errors::Result<AudioBufferRef<'_>> {
+        self.inner.decode(packet)
+    }
+
+    fn finalize(&mut self) -> symphonia::core::codecs::FinalizeResult {
+        self.inner.finalize()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.inner.last_decoded()
+    }
+}
+
+#[test]
+fn seek_without_sample_rate_is_error() {
+    let samples: Vec<i16> = (0..100).collect();
+    let wav = crate::tests::wav_bytes(1, 8000, &samples);
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(0));
+    let inner = CODEC_REGISTRY
+        .make(decoder.decoder.codec_params(), &DecoderOptions::default())
+        .unwrap();
+    let mut params = inner.codec_params().clone();
+    params.sample_rate = None;
+    decoder.decoder = TrackDecoder::Other(Box::new(WithoutSampleRate { inner, params }));
+
+    let result = decoder.seek(Duration::from_millis(5));
+    assert!(result.unwrap_err().to_string().contains("sample rate"));
+    // The only packet was read before the seek so the format was not seeked
+    assert!(decoder.probed.format.next_packet().is_err());
+    assert_eq!(decoder.emitted_frame_index(), 1);
+}
+
+/// Fails to decode any packet after it has been reset.
+struct FailAfterReset {
+    inner: Box<dyn Decoder>,
+    reset: bool,
+}
+
+impl Decoder for FailAfterReset {
+    fn try_new(_: &CodecParameters, _: &DecoderOptions) -> symphonia::core::errors::Result<Self> {
+        unimplemented!()
+    }
+
+    fn supported_codecs() -> &'static [symphonia::core::codecs::CodecDescriptor] {
+        &[]
+    }
+
+    fn reset(&mut self) {
+        self.reset = true;
+        self.inner.reset()
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        self.inner.codec_params()
+    }
+
+    fn decode(&mut self, packet: &Packet) -> symphonia::core::errors::Result<AudioBufferRef<'_>> {
+        if self.reset {
+            return Err(Error::Unsupported("failing after reset"));
+        }
+        self.inner.decode(packet)
+    }
+
+    fn finalize(&mut self) -> symphonia::core::codecs::FinalizeResult {
+        self.inner.finalize()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.inner.last_decoded()
+    }
+}
+
+#[test]
+fn seek_returns_decode_error() {
+    let samples: Vec<i16> = (0..100).collect();
+    let wav = crate::tests::wav_bytes(1, 8000, &samples);
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    let inner = CODEC_REGISTRY
+        .make(decoder.decoder.codec_params(), &DecoderOptions::default())
+        .unwrap();
+    decoder.decoder = TrackDecoder::Other(Box::new(FailAfterReset {
+        inner,
+        reset: false,
+    }));
+
+    let result = decoder.seek(Duration::from_millis(5));
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("failing after reset"));
+}
+
+#[test]
+fn scan_peak_of_half_scale_source() {
+    let samples: Vec<i16> = (0..8000)
+        .map(|i| ((i as f32 * 0.05).sin() * 16384.0) as i16)
+        .collect();
+    let bytes = crate::tests::wav_bytes(1, 8000, &samples);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(bytes)), Some("wav")).unwrap();
+    decoder.next_sample().unwrap();
+    decoder.next_sample().unwrap();
+    let peak = decoder.scan_peak().unwrap();
+    assert!((peak - 0.5).abs() < 0.01, "{peak}");
+    assert_eq!(
+        decoder.next_sample().unwrap(),
+        NextSample::Sample(samples[0])
+    );
+    assert_eq!(
+        decoder.next_sample().unwrap(),
+        NextSample::Sample(samples[1])
+    );
+}
+
+const FLAC_SAMPLES: [i16; 16] = [
+    0, 100, 200, 300, 400, 500, 600, 700, 800, 900, 1000, 1100, 1200, 1300, 1400, 1500,
+];
+
+/// The STREAMINFO block of a mono, 44100 Hz, 16 bit FLAC file with
+/// FLAC_SAMPLES in a single frame.
+fn flac_stream_info() -> Vec<u8> {
+    let mut stream_info = Vec::new();
+    // Min and max block size
+    stream_info.extend_from_slice(&16u16.to_be_bytes());
+    stream_info.extend_from_slice(&16u16.to_be_bytes());
+    // Unknown min and max frame size
+    stream_info.extend_from_slice(&[0; 6]);
+    // Sample rate, channel count - 1, bits per sample - 1 and sample count
+    let packed: u64 = (44100 << 44) | (15 << 36) | FLAC_SAMPLES.len() as u64;
+    stream_info.extend_from_slice(&packed.to_be_bytes());
+    // Unknown MD5
+    stream_info.extend_from_slice(&[0; 16]);
+    stream_info
+}
+
+fn crc(data: &[u8], poly: u16, width: u32) -> u16 {
+    let top_bit = 1 << (width - 1);
+    let mask = ((1u32 << width) - 1) as u16;
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= (*byte as u16) << (width - 8);
+        for _ in 0..8 {
+            crc = if crc & top_bit != 0 {
+                (crc << 1) ^ poly
+            } else {
+                crc << 1
+            };
+        }
+        crc &= mask;
+    }
+    crc
+}
+
+/// A FLAC file with the STREAMINFO block and a single frame containing
+/// FLAC_SAMPLES as a verbatim subframe.
+fn flac_file(stream_info: &[u8]) -> Vec<u8> {
+    let mut file = b"fLaC".to_vec();
+    // Last metadata block of type STREAMINFO
+    file.push(0x80);
+    file.extend_from_slice(&(stream_info.len() as u32).to_be_bytes()[1..]);
+    file.extend_from_slice(stream_info);
+
+    // Sync code, 8 bit block size at the end of the header, sample rate from
+    // STREAMINFO, mono, 16 bits per sample, frame number 0, block size - 1
+    let mut frame = vec![0xFF, 0xF8, 0x60, 0x08, 0x00, FLAC_SAMPLES.len() as u8 - 1];
+    frame.push(crc(&frame, 0x07, 8) as u8);
+    // Verbatim subframe
+    frame.push(0x02);
+    for sample in FLAC_SAMPLES {
+        frame.extend_from_slice(&sample.to_be_bytes());
+    }
+    frame.extend_from_slice(&crc(&frame, 0x8005, 16).to_be_bytes());
+    file.extend_from_slice(&frame);
+    file
+}
+
+#[test]
+fn codec_extra_data_is_setup_header() {
+    let stream_info = flac_stream_info();
+    let decoder = SymphoniaDecoder::new(
+        Box::new(std::io::Cursor::new(flac_file(&stream_info))),
+        Some("flac"),
+    )
+    .unwrap();
+    assert_eq!(decoder.codec_extra_data(), Some(stream_info.as_slice()));
+
+    let mut boxed: Box<dyn Sound> = Box::new(decoder);
+    assert_eq!(boxed.codec_extra_data(), Some(stream_info.as_slice()));
+    for expected in FLAC_SAMPLES {
+        assert_eq!(boxed.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+}
+#[test]
+fn frame_alignment_of_flac_is_block_size() {
+    let decoder = SymphoniaDecoder::new(
+        Box::new(std::io::Cursor::new(flac_file(&flac_stream_info()))),
+        Some("flac"),
+    )
+    .unwrap();
+    assert_eq!(decoder.frame_alignment(), Some(FLAC_SAMPLES.len() as u64));
+
+    let wav = crate::tests::wav_bytes(1, 8000, &[0; 100]);
+    let decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), Some("wav")).unwrap();
+    assert_eq!(decoder.frame_alignment(), Some(1));
+}
+
+#[test]
+fn sound_list_trims_flac_at_block_boundary() {
+    let mut list = crate::sounds::SoundList::new();
+    let flac = SymphoniaDecoder::new(
+        Box::new(std::io::Cursor::new(flac_file(&flac_stream_info()))),
+        Some("flac"),
+    )
+    .unwrap();
+    // 10 frames is nearer the end of the 16 frame block than its start
+    let end = list.add_trimmed(
+        Box::new(flac),
+        Duration::from_micros(10 * 1_000_000 / 44100),
+    );
+    assert_eq!(utils::duration_to_num_samples(end, 1, 44100), 16);
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    for expected in FLAC_SAMPLES {
+        assert_eq!(list.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(list.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn codec_extra_data_of_wav_is_none() {
+    let wav = crate::tests::wav_bytes(1, 8000, &[0; 100]);
+    let decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), Some("wav")).unwrap();
+    assert_eq!(decoder.codec_extra_data(), None);
+}
+
+#[test]
+fn toggle_looping_while_playing() {
+    let wav = crate::tests::wav_bytes(1, 8000, &[1, 2, 3, 4]);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), Some("wav")).unwrap();
+    assert!(!decoder.is_looping());
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(2));
+    decoder.set_looping(true);
+    assert!(decoder.is_looping());
+    for expected in [3, 4, 1, 2, 3, 4, 1] {
+        assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    decoder.set_looping(false);
+    for expected in [2, 3, 4] {
+        assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn looping_empty_track_finishes() {
+    let wav = crate::tests::wav_bytes(1, 8000, &[]);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), Some("wav")).unwrap();
+    decoder.set_looping(true);
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+/// A source that sleeps on every read once `slow` is set.
+struct SlowSource {
+    inner: std::io::Cursor<Vec<u8>>,
+    slow: Arc<AtomicBool>,
+}
+
+impl std::io::Read for SlowSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.slow.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(30));
+        }
+        // Small reads so packets need to read from the source
+        let len = buf.len().min(512);
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+impl std::io::Seek for SlowSource {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl MediaSource for SlowSource {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.inner.get_ref().len() as u64)
+    }
+}
+
+#[test]
+fn decode_diagnostics_report_slow_source() {
+    let slow = Arc::new(AtomicBool::new(false));
+    let source = SlowSource {
+        inner: std::io::Cursor::new(crate::tests::wav_bytes(1, 8000, &[1; 8000])),
+        slow: slow.clone(),
+    };
+    let mut decoder = SymphoniaDecoder::new(Box::new(source), Some("wav")).unwrap();
+    let diagnostics = decoder.enable_decode_diagnostics(Duration::from_millis(20));
+    assert_eq!(diagnostics.packet_count(), 0);
+    assert!(!diagnostics.take_slow());
+
+    slow.store(true, Ordering::Relaxed);
+    while let NextSample::Sample(_) = decoder.next_sample().unwrap() {}
+    assert!(diagnostics.packet_count() > 0);
+    assert!(diagnostics.worst_decode_time() >= Duration::from_millis(30));
+    assert!(diagnostics.average_decode_time() > Duration::ZERO);
+    assert!(diagnostics.slow_decode_count() > 0);
+    assert!(diagnostics.take_slow());
+    assert!(!diagnostics.take_slow());
+
+    diagnostics.reset();
+    assert_eq!(diagnostics.packet_count(), 0);
+    assert_eq!(diagnostics.worst_decode_time(), Duration::ZERO);
+}
+
+#[test]
+fn aac_lc_m4a_is_fully_supported() {
+    // AAC-LC, 44100 Hz, mono
+    let asc = [0x12, 0x08];
+    let frames = vec![crate::tests::silent_aac_frame(); 4];
+    let m4a = crate::tests::m4a_bytes(&asc, 1, 44100, &frames);
+    let decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(m4a)), Some("m4a")).unwrap();
+    assert_eq!(decoder.sample_rate(), 44100);
+    assert!(!decoder.aac_sbr_ignored());
+}
+
+#[test]
+fn he_aac_decodes_core_at_base_rate() {
+    // HE-AAC: SBR object type, 22050 Hz core, mono, 44100 Hz extension,
+    // AAC-LC core object type
+    let asc = [0x2B, 0x89, 0x88, 0x00];
+    let frames = vec![crate::tests::silent_aac_frame(); 4];
+    let m4a = crate::tests::m4a_bytes(&asc, 1, 44100, &frames);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(m4a)), Some("m4a")).unwrap();
+    assert!(decoder.aac_sbr_ignored());
+    // The core is played at its own rate instead of at the container's rate
+    // which would double the speed and pitch.
+    assert_eq!(decoder.sample_rate(), 22050);
+    assert_eq!(decoder.channel_count(), 1);
+    let mut num_samples = 0;
+    while let NextSample::Sample(_) = decoder.next_sample().unwrap() {
+        num_samples += 1;
+    }
+    assert_eq!(num_samples, 4 * 1024);
+}
+
+/// SINE_WAVE_FILE with its ID3v2 tag replaced by one with a `TSSE` frame of
+/// `encoder`.
+fn mp3_with_encoder_tag(encoder: &str) -> Vec<u8> {
+    let syncsafe =
+        |n: usize| -> [u8; 4] { [21, 14, 7, 0].map(|shift| ((n >> shift) & 0x7f) as u8) };
+    let old_tag_len = 10 + u32::from_be_bytes(SINE_WAVE_FILE[6..10].try_into().unwrap()) as usize;
+    let mut frame = b"TSSE".to_vec();
+    frame.extend(syncsafe(encoder.len() + 1));
+    frame.extend([0, 0, 3]);
+    frame.extend(encoder.as_bytes());
+    let mut file = b"ID3\x04\x00\x00".to_vec();
+    file.extend(syncsafe(frame.len()));
+    file.extend(frame);
+    file.extend(&SINE_WAVE_FILE[old_tag_len..]);
+    file
+}
+
+#[test]
+fn encoder_tag_and_bitrate_of_mp3() {
+    let mut decoder = SymphoniaDecoder::new(
+        Box::new(std::io::Cursor::new(mp3_with_encoder_tag("LAME3.100"))),
+        Some("mp3"),
+    )
+    .unwrap();
+    assert_eq!(decoder.encoder(), Some("LAME3.100"));
+    assert_eq!(decoder.bitrate(), Some(64_000));
+    while let NextSample::Sample(_) = decoder.next_sample().unwrap() {}
+    assert_eq!(decoder.bitrate(), Some(64_000));
+
+    let decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(SINE_WAVE_FILE)), None).unwrap();
+    assert_eq!(decoder.encoder(), Some("Lavf60.3.100"));
+}
+
+#[test]
+fn bitrate_of_wav_from_packet_size() {
+    let wav = crate::tests::wav_bytes(2, 8000, &[0; 4000]);
+    let decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    assert_eq!(decoder.encoder(), None);
+    assert_eq!(decoder.bitrate(), Some(2 * 16 * 8000));
+}
+
+/// An MPEG-1 Layer III mono 44.1kHz stream of silent frames with the
+/// bitrate index of each frame from `bitrate_indexes`.
+fn vbr_mp3(bitrate_indexes: &[u8]) -> Vec<u8> {
+    const KBPS: [u32; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    let mut file = Vec::new();
+    for index in bitrate_indexes {
+        let frame_len = 144 * KBPS[*index as usize] * 1000 / 44100;
+        file.extend([0xff, 0xfb, index << 4, 0xc0]);
+        file.resize(file.len() + frame_len as usize - 4, 0);
+    }
+    file
+}
+
+#[test]
+fn current_bitrate_of_vbr_mp3() {
+    // Alternating 96 and 160 kbps frames for a nominal bitrate of 128 kbps
+    // followed by 320 kbps frames.
+    let mut indexes = [7, 10].repeat(50);
+    indexes.extend([14; 50]);
+    let mut decoder = SymphoniaDecoder::new(
+        Box::new(std::io::Cursor::new(vbr_mp3(&indexes))),
+        Some("mp3"),
+    )
+    .unwrap();
+    // 1152 frames per packet so about 38 packets per second
+    for _ in 0..60 * 1152 {
+        assert!(matches!(
+            decoder.next_sample().unwrap(),
+            NextSample::Sample(_)
+        ));
+    }
+    let current = decoder.current_bitrate().unwrap();
+    assert!((120_000..=136_000).contains(&current), "{}", current);
+
+    while let NextSample::Sample(_) = decoder.next_sample().unwrap() {}
+    assert_eq!(decoder.current_bitrate(), Some(320_000));
+    let average = decoder.bitrate().unwrap();
+    assert!((190_000..=200_000).contains(&average), "{}", average);
+}
+
+#[test]
+fn current_bitrate_of_wav_is_none() {
+    let wav = crate::tests::wav_bytes(2, 8000, &[0; 4000]);
+    let decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    assert!(decoder.bitrate().is_some());
+    assert_eq!(decoder.current_bitrate(), None);
+}
+
+/// Decode all of `data` with decode_into and interleave the result.
+fn decode_into_interleaved(data: Vec<u8>, frames_per_buffer: usize) -> (Vec<f32>, u64) {
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(data)), None).unwrap();
+    let channel_count = decoder.channel_count() as usize;
+    let mut buffers = vec![vec![0.0; frames_per_buffer]; channel_count];
+    let mut interleaved = Vec::new();
+    let mut packets = 0;
+    loop {
+        let mut out: Vec<&mut [f32]> = buffers.iter_mut().map(|b| b.as_mut_slice()).collect();
+        let frames = decoder.decode_into(&mut out).unwrap();
+        if frames == 0 {
+            break;
+        }
+        packets += 1;
+        for frame in 0..frames {
+            interleaved.extend(buffers.iter().map(|b| b[frame]));
+        }
+    }
+    (interleaved, packets)
+}
+
+fn decode_f32(data: Vec<u8>) -> Vec<f32> {
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(data)), None).unwrap();
+    let mut samples = vec![0.0; 100_000];
+    let len = decoder.next_samples_f32(&mut samples).unwrap();
+    samples.truncate(len);
+    samples
+}
+
+#[test]
+fn decode_into_matches_standard_decode() {
+    let (decoded, packets) = decode_into_interleaved(SINE_WAVE_FILE.to_vec(), 1152);
+    assert!(packets > 1);
+    assert_eq!(decoded, decode_f32(SINE_WAVE_FILE.to_vec()));
+
+    let samples: Vec<i16> = (0..3000).map(|i| (i * 7) as i16).collect();
+    let wav = crate::tests::wav_bytes(2, 8000, &samples);
+    let (decoded, _) = decode_into_interleaved(wav.clone(), 4096);
+    assert_eq!(decoded.len(), 3000);
+    assert_eq!(decoded, decode_f32(wav));
+}
+
+#[test]
+fn decode_into_rejects_wrong_buffers() {
+    let samples: Vec<i16> = (0..3000).map(|i| (i * 7) as i16).collect();
+    let wav = crate::tests::wav_bytes(2, 8000, &samples);
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    let mut left = vec![0.0; 10];
+    let mut right = vec![0.0; 10];
+    assert!(decoder.decode_into(&mut [&mut left]).is_err());
+    assert!(decoder.decode_into(&mut [&mut left, &mut right]).is_err());
+
+    // The packet was not consumed
+    let mut left = vec![0.0; 4096];
+    let mut right = vec![0.0; 4096];
+    let frames = decoder.decode_into(&mut [&mut left, &mut right]).unwrap();
+    assert!(frames > 10);
+    assert_eq!(left[1], f32::from_sample(14_i16));
+    assert_eq!(right[1], f32::from_sample(21_i16));
+
+    // The rest of a partially played packet is returned
+    decoder.seek(Duration::ZERO).unwrap();
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(0));
+    assert!(decoder.decode_into(&mut [&mut left, &mut right]).is_err());
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(7));
+    assert_eq!(
+        decoder.decode_into(&mut [&mut left, &mut right]).unwrap(),
+        frames - 1
+    );
+    assert_eq!(left[0], f32::from_sample(14_i16));
+}
+
+#[test]
+fn may_block() {
+    let decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(SINE_WAVE_FILE)), None).unwrap();
+    assert!(decoder.may_block());
+}
+
+#[test]
+fn recovered_errors_counts_corrupt_packets() {
+    // Two single channel elements (29 bits each) in a mono stream
+    let element: u64 = 100 << 14;
+    let corrupt = ((element << 35) | (element << 6) | (0b111 << 3))
+        .to_be_bytes()
+        .to_vec();
+    let mut frames = vec![crate::tests::silent_aac_frame(); 10];
+    for idx in [2, 5, 6] {
+        frames[idx] = corrupt.clone();
+    }
+    let m4a = crate::tests::m4a_bytes(&[0x12, 0x08], 1, 44100, &frames);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(m4a)), Some("m4a")).unwrap();
+    assert_eq!(decoder.recovered_errors(), 0);
+    let mut num_samples = 0;
+    while let NextSample::Sample(_) = decoder.next_sample().unwrap() {
+        num_samples += 1;
+    }
+    assert_eq!(decoder.recovered_errors(), 3);
+    assert_eq!(num_samples, 7 * 1024);
+}
+
+#[test]
+fn next_samples_f32_is_not_quantized() {
+    let values: Vec<f32> = (0..100).map(|i| (i as f32 * 0.37).sin() * 0.9).collect();
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16_u32.to_le_bytes());
+    wav.extend_from_slice(&3_u16.to_le_bytes());
+    wav.extend_from_slice(&2_u16.to_le_bytes());
+    wav.extend_from_slice(&8000_u32.to_le_bytes());
+    wav.extend_from_slice(&(8000_u32 * 8).to_le_bytes());
+    wav.extend_from_slice(&8_u16.to_le_bytes());
+    wav.extend_from_slice(&32_u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(&data);
+
+    let mut decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), None).unwrap();
+    assert_eq!(decoder.channel_count(), 2);
+    let mut out = vec![0.0; 64];
+    let mut decoded = Vec::new();
+    loop {
+        let written = decoder.next_samples_f32(&mut out).unwrap();
+        decoded.extend_from_slice(&out[..written]);
+        if written < out.len() {
+            break;
+        }
+    }
+    assert_eq!(decoder.channel_count(), 2);
+    assert_eq!(decoded.len(), values.len());
+    for (decoded, value) in decoded.iter().zip(&values) {
+        assert_eq!(decoded.to_bits(), value.to_bits());
+    }
+    // At least some values fall between the steps of i16
+    assert!(decoded.iter().any(|v| (v * 32768.0).fract() != 0.0));
+}
+
+#[test]
+fn encoded_packets_of_wav_are_the_data_chunk() {
+    let samples: Vec<i16> = (0..5000).map(|i| (i * 7) as i16).collect();
+    let wav = crate::tests::wav_bytes(2, 8000, &samples);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav.clone())), None).unwrap();
+    let mut payload = Vec::new();
+    let mut last_timestamp = None;
+    while let Some(packet) = decoder.next_encoded_packet().unwrap() {
+        let timestamp = decoder.encoded_packet_timestamp().unwrap();
+        assert!(last_timestamp < Some(timestamp));
+        last_timestamp = Some(timestamp);
+        payload.extend(packet);
+    }
+    assert_eq!(payload, wav[44..]);
+    assert_eq!(decoder.next_encoded_packet().unwrap(), None);
+}
+
+#[test]
+fn encoded_packets_of_m4a_are_the_aac_frames() {
+    let frames: Vec<Vec<u8>> = (0..6)
+        .map(|i| {
+            let mut frame = crate::tests::silent_aac_frame();
+            // Padding so each frame is distinguishable
+            frame.extend(std::iter::repeat_n(0, i));
+            frame
+        })
+        .collect();
+    let m4a = crate::tests::m4a_bytes(&[0x12, 0x08], 1, 44100, &frames);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(m4a)), Some("m4a")).unwrap();
+    let mut packets = Vec::new();
+    let mut timestamps = Vec::new();
+    while let Some(packet) = decoder.next_encoded_packet().unwrap() {
+        packets.push(packet);
+        timestamps.push(decoder.encoded_packet_timestamp().unwrap());
+    }
+    assert_eq!(packets, frames);
+    let expected: Vec<Duration> = (0..6)
+        .map(|i| Duration::from_nanos(i * 1024 * 1_000_000_000 / 44100))
+        .collect();
+    assert_eq!(timestamps, expected);
+}
+
+#[test]
+fn emitted_frame_index_counts_complete_frames() {
+    // Long enough to span several packets
+    let samples: Vec<i16> = (0..6000).map(|i| i as i16).collect();
+    let wav = crate::tests::wav_bytes(2, 8000, &samples);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), Some("wav")).unwrap();
+    assert_eq!(decoder.emitted_frame_index(), 0);
+    for frame in 0..3000 {
+        assert_eq!(
+            decoder.next_sample().unwrap(),
+            NextSample::Sample(frame * 2)
+        );
+        assert_eq!(decoder.emitted_frame_index(), frame as u64);
+        assert_eq!(
+            decoder.next_sample().unwrap(),
+            NextSample::Sample(frame * 2 + 1)
+        );
+        assert_eq!(decoder.emitted_frame_index(), frame as u64 + 1);
+    }
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(decoder.emitted_frame_index(), 3000);
+
+    decoder.seek(Duration::from_millis(100)).unwrap();
+    assert_eq!(decoder.emitted_frame_index(), 800);
+    let mut out = [0.0; 8];
+    assert_eq!(decoder.next_samples_f32(&mut out).unwrap(), 8);
+    assert_eq!(decoder.emitted_frame_index(), 804);
+    let (mut left, mut right) = (vec![0.0; 4096], vec![0.0; 4096]);
+    let frames = decoder.decode_into(&mut [&mut left, &mut right]).unwrap();
+    assert_eq!(decoder.emitted_frame_index(), 804 + frames as u64);
+}
+
+#[test]
+fn source_properties_of_seekable_and_reader_sources() {
+    let wav = crate::tests::wav_bytes(1, 8000, &[0; 800]);
+    let decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav.clone())), Some("wav")).unwrap();
+    assert!(decoder.source_is_seekable());
+    assert_eq!(decoder.source_byte_len(), Some(wav.len() as u64));
+
+    let reader = symphonia_core::io::ReadOnlySource::new(std::io::Cursor::new(wav));
+    let mut decoder = SymphoniaDecoder::new(Box::new(reader), Some("wav")).unwrap();
+    assert!(!decoder.source_is_seekable());
+    assert_eq!(decoder.source_byte_len(), None);
+    // The codec alone would allow seeking
+    assert!(decoder.seek_granularity().is_some());
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(0));
+}
+
+#[test]
+fn progress_of_known_length_source() {
+    let wav = crate::tests::wav_bytes(2, 8000, &[0; 1600]);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav.clone())), Some("wav")).unwrap();
+    assert_eq!(decoder.progress(), Some(0.0));
+    decoder.skip(Duration::from_millis(50)).unwrap();
+    assert!((decoder.progress().unwrap() - 0.5).abs() < 0.01);
+    let boxed: Box<dyn Sound> = Box::new(decoder);
+    assert!((boxed.progress().unwrap() - 0.5).abs() < 0.01);
+
+    let reader = symphonia_core::io::ReadOnlySource::new(std::io::Cursor::new(wav));
+    let decoder = SymphoniaDecoder::new(Box::new(reader), Some("wav")).unwrap();
+    assert_eq!(decoder.progress(), None);
+}
+
+#[test]
+fn set_end_finishes_at_position() {
+    let samples: Vec<i16> = (0..1600).collect();
+    let wav = crate::tests::wav_bytes(2, 8000, &samples);
+    let mut decoder =
+        SymphoniaDecoder::new(Box::new(std::io::Cursor::new(wav)), Some("wav")).unwrap();
+    decoder.skip(Duration::from_millis(10)).unwrap();
+    decoder.set_end(Some(Duration::from_millis(50)));
+    assert_eq!(decoder.end(), Some(Duration::from_millis(50)));
+    let mut last = 0;
+    while let NextSample::Sample(s) = decoder.next_sample().unwrap() {
+        last = s;
+    }
+    assert_eq!(decoder.emitted_frame_index(), 400);
+    assert_eq!(last, 799);
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+
+    // Moving the end later continues playback and also limits decode_into
+    decoder.set_end(Some(Duration::from_millis(60)));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(800));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(801));
+    let (mut left, mut right) = (vec![0.0; 4096], vec![0.0; 4096]);
+    let mut frames = 0;
+    loop {
+        let decoded = decoder.decode_into(&mut [&mut left, &mut right]).unwrap();
+        if decoded == 0 {
+            break;
+        }
+        frames += decoded;
+    }
+    assert_eq!(frames, 79);
+    assert_eq!(decoder.emitted_frame_index(), 480);
+    let mut out = [0.0; 2];
+    assert_eq!(decoder.next_samples_f32(&mut out).unwrap(), 0);
+
+    decoder.set_end(None);
+    assert_eq!(decoder.end(), None);
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(960));
+}
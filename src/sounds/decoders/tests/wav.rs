@@ -35,3 +35,242 @@ fn samples_of_test_file() -> std::io::Result<()> {
     assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
     Ok(())
 }
+
+/// Build a `WAVE_FORMAT_EXTENSIBLE` file with 24 bit integer PCM samples.
+fn extensible_wav(channel_count: u16, channel_mask: u32, samples: &[i32]) -> Vec<u8> {
+    const KSDATAFORMAT_SUBTYPE_PCM: [u8; 16] = [
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b,
+        0x71,
+    ];
+    let sample_rate: u32 = 48000;
+    let block_align = channel_count * 3;
+    let data_len = samples.len() as u32 * 3;
+
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(4 + 8 + 40 + 8 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&40_u32.to_le_bytes());
+    wav.extend_from_slice(&0xFFFE_u16.to_le_bytes());
+    wav.extend_from_slice(&channel_count.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&24_u16.to_le_bytes());
+    wav.extend_from_slice(&22_u16.to_le_bytes());
+    wav.extend_from_slice(&24_u16.to_le_bytes());
+    wav.extend_from_slice(&channel_mask.to_le_bytes());
+    wav.extend_from_slice(&KSDATAFORMAT_SUBTYPE_PCM);
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes()[0..3]);
+    }
+    wav
+}
+
+#[test]
+fn extensible_5_1_24_bit() {
+    let samples = [
+        0x7FFFFF, -0x800000, 0x000100, -0x000100, 0x123456, 0, // frame 1
+        1, 2, 3, 4, 5, 6, // frame 2
+    ];
+    let wav = extensible_wav(6, 0x3F, &samples);
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(wav)).unwrap();
+    assert_eq!(decoder.sample_rate(), 48000);
+    assert_eq!(decoder.channel_count(), 6);
+    assert_eq!(decoder.channel_mask(), Some(0x3F));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(i16::MAX));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(i16::MIN));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(-1));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(0x1234));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(0));
+    for _ in 0..6 {
+        assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(0));
+    }
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn plain_format_has_no_channel_mask() {
+    let decoder = WavDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    assert_eq!(decoder.channel_mask(), None);
+}
+
+#[test]
+fn unsigned_8_bit() {
+    let data = [0_u8, 64, 128, 192, 255];
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16_u32.to_le_bytes());
+    wav.extend_from_slice(&1_u16.to_le_bytes());
+    wav.extend_from_slice(&1_u16.to_le_bytes());
+    wav.extend_from_slice(&8000_u32.to_le_bytes());
+    wav.extend_from_slice(&8000_u32.to_le_bytes());
+    wav.extend_from_slice(&1_u16.to_le_bytes());
+    wav.extend_from_slice(&8_u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(&data);
+
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(wav)).unwrap();
+    for expected in [-32768, -16384, 0, 16384, 32512] {
+        assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+/// Build a mono `WAVE_FORMAT_IEEE_FLOAT` file from little endian `data`.
+fn float_wav(bits_per_sample: u16, data: &[u8]) -> Vec<u8> {
+    let block_align = bits_per_sample / 8;
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16_u32.to_le_bytes());
+    wav.extend_from_slice(&3_u16.to_le_bytes());
+    wav.extend_from_slice(&1_u16.to_le_bytes());
+    wav.extend_from_slice(&8000_u32.to_le_bytes());
+    wav.extend_from_slice(&(8000 * block_align as u32).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&bits_per_sample.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(data);
+    wav
+}
+
+const FLOAT_VALUES: [f64; 6] = [0.0, 0.5, -0.5, 1.0, -1.0, 2.0];
+const FLOAT_EXPECTED: [i16; 6] = [0, 16383, -16383, 32767, -32767, 32767];
+
+#[test]
+fn float_32_bit() {
+    let data: Vec<u8> = FLOAT_VALUES
+        .iter()
+        .flat_map(|v| (*v as f32).to_le_bytes())
+        .collect();
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(float_wav(32, &data))).unwrap();
+    assert_eq!(decoder.sample_rate(), 8000);
+    assert_eq!(decoder.channel_count(), 1);
+    for expected in FLOAT_EXPECTED {
+        assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn float_64_bit() {
+    let data: Vec<u8> = FLOAT_VALUES.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(float_wav(64, &data))).unwrap();
+    assert_eq!(decoder.sample_rate(), 8000);
+    assert_eq!(decoder.channel_count(), 1);
+    for expected in FLOAT_EXPECTED {
+        assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn unsupported_float_width_is_an_error() {
+    assert!(WavDecoder::new(std::io::Cursor::new(float_wav(16, &[0, 0]))).is_err());
+}
+
+/// Insert a `LIST` `INFO` chunk with `fields` before the data chunk of a
+/// canonical 44 byte header WAV file.
+fn with_info_chunk(mut wav: Vec<u8>, fields: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+    let mut list = b"INFO".to_vec();
+    for (id, value) in fields {
+        list.extend_from_slice(*id);
+        list.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        list.extend_from_slice(value);
+        if value.len() % 2 == 1 {
+            list.push(0);
+        }
+    }
+    let mut chunk = b"LIST".to_vec();
+    chunk.extend_from_slice(&(list.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(&list);
+    let riff_len = u32::from_le_bytes(wav[4..8].try_into().unwrap()) + chunk.len() as u32;
+    wav[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    wav.splice(36..36, chunk);
+    wav
+}
+
+#[test]
+fn info_tags() {
+    let wav = with_info_chunk(
+        crate::tests::wav_bytes(1, 8000, &[1, 2, 3]),
+        &[
+            (b"IART", b"Some Artist\0"),
+            (b"INAM", b"A Title\0"),
+            (b"ICMT", b"odd"),
+        ],
+    );
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(wav)).unwrap();
+    assert_eq!(
+        decoder.info_tags(),
+        vec![
+            ("IART".to_owned(), "Some Artist".to_owned()),
+            ("INAM".to_owned(), "A Title".to_owned()),
+            ("ICMT".to_owned(), "odd".to_owned()),
+        ]
+    );
+    for expected in [1, 2, 3] {
+        assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+/// Insert a chunk of `len` zero bytes that declares `declared_len` bytes
+/// before the data chunk.
+fn with_unknown_chunk(mut wav: Vec<u8>, declared_len: u32, len: usize) -> Vec<u8> {
+    let mut chunk = b"JUNK".to_vec();
+    chunk.extend_from_slice(&declared_len.to_le_bytes());
+    chunk.resize(8 + len, 0);
+    wav.splice(36..36, chunk);
+    wav
+}
+
+#[test]
+fn large_unknown_chunk_is_skipped() {
+    let len = 3 << 20;
+    let wav = with_unknown_chunk(crate::tests::wav_bytes(1, 8000, &[1, 2]), len, len as usize);
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(wav)).unwrap();
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(decoder.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn truncated_oversized_chunk_is_an_error() {
+    // Would allocate 4 GB if the chunk were buffered
+    let wav = with_unknown_chunk(crate::tests::wav_bytes(1, 8000, &[1, 2]), u32::MAX - 1, 16);
+    assert!(WavDecoder::new(std::io::Cursor::new(wav)).is_err());
+    let mut list = b"LIST".to_vec();
+    list.extend_from_slice(&(u32::MAX - 1).to_le_bytes());
+    let mut wav = crate::tests::wav_bytes(1, 8000, &[1, 2]);
+    wav.splice(36..36, list);
+    assert!(WavDecoder::new(std::io::Cursor::new(wav)).is_err());
+}
+
+#[test]
+fn no_info_tags() {
+    let decoder = WavDecoder::new(std::io::Cursor::new(SINE_WAVE_FILE)).unwrap();
+    assert!(decoder.info_tags().is_empty());
+}
+
+#[test]
+fn samples_remaining_counts_down() {
+    let wav = crate::tests::wav_bytes(1, 8000, &[0; 100]);
+    let mut decoder = WavDecoder::new(std::io::Cursor::new(wav)).unwrap();
+    assert_eq!(decoder.samples_remaining(), Some(100));
+    for _ in 0..30 {
+        decoder.next_sample().unwrap();
+    }
+    assert_eq!(decoder.samples_remaining(), Some(70));
+}
@@ -1,4 +1,4 @@
-use std::io::Read;
+use std::io::{Chain, Cursor, Read};
 
 use crate::sound::NextSample;
 use crate::Sound;
@@ -8,13 +8,35 @@ use hound::{SampleFormat, WavReader};
 // Originally based off Decoder from Rodio.
 
 /// Decoder for the WAV format.
+///
+/// 8, 16, 24 and 32 bit integer and 32 and 64 bit float samples are
+/// supported. hound does not support 64 bit float samples so those are read
+/// directly. Any other sample format returns an error when the decoder is
+/// created.
 pub struct WavDecoder<R>
 where
     R: Read + Send,
 {
-    reader: WavReader<R>,
+    source: Source<R>,
     sample_rate: u32,
     channel_count: u16,
+    channel_mask: Option<u32>,
+    info_tags: Vec<(String, String)>,
+    /// The number of samples returned by hound.
+    samples_returned: u64,
+}
+
+enum Source<R>
+where
+    R: Read + Send,
+{
+    Hound(WavReader<Chain<Cursor<Vec<u8>>, R>>),
+    /// 64 bit float samples read from the data chunk since hound does not
+    /// support them.
+    Float64 {
+        data: R,
+        remaining_bytes: u64,
+    },
 }
 
 impl<R> WavDecoder<R>
@@ -22,23 +44,71 @@ where
     R: Read + Send,
 {
     /// Attempts to decode the data as WAV.
-    pub fn new(data: R) -> Result<WavDecoder<R>, hound::Error> {
-        let reader = WavReader::new(data)?;
+    pub fn new(mut data: R) -> Result<WavDecoder<R>, hound::Error> {
+        // Hound does not expose everything we want from the header so read it
+        // ourselves first and then let hound parse the same bytes.
+        let header = read_header(&mut data)?;
+        if let Some(format) = header.format.as_ref().filter(|f| f.is_float64()) {
+            if format.channel_count == 0 || format.sample_rate == 0 {
+                return Err(hound::Error::FormatError("invalid fmt chunk"));
+            }
+            return Ok(WavDecoder {
+                sample_rate: format.sample_rate,
+                channel_count: format.channel_count,
+                channel_mask: header.channel_mask,
+                info_tags: header.info_tags,
+                samples_returned: 0,
+                source: Source::Float64 {
+                    data,
+                    remaining_bytes: header.data_len as u64,
+                },
+            });
+        }
+        let reader = WavReader::new(Cursor::new(header.bytes).chain(data))?;
         let spec = reader.spec();
+        match (spec.sample_format, spec.bits_per_sample) {
+            (SampleFormat::Float, 32) | (SampleFormat::Int, 8 | 16 | 24 | 32) => (),
+            _ => return Err(hound::Error::Unsupported),
+        }
 
         let sample_rate = spec.sample_rate;
         let channel_count = spec.channels;
 
         Ok(WavDecoder {
-            reader,
+            source: Source::Hound(reader),
             sample_rate,
             channel_count,
+            channel_mask: header.channel_mask,
+            info_tags: header.info_tags,
+            samples_returned: 0,
         })
     }
 
+    /// Return the speaker positions of the channels as stored in the
+    /// `dwChannelMask` field of a `WAVE_FORMAT_EXTENSIBLE` header (e.g. 0x3F
+    /// for 5.1 audio).
+    ///
+    /// Returns None if the file does not use the extensible format.
+    pub fn channel_mask(&self) -> Option<u32> {
+        self.channel_mask
+    }
+
+    /// Return the fields of the `LIST` `INFO` chunk as pairs of the four
+    /// character field ID and its value (e.g. `("IART", "Artist")` or
+    /// `("INAM", "Title")`) in the order they appear in the file.
+    ///
+    /// Only chunks preceding the audio data are read. Returns an empty Vec
+    /// if there are none.
+    pub fn info_tags(&self) -> Vec<(String, String)> {
+        self.info_tags.clone()
+    }
+
     /// Return the wrapped Reader
     pub fn into_inner(self) -> R {
-        self.reader.into_inner()
+        match self.source {
+            Source::Hound(reader) => reader.into_inner().into_inner().1,
+            Source::Float64 { data, .. } => data,
+        }
     }
 }
 
@@ -55,46 +125,225 @@ where
     }
 
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
-        let spec = self.reader.spec();
+        let reader = match &mut self.source {
+            Source::Hound(reader) => reader,
+            Source::Float64 {
+                data,
+                remaining_bytes,
+            } => return next_f64_sample(data, remaining_bytes),
+        };
+        let spec = reader.spec();
         let maybe_sample = match (spec.sample_format, spec.bits_per_sample) {
-            (SampleFormat::Float, 32) => self
-                .reader
-                .samples()
-                .next()
-                .map(|value| value.map(f32_to_i16)),
-            (SampleFormat::Int, 8) => self
-                .reader
-                .samples()
-                .next()
-                .map(|value| value.map(i8_to_i16)),
-            (SampleFormat::Int, 16) => self.reader.samples().next(),
-            (SampleFormat::Int, 24) => self
-                .reader
-                .samples()
-                .next()
-                .map(|value| value.map(i24_to_i16)),
-            (SampleFormat::Int, 32) => self
-                .reader
-                .samples()
-                .next()
-                .map(|value| value.map(i32_to_i16)),
+            (SampleFormat::Float, 32) => reader.samples().next().map(|value| value.map(f32_to_i16)),
+            (SampleFormat::Int, 8) => reader.samples().next().map(|value| value.map(i8_to_i16)),
+            (SampleFormat::Int, 16) => reader.samples().next(),
+            (SampleFormat::Int, 24) => reader.samples().next().map(|value| value.map(i24_to_i16)),
+            (SampleFormat::Int, 32) => reader.samples().next().map(|value| value.map(i32_to_i16)),
             (sample_format, bits_per_sample) => {
-                unimplemented!("wav spec: {:?}, {}", sample_format, bits_per_sample)
+                unreachable!("wav spec: {:?}, {}", sample_format, bits_per_sample)
             }
         };
         match maybe_sample {
-            Some(Ok(sample)) => Ok(NextSample::Sample(sample)),
+            Some(Ok(sample)) => {
+                self.samples_returned += 1;
+                Ok(NextSample::Sample(sample))
+            }
             Some(Err(e)) => Err(e.into()),
             None => Ok(NextSample::Finished),
         }
     }
 
     fn on_start_of_batch(&mut self) {}
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Trivial
+    }
+
+    fn frame_alignment(&self) -> Option<u64> {
+        Some(1)
+    }
+
+    fn samples_remaining(&self) -> Option<u64> {
+        match &self.source {
+            Source::Hound(reader) => {
+                Some((reader.len() as u64).saturating_sub(self.samples_returned))
+            }
+            Source::Float64 {
+                remaining_bytes, ..
+            } => Some(remaining_bytes / 8),
+        }
+    }
+}
+
+/// Read the next 64 bit float sample of the data chunk.
+fn next_f64_sample<R: Read>(
+    data: &mut R,
+    remaining_bytes: &mut u64,
+) -> Result<NextSample, crate::Error> {
+    if *remaining_bytes < 8 {
+        return Ok(NextSample::Finished);
+    }
+    let mut bytes = [0; 8];
+    match data.read_exact(&mut bytes) {
+        Ok(()) => (),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            *remaining_bytes = 0;
+            return Ok(NextSample::Finished);
+        }
+        Err(e) => return Err(e.into()),
+    }
+    *remaining_bytes -= 8;
+    Ok(NextSample::Sample(f64_to_i16(f64::from_le_bytes(bytes))))
+}
+
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// The largest `fmt ` or `LIST` chunk read into memory. Larger chunks are
+/// skipped.
+const MAX_PARSED_CHUNK_LEN: u32 = 1 << 20;
+
+struct Header {
+    /// The bytes to pass to hound: the RIFF header, the `fmt ` chunk and the
+    /// header of the data chunk. Other chunks are skipped.
+    bytes: Vec<u8>,
+    channel_mask: Option<u32>,
+    format: Option<Format>,
+    info_tags: Vec<(String, String)>,
+    /// The length in bytes of the data chunk.
+    data_len: u32,
+}
+
+/// The fields of the fmt chunk needed to decode samples hound does not
+/// support.
+struct Format {
+    /// The format tag or for the extensible format the tag of its SubFormat.
+    format_tag: u16,
+    channel_count: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+}
+
+impl Format {
+    fn is_float64(&self) -> bool {
+        self.format_tag == WAVE_FORMAT_IEEE_FLOAT && self.bits_per_sample == 64
+    }
+}
+
+/// Read all chunks preceding the audio data.
+///
+/// Only the fields hound does not expose are parsed. Validation is left to
+/// hound so if the data does not look like a WAV file we stop early. Only
+/// the `fmt ` and `LIST` chunks of up to [MAX_PARSED_CHUNK_LEN] bytes are
+/// read into memory so a large or corrupt chunk length does not allocate.
+fn read_header<R: Read>(reader: &mut R) -> std::io::Result<Header> {
+    let mut header = Header {
+        bytes: vec![0; 12],
+        channel_mask: None,
+        format: None,
+        info_tags: Vec::new(),
+        data_len: 0,
+    };
+    reader.read_exact(&mut header.bytes)?;
+    if &header.bytes[0..4] != b"RIFF" || &header.bytes[8..12] != b"WAVE" {
+        return Ok(header);
+    }
+    loop {
+        let mut chunk_header = [0; 8];
+        reader.read_exact(&mut chunk_header)?;
+        let chunk_id: [u8; 4] = chunk_header[0..4].try_into().unwrap();
+        let chunk_len = read_le_u32(&chunk_header[4..]);
+        if &chunk_id == b"data" {
+            header.bytes.extend_from_slice(&chunk_header);
+            header.data_len = chunk_len;
+            return Ok(header);
+        }
+        // Chunks are padded to an even length
+        let padded_len = chunk_len as u64 + (chunk_len & 1) as u64;
+        let parsed = &chunk_id == b"fmt " || &chunk_id == b"LIST";
+        if !parsed || chunk_len > MAX_PARSED_CHUNK_LEN {
+            let skipped = std::io::copy(&mut reader.take(padded_len), &mut std::io::sink())?;
+            if skipped != padded_len {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            continue;
+        }
+        let mut body = vec![0; padded_len as usize];
+        reader.read_exact(&mut body)?;
+        if &chunk_id == b"fmt " {
+            header.bytes.extend_from_slice(&chunk_header);
+            header.bytes.extend_from_slice(&body);
+            let body = &body[..chunk_len as usize];
+            header.channel_mask = parse_channel_mask(body);
+            header.format = parse_format(body);
+        } else if body.starts_with(b"INFO") {
+            header
+                .info_tags
+                .extend(parse_info(&body[4..chunk_len as usize]));
+        }
+    }
+}
+
+fn parse_channel_mask(fmt: &[u8]) -> Option<u32> {
+    // WAVEFORMATEXTENSIBLE is WAVEFORMATEX (18 bytes) followed by
+    // wValidBitsPerSample, dwChannelMask and the SubFormat GUID.
+    if fmt.len() < 40 || read_le_u16(fmt) != WAVE_FORMAT_EXTENSIBLE {
+        return None;
+    }
+    Some(read_le_u32(&fmt[20..]))
+}
+
+fn parse_format(fmt: &[u8]) -> Option<Format> {
+    if fmt.len() < 16 {
+        return None;
+    }
+    let mut format_tag = read_le_u16(fmt);
+    if format_tag == WAVE_FORMAT_EXTENSIBLE {
+        // The first two bytes of the SubFormat GUID are the format tag
+        format_tag = read_le_u16(fmt.get(24..40)?);
+    }
+    Some(Format {
+        format_tag,
+        channel_count: read_le_u16(&fmt[2..]),
+        sample_rate: read_le_u32(&fmt[4..]),
+        bits_per_sample: read_le_u16(&fmt[14..]),
+    })
+}
+
+/// Parse the subchunks of a `LIST` `INFO` chunk. Each holds a null
+/// terminated string. Parsing stops at the first malformed subchunk.
+fn parse_info(mut info: &[u8]) -> Vec<(String, String)> {
+    let mut tags = Vec::new();
+    while info.len() >= 8 {
+        let id = String::from_utf8_lossy(&info[0..4]).into_owned();
+        let len = read_le_u32(&info[4..]) as usize;
+        let Some(value) = info.get(8..8 + len) else {
+            break;
+        };
+        let value = value.split(|b| *b == 0).next().unwrap_or_default();
+        tags.push((id, String::from_utf8_lossy(value).into_owned()));
+        // Subchunks are padded to an even length
+        info = info.get(8 + len + (len & 1)..).unwrap_or_default();
+    }
+    tags
+}
+
+fn read_le_u16(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes(bytes[0..2].try_into().unwrap())
+}
+
+fn read_le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[0..4].try_into().unwrap())
 }
 
 // Lossy
 fn f32_to_i16(f: f32) -> i16 {
-    (f.max(-1.0).min(1.0) * i16::MAX as f32) as i16
+    (f.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+// Lossy
+fn f64_to_i16(f: f64) -> i16 {
+    (f.clamp(-1.0, 1.0) * i16::MAX as f64) as i16
 }
 
 fn i8_to_i16(i: i8) -> i16 {
@@ -0,0 +1,164 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+/// Row (low) frequencies in Hz.
+const LOW_FREQUENCIES: [f32; 4] = [697.0, 770.0, 852.0, 941.0];
+/// Column (high) frequencies in Hz.
+const HIGH_FREQUENCIES: [f32; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+/// The keypad layout. The row selects the low frequency and the column the
+/// high frequency.
+const KEYPAD: [[char; 4]; 4] = [
+    ['1', '2', '3', 'A'],
+    ['4', '5', '6', 'B'],
+    ['7', '8', '9', 'C'],
+    ['*', '0', '#', 'D'],
+];
+/// The amplitude of each of the two tones as a fraction of full scale.
+const TONE_AMPLITUDE: f32 = 0.45;
+
+/// Dual-tone multi-frequency (DTMF) telephone tones for a string of digits.
+///
+/// Each digit is played as the sum of its standard low and high frequency
+/// sine waves for the tone duration (100ms by default) followed by silence
+/// for the gap duration (100ms by default) if another digit follows. Once all
+/// digits have been played `Finished` is returned.
+///
+/// The output is mono with a default sample rate of 8,000.
+pub struct Dtmf {
+    /// The low and high frequency of each digit.
+    tones: Vec<(f32, f32)>,
+    sample_rate: u32,
+    tone_frames: u64,
+    gap_frames: u64,
+    /// The digit currently being played.
+    tone_idx: usize,
+    /// The frame within the current tone and the gap following it.
+    frame: u64,
+}
+
+impl Dtmf {
+    /// DTMF tones for `digits` with a sample rate of 8,000.
+    ///
+    /// Valid digits are `0-9`, `*`, `#` and `A-D` (case insensitive).
+    /// Returns an error if any other character is present.
+    pub fn new(digits: &str) -> Result<Dtmf, crate::Error> {
+        Self::with_sample_rate(digits, 8000)
+    }
+
+    /// DTMF tones for `digits` with `sample_rate`.
+    ///
+    /// Returns an error if `sample_rate` is less than 4,000 since the highest
+    /// tone could not be represented.
+    pub fn with_sample_rate(digits: &str, sample_rate: u32) -> Result<Dtmf, crate::Error> {
+        if sample_rate < 4000 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "sample_rate must be at least 4000",
+            )
+            .into());
+        }
+        let tones = digits
+            .chars()
+            .map(|digit| {
+                frequencies(digit).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("invalid DTMF digit: {:?}", digit),
+                    )
+                    .into()
+                })
+            })
+            .collect::<Result<Vec<_>, crate::Error>>()?;
+        let mut dtmf = Dtmf {
+            tones,
+            sample_rate,
+            tone_frames: 0,
+            gap_frames: 0,
+            tone_idx: 0,
+            frame: 0,
+        };
+        dtmf.set_tone_duration(Duration::from_millis(100));
+        dtmf.set_gap_duration(Duration::from_millis(100));
+        Ok(dtmf)
+    }
+
+    /// Set how long each digit's tone is played.
+    pub fn set_tone_duration(&mut self, duration: Duration) {
+        self.tone_frames = utils::duration_to_num_samples(duration, 1, self.sample_rate);
+    }
+
+    /// Set the silence between digits.
+    pub fn set_gap_duration(&mut self, duration: Duration) {
+        self.gap_frames = utils::duration_to_num_samples(duration, 1, self.sample_rate);
+    }
+}
+
+/// The low and high frequency of `digit`.
+fn frequencies(digit: char) -> Option<(f32, f32)> {
+    let digit = digit.to_ascii_uppercase();
+    KEYPAD.iter().enumerate().find_map(|(row, keys)| {
+        keys.iter()
+            .position(|key| *key == digit)
+            .map(|column| (LOW_FREQUENCIES[row], HIGH_FREQUENCIES[column]))
+    })
+}
+
+impl Sound for Dtmf {
+    fn channel_count(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        loop {
+            let Some((low, high)) = self.tones.get(self.tone_idx).copied() else {
+                return Ok(NextSample::Finished);
+            };
+            let is_last = self.tone_idx + 1 == self.tones.len();
+            let segment_frames = if is_last {
+                self.tone_frames
+            } else {
+                self.tone_frames + self.gap_frames
+            };
+            if self.frame >= segment_frames {
+                self.tone_idx += 1;
+                self.frame = 0;
+                continue;
+            }
+            let frame = self.frame;
+            self.frame += 1;
+            if frame >= self.tone_frames {
+                return Ok(NextSample::Sample(0));
+            }
+            let t = frame as f32 / self.sample_rate as f32;
+            let value = (2.0 * PI * low * t).sin() + (2.0 * PI * high * t).sin();
+            return Ok(NextSample::Sample(
+                (value * TONE_AMPLITUDE * i16::MAX as f32) as i16,
+            ));
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn may_block(&self) -> bool {
+        false
+    }
+
+    fn try_clone_boxed(&self) -> Option<Box<dyn Sound>> {
+        Some(Box::new(Dtmf {
+            tones: self.tones.clone(),
+            tone_idx: 0,
+            frame: 0,
+            ..*self
+        }))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/dtmf.rs"]
+mod tests;
@@ -0,0 +1,236 @@
+use std::f32::consts::TAU;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::utils::Xorshift64;
+use crate::{utils, NextSample, Sound};
+
+/// The seed used unless [set_seed][Granular::set_seed] is called.
+const DEFAULT_SEED: u64 = 0x853c_49e6_748f_ea9b;
+
+/// Granular synthesis from a short buffer of samples.
+///
+/// Grains (short Hann windowed snippets of the buffer) are started at a
+/// regular rate given by the density. Each grain reads from a random position
+/// around the configured position and is played at a random pitch within the
+/// pitch jitter. Reads wrap around to the start of the buffer. The output is
+/// scaled by the maximum number of overlapping grains so it never exceeds
+/// the level of the buffer.
+///
+/// The sound plays forever unless a duration is set with
+/// [set_duration][Granular::set_duration]. Random values come from a simple
+/// generator seeded with a fixed value by default so output is reproducible;
+/// use [set_seed][Granular::set_seed] to vary it.
+pub struct Granular {
+    samples: Arc<Vec<i16>>,
+    channel_count: u16,
+    sample_rate: u32,
+    grain_frames: u64,
+    density: f32,
+    position: f32,
+    position_jitter: f32,
+    pitch_jitter: f32,
+    /// The seed of `rng`, for clones.
+    seed: u64,
+    rng: Xorshift64,
+    grains: Vec<Grain>,
+    /// Frames until the next grain starts.
+    until_next_grain: f64,
+    grains_started: u64,
+    /// The duration last set, for clones.
+    duration_frames: Option<u64>,
+    remaining_frames: Option<u64>,
+    frame: Vec<i16>,
+    next_channel_idx: usize,
+}
+
+struct Grain {
+    /// The frame in the buffer being read.
+    position: f64,
+    /// Buffer frames advanced per output frame.
+    rate: f64,
+    age: u64,
+}
+
+impl Granular {
+    /// Granular synthesis from interleaved `samples`.
+    ///
+    /// Defaults to 50ms grains, 20 grains per second read from anywhere in
+    /// the buffer with no pitch jitter.
+    ///
+    /// Panics if `samples` does not contain at least one frame.
+    pub fn new(samples: Arc<Vec<i16>>, channel_count: u16, sample_rate: u32) -> Granular {
+        assert!(
+            samples.len() >= channel_count as usize && channel_count > 0,
+            "samples must contain at least one frame"
+        );
+        let mut granular = Granular {
+            samples,
+            channel_count,
+            sample_rate,
+            grain_frames: 0,
+            density: 20.0,
+            position: 0.5,
+            position_jitter: 1.0,
+            pitch_jitter: 0.0,
+            seed: DEFAULT_SEED,
+            rng: Xorshift64::new(DEFAULT_SEED),
+            grains: Vec::new(),
+            until_next_grain: 0.0,
+            grains_started: 0,
+            duration_frames: None,
+            remaining_frames: None,
+            frame: Vec::new(),
+            next_channel_idx: 0,
+        };
+        granular.set_grain_duration(Duration::from_millis(50));
+        granular
+    }
+
+    /// Set the length of each grain. Affects grains started afterward.
+    pub fn set_grain_duration(&mut self, duration: Duration) {
+        self.grain_frames = utils::duration_to_num_samples(duration, 1, self.sample_rate).max(1);
+    }
+
+    /// Set the number of grains started per second.
+    ///
+    /// Panics if `density` is not positive.
+    pub fn set_density(&mut self, density: f32) {
+        assert!(density > 0.0, "density must be positive");
+        self.density = density;
+    }
+
+    /// Set the center of the region grains are read from as a fraction of the
+    /// buffer length (`0.0..=1.0`).
+    pub fn set_position(&mut self, position: f32) {
+        self.position = position;
+    }
+
+    /// Set how far grains may start from the position as a fraction of the
+    /// buffer length. 1.0 reads from anywhere in the buffer.
+    pub fn set_position_jitter(&mut self, jitter: f32) {
+        self.position_jitter = jitter;
+    }
+
+    /// Set the maximum random change in pitch of each grain in semitones.
+    pub fn set_pitch_jitter(&mut self, semitones: f32) {
+        self.pitch_jitter = semitones;
+    }
+
+    /// Finish after `duration` from now. `None` plays forever. Clones made
+    /// with [try_clone_boxed][Sound::try_clone_boxed] play for the duration
+    /// last set.
+    pub fn set_duration(&mut self, duration: Option<Duration>) {
+        self.duration_frames =
+            duration.map(|d| utils::duration_to_num_samples(d, 1, self.sample_rate));
+        self.remaining_frames = self.duration_frames;
+    }
+
+    /// Seed the random number generator. Clones made with
+    /// [try_clone_boxed][Sound::try_clone_boxed] start from the seed last set.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Xorshift64::new(seed);
+    }
+
+    /// The number of grains started so far.
+    pub fn grains_started(&self) -> u64 {
+        self.grains_started
+    }
+
+    fn start_grain(&mut self) {
+        let num_frames = (self.samples.len() / self.channel_count as usize) as f64;
+        let offset = self.rng.next_f64() * self.position_jitter as f64 / 2.0;
+        let position = (self.position as f64 + offset).rem_euclid(1.0) * num_frames;
+        let semitones = self.rng.next_f64() * self.pitch_jitter as f64;
+        self.grains.push(Grain {
+            position,
+            rate: 2_f64.powf(semitones / 12.0),
+            age: 0,
+        });
+        self.grains_started += 1;
+    }
+
+    fn render_frame(&mut self) {
+        let interval = self.sample_rate as f64 / self.density as f64;
+        if self.until_next_grain <= 0.0 {
+            self.until_next_grain += interval;
+            self.start_grain();
+        }
+        self.until_next_grain -= 1.0;
+
+        let channel_count = self.channel_count as usize;
+        let num_frames = self.samples.len() / channel_count;
+        let max_overlap = (self.grain_frames as f64 / interval).ceil().max(1.0) as f32;
+        let mut mix = vec![0.0_f32; channel_count];
+        let grain_frames = self.grain_frames;
+        for grain in &mut self.grains {
+            let window = 0.5 - 0.5 * (TAU * grain.age as f32 / grain_frames as f32).cos();
+            let idx = grain.position as usize % num_frames;
+            let next_idx = (idx + 1) % num_frames;
+            let fraction = grain.position.fract() as f32;
+            for (channel, value) in mix.iter_mut().enumerate() {
+                let a = self.samples[idx * channel_count + channel] as f32;
+                let b = self.samples[next_idx * channel_count + channel] as f32;
+                *value += (a + (b - a) * fraction) * window;
+            }
+            grain.position = (grain.position + grain.rate) % num_frames as f64;
+            grain.age += 1;
+        }
+        self.grains.retain(|g| g.age < grain_frames);
+
+        self.frame.clear();
+        self.frame
+            .extend(mix.iter().map(|v| (v / max_overlap).round() as i16));
+    }
+}
+
+impl Sound for Granular {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.frame.get(self.next_channel_idx) {
+            self.next_channel_idx += 1;
+            return Ok(NextSample::Sample(*sample));
+        }
+        if let Some(remaining) = &mut self.remaining_frames {
+            if *remaining == 0 {
+                return Ok(NextSample::Finished);
+            }
+            *remaining -= 1;
+        }
+        self.render_frame();
+        self.next_channel_idx = 1;
+        Ok(NextSample::Sample(self.frame[0]))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn may_block(&self) -> bool {
+        false
+    }
+
+    fn try_clone_boxed(&self) -> Option<Box<dyn Sound>> {
+        Some(Box::new(Granular {
+            samples: self.samples.clone(),
+            rng: Xorshift64::new(self.seed),
+            grains: Vec::new(),
+            until_next_grain: 0.0,
+            grains_started: 0,
+            remaining_frames: self.duration_frames,
+            frame: Vec::new(),
+            next_channel_idx: 0,
+            ..*self
+        }))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/granular.rs"]
+mod tests;
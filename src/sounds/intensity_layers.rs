@@ -0,0 +1,221 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sounds::UnsupportedMetadataChangeError;
+use crate::{utils, NextSample, Sound};
+
+/// Mix stacked layers of a piece of music, bringing each in as a single
+/// intensity parameter rises past the layer's threshold.
+///
+/// All layers must have the same channel count and sample rate. They are all
+/// played in lockstep the whole time, even while silent, so a layer fading in
+/// is at the same position as the layers already playing. A layer is heard
+/// while the intensity set with [IntensityLayersHandle::set_intensity] is at
+/// or above its threshold. Layers fade in and out linearly over the fade
+/// duration whenever the intensity crosses their threshold.
+///
+/// A layer that is paused or has finished is treated as silence. `Finished`
+/// is returned once all layers have finished.
+///
+/// If a layer changes its channel count or sample rate an IoError of
+/// ErrorKind::Other with a UnsupportedMetadataChangeError is returned.
+pub struct IntensityLayers {
+    layers: Vec<Layer>,
+    channel_count: u16,
+    sample_rate: u32,
+    /// How much a layer's gain changes each frame while fading.
+    fade_step: f32,
+    intensity: Arc<AtomicU32>,
+    /// The current output frame.
+    output: Vec<i16>,
+    next_channel_idx: usize,
+    mix: Vec<f32>,
+    scratch: Vec<i16>,
+}
+
+struct Layer {
+    sound: Box<dyn Sound>,
+    threshold: f32,
+    gain: f32,
+    finished: bool,
+}
+
+/// Sets the intensity of an [IntensityLayers].
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct IntensityLayersHandle {
+    intensity: Arc<AtomicU32>,
+}
+
+impl IntensityLayers {
+    /// Mix `layers`, each given with the intensity at which it is heard,
+    /// fading each one in or out over `fade`. The intensity starts at 0.0 and
+    /// the layers with a threshold of 0.0 are heard immediately.
+    ///
+    /// An error is returned if `layers` is empty, a threshold is not between
+    /// 0.0 and 1.0 or the layers do not all have the same channel count and
+    /// sample rate.
+    pub fn new(
+        layers: Vec<(Box<dyn Sound>, f32)>,
+        fade: Duration,
+    ) -> Result<(IntensityLayers, IntensityLayersHandle), crate::Error> {
+        let invalid = |msg: &'static str| -> crate::Error {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, msg).into()
+        };
+        let Some((first, _)) = layers.first() else {
+            return Err(invalid("IntensityLayers requires at least one layer"));
+        };
+        let channel_count = first.channel_count();
+        let sample_rate = first.sample_rate();
+        if layers
+            .iter()
+            .any(|(l, _)| l.channel_count() != channel_count || l.sample_rate() != sample_rate)
+        {
+            return Err(invalid(
+                "all IntensityLayers layers must have the same channel count and sample rate",
+            ));
+        }
+        if layers
+            .iter()
+            .any(|(_, threshold)| !(0.0..=1.0).contains(threshold))
+        {
+            return Err(invalid("layer thresholds must be between 0.0 and 1.0"));
+        }
+        let fade_frames = utils::duration_to_num_samples(fade, 1, sample_rate);
+        let intensity = Arc::new(AtomicU32::new(0f32.to_bits()));
+        let handle = IntensityLayersHandle {
+            intensity: intensity.clone(),
+        };
+        let layers = layers
+            .into_iter()
+            .map(|(sound, threshold)| Layer {
+                sound,
+                threshold,
+                gain: if threshold <= 0.0 { 1.0 } else { 0.0 },
+                finished: false,
+            })
+            .collect();
+        let intensity_layers = IntensityLayers {
+            layers,
+            channel_count,
+            sample_rate,
+            fade_step: 1.0 / fade_frames.max(1) as f32,
+            intensity,
+            output: Vec::new(),
+            next_channel_idx: 0,
+            mix: Vec::new(),
+            scratch: Vec::new(),
+        };
+        Ok((intensity_layers, handle))
+    }
+
+    /// The current gain of each layer between 0.0 and 1.0.
+    pub fn gains(&self) -> Vec<f32> {
+        self.layers.iter().map(|l| l.gain).collect()
+    }
+
+    /// Read the next frame of every layer and mix them into `output`.
+    fn read_frame(&mut self) -> Result<(), crate::Error> {
+        let channel_count = self.channel_count as usize;
+        let intensity = f32::from_bits(self.intensity.load(Ordering::Relaxed));
+        self.mix.clear();
+        self.mix.resize(channel_count, 0.0);
+        for layer in &mut self.layers {
+            self.scratch.clear();
+            if !layer.finished {
+                loop {
+                    match layer.sound.append_next_frame_to(&mut self.scratch) {
+                        Ok(()) => break,
+                        Err(Ok(NextSample::MetadataChanged)) => {
+                            if layer.sound.channel_count() != self.channel_count
+                                || layer.sound.sample_rate() != self.sample_rate
+                            {
+                                return Err(crate::Error::IoError(std::io::Error::other(
+                                    UnsupportedMetadataChangeError {},
+                                )));
+                            }
+                            self.scratch.clear();
+                        }
+                        Err(Ok(NextSample::Finished)) => {
+                            layer.finished = true;
+                            break;
+                        }
+                        Err(Ok(NextSample::Paused)) | Err(Ok(NextSample::Sample(_))) => break,
+                        Err(Err(e)) => return Err(e),
+                    }
+                }
+            }
+            let target = if intensity >= layer.threshold {
+                1.0
+            } else {
+                0.0
+            };
+            layer.gain = if layer.gain < target {
+                (layer.gain + self.fade_step).min(target)
+            } else {
+                (layer.gain - self.fade_step).max(target)
+            };
+            for (mixed, sample) in self.mix.iter_mut().zip(&self.scratch) {
+                *mixed += *sample as f32 * layer.gain;
+            }
+        }
+        self.output.clear();
+        self.output.extend(
+            self.mix
+                .iter()
+                .map(|s| s.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16),
+        );
+        Ok(())
+    }
+}
+
+impl IntensityLayersHandle {
+    /// Set the intensity, clamped between 0.0 and 1.0.
+    pub fn set_intensity(&self, intensity: f32) {
+        self.intensity
+            .store(intensity.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// The most recently set intensity. Layers may still be fading to it.
+    pub fn intensity(&self) -> f32 {
+        f32::from_bits(self.intensity.load(Ordering::Relaxed))
+    }
+}
+
+impl Sound for IntensityLayers {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output.get(self.next_channel_idx) {
+            self.next_channel_idx += 1;
+            return Ok(NextSample::Sample(*sample));
+        }
+        if self.layers.iter().all(|l| l.finished) {
+            return Ok(NextSample::Finished);
+        }
+        self.read_frame()?;
+        if self.layers.iter().all(|l| l.finished) {
+            return Ok(NextSample::Finished);
+        }
+        self.next_channel_idx = 1;
+        Ok(NextSample::Sample(self.output[0]))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        for layer in &mut self.layers {
+            layer.sound.on_start_of_batch();
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/intensity_layers.rs"]
+mod tests;
@@ -0,0 +1,161 @@
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+/// An exponential (log) sine sweep for measuring frequency responses and
+/// capturing impulse responses.
+///
+/// The frequency rises exponentially from the start frequency to the end
+/// frequency over the sweep duration so every octave gets the same amount of
+/// time. The sweep is preceded and followed by silence (none by default)
+/// which leaves room to record the decay of the system being measured.
+///
+/// To recover the impulse response, convolve the recording with
+/// [inverse_filter][MeasurementSweep::inverse_filter]. The impulse response
+/// then starts `pre_silence + duration` into the result.
+///
+/// The output is mono with a default sample rate of 48,000.
+pub struct MeasurementSweep {
+    start_frequency: f64,
+    end_frequency: f64,
+    sample_rate: u32,
+    amplitude: f32,
+    pre_silence_frames: u64,
+    sweep_frames: u64,
+    post_silence_frames: u64,
+    frame: u64,
+}
+
+impl MeasurementSweep {
+    /// A sweep from `start_frequency` to `end_frequency` Hz lasting
+    /// `duration` with a sample rate of 48,000.
+    pub fn new(start_frequency: f32, end_frequency: f32, duration: Duration) -> MeasurementSweep {
+        Self::with_sample_rate(start_frequency, end_frequency, duration, 48000)
+    }
+
+    /// A sweep from `start_frequency` to `end_frequency` Hz lasting
+    /// `duration` with `sample_rate`.
+    ///
+    /// Panics unless `0 < start_frequency < end_frequency <= sample_rate / 2`.
+    pub fn with_sample_rate(
+        start_frequency: f32,
+        end_frequency: f32,
+        duration: Duration,
+        sample_rate: u32,
+    ) -> MeasurementSweep {
+        assert!(
+            start_frequency > 0.0 && start_frequency < end_frequency,
+            "frequencies must be positive and increasing"
+        );
+        assert!(
+            end_frequency <= sample_rate as f32 / 2.0,
+            "end_frequency must not be above the Nyquist frequency"
+        );
+        MeasurementSweep {
+            start_frequency: start_frequency as f64,
+            end_frequency: end_frequency as f64,
+            sample_rate,
+            amplitude: 0.5,
+            pre_silence_frames: 0,
+            sweep_frames: utils::duration_to_num_samples(duration, 1, sample_rate),
+            post_silence_frames: 0,
+            frame: 0,
+        }
+    }
+
+    /// Set the silence played before and after the sweep.
+    pub fn set_silence(&mut self, pre: Duration, post: Duration) {
+        self.pre_silence_frames = utils::duration_to_num_samples(pre, 1, self.sample_rate);
+        self.post_silence_frames = utils::duration_to_num_samples(post, 1, self.sample_rate);
+    }
+
+    /// Set the peak amplitude of the sweep as a fraction of full scale.
+    /// Defaults to 0.5. Clamped to `0.0..=1.0`.
+    pub fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude.clamp(0.0, 1.0);
+    }
+
+    /// The number of frames of the sweep itself, excluding the silence.
+    pub fn sweep_frames(&self) -> u64 {
+        self.sweep_frames
+    }
+
+    /// The instantaneous frequency in Hz of the sweep at `frame` frames after
+    /// it starts.
+    pub fn frequency_at(&self, frame: u64) -> f32 {
+        (self.start_frequency * (frame as f64 / self.rate_constant()).exp()) as f32
+    }
+
+    /// The inverse filter of the sweep: the sweep reversed in time with its
+    /// amplitude decreasing by 6 dB per octave to compensate for the extra
+    /// energy at low frequencies.
+    ///
+    /// Convolving the sweep (at full scale, regardless of the amplitude set)
+    /// with this filter results in an impulse of height 1.0 at index
+    /// `sweep_frames - 1` so convolving a recording of the sweep with it
+    /// gives the impulse response of the system it was played through.
+    pub fn inverse_filter(&self) -> Vec<f32> {
+        let rate_constant = self.rate_constant();
+        let n = self.sweep_frames;
+        let filter: Vec<f64> = (0..n)
+            .map(|i| self.sweep_value(n - 1 - i) * (-(i as f64) / rate_constant).exp())
+            .collect();
+        // The value of the convolution at its peak
+        let peak: f64 = (0..n)
+            .map(|i| self.sweep_value(i) * filter[(n - 1 - i) as usize])
+            .sum();
+        filter.iter().map(|v| (v / peak) as f32).collect()
+    }
+
+    /// The sweep length in frames divided by the number of e-foldings of its
+    /// frequency.
+    fn rate_constant(&self) -> f64 {
+        self.sweep_frames as f64 / (self.end_frequency / self.start_frequency).ln()
+    }
+
+    /// The full scale value of the sweep at `frame`.
+    fn sweep_value(&self, frame: u64) -> f64 {
+        let rate_constant = self.rate_constant();
+        let cycles = self.start_frequency * rate_constant / self.sample_rate as f64
+            * ((frame as f64 / rate_constant).exp() - 1.0);
+        (TAU * cycles).sin()
+    }
+}
+
+impl Sound for MeasurementSweep {
+    fn channel_count(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let total = self.pre_silence_frames + self.sweep_frames + self.post_silence_frames;
+        if self.frame >= total {
+            return Ok(NextSample::Finished);
+        }
+        let frame = self.frame;
+        self.frame += 1;
+        let Some(sweep_frame) = frame
+            .checked_sub(self.pre_silence_frames)
+            .filter(|f| *f < self.sweep_frames)
+        else {
+            return Ok(NextSample::Sample(0));
+        };
+        let value = self.sweep_value(sweep_frame) * self.amplitude as f64;
+        Ok(NextSample::Sample((value * i16::MAX as f64) as i16))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn may_block(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/measurement_sweep.rs"]
+mod tests;
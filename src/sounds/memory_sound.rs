@@ -1,6 +1,10 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use crate::{NextSample, Sound};
+use crate::{utils, NextSample, Sound};
+
+/// The level mixed into the first frame after each loop restart when
+/// [MemorySound::set_loop_click] is enabled.
+const LOOP_CLICK_LEVEL: i16 = i16::MAX / 2;
 
 /// A Sound that stores all samples on the heap.
 ///
@@ -14,6 +18,13 @@ pub struct MemorySound {
 
     next_sample: usize,
     should_loop: bool,
+    /// see [Sound::set_end]
+    end: Option<Duration>,
+    /// The index of the sample to finish at.
+    end_sample: usize,
+    loop_click: bool,
+    /// Samples of the current frame still to have the loop click mixed in.
+    click_samples_remaining: usize,
 }
 
 /// A [MetadataChanged][NextSample::MetadataChanged] was returned while reading
@@ -69,7 +80,7 @@ impl MemorySound {
                     if channel_idx != 0 {
                         let outputs_to_stay_in_sync = channel_count as usize - channel_idx;
                         // This should be rare so lets just output 0 for the filler samples.
-                        samples.extend(std::iter::repeat(0).take(outputs_to_stay_in_sync));
+                        samples.extend(std::iter::repeat_n(0, outputs_to_stay_in_sync));
                     }
                 }
                 crate::NextSample::Paused | crate::NextSample::Finished => break,
@@ -82,6 +93,10 @@ impl MemorySound {
             sample_rate,
             next_sample: 0,
             should_loop: false,
+            end: None,
+            end_sample: usize::MAX,
+            loop_click: false,
+            click_samples_remaining: 0,
         })
     }
 
@@ -100,14 +115,44 @@ impl MemorySound {
             sample_rate,
             next_sample: 0,
             should_loop: false,
+            end: None,
+            end_sample: usize::MAX,
+            loop_click: false,
+            click_samples_remaining: 0,
         }
     }
 
+    /// The largest absolute sample value normalized to `0.0..=1.0` where 1.0
+    /// is full scale.
+    ///
+    /// The playback position is not affected.
+    pub fn scan_peak(&self) -> f32 {
+        let peak = self
+            .samples
+            .iter()
+            .map(|s| s.unsigned_abs())
+            .max()
+            .unwrap_or(0);
+        (peak as f32 / i16::MAX as f32).min(1.0)
+    }
+
     /// Instead of finishing after playing all samples, start back at the
     /// beginning and continue forever.
     pub fn set_looping(&mut self, should_loop: bool) {
         self.should_loop = should_loop;
     }
+
+    /// Mix a short click into the first frame after each time the sound loops
+    /// back to the beginning so loop points can be checked by ear.
+    ///
+    /// Only intended for debugging. The samples are returned unchanged while
+    /// disabled.
+    pub fn set_loop_click(&mut self, enabled: bool) {
+        self.loop_click = enabled;
+        if !enabled {
+            self.click_samples_remaining = 0;
+        }
+    }
 }
 
 impl Sound for MemorySound {
@@ -120,11 +165,19 @@ impl Sound for MemorySound {
     }
 
     fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
-        if let Some(sample) = self.samples.get(self.next_sample) {
+        let samples = &self.samples[..self.end_sample.min(self.samples.len())];
+        if let Some(sample) = samples.get(self.next_sample) {
             self.next_sample += 1;
+            if self.click_samples_remaining > 0 {
+                self.click_samples_remaining -= 1;
+                return Ok(NextSample::Sample(sample.saturating_add(LOOP_CLICK_LEVEL)));
+            }
             Ok(NextSample::Sample(*sample))
-        } else if self.should_loop && !self.samples.is_empty() {
+        } else if self.should_loop && !samples.is_empty() {
             self.next_sample = 0;
+            if self.loop_click {
+                self.click_samples_remaining = self.channel_count as usize;
+            }
             self.next_sample()
         } else {
             Ok(NextSample::Finished)
@@ -132,6 +185,89 @@ impl Sound for MemorySound {
     }
 
     fn on_start_of_batch(&mut self) {}
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Trivial
+    }
+
+    fn frame_alignment(&self) -> Option<u64> {
+        Some(1)
+    }
+
+    fn set_looping(&mut self, enabled: bool) {
+        self.should_loop = enabled;
+    }
+
+    fn is_looping(&self) -> bool {
+        self.should_loop
+    }
+
+    fn set_end(&mut self, at: Option<Duration>) {
+        self.end = at;
+        self.end_sample = match at {
+            Some(at) => {
+                let frames = utils::duration_to_num_samples(at, 1, self.sample_rate);
+                (frames as usize).saturating_mul(self.channel_count as usize)
+            }
+            None => usize::MAX,
+        };
+    }
+
+    fn end(&self) -> Option<Duration> {
+        self.end
+    }
+
+    fn may_block(&self) -> bool {
+        false
+    }
+
+    /// Shares the samples with this sound.
+    fn try_clone_boxed(&self) -> Option<Box<dyn Sound>> {
+        Some(Box::new(MemorySound {
+            samples: self.samples.clone(),
+            next_sample: 0,
+            click_samples_remaining: 0,
+            ..*self
+        }))
+    }
+
+    fn seek(&mut self, seek_to: Duration) -> Result<Duration, crate::Error> {
+        let num_frames = self.samples.len() / self.channel_count as usize;
+        let frame = utils::duration_to_num_samples(seek_to, 1, self.sample_rate);
+        let frame = (frame as usize).min(num_frames);
+        self.next_sample = frame * self.channel_count as usize;
+        Ok(utils::num_samples_to_duration(
+            frame as u64,
+            1,
+            self.sample_rate,
+        ))
+    }
+
+    fn seek_granularity(&self) -> Option<Duration> {
+        Some(utils::num_samples_to_duration(1, 1, self.sample_rate))
+    }
+
+    fn emitted_frame_index(&self) -> u64 {
+        (self.next_sample / self.channel_count as usize) as u64
+    }
+
+    /// `None` if there is not a complete frame.
+    fn progress(&self) -> Option<f32> {
+        let num_frames = self.samples.len() / self.channel_count as usize;
+        if num_frames == 0 {
+            return None;
+        }
+        Some(self.emitted_frame_index() as f32 / num_frames as f32)
+    }
+
+    /// `None` if looping.
+    fn samples_remaining(&self) -> Option<u64> {
+        if self.should_loop {
+            return None;
+        }
+        let end = self.end_sample.min(self.samples.len());
+        Some(end.saturating_sub(self.next_sample) as u64)
+    }
 }
 
 #[cfg(test)]
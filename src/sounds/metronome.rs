@@ -0,0 +1,164 @@
+use std::f64::consts::{PI, TAU};
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+/// How long each click lasts.
+const CLICK_DURATION: Duration = Duration::from_millis(15);
+/// Frequency and amplitude (as a fraction of full scale) of a normal click.
+const CLICK: (f64, f64) = (1000.0, 0.5);
+/// Frequency and amplitude of the click on the first beat of a bar.
+const ACCENT_CLICK: (f64, f64) = (1500.0, 0.9);
+
+/// A metronome: a short click on every beat at a fixed tempo.
+///
+/// Each click is a tone shaped by a Hann window so it starts and ends at
+/// silence. By default the first beat of every bar of 4 beats is accented
+/// with a higher and louder click. The same value is returned for every
+/// channel. The metronome plays forever unless a number of bars is set with
+/// [set_bars][Metronome::set_bars].
+///
+/// Beat positions are computed from the start so they do not drift even when
+/// a beat is not a whole number of frames.
+pub struct Metronome {
+    bpm: f64,
+    channel_count: u16,
+    sample_rate: u32,
+    beats_per_bar: u32,
+    accent: bool,
+    /// The number of bars to play. None plays forever.
+    bars: Option<u64>,
+    click_frames: u64,
+    frame: u64,
+    /// The beat currently playing and the frame it started at.
+    beat: u64,
+    beat_start: u64,
+    next_channel_idx: u16,
+    value: i16,
+}
+
+impl Metronome {
+    /// A mono metronome at `bpm` beats per minute with a sample rate of
+    /// 44,100.
+    pub fn new(bpm: f64) -> Metronome {
+        Self::with_format(bpm, 1, 44100)
+    }
+
+    /// A metronome at `bpm` beats per minute with `channel_count` and
+    /// `sample_rate`.
+    ///
+    /// Panics if `bpm` is not positive or `channel_count` is 0.
+    pub fn with_format(bpm: f64, channel_count: u16, sample_rate: u32) -> Metronome {
+        assert!(bpm > 0.0, "bpm must be positive");
+        assert!(channel_count > 0, "channel_count must be at least 1");
+        Metronome {
+            bpm,
+            channel_count,
+            sample_rate,
+            beats_per_bar: 4,
+            accent: true,
+            bars: None,
+            click_frames: utils::duration_to_num_samples(CLICK_DURATION, 1, sample_rate),
+            frame: 0,
+            beat: 0,
+            beat_start: 0,
+            next_channel_idx: 0,
+            value: 0,
+        }
+    }
+
+    /// The tempo in beats per minute.
+    pub fn bpm(&self) -> f64 {
+        self.bpm
+    }
+
+    /// Set the number of beats in a bar. The first beat of each bar is
+    /// accented if accents are enabled.
+    ///
+    /// Panics if `beats_per_bar` is 0.
+    pub fn set_beats_per_bar(&mut self, beats_per_bar: u32) {
+        assert!(beats_per_bar > 0, "beats_per_bar must be at least 1");
+        self.beats_per_bar = beats_per_bar;
+    }
+
+    /// Enable or disable the accented click on the first beat of each bar.
+    pub fn set_accent(&mut self, accent: bool) {
+        self.accent = accent;
+    }
+
+    /// Finish after `bars` bars counted from the start. `None` plays forever.
+    ///
+    /// The last bar is played in full including the silence after its last
+    /// click.
+    pub fn set_bars(&mut self, bars: Option<u64>) {
+        self.bars = bars;
+    }
+
+    /// The frame the click of `beat` starts at.
+    fn beat_start(&self, beat: u64) -> u64 {
+        (beat as f64 * 60.0 * self.sample_rate as f64 / self.bpm).round() as u64
+    }
+
+    fn click_value(&self, offset: u64) -> i16 {
+        if offset >= self.click_frames {
+            return 0;
+        }
+        let accented = self.accent && self.beat.is_multiple_of(self.beats_per_bar as u64);
+        let (frequency, amplitude) = if accented { ACCENT_CLICK } else { CLICK };
+        let window = (PI * offset as f64 / self.click_frames as f64)
+            .sin()
+            .powi(2);
+        let tone = (TAU * frequency * offset as f64 / self.sample_rate as f64).sin();
+        (window * tone * amplitude * i16::MAX as f64) as i16
+    }
+}
+
+impl Sound for Metronome {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.next_channel_idx == 0 {
+            if let Some(bars) = self.bars {
+                if self.frame >= self.beat_start(bars * self.beats_per_bar as u64) {
+                    return Ok(NextSample::Finished);
+                }
+            }
+            let next_beat_start = self.beat_start(self.beat + 1);
+            if self.frame >= next_beat_start {
+                self.beat += 1;
+                self.beat_start = next_beat_start;
+            }
+            self.value = self.click_value(self.frame - self.beat_start);
+            self.frame += 1;
+        }
+        self.next_channel_idx = (self.next_channel_idx + 1) % self.channel_count;
+        Ok(NextSample::Sample(self.value))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn may_block(&self) -> bool {
+        false
+    }
+
+    fn try_clone_boxed(&self) -> Option<Box<dyn Sound>> {
+        Some(Box::new(Metronome {
+            frame: 0,
+            beat: 0,
+            beat_start: 0,
+            next_channel_idx: 0,
+            value: 0,
+            ..*self
+        }))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/metronome.rs"]
+mod tests;
@@ -0,0 +1,238 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    sounds::{
+        decoders::SymphoniaDecoder,
+        wrappers::{ChannelCountConverter, SampleRateConverter, Wrapper},
+    },
+    utils, NextSample, Sound,
+};
+
+type ConvertedDecoder = SampleRateConverter<ChannelCountConverter<SymphoniaDecoder>>;
+
+/// Play a list of files one after the other as if they were a single Sound.
+///
+/// Files are opened lazily: the first file is opened by
+/// [new][MultiFileSound::new] to determine the output format and each
+/// following file is opened once the previous one has finished. Every file is
+/// converted to the channel count and sample rate of the first file.
+///
+/// The position of the sound is measured from the start of the first file so
+/// [seek][Sound::seek] and [current_position][MultiFileSound::current_position]
+/// span all files. Seeking past a file that has not been opened yet opens it
+/// to read its duration which requires the duration to be known from the
+/// file's container.
+pub struct MultiFileSound {
+    paths: Vec<PathBuf>,
+    /// Duration of each file once it has been opened or played to the end.
+    durations: Vec<Option<Duration>>,
+    current_idx: usize,
+    current: Option<ConvertedDecoder>,
+    channel_count: u16,
+    sample_rate: u32,
+    /// Position within the current file that `frames_played` is counted from.
+    start_in_current: Duration,
+    frames_played: u64,
+    next_channel_idx: u16,
+}
+
+impl MultiFileSound {
+    /// Play the files at `paths` in order.
+    ///
+    /// The first file is opened immediately. An error is returned if it can
+    /// not be opened or if `paths` is empty.
+    pub fn new<I>(paths: I) -> Result<MultiFileSound, crate::Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .map(|p| p.as_ref().to_path_buf())
+            .collect();
+        if paths.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "MultiFileSound requires at least one path",
+            )
+            .into());
+        }
+        let mut sound = MultiFileSound {
+            durations: vec![None; paths.len()],
+            paths,
+            current_idx: 0,
+            current: None,
+            channel_count: 0,
+            sample_rate: 0,
+            start_in_current: Duration::ZERO,
+            frames_played: 0,
+            next_channel_idx: 0,
+        };
+        let first = sound.open(0)?;
+        sound.channel_count = first.channel_count();
+        sound.sample_rate = first.sample_rate();
+        sound.current = Some(sound.convert(first)?);
+        Ok(sound)
+    }
+
+    /// The position of the next sample measured from the start of the first
+    /// file.
+    pub fn current_position(&self) -> Duration {
+        let previous: Duration = self.durations[..self.current_idx]
+            .iter()
+            .map(|d| d.unwrap_or_default())
+            .sum();
+        previous
+            + self.start_in_current
+            + utils::num_samples_to_duration(self.frames_played, 1, self.sample_rate)
+    }
+
+    /// The combined duration of all files.
+    ///
+    /// Files that have not been opened yet are opened to read their duration
+    /// from their container. An error is returned if it is not known.
+    pub fn total_duration(&mut self) -> Result<Duration, crate::Error> {
+        (0..self.paths.len()).map(|idx| self.duration_of(idx)).sum()
+    }
+
+    fn open(&mut self, idx: usize) -> Result<SymphoniaDecoder, crate::Error> {
+        let path = &self.paths[idx];
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        let decoder = SymphoniaDecoder::new(Box::new(File::open(path)?), extension.as_deref())?;
+        if self.durations[idx].is_none() {
+            self.durations[idx] = decoder.duration();
+        }
+        Ok(decoder)
+    }
+
+    fn convert(&self, decoder: SymphoniaDecoder) -> Result<ConvertedDecoder, crate::Error> {
+        Ok(SampleRateConverter::new(
+            ChannelCountConverter::try_new(decoder, self.channel_count)?,
+            self.sample_rate,
+        ))
+    }
+
+    fn duration_of(&mut self, idx: usize) -> Result<Duration, crate::Error> {
+        if self.durations[idx].is_none() {
+            self.open(idx)?;
+        }
+        self.durations[idx].ok_or_else(|| {
+            symphonia::core::errors::Error::Unsupported(
+                "unable to seek past a file with an unknown duration",
+            )
+            .into()
+        })
+    }
+
+    fn start_file(
+        &mut self,
+        idx: usize,
+        decoder: SymphoniaDecoder,
+        start: Duration,
+    ) -> Result<(), crate::Error> {
+        self.current = Some(self.convert(decoder)?);
+        self.current_idx = idx;
+        self.start_in_current = start;
+        self.frames_played = 0;
+        self.next_channel_idx = 0;
+        Ok(())
+    }
+}
+
+impl Sound for MultiFileSound {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        loop {
+            let Some(current) = &mut self.current else {
+                return Ok(NextSample::Finished);
+            };
+            match current.next_sample()? {
+                NextSample::Sample(s) => {
+                    self.next_channel_idx += 1;
+                    if self.next_channel_idx >= self.channel_count {
+                        self.next_channel_idx = 0;
+                        self.frames_played += 1;
+                    }
+                    return Ok(NextSample::Sample(s));
+                }
+                NextSample::MetadataChanged => {
+                    self.next_channel_idx = 0;
+                    return Ok(NextSample::MetadataChanged);
+                }
+                NextSample::Paused => return Ok(NextSample::Paused),
+                NextSample::Finished => {
+                    let played = self.start_in_current
+                        + utils::num_samples_to_duration(self.frames_played, 1, self.sample_rate);
+                    self.durations[self.current_idx].get_or_insert(played);
+                    self.current = None;
+                    self.current_idx += 1;
+                    self.start_in_current = Duration::ZERO;
+                    self.frames_played = 0;
+                    self.next_channel_idx = 0;
+                    if self.current_idx < self.paths.len() {
+                        let decoder = self.open(self.current_idx)?;
+                        self.start_file(self.current_idx, decoder, Duration::ZERO)?;
+                    }
+                }
+            }
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        if let Some(current) = &mut self.current {
+            current.on_start_of_batch();
+        }
+    }
+
+    fn seek(&mut self, seek_to: Duration) -> Result<Duration, crate::Error> {
+        let last_idx = self.paths.len() - 1;
+        let mut file_start = Duration::ZERO;
+        for idx in 0..=last_idx {
+            if idx < last_idx {
+                let duration = self.duration_of(idx)?;
+                if seek_to >= file_start + duration {
+                    file_start += duration;
+                    continue;
+                }
+            }
+            // Keep playing the current file if the target file can not be
+            // opened.
+            let mut decoder = match self.current.take() {
+                Some(current) if idx == self.current_idx => current.into_inner().into_inner(),
+                current => {
+                    self.current = current;
+                    self.open(idx)?
+                }
+            };
+            let result = decoder.seek(seek_to - file_start);
+            let start = *result.as_ref().unwrap_or(&Duration::ZERO);
+            self.start_file(idx, decoder, start)?;
+            return Ok(file_start + result?);
+        }
+        unreachable!("the last file always contains the seek position")
+    }
+
+    fn seek_granularity(&self) -> Option<Duration> {
+        self.current
+            .as_ref()
+            .and_then(|current| current.inner().inner().seek_granularity())
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/multi_file_sound.rs"]
+mod tests;
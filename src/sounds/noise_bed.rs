@@ -0,0 +1,180 @@
+use std::f64::consts::PI;
+
+use crate::utils::Xorshift64;
+use crate::{NextSample, Sound};
+
+/// The spectrum of the noise played by [NoiseBed].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseColor {
+    /// Equal power at every frequency, a bright hiss.
+    White,
+    /// Power falling by 3dB per octave, equal power in every octave.
+    Pink,
+    /// Power falling by 6dB per octave, a deep rumble.
+    Brown,
+}
+
+/// How quickly the spectral tilt moves in Hz.
+const TILT_RATE: f64 = 0.05;
+/// How much the level above [TILT_CUTOFF] moves with the tilt.
+const TILT_DEPTH: f64 = 0.3;
+/// The frequency in Hz above which the tilt changes the level.
+const TILT_CUTOFF: f64 = 1000.0;
+
+/// Wide noise of a selectable color that slowly moves for sleep or focus
+/// sounds and plays forever.
+///
+/// Each channel has its own random generator so the channels are
+/// decorrelated and the noise sounds wide rather than centered. The higher
+/// frequencies of each channel are slowly raised and lowered by a 20 second
+/// cycle, offset between channels, so the sound gently moves around without
+/// changing its color. The output stays within the range of i16.
+///
+/// The default sample rate is 44,100. Random values are seeded with a fixed
+/// value by default so output is reproducible; use
+/// [set_seed][NoiseBed::set_seed] to vary it.
+pub struct NoiseBed {
+    color: NoiseColor,
+    sample_rate: u32,
+    channels: Vec<ChannelNoise>,
+    /// The phase of the tilt in cycles in the range `0.0..1.0`.
+    tilt_phase: f64,
+    /// The one pole low pass coefficient for splitting off the higher
+    /// frequencies to tilt.
+    tilt_coefficient: f64,
+    next_channel_idx: usize,
+}
+
+struct ChannelNoise {
+    rng: Xorshift64,
+    /// The filter states of the pink noise approximation.
+    pink: [f64; 7],
+    brown: f64,
+    tilt_low: f64,
+}
+
+impl NoiseBed {
+    /// Noise of `color` with `channel_count` channels at a sample rate of
+    /// 44,100.
+    pub fn new(color: NoiseColor, channel_count: u16) -> NoiseBed {
+        Self::with_sample_rate(color, channel_count, 44100)
+    }
+
+    /// Noise of `color` with `channel_count` channels at `sample_rate`.
+    pub fn with_sample_rate(color: NoiseColor, channel_count: u16, sample_rate: u32) -> NoiseBed {
+        let mut bed = NoiseBed {
+            color,
+            sample_rate,
+            channels: (0..channel_count)
+                .map(|_| ChannelNoise {
+                    rng: Xorshift64::new(1),
+                    pink: [0.0; 7],
+                    brown: 0.0,
+                    tilt_low: 0.0,
+                })
+                .collect(),
+            tilt_phase: 0.0,
+            tilt_coefficient: (-2.0 * PI * TILT_CUTOFF / sample_rate as f64).exp(),
+            next_channel_idx: 0,
+        };
+        bed.set_seed(0x853c_49e6_748f_ea9b);
+        bed
+    }
+
+    /// The color of the noise being played.
+    pub fn color(&self) -> NoiseColor {
+        self.color
+    }
+
+    /// Change the color of the noise.
+    pub fn set_color(&mut self, color: NoiseColor) {
+        self.color = color;
+    }
+
+    /// Seed the random number generators. Each channel gets a different seed
+    /// derived from `seed`.
+    pub fn set_seed(&mut self, seed: u64) {
+        let mut state = seed;
+        for channel in &mut self.channels {
+            // splitmix64 so nearby seeds give unrelated channels
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            channel.rng = Xorshift64::new(z ^ (z >> 31));
+        }
+    }
+
+    /// The next sample of `channel_idx` in the range `-1.0..=1.0`.
+    fn next_value(&mut self, channel_idx: usize) -> f64 {
+        let color = self.color;
+        let channel_count = self.channels.len();
+        let channel = &mut self.channels[channel_idx];
+        let white = channel.rng.next_f64();
+        let noise = match color {
+            NoiseColor::White => white * 0.5,
+            NoiseColor::Pink => channel.pink(white),
+            NoiseColor::Brown => {
+                // Leaky so it does not wander off
+                channel.brown = (channel.brown + 0.02 * white) / 1.02;
+                channel.brown * 3.5
+            }
+        };
+        channel.tilt_low = noise + (channel.tilt_low - noise) * self.tilt_coefficient;
+        let offset = channel_idx as f64 / channel_count as f64;
+        let tilt = (2.0 * PI * (self.tilt_phase + offset)).sin();
+        let high = noise - channel.tilt_low;
+        let value = channel.tilt_low + high * (1.0 + TILT_DEPTH * tilt);
+        value.clamp(-1.0, 1.0)
+    }
+}
+
+impl ChannelNoise {
+    /// Filter `white` to pink noise with Paul Kellett's refined method.
+    fn pink(&mut self, white: f64) -> f64 {
+        let b = &mut self.pink;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.1538520;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+        pink * 0.11
+    }
+}
+
+impl Sound for NoiseBed {
+    fn channel_count(&self) -> u16 {
+        self.channels.len() as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.channels.is_empty() {
+            return Ok(NextSample::Finished);
+        }
+        let value = self.next_value(self.next_channel_idx);
+        self.next_channel_idx += 1;
+        if self.next_channel_idx >= self.channels.len() {
+            self.next_channel_idx = 0;
+            self.tilt_phase =
+                (self.tilt_phase + TILT_RATE / self.sample_rate as f64).rem_euclid(1.0);
+        }
+        Ok(NextSample::Sample((value * i16::MAX as f64) as i16))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn may_block(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/noise_bed.rs"]
+mod tests;
@@ -1,11 +1,25 @@
-use crate::Sound;
-use std::{fs::File, io::BufReader};
+use crate::{
+    sounds::{
+        wrappers::{ResampleQuality, SampleRateConverter, SincSampleRateConverter},
+        UnsupportedMetadataChangeError,
+    },
+    utils, NextSample, Sound,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Seek},
+    time::Duration,
+};
 
 /// Create a Sound that reads from a file with the correct decoder based on the
 /// file extension.
 ///
-/// If the file type is not able to be decoded than an [ErrorKind::Unsupported]
-/// is returned.
+/// The decoder matching the extension is tried first. If it fails the other
+/// enabled decoders are tried in turn (Symphonia, then WAV, then QOA) so files
+/// with a misleading extension or that one decoder rejects can still be
+/// opened. If more than one decoder was tried and all of them failed a
+/// [DecodeChainError] with each decoder's error is returned. If no enabled
+/// decoder can handle the file type an [ErrorKind::Unsupported] is returned.
 ///
 /// Uses a BufReader internally with the default capacity.
 ///
@@ -29,6 +43,198 @@ pub fn open_file_with_buffer_capacity<P: AsRef<std::path::Path>>(
     open_file_with_reader(path.as_ref(), reader)
 }
 
+/// Same as `open_file` but the returned Sound is converted to `sample_rate`
+/// using the resampler selected by `quality`.
+///
+/// Use [ResampleQuality::Linear] on CPU constrained targets and
+/// [ResampleQuality::Sinc] with more taps for higher fidelity.
+/// `ResampleQuality::default()` is a balance between the two.
+pub fn open_file_resampled<P: AsRef<std::path::Path>>(
+    path: P,
+    sample_rate: u32,
+    quality: ResampleQuality,
+) -> Result<Box<dyn Sound>, crate::Error> {
+    let sound = open_file(path)?;
+    Ok(match quality {
+        ResampleQuality::Linear => Box::new(SampleRateConverter::new(sound, sample_rate)),
+        ResampleQuality::Sinc { taps } => {
+            Box::new(SincSampleRateConverter::new(sound, sample_rate, taps)?)
+        }
+    })
+}
+
+/// Create a Sound that decodes `source` with Symphonia, for audio that is
+/// not a file on the filesystem such as an asset bundled with a mobile app.
+///
+/// Platform glue implements [MediaSource][super::decoders::MediaSource] on
+/// top of the platform's asset reader (e.g. an Android `AssetManager` asset
+/// or a file in an iOS bundle) so the asset is streamed without first being
+/// copied to a temporary file. A source that can not seek can be wrapped in
+/// `symphonia_core::io::ReadOnlySource` but then seeking is not supported.
+///
+/// `extension` (e.g. `"mp3"`) is a hint for the format. The format is still
+/// detected from the content if it is `None` or wrong.
+#[cfg(feature = "symphonia")]
+pub fn open_asset(
+    source: Box<dyn super::decoders::MediaSource>,
+    extension: Option<&str>,
+) -> Result<Box<dyn Sound>, crate::Error> {
+    Ok(Box::new(super::decoders::SymphoniaDecoder::new(
+        source, extension,
+    )?))
+}
+
+/// Decode only the part of a file from `start` to `end`.
+///
+/// Returns the interleaved samples, the sample rate and the channel count.
+/// The file is opened with `open_file` and seeked to `start` so the samples
+/// before `start` do not need to be decoded if the decoder supports seeking.
+/// If the seek lands before `start` the remaining samples are skipped so the
+/// range boundaries are sample accurate. Fewer samples are returned if the
+/// file ends before `end`.
+///
+/// If the channel count or sample rate changes within the range an IoError of
+/// ErrorKind::Other with a UnsupportedMetadataChangeError is returned.
+pub fn decode_range<P: AsRef<std::path::Path>>(
+    path: P,
+    start: Duration,
+    end: Duration,
+) -> Result<(Vec<i16>, u32, u16), crate::Error> {
+    let mut sound = open_file(path)?;
+    let position = sound.seek(start)?;
+    if position < start && !sound.skip(start - position)? {
+        return Ok((Vec::new(), sound.sample_rate(), sound.channel_count()));
+    }
+
+    let channel_count = sound.channel_count();
+    let sample_rate = sound.sample_rate();
+    let num_samples =
+        utils::duration_to_num_samples(end.saturating_sub(start), channel_count, sample_rate);
+    let mut samples = Vec::with_capacity(num_samples as usize);
+    while (samples.len() as u64) < num_samples {
+        match sound.next_sample()? {
+            NextSample::Sample(s) => samples.push(s),
+            NextSample::MetadataChanged => {
+                if sound.channel_count() != channel_count || sound.sample_rate() != sample_rate {
+                    return Err(crate::Error::IoError(std::io::Error::other(
+                        UnsupportedMetadataChangeError {},
+                    )));
+                }
+            }
+            NextSample::Paused | NextSample::Finished => break,
+        }
+    }
+    Ok((samples, sample_rate, channel_count))
+}
+
+/// Every decoder failed to open a file.
+///
+/// Returned by [open_file] wrapped in [Error::FormatError][crate::Error::FormatError]
+/// when more than one decoder was tried.
+#[derive(Debug)]
+pub struct DecodeChainError {
+    errors: Vec<(&'static str, crate::Error)>,
+}
+
+impl DecodeChainError {
+    /// The name of each decoder tried and the error it returned in the order
+    /// they were tried.
+    pub fn errors(&self) -> &[(&'static str, crate::Error)] {
+        &self.errors
+    }
+}
+
+impl std::fmt::Display for DecodeChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no decoder could open the file")?;
+        for (i, (decoder, error)) in self.errors.iter().enumerate() {
+            let separator = if i == 0 { ": " } else { "; " };
+            write!(f, "{}{}: {}", separator, decoder, error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DecodeChainError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DecoderKind {
+    #[cfg(feature = "rmp3-mp3")]
+    Mp3,
+    #[cfg(feature = "qoa")]
+    Qoa,
+    #[cfg(feature = "hound-wav")]
+    Wav,
+    #[cfg(feature = "symphonia")]
+    Symphonia,
+}
+
+impl DecoderKind {
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "rmp3-mp3")]
+            DecoderKind::Mp3 => "rmp3",
+            #[cfg(feature = "qoa")]
+            DecoderKind::Qoa => "qoaudio",
+            #[cfg(feature = "hound-wav")]
+            DecoderKind::Wav => "hound",
+            #[cfg(feature = "symphonia")]
+            DecoderKind::Symphonia => "symphonia",
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn open(
+        self,
+        reader: BufReader<File>,
+        extension: &str,
+    ) -> Result<Box<dyn Sound>, crate::Error> {
+        Ok(match self {
+            #[cfg(feature = "rmp3-mp3")]
+            DecoderKind::Mp3 => Box::new(super::decoders::Mp3Decoder::new(reader)),
+            #[cfg(feature = "qoa")]
+            DecoderKind::Qoa => Box::new(super::decoders::QoaDecoder::new(reader)?),
+            #[cfg(feature = "hound-wav")]
+            DecoderKind::Wav => Box::new(super::decoders::WavDecoder::new(reader)?),
+            #[cfg(feature = "symphonia")]
+            DecoderKind::Symphonia => Box::new(super::decoders::SymphoniaDecoder::new(
+                Box::new(reader.into_inner()),
+                Some(extension),
+            )?),
+        })
+    }
+}
+
+/// The decoders to try in order. The decoder matching the extension is tried
+/// first followed by Symphonia, WAV and QOA. The MP3 decoder is only used for
+/// the mp3 extension since it does not validate its input when created.
+fn decoder_chain(extension: &str) -> Vec<DecoderKind> {
+    let mut chain = Vec::new();
+    match extension {
+        #[cfg(feature = "rmp3-mp3")]
+        "mp3" => chain.push(DecoderKind::Mp3),
+        #[cfg(feature = "qoa")]
+        "qoa" => chain.push(DecoderKind::Qoa),
+        #[cfg(feature = "hound-wav")]
+        "wav" => chain.push(DecoderKind::Wav),
+        _ => (),
+    }
+    let fallbacks = [
+        #[cfg(feature = "symphonia")]
+        DecoderKind::Symphonia,
+        #[cfg(feature = "hound-wav")]
+        DecoderKind::Wav,
+        #[cfg(feature = "qoa")]
+        DecoderKind::Qoa,
+    ];
+    for kind in fallbacks {
+        if !chain.contains(&kind) {
+            chain.push(kind);
+        }
+    }
+    chain
+}
+
 fn open_file_with_reader(
     path: &std::path::Path,
     reader: BufReader<File>,
@@ -39,27 +245,28 @@ fn open_file_with_reader(
         .to_str()
         .unwrap_or_default()
         .to_lowercase();
-    let decoder: Box<dyn Sound> = match extension.as_ref() {
-        #[cfg(feature = "rmp3-mp3")]
-        "mp3" => Box::new(super::decoders::Mp3Decoder::new(reader)),
-        #[cfg(feature = "qoa")]
-        "qoa" => Box::new(super::decoders::QoaDecoder::new(reader)?),
-        #[cfg(feature = "hound-wav")]
-        "wav" => Box::new(super::decoders::WavDecoder::new(reader)?),
-        "_SILENCE_NEVER_MATCH_" => {
-            println!(
-                "Included to satisfy unused warnings when all features are off: {:?}",
-                reader
-            );
-            Box::new(crate::sounds::Silence::new(1, 1000))
+    let capacity = reader.capacity();
+    let file = reader.into_inner();
+    let mut errors = Vec::new();
+    for kind in decoder_chain(&extension) {
+        // Each decoder gets its own reader starting at the beginning of the
+        // file since a failed decoder may have consumed some of it.
+        let mut file = file.try_clone()?;
+        file.rewind()?;
+        match kind.open(BufReader::with_capacity(capacity, file), &extension) {
+            Ok(decoder) => return Ok(decoder),
+            Err(e) => errors.push((kind.name(), e)),
         }
-        #[cfg(feature = "symphonia")]
-        _ => Box::new(super::decoders::SymphoniaDecoder::new(
-            Box::new(reader.into_inner()),
-            Some(&extension),
-        )?),
-        #[cfg(not(feature = "symphonia"))]
-        _ => return Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into()),
-    };
-    Ok(decoder)
+    }
+    match errors.len() {
+        0 => Err(std::io::Error::from(std::io::ErrorKind::Unsupported).into()),
+        1 => Err(errors.pop().unwrap().1),
+        _ => Err(crate::Error::FormatError(Box::new(DecodeChainError {
+            errors,
+        }))),
+    }
 }
+
+#[cfg(test)]
+#[path = "./tests/open_file.rs"]
+mod tests;
@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound, UnderrunStats};
+
+/// A Sound that plays samples pushed from another thread via a
+/// [QueueSoundHandle].
+///
+/// Samples are stored in a `Mutex<VecDeque<i16>>` so this is best suited for
+/// lower throughput pipelines where producers and consumers are not strictly
+/// one to one. If the queue does not hold a whole frame, a frame of silence
+/// is returned and the starvation is recorded so the producer can learn that
+/// it is falling behind. Once the queue has been closed and all whole frames
+/// have been played, `Finished` is returned.
+///
+/// Samples may be pushed in any amounts. A partial frame waits in the queue
+/// until the rest of it is pushed so the channels stay in sync.
+pub struct QueueSound {
+    shared: Arc<Mutex<QueueState>>,
+    channel_count: u16,
+    sample_rate: u32,
+    next_channel_idx: u16,
+    /// Whether the frame being returned is silence because of an underrun.
+    silent_frame: bool,
+}
+
+/// The producer side of a [QueueSound].
+///
+/// Can be cloned to allow multiple producers.
+#[derive(Clone)]
+pub struct QueueSoundHandle {
+    shared: Arc<Mutex<QueueState>>,
+    channel_count: u16,
+    sample_rate: u32,
+}
+
+struct QueueState {
+    samples: VecDeque<i16>,
+    closed: bool,
+    starved: bool,
+    starved_count: u64,
+    /// The number of separate runs of silent frames.
+    underruns: u64,
+}
+
+impl QueueState {
+    fn underrun_stats(&self, channel_count: u16, sample_rate: u32) -> UnderrunStats {
+        UnderrunStats {
+            underruns: self.underruns,
+            silent_samples: self.starved_count,
+            silence: utils::num_samples_to_duration(self.starved_count, channel_count, sample_rate),
+        }
+    }
+}
+
+impl QueueSound {
+    /// Create a new empty queue and the handle used to push samples to it.
+    pub fn new(channel_count: u16, sample_rate: u32) -> (QueueSound, QueueSoundHandle) {
+        let shared = Arc::new(Mutex::new(QueueState {
+            samples: VecDeque::new(),
+            closed: false,
+            starved: false,
+            starved_count: 0,
+            underruns: 0,
+        }));
+        let sound = QueueSound {
+            shared: shared.clone(),
+            channel_count,
+            sample_rate,
+            next_channel_idx: 0,
+            silent_frame: false,
+        };
+        let handle = QueueSoundHandle {
+            shared,
+            channel_count,
+            sample_rate,
+        };
+        (sound, handle)
+    }
+}
+
+impl Sound for QueueSound {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let mut state = self.shared.lock().unwrap();
+        if self.next_channel_idx == 0 {
+            let was_silent = self.silent_frame;
+            self.silent_frame = state.samples.len() < self.channel_count as usize;
+            if self.silent_frame {
+                if state.closed {
+                    return Ok(NextSample::Finished);
+                }
+                if !was_silent {
+                    state.underruns += 1;
+                }
+                state.starved = true;
+                state.starved_count += self.channel_count as u64;
+            }
+        }
+        self.next_channel_idx = (self.next_channel_idx + 1) % self.channel_count.max(1);
+        if self.silent_frame {
+            return Ok(NextSample::Sample(0));
+        }
+        // A whole frame was in the queue at the start of the frame and only
+        // this sound removes samples
+        Ok(NextSample::Sample(state.samples.pop_front().unwrap_or(0)))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Trivial
+    }
+
+    fn buffered_duration(&self) -> Duration {
+        let len = self.shared.lock().unwrap().samples.len();
+        utils::num_samples_to_duration(len as u64, self.channel_count, self.sample_rate)
+    }
+
+    fn underrun_stats(&self) -> UnderrunStats {
+        self.shared
+            .lock()
+            .unwrap()
+            .underrun_stats(self.channel_count, self.sample_rate)
+    }
+}
+
+impl QueueSoundHandle {
+    /// Append samples to the end of the queue.
+    ///
+    /// Samples pushed after the queue has been closed are ignored.
+    pub fn push(&self, samples: impl IntoIterator<Item = i16>) {
+        let mut state = self.shared.lock().unwrap();
+        if state.closed {
+            return;
+        }
+        state.samples.extend(samples);
+    }
+
+    /// Return the number of samples waiting to be played.
+    pub fn len(&self) -> usize {
+        self.shared.lock().unwrap().samples.len()
+    }
+
+    /// Return how long the samples waiting to be played will take to play.
+    pub fn buffered_duration(&self) -> Duration {
+        utils::num_samples_to_duration(self.len() as u64, self.channel_count, self.sample_rate)
+    }
+
+    /// Return true if there are no samples waiting to be played.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stop accepting samples. The QueueSound returns Finished once the
+    /// remaining whole frames have been played. A partial frame at the end
+    /// is dropped.
+    pub fn close(&self) {
+        self.shared.lock().unwrap().closed = true;
+    }
+
+    /// Return true if the queue ran out of samples and played silence since
+    /// the last call to this function.
+    pub fn take_starved(&self) -> bool {
+        std::mem::take(&mut self.shared.lock().unwrap().starved)
+    }
+
+    /// Return the total number of silent samples played because the queue was
+    /// empty.
+    pub fn starved_count(&self) -> u64 {
+        self.shared.lock().unwrap().starved_count
+    }
+
+    /// Return the underruns since the queue was created or the stats were
+    /// last reset. See [Sound::underrun_stats].
+    pub fn underrun_stats(&self) -> UnderrunStats {
+        self.shared
+            .lock()
+            .unwrap()
+            .underrun_stats(self.channel_count, self.sample_rate)
+    }
+
+    /// Clear the underrun stats, including the
+    /// [starved_count][QueueSoundHandle::starved_count], e.g. at the start of
+    /// a new session.
+    pub fn reset_underrun_stats(&self) {
+        let mut state = self.shared.lock().unwrap();
+        state.starved_count = 0;
+        state.underruns = 0;
+    }
+}
+
+impl std::fmt::Debug for QueueSoundHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueueSoundHandle")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/queue_sound.rs"]
+mod tests;
@@ -0,0 +1,339 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound, UnderrunStats};
+
+/// Identifies an initialized region. Stored last when creating a region so a
+/// reader never sees a partially written header.
+const MAGIC: u32 = u32::from_le_bytes(*b"AWSM");
+/// Set in the flags once the writer will not write any more samples.
+const FLAG_FINISHED: u32 = 1;
+
+const MAGIC_OFFSET: usize = 0;
+const CHANNEL_COUNT_OFFSET: usize = 4;
+const SAMPLE_RATE_OFFSET: usize = 8;
+const FLAGS_OFFSET: usize = 12;
+/// The capacity of the ring buffer in samples.
+const CAPACITY_OFFSET: usize = 16;
+/// The total number of samples ever written. Only the writer stores it.
+const WRITE_CURSOR_OFFSET: usize = 24;
+/// The total number of samples ever read. Only the reader stores it.
+const READ_CURSOR_OFFSET: usize = 32;
+/// The size of the header before the interleaved i16 samples. Leaves room to
+/// add fields without changing the layout of existing ones.
+const HEADER_LEN: usize = 64;
+
+/// A region of memory shared by a [SharedMemoryWriter] and a
+/// [SharedMemorySound], possibly in different processes.
+///
+/// The header only contains atomics so the two sides synchronize without
+/// locks. Both cursors count samples since the start and only increase so the
+/// ring buffer position is the cursor modulo the capacity and a full buffer
+/// can not be mistaken for an empty one.
+struct SharedRegion {
+    ptr: *mut u8,
+    capacity: u64,
+}
+
+// The region is only accessed through atomics and the samples between the
+// cursors, which only one side accesses at a time.
+unsafe impl Send for SharedRegion {}
+
+impl SharedRegion {
+    /// Check a region can hold a header and at least 2 samples.
+    fn check(ptr: *mut u8, len: usize) -> Result<(), crate::Error> {
+        if ptr.is_null() || ptr.align_offset(std::mem::align_of::<AtomicU64>()) != 0 {
+            return Err(invalid_input(
+                "shared memory region must be 8 byte aligned".to_owned(),
+            ));
+        }
+        if len < HEADER_LEN + 2 * std::mem::size_of::<i16>() {
+            return Err(invalid_input(format!(
+                "shared memory region of {len} bytes is too small"
+            )));
+        }
+        Ok(())
+    }
+
+    fn u32_at(&self, offset: usize) -> &AtomicU32 {
+        // Safety: the offset is within the header, aligned and the region
+        // outlives self
+        unsafe { &*self.ptr.add(offset).cast::<AtomicU32>() }
+    }
+
+    fn u64_at(&self, offset: usize) -> &AtomicU64 {
+        // Safety: see u32_at
+        unsafe { &*self.ptr.add(offset).cast::<AtomicU64>() }
+    }
+
+    fn samples(&self) -> *mut i16 {
+        // Safety: HEADER_LEN is within the region
+        unsafe { self.ptr.add(HEADER_LEN).cast::<i16>() }
+    }
+
+    fn write_cursor(&self) -> &AtomicU64 {
+        self.u64_at(WRITE_CURSOR_OFFSET)
+    }
+
+    fn read_cursor(&self) -> &AtomicU64 {
+        self.u64_at(READ_CURSOR_OFFSET)
+    }
+
+    fn flags(&self) -> &AtomicU32 {
+        self.u32_at(FLAGS_OFFSET)
+    }
+}
+
+fn invalid_input(msg: String) -> crate::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, msg).into()
+}
+
+/// Writes interleaved PCM into a shared memory region for a
+/// [SharedMemorySound] to play, typically in another process.
+///
+/// There must be only one writer for a region.
+pub struct SharedMemoryWriter {
+    region: SharedRegion,
+}
+
+impl SharedMemoryWriter {
+    /// The number of bytes needed for a region holding `capacity` samples.
+    pub fn region_len(capacity: usize) -> usize {
+        HEADER_LEN + capacity * std::mem::size_of::<i16>()
+    }
+
+    /// Initialize the region at `ptr` of `len` bytes with the given format
+    /// and return a writer for it. Any previous contents are discarded.
+    ///
+    /// The capacity is as many samples as fit after the header. See
+    /// [region_len][SharedMemoryWriter::region_len]. Returns an error if the
+    /// region is not 8 byte aligned, can not hold at least 2 samples or
+    /// `channel_count` or `sample_rate` is 0.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads and writes of `len` bytes (e.g. a shared
+    /// memory mapping) for as long as the writer exists. The region must only
+    /// be accessed through this writer and a single [SharedMemorySound].
+    pub unsafe fn create(
+        ptr: *mut u8,
+        len: usize,
+        channel_count: u16,
+        sample_rate: u32,
+    ) -> Result<SharedMemoryWriter, crate::Error> {
+        SharedRegion::check(ptr, len)?;
+        if channel_count == 0 || sample_rate == 0 {
+            return Err(invalid_input(format!(
+                "invalid shared memory format of {channel_count} channels at {sample_rate} Hz"
+            )));
+        }
+        let capacity = ((len - HEADER_LEN) / std::mem::size_of::<i16>()) as u64;
+        let region = SharedRegion { ptr, capacity };
+        region.u32_at(MAGIC_OFFSET).store(0, Ordering::Release);
+        region
+            .u32_at(CHANNEL_COUNT_OFFSET)
+            .store(channel_count as u32, Ordering::Relaxed);
+        region
+            .u32_at(SAMPLE_RATE_OFFSET)
+            .store(sample_rate, Ordering::Relaxed);
+        region.flags().store(0, Ordering::Relaxed);
+        region
+            .u64_at(CAPACITY_OFFSET)
+            .store(capacity, Ordering::Relaxed);
+        region.write_cursor().store(0, Ordering::Relaxed);
+        region.read_cursor().store(0, Ordering::Relaxed);
+        region.u32_at(MAGIC_OFFSET).store(MAGIC, Ordering::Release);
+        Ok(SharedMemoryWriter { region })
+    }
+
+    /// The number of samples that can be written without overwriting samples
+    /// the reader has not played.
+    pub fn available_space(&self) -> usize {
+        let written = self.region.write_cursor().load(Ordering::Relaxed);
+        let read = self.region.read_cursor().load(Ordering::Acquire);
+        (self.region.capacity - (written - read)) as usize
+    }
+
+    /// Write as many of the interleaved `samples` as fit and return how many
+    /// were written.
+    ///
+    /// Only whole frames are played so a partial frame is held back until the
+    /// rest of it is written.
+    pub fn write(&mut self, samples: &[i16]) -> usize {
+        let count = samples.len().min(self.available_space());
+        let capacity = self.region.capacity as usize;
+        let written = self.region.write_cursor().load(Ordering::Relaxed);
+        let start = (written % self.region.capacity) as usize;
+        let first = count.min(capacity - start);
+        // Safety: both ranges are within the ring buffer and not between the
+        // read and write cursors so the reader is not accessing them
+        unsafe {
+            let dst = self.region.samples();
+            std::ptr::copy_nonoverlapping(samples.as_ptr(), dst.add(start), first);
+            std::ptr::copy_nonoverlapping(samples[first..].as_ptr(), dst, count - first);
+        }
+        self.region
+            .write_cursor()
+            .store(written + count as u64, Ordering::Release);
+        count
+    }
+
+    /// Signal that no more samples will be written. The sound finishes once
+    /// all whole frames already written have been played.
+    pub fn finish(&mut self) {
+        self.region
+            .flags()
+            .fetch_or(FLAG_FINISHED, Ordering::Release);
+    }
+}
+
+/// Plays interleaved PCM written to a shared memory region by a
+/// [SharedMemoryWriter], for passing audio between processes without copying
+/// it through a socket or pipe.
+///
+/// The region is a ring buffer with a header describing the format and the
+/// read and write positions. When a whole frame is not available a frame of
+/// silence is returned instead so playback continues in real time. Once the
+/// writer has called [finish][SharedMemoryWriter::finish] and all whole
+/// frames have been played `Finished` is returned.
+///
+/// The region can be mapped by any means (e.g. `shm_open` and `mmap` or a
+/// named file mapping on Windows) and passed as a pointer.
+pub struct SharedMemorySound {
+    region: SharedRegion,
+    channel_count: u16,
+    sample_rate: u32,
+    next_channel_idx: u16,
+    /// Whether the frame being returned is silence because of an underrun.
+    silent_frame: bool,
+    underruns: u64,
+    /// The number of separate runs of silent frames.
+    underrun_events: u64,
+}
+
+impl SharedMemorySound {
+    /// Play the region at `ptr` of `len` bytes that has been initialized by
+    /// [SharedMemoryWriter::create].
+    ///
+    /// Returns an error if the region is not aligned, has not been
+    /// initialized or its header does not fit `len`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads and writes of `len` bytes for as long as
+    /// the sound exists. The region must only be accessed through a single
+    /// [SharedMemoryWriter] and this sound.
+    pub unsafe fn open(ptr: *mut u8, len: usize) -> Result<SharedMemorySound, crate::Error> {
+        SharedRegion::check(ptr, len)?;
+        let region = SharedRegion { ptr, capacity: 0 };
+        if region.u32_at(MAGIC_OFFSET).load(Ordering::Acquire) != MAGIC {
+            return Err(invalid_input(
+                "shared memory region has not been initialized".to_owned(),
+            ));
+        }
+        let capacity = region.u64_at(CAPACITY_OFFSET).load(Ordering::Relaxed);
+        let channel_count = region.u32_at(CHANNEL_COUNT_OFFSET).load(Ordering::Relaxed);
+        let sample_rate = region.u32_at(SAMPLE_RATE_OFFSET).load(Ordering::Relaxed);
+        let fits = usize::try_from(capacity)
+            .is_ok_and(|capacity| SharedMemoryWriter::region_len(capacity) <= len);
+        if capacity == 0 || !fits || channel_count == 0 || sample_rate == 0 {
+            return Err(invalid_input(
+                "shared memory region has an invalid header".to_owned(),
+            ));
+        }
+        Ok(SharedMemorySound {
+            region: SharedRegion { ptr, capacity },
+            channel_count: channel_count.try_into().map_err(|_| {
+                invalid_input(format!(
+                    "invalid shared memory channel count {channel_count}"
+                ))
+            })?,
+            sample_rate,
+            next_channel_idx: 0,
+            silent_frame: false,
+            underruns: 0,
+            underrun_events: 0,
+        })
+    }
+
+    /// The number of frames of silence returned because the writer had not
+    /// written a whole frame in time.
+    pub fn underruns(&self) -> u64 {
+        self.underruns
+    }
+}
+
+impl Sound for SharedMemorySound {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.next_channel_idx == 0 {
+            let read = self.region.read_cursor().load(Ordering::Relaxed);
+            // Check the flag first so samples written before finishing are
+            // seen by the cursor load
+            let finished = self.region.flags().load(Ordering::Acquire) & FLAG_FINISHED != 0;
+            let written = self.region.write_cursor().load(Ordering::Acquire);
+            let was_silent = self.silent_frame;
+            self.silent_frame = written - read < self.channel_count as u64;
+            if self.silent_frame {
+                if finished {
+                    return Ok(NextSample::Finished);
+                }
+                if !was_silent {
+                    self.underrun_events += 1;
+                }
+                self.underruns += 1;
+            }
+        }
+        self.next_channel_idx = (self.next_channel_idx + 1) % self.channel_count;
+        if self.silent_frame {
+            return Ok(NextSample::Sample(0));
+        }
+        let read = self.region.read_cursor().load(Ordering::Relaxed);
+        let idx = (read % self.region.capacity) as usize;
+        // Safety: the sample is between the read and write cursors so the
+        // writer is not accessing it
+        let sample = unsafe { self.region.samples().add(idx).read() };
+        self.region.read_cursor().store(read + 1, Ordering::Release);
+        Ok(NextSample::Sample(sample))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Trivial
+    }
+
+    fn buffered_duration(&self) -> Duration {
+        let read = self.region.read_cursor().load(Ordering::Relaxed);
+        let written = self.region.write_cursor().load(Ordering::Acquire);
+        utils::num_samples_to_duration(written - read, self.channel_count, self.sample_rate)
+    }
+
+    fn underrun_stats(&self) -> UnderrunStats {
+        let silent_samples = self.underruns * self.channel_count as u64;
+        UnderrunStats {
+            underruns: self.underrun_events,
+            silent_samples,
+            silence: utils::num_samples_to_duration(
+                silent_samples,
+                self.channel_count,
+                self.sample_rate,
+            ),
+        }
+    }
+
+    fn may_block(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/shared_memory_sound.rs"]
+mod tests;
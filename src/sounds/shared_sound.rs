@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::{NextSample, Sound};
+
+/// The number of frames pulled from the shared sound per lock by default.
+const DEFAULT_BATCH_FRAMES: usize = 64;
+
+/// A Sound that can be accessed from multiple places (e.g. controlled from a
+/// UI thread and fed from a network thread) while also being played.
+///
+/// The inner sound is stored in an `Arc<Mutex<Box<dyn Sound>>>` which can be
+/// obtained with [shared][SharedSound::shared] and locked to access the sound
+/// directly.
+///
+/// Holding a lock on the audio thread is a real-time risk: if another thread
+/// holds the lock for a long time the audio thread will block and the output
+/// will glitch. To reduce contention samples are pulled in batches of frames
+/// with a single lock so the lock is taken rarely and held briefly. Other
+/// users of the lock should also hold it as briefly as possible. Since
+/// samples are read ahead in batches, changes made through the lock take
+/// effect after the already pulled samples have been played.
+pub struct SharedSound {
+    shared: Arc<Mutex<Box<dyn Sound>>>,
+    batch_frames: usize,
+    channel_count: u16,
+    sample_rate: u32,
+    /// Samples pulled in the last batch that have not been returned yet.
+    buffer: VecDeque<i16>,
+    /// What to return after `buffer` has been drained.
+    pending: Option<NextSample>,
+}
+
+impl SharedSound {
+    /// Share `sound`.
+    pub fn new(sound: Box<dyn Sound>) -> SharedSound {
+        SharedSound::from_shared(Arc::new(Mutex::new(sound)))
+    }
+
+    /// Play a sound that is already shared.
+    pub fn from_shared(shared: Arc<Mutex<Box<dyn Sound>>>) -> SharedSound {
+        let (channel_count, sample_rate) = {
+            let sound = shared.lock().unwrap();
+            (sound.channel_count(), sound.sample_rate())
+        };
+        SharedSound {
+            shared,
+            batch_frames: DEFAULT_BATCH_FRAMES,
+            channel_count,
+            sample_rate,
+            buffer: VecDeque::new(),
+            pending: None,
+        }
+    }
+
+    /// Access to the shared inner sound.
+    pub fn shared(&self) -> Arc<Mutex<Box<dyn Sound>>> {
+        self.shared.clone()
+    }
+
+    /// Set the number of frames pulled per lock. Larger batches reduce
+    /// contention but increase the delay before changes made through the lock
+    /// are heard.
+    ///
+    /// Panics if `batch_frames` is 0.
+    pub fn set_batch_frames(&mut self, batch_frames: usize) {
+        assert!(batch_frames > 0, "batch_frames must be at least 1");
+        self.batch_frames = batch_frames;
+    }
+
+    fn fill_buffer(&mut self) -> Result<(), crate::Error> {
+        let mut sound = self.shared.lock().unwrap();
+        let num_samples = self.batch_frames * self.channel_count as usize;
+        while self.buffer.len() < num_samples {
+            match sound.next_sample()? {
+                NextSample::Sample(s) => self.buffer.push_back(s),
+                special => {
+                    self.pending = Some(special);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Sound for SharedSound {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.buffer.pop_front() {
+            return Ok(NextSample::Sample(sample));
+        }
+        if self.pending.is_none() {
+            self.fill_buffer()?;
+            if let Some(sample) = self.buffer.pop_front() {
+                return Ok(NextSample::Sample(sample));
+            }
+        }
+        let pending = self.pending.take().unwrap();
+        if pending == NextSample::MetadataChanged {
+            let sound = self.shared.lock().unwrap();
+            self.channel_count = sound.channel_count();
+            self.sample_rate = sound.sample_rate();
+        }
+        Ok(pending)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.shared.lock().unwrap().on_start_of_batch()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/shared_sound.rs"]
+mod tests;
@@ -30,4 +30,16 @@ impl crate::Sound for Silence {
     }
 
     fn on_start_of_batch(&mut self) {}
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        crate::CostHint::Trivial
+    }
+
+    fn may_block(&self) -> bool {
+        false
+    }
+
+    fn try_clone_boxed(&self) -> Option<Box<dyn crate::Sound>> {
+        Some(Box::new(Silence::new(self.channel_count, self.sample_rate)))
+    }
 }
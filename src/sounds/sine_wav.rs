@@ -54,6 +54,17 @@ impl crate::Sound for SineWav {
     }
 
     fn on_start_of_batch(&mut self) {}
+
+    fn may_block(&self) -> bool {
+        false
+    }
+
+    fn try_clone_boxed(&self) -> Option<Box<dyn crate::Sound>> {
+        Some(Box::new(SineWav {
+            sample_num: 0,
+            ..*self
+        }))
+    }
 }
 
 #[cfg(test)]
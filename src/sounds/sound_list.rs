@@ -1,5 +1,8 @@
+use std::time::Duration;
+
 use crate::sound::NextSample;
 use crate::sounds::wrappers::{AddSound, ClearSounds};
+use crate::utils::{duration_to_num_samples, num_samples_to_duration};
 use crate::Sound;
 
 /// Play Sounds sequentially one after the other.
@@ -32,6 +35,25 @@ impl SoundList {
         self.sounds.push(sound);
     }
 
+    /// Add a Sound to be played until `end`, measured from its start, after
+    /// any existing sounds have `Finished`.
+    ///
+    /// `end` is moved to the nearest multiple of the sound's
+    /// [frame_alignment][Sound::frame_alignment] so the join with the next
+    /// sound lands on a codec frame boundary. The end actually used is
+    /// returned. The sound is trimmed with [set_end][Sound::set_end] so this
+    /// has no effect on sounds that do not implement it.
+    pub fn add_trimmed(&mut self, mut sound: Box<dyn Sound>, end: Duration) -> Duration {
+        let sample_rate = sound.sample_rate();
+        let alignment = sound.frame_alignment().unwrap_or(1).max(1);
+        let frames = duration_to_num_samples(end, 1, sample_rate);
+        let aligned = (frames + alignment / 2) / alignment * alignment;
+        let end = num_samples_to_duration(aligned, 1, sample_rate);
+        sound.set_end(Some(end));
+        self.add(sound);
+        end
+    }
+
     /// Stop all sounds including the currently playing one.
     pub fn clear(&mut self) {
         self.sounds.clear();
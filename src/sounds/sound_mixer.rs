@@ -10,6 +10,11 @@ type MixedSound = SampleRateConverter<ChannelCountConverter<Box<dyn Sound>>>;
 /// not need to crate one yourself but instead add multiple sounds on the
 /// Manager.
 ///
+/// Every added Sound is converted to the output channel count and sample rate
+/// (see [ChannelCountConverter] for how channels are up-mixed and
+/// down-mixed) so sounds with different channel counts are never interleaved
+/// out of step.
+///
 /// If a Sound returns an Error from next_sample, the error is logged and the
 /// Sound is dropped but other sounds keep playing.
 pub struct SoundMixer {
@@ -61,6 +66,20 @@ impl SoundMixer {
             self.add(inner);
         }
     }
+
+    /// Add a sound to be mixed, converting it to the output channel count and
+    /// sample rate.
+    ///
+    /// Returns an error and drops `sound` if its channels can not be converted
+    /// to the output channel count (i.e. either has no channels).
+    /// [add][AddSound::add] logs the error instead.
+    pub fn try_add(&mut self, sound: Box<dyn Sound>) -> Result<(), crate::Error> {
+        self.sounds.push(SampleRateConverter::new(
+            ChannelCountConverter::try_new(sound, self.output_channel_count)?,
+            self.output_sample_rate,
+        ));
+        Ok(())
+    }
 }
 
 impl Sound for SoundMixer {
@@ -167,10 +186,9 @@ impl Sound for SoundMixer {
 
 impl AddSound for SoundMixer {
     fn add(&mut self, sound: Box<dyn Sound>) {
-        self.sounds.push(SampleRateConverter::new(
-            ChannelCountConverter::new(sound, self.output_channel_count),
-            self.output_sample_rate,
-        ));
+        if let Err(e) = self.try_add(sound) {
+            log::error!("dropping sound added to SoundMixer: {}", e);
+        }
     }
 }
 
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::wrappers::WindowFunction;
+use super::UnsupportedMetadataChangeError;
+use crate::{NextSample, Sound};
+
+/// The most magnitudes [spectrogram] will return (256 MiB) so a sound that
+/// never finishes returns an error instead of using all memory.
+const MAX_MAGNITUDES: usize = 1 << 26;
+
+/// Pull every sample of `sound` and return the magnitude spectrum of each
+/// `fft_size` frames, starting every `hop` frames.
+///
+/// All channels are averaged to mono and each block has `window` applied
+/// before its FFT. Every spectrum has `fft_size / 2 + 1` bins from 0 Hz to
+/// the Nyquist frequency, scaled the same as
+/// [SpectrumTap][super::wrappers::SpectrumTap] so a full scale sine wave has a
+/// magnitude of 1.0. Only complete blocks are included so a sound shorter
+/// than `fft_size` frames returns no spectra.
+///
+/// Stops when `sound` returns `Finished` or `Paused`. Returns an error if
+/// `fft_size` is less than 2 or `hop` is 0, if the spectrogram would hold
+/// more than 2^26 magnitudes (e.g. because `sound` never finishes) or if the
+/// sample rate of `sound` changes (an [UnsupportedMetadataChangeError]).
+pub fn spectrogram(
+    sound: impl Sound,
+    fft_size: usize,
+    hop: usize,
+    window: WindowFunction,
+) -> Result<Vec<Vec<f32>>, crate::Error> {
+    spectrogram_with_limit(sound, fft_size, hop, window, MAX_MAGNITUDES)
+}
+
+fn spectrogram_with_limit(
+    mut sound: impl Sound,
+    fft_size: usize,
+    hop: usize,
+    window: WindowFunction,
+    max_magnitudes: usize,
+) -> Result<Vec<Vec<f32>>, crate::Error> {
+    if fft_size < 2 || hop == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "fft_size must be at least 2 and hop at least 1",
+        )
+        .into());
+    }
+    let sample_rate = sound.sample_rate();
+    let fft = FftPlanner::new().plan_fft_forward(fft_size);
+    let window = window.coefficients(fft_size);
+    // Scale so a full scale sine wave has a magnitude of 1.0
+    let scale = 2.0 / window.iter().sum::<f32>();
+    let num_bins = fft_size / 2 + 1;
+
+    let mut spectra = Vec::new();
+    let mut block: VecDeque<f32> = VecDeque::with_capacity(fft_size);
+    // Frames to drop before the next block when `hop` is larger than
+    // `fft_size`
+    let mut frames_to_skip = 0;
+    let mut scratch = Vec::with_capacity(fft_size);
+    let mut frame_sum = 0.0;
+    let mut next_channel_idx = 0;
+    loop {
+        match sound.next_sample()? {
+            NextSample::Sample(s) => {
+                frame_sum += s as f32 / i16::MAX as f32;
+                next_channel_idx += 1;
+                let channel_count = sound.channel_count();
+                if next_channel_idx < channel_count {
+                    continue;
+                }
+                let value = frame_sum / channel_count as f32;
+                frame_sum = 0.0;
+                next_channel_idx = 0;
+                if frames_to_skip > 0 {
+                    frames_to_skip -= 1;
+                    continue;
+                }
+                block.push_back(value);
+                if block.len() < fft_size {
+                    continue;
+                }
+
+                if (spectra.len() + 1) * num_bins > max_magnitudes {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::OutOfMemory,
+                        "sound is too long for a spectrogram",
+                    )
+                    .into());
+                }
+                scratch.clear();
+                scratch.extend(
+                    block
+                        .iter()
+                        .zip(&window)
+                        .map(|(s, w)| Complex::new(s * w, 0.0)),
+                );
+                fft.process(&mut scratch);
+                spectra.push(
+                    scratch[..num_bins]
+                        .iter()
+                        .map(|bin| bin.norm() * scale)
+                        .collect(),
+                );
+                let removed = hop.min(fft_size);
+                block.drain(..removed);
+                frames_to_skip = hop - removed;
+            }
+            NextSample::MetadataChanged => {
+                if sound.sample_rate() != sample_rate {
+                    return Err(crate::Error::IoError(std::io::Error::other(
+                        UnsupportedMetadataChangeError {},
+                    )));
+                }
+                frame_sum = 0.0;
+                next_channel_idx = 0;
+            }
+            NextSample::Paused | NextSample::Finished => return Ok(spectra),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/spectrogram.rs"]
+mod tests;
@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use crate::sounds::UnsupportedMetadataChangeError;
+use crate::{utils, NextSample, Sound};
+
+/// Play several sounds (e.g. the stems of a song) mixed together while
+/// keeping them sample-locked.
+///
+/// All members must have the same channel count and sample rate. They are
+/// pulled in lockstep one frame at a time: if any member returns `Paused` the
+/// whole group returns `Paused` and no member advances until it resumes. A
+/// member that has finished contributes silence until all members have
+/// finished.
+///
+/// [seek][Sound::seek] seeks every member to the same position. Members that
+/// land before the position of the others (e.g. because they can only seek
+/// to packet boundaries) are advanced to match so the members stay in sync.
+///
+/// If a member changes its channel count or sample rate an IoError of
+/// ErrorKind::Other with a UnsupportedMetadataChangeError is returned.
+pub struct SyncGroup {
+    members: Vec<Box<dyn Sound>>,
+    /// The next frame of each member. Empty if it has not been read yet.
+    frames: Vec<Vec<i16>>,
+    finished: Vec<bool>,
+    channel_count: u16,
+    sample_rate: u32,
+    /// The mixed frame being returned.
+    output: Vec<i16>,
+    next_channel_idx: usize,
+}
+
+impl SyncGroup {
+    /// Play `members` in sync.
+    ///
+    /// An error is returned if `members` is empty or the members do not all
+    /// have the same channel count and sample rate.
+    pub fn new(members: Vec<Box<dyn Sound>>) -> Result<SyncGroup, crate::Error> {
+        let invalid = |msg: &'static str| -> crate::Error {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, msg).into()
+        };
+        let Some(first) = members.first() else {
+            return Err(invalid("SyncGroup requires at least one member"));
+        };
+        let channel_count = first.channel_count();
+        let sample_rate = first.sample_rate();
+        if members
+            .iter()
+            .any(|m| m.channel_count() != channel_count || m.sample_rate() != sample_rate)
+        {
+            return Err(invalid(
+                "all SyncGroup members must have the same channel count and sample rate",
+            ));
+        }
+        Ok(SyncGroup {
+            frames: vec![Vec::new(); members.len()],
+            finished: vec![false; members.len()],
+            members,
+            channel_count,
+            sample_rate,
+            output: Vec::new(),
+            next_channel_idx: 0,
+        })
+    }
+
+    /// The sounds in the group in the order they were given.
+    pub fn members(&self) -> &[Box<dyn Sound>] {
+        &self.members
+    }
+
+    /// Read the next frame of member `idx` into `frames` if it has not been
+    /// read yet. Returns false if the member is paused.
+    fn read_frame(&mut self, idx: usize) -> Result<bool, crate::Error> {
+        if self.finished[idx] || !self.frames[idx].is_empty() {
+            return Ok(true);
+        }
+        let member = &mut self.members[idx];
+        loop {
+            match member.append_next_frame_to(&mut self.frames[idx]) {
+                Ok(()) => return Ok(true),
+                Err(Ok(NextSample::MetadataChanged)) => {
+                    if member.channel_count() != self.channel_count
+                        || member.sample_rate() != self.sample_rate
+                    {
+                        return Err(crate::Error::IoError(std::io::Error::other(
+                            UnsupportedMetadataChangeError {},
+                        )));
+                    }
+                    self.frames[idx].clear();
+                }
+                Err(Ok(NextSample::Finished)) => {
+                    self.frames[idx].clear();
+                    self.finished[idx] = true;
+                    return Ok(true);
+                }
+                Err(Ok(NextSample::Paused)) | Err(Ok(NextSample::Sample(_))) => {
+                    self.frames[idx].clear();
+                    return Ok(false);
+                }
+                Err(Err(e)) => {
+                    self.frames[idx].clear();
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+impl Sound for SyncGroup {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output.get(self.next_channel_idx) {
+            self.next_channel_idx += 1;
+            return Ok(NextSample::Sample(*sample));
+        }
+        let mut paused = false;
+        for idx in 0..self.members.len() {
+            paused |= !self.read_frame(idx)?;
+        }
+        if paused {
+            return Ok(NextSample::Paused);
+        }
+        if self.finished.iter().all(|f| *f) {
+            return Ok(NextSample::Finished);
+        }
+        self.output.clear();
+        self.output.resize(self.channel_count as usize, 0);
+        for frame in &mut self.frames {
+            for (output, sample) in self.output.iter_mut().zip(frame.iter()) {
+                *output = output.saturating_add(*sample);
+            }
+            frame.clear();
+        }
+        self.next_channel_idx = 1;
+        Ok(NextSample::Sample(self.output[0]))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        for member in &mut self.members {
+            member.on_start_of_batch();
+        }
+    }
+
+    /// Seek all members to `seek_to`.
+    ///
+    /// Returns the position all members are at which is the furthest position
+    /// any member landed at. If a member returns an error the members may no
+    /// longer be in sync until the next successful seek.
+    fn seek(&mut self, seek_to: Duration) -> Result<Duration, crate::Error> {
+        self.output.clear();
+        self.next_channel_idx = 0;
+        let mut positions = Vec::with_capacity(self.members.len());
+        for (idx, member) in self.members.iter_mut().enumerate() {
+            self.frames[idx].clear();
+            self.finished[idx] = false;
+            positions.push(member.seek(seek_to)?);
+        }
+        let position = positions.iter().copied().max().unwrap_or_default();
+        for (idx, member_position) in positions.into_iter().enumerate() {
+            let behind = position - member_position;
+            let frames = utils::duration_to_num_samples(behind, 1, self.sample_rate);
+            for _ in 0..frames {
+                if !self.read_frame(idx)? || self.finished[idx] {
+                    break;
+                }
+                self.frames[idx].clear();
+            }
+        }
+        Ok(position)
+    }
+
+    /// The coarsest granularity of the members or None if any member's is
+    /// unknown.
+    fn seek_granularity(&self) -> Option<Duration> {
+        self.members
+            .iter()
+            .map(|m| m.seek_granularity())
+            .try_fold(Duration::ZERO, |max, g| Some(max.max(g?)))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/sync_group.rs"]
+mod tests;
@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::decoders::{decode_sample, Endianness, PcmFormat};
+use crate::{NextSample, Sound};
+
+/// The most bytes read from the file at once.
+const READ_SIZE: usize = 4096;
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Play a PCM file that is still being written (e.g. a recording in progress).
+///
+/// Unlike a normal decoder, reaching the end of the file does not finish the
+/// sound. Instead `Paused` is returned and newly appended data is played as
+/// soon as it is available. Only whole frames are played so a partially
+/// written frame at the end of the file is held back until it is complete.
+/// Once [stop][TailSoundHandle::stop] has been called the data already in the
+/// file is played followed by `Finished`.
+///
+/// The length fields of a WAV header are ignored since they are usually not
+/// correct until the recording is finished.
+///
+/// The file is read on the thread pulling samples. This is generally not
+/// recommended on the renderer thread as reading from a file could block the
+/// renderer.
+pub struct TailSound {
+    file: File,
+    channel_count: u16,
+    sample_rate: u32,
+    format: PcmFormat,
+    /// Bytes read from the file that do not make up a whole frame yet.
+    partial: Vec<u8>,
+    samples: VecDeque<i16>,
+    stopped: Arc<AtomicBool>,
+}
+
+/// Used to stop a [TailSound].
+#[derive(Clone)]
+pub struct TailSoundHandle {
+    stopped: Arc<AtomicBool>,
+}
+
+impl TailSound {
+    /// Play the PCM WAV file at `path` as it grows.
+    ///
+    /// The header must already have been written up to the start of the data
+    /// chunk. Only integer PCM is supported.
+    pub fn open_wav<P: AsRef<Path>>(path: P) -> Result<(TailSound, TailSoundHandle), crate::Error> {
+        let mut file = File::open(path)?;
+        let (channel_count, sample_rate, format) = read_wav_header(&mut file)?;
+        Ok(TailSound::new(file, channel_count, sample_rate, format))
+    }
+
+    /// Play the headerless PCM file at `path` as it grows.
+    ///
+    /// Panics if `format.bits_per_sample` is not 8, 16, 24 or 32 or if
+    /// `channel_count` or `sample_rate` are 0.
+    pub fn open_raw<P: AsRef<Path>>(
+        path: P,
+        channel_count: u16,
+        sample_rate: u32,
+        format: PcmFormat,
+    ) -> Result<(TailSound, TailSoundHandle), crate::Error> {
+        assert!(
+            matches!(format.bits_per_sample, 8 | 16 | 24 | 32),
+            "unsupported bits per sample: {}",
+            format.bits_per_sample
+        );
+        assert!(channel_count > 0, "channel_count must be at least 1");
+        assert!(sample_rate > 0, "sample_rate must be at least 1");
+        let file = File::open(path)?;
+        Ok(TailSound::new(file, channel_count, sample_rate, format))
+    }
+
+    fn new(
+        file: File,
+        channel_count: u16,
+        sample_rate: u32,
+        format: PcmFormat,
+    ) -> (TailSound, TailSoundHandle) {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let sound = TailSound {
+            file,
+            channel_count,
+            sample_rate,
+            format,
+            partial: Vec::new(),
+            samples: VecDeque::new(),
+            stopped: stopped.clone(),
+        };
+        (sound, TailSoundHandle { stopped })
+    }
+
+    /// The encoding of the samples.
+    pub fn format(&self) -> PcmFormat {
+        self.format
+    }
+
+    /// Read any newly appended whole frames into `samples`.
+    fn read_appended(&mut self) -> Result<(), crate::Error> {
+        let sample_bytes = self.format.bits_per_sample as usize / 8;
+        let frame_bytes = sample_bytes * self.channel_count as usize;
+        let start = self.partial.len();
+        self.partial.resize(start + READ_SIZE, 0);
+        let num_read = loop {
+            match self.file.read(&mut self.partial[start..]) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(e) => {
+                    self.partial.truncate(start);
+                    return Err(e.into());
+                }
+            }
+        };
+        self.partial.truncate(start + num_read);
+        let whole_len = self.partial.len() / frame_bytes * frame_bytes;
+        for bytes in self.partial[..whole_len].chunks_exact(sample_bytes) {
+            self.samples.push_back(decode_sample(bytes, self.format));
+        }
+        self.partial.drain(..whole_len);
+        Ok(())
+    }
+}
+
+impl TailSoundHandle {
+    /// Finish the sound once it has played all data currently in the file.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+    }
+}
+
+impl Sound for TailSound {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.samples.pop_front() {
+            return Ok(NextSample::Sample(sample));
+        }
+        // Check before reading so everything written before stop was called
+        // is played.
+        let stopped = self.stopped.load(Ordering::Acquire);
+        self.read_appended()?;
+        match self.samples.pop_front() {
+            Some(sample) => Ok(NextSample::Sample(sample)),
+            None if stopped => Ok(NextSample::Finished),
+            None => Ok(NextSample::Paused),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+/// Read a WAV header up to the start of the data chunk.
+fn read_wav_header<R: Read>(reader: &mut R) -> Result<(u16, u32, PcmFormat), crate::Error> {
+    let invalid = |msg: &str| -> crate::Error {
+        std::io::Error::new(ErrorKind::InvalidData, msg.to_owned()).into()
+    };
+    let mut riff = [0; 12];
+    reader.read_exact(&mut riff)?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        return Err(invalid("not a WAV file"));
+    }
+    let mut fmt = None;
+    loop {
+        let mut chunk_header = [0; 8];
+        reader.read_exact(&mut chunk_header)?;
+        if &chunk_header[0..4] == b"data" {
+            break;
+        }
+        let chunk_len = u32::from_le_bytes(chunk_header[4..8].try_into().unwrap()) as usize;
+        // Chunks are padded to an even length
+        let mut body = vec![0; chunk_len + (chunk_len & 1)];
+        reader.read_exact(&mut body)?;
+        if &chunk_header[0..4] == b"fmt " {
+            if chunk_len < 16 {
+                return Err(invalid("fmt chunk too short"));
+            }
+            fmt = Some(body);
+        }
+    }
+    let fmt = fmt.ok_or_else(|| invalid("missing fmt chunk"))?;
+    let read_u16 = |offset: usize| u16::from_le_bytes([fmt[offset], fmt[offset + 1]]);
+    let format_tag = read_u16(0);
+    let channel_count = read_u16(2);
+    let sample_rate = u32::from_le_bytes(fmt[4..8].try_into().unwrap());
+    let bits_per_sample = read_u16(14);
+    let is_pcm = match format_tag {
+        WAVE_FORMAT_PCM => true,
+        // The first two bytes of the SubFormat GUID are the format tag
+        WAVE_FORMAT_EXTENSIBLE => fmt.len() >= 26 && read_u16(24) == WAVE_FORMAT_PCM,
+        _ => false,
+    };
+    if !is_pcm {
+        return Err(invalid("only integer PCM WAV files are supported"));
+    }
+    if !matches!(bits_per_sample, 8 | 16 | 24 | 32) {
+        return Err(invalid("unsupported bits per sample"));
+    }
+    if channel_count == 0 || sample_rate == 0 {
+        return Err(invalid("invalid channel count or sample rate"));
+    }
+    let format = PcmFormat {
+        bits_per_sample,
+        // 8 bit WAV samples are unsigned
+        signed: bits_per_sample != 8,
+        endianness: Endianness::Little,
+    };
+    Ok((channel_count, sample_rate, format))
+}
+
+#[cfg(test)]
+#[path = "./tests/tail_sound.rs"]
+mod tests;
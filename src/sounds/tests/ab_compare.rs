@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::{MemorySound, SineWav};
+
+const SAMPLE_RATE: u32 = 8000;
+
+fn source() -> Box<dyn Sound> {
+    Box::new(MemorySound::from_samples(
+        Arc::new(vec![10000; 2000]),
+        2,
+        SAMPLE_RATE,
+    ))
+}
+
+fn compare() -> (ABCompare, ABCompareHandle) {
+    ABCompare::new(
+        source(),
+        |tap| Box::new(tap.with_adjustable_volume_of(1.0)),
+        |tap| Box::new(tap.with_adjustable_volume_of(0.5)),
+    )
+    .unwrap()
+}
+
+fn next_frames(compare: &mut ABCompare, count: usize) -> Vec<i16> {
+    (0..count)
+        .map(|_| {
+            let frame = compare.next_frame().unwrap();
+            assert_eq!(frame[0], frame[1]);
+            frame[0]
+        })
+        .collect()
+}
+
+#[test]
+fn toggle_crossfades_without_gap() {
+    let (mut compare, handle) = compare();
+    assert_eq!(handle.selected(), ABChoice::A);
+    assert!(next_frames(&mut compare, 100).iter().all(|s| *s == 10000));
+
+    handle.toggle();
+    assert_eq!(handle.selected(), ABChoice::B);
+    // 5ms is 40 frames
+    let fade = next_frames(&mut compare, 40);
+    assert!(fade.windows(2).all(|pair| pair[1] < pair[0]), "{fade:?}");
+    assert!(fade[0] > 9800 && fade[0] < 10000, "{fade:?}");
+    assert_eq!(fade[39], 5000);
+    assert!(next_frames(&mut compare, 100).iter().all(|s| *s == 5000));
+
+    handle.select(ABChoice::A);
+    let fade = next_frames(&mut compare, 40);
+    assert!(fade.windows(2).all(|pair| pair[1] > pair[0]), "{fade:?}");
+    assert_eq!(fade[39], 10000);
+
+    // Every source frame is played exactly once
+    assert_eq!(next_frames(&mut compare, 720).len(), 720);
+    assert_eq!(compare.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn chains_must_match_source_format() {
+    let result = ABCompare::new(
+        source(),
+        |tap| tap,
+        |_| Box::new(SineWav::with_sample_rate(440.0, 44100)),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn paused_chain_stays_in_sync() {
+    let ramp: Vec<i16> = (0..1000).flat_map(|i| [i, i]).collect();
+    let source = Box::new(MemorySound::from_samples(Arc::new(ramp), 2, SAMPLE_RATE));
+    let mut controller = None;
+    let (mut compare, handle) = ABCompare::new(
+        source,
+        |tap| tap,
+        |tap| {
+            let (chain, chain_controller) = tap.pausable().controllable();
+            controller = Some(chain_controller);
+            Box::new(chain)
+        },
+    )
+    .unwrap();
+    let mut controller = controller.unwrap();
+    compare.set_crossfade(Duration::ZERO);
+    assert_eq!(next_frames(&mut compare, 10), (0..10).collect::<Vec<_>>());
+
+    controller.set_paused(true);
+    compare.on_start_of_batch();
+    assert_eq!(compare.next_sample().unwrap(), NextSample::Paused);
+    assert_eq!(compare.next_sample().unwrap(), NextSample::Paused);
+
+    controller.set_paused(false);
+    compare.on_start_of_batch();
+    assert_eq!(next_frames(&mut compare, 1), [10]);
+    handle.select(ABChoice::B);
+    assert_eq!(
+        next_frames(&mut compare, 989),
+        (11..1000).collect::<Vec<_>>()
+    );
+    // Controllable only finishes once its controller is dropped
+    drop(controller);
+    compare.on_start_of_batch();
+    assert_eq!(compare.next_sample().unwrap(), NextSample::Finished);
+}
@@ -0,0 +1,64 @@
+use super::*;
+use crate::tests::{collect_n, goertzel};
+
+const SAMPLE_RATE: u32 = 22050;
+
+/// The index of the band of `bands` with the most average power.
+fn loudest_band(samples: &[i16], bands: &[&[f32]]) -> usize {
+    let power = |band: &[f32]| {
+        band.iter()
+            .map(|f| goertzel(samples, *f, SAMPLE_RATE))
+            .sum::<f32>()
+            / band.len() as f32
+    };
+    (0..bands.len())
+        .max_by(|a, b| power(bands[*a]).total_cmp(&power(bands[*b])))
+        .unwrap()
+}
+
+#[test]
+fn presets_are_bounded_and_do_not_repeat() {
+    for preset in [NoisePreset::Wind, NoisePreset::Ocean, NoisePreset::Rain] {
+        let mut sound = AmbientNoise::with_sample_rate(preset, SAMPLE_RATE);
+        assert_eq!(sound.channel_count(), 1);
+        let samples = collect_n(&mut sound, 10 * SAMPLE_RATE as usize);
+        let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap();
+        assert!(peak > 1000, "{preset:?} peak {peak}");
+        assert!(peak < i16::MAX as u16, "{preset:?} clipped");
+        // No second of audio is repeated later
+        let (first, rest) = samples.split_at(SAMPLE_RATE as usize);
+        for second in rest.chunks(SAMPLE_RATE as usize) {
+            assert_ne!(first, second);
+        }
+        assert!(!rest.windows(100).any(|w| w == &first[..100]));
+    }
+}
+
+#[test]
+fn presets_have_different_dominant_bands() {
+    let low: &[f32] = &[60.0, 90.0, 130.0, 180.0, 250.0];
+    let mid: &[f32] = &[400.0, 550.0, 700.0, 900.0, 1200.0];
+    let high: &[f32] = &[3000.0, 4000.0, 5500.0, 7000.0, 9000.0];
+    let bands = [low, mid, high];
+    for (preset, expected) in [
+        (NoisePreset::Ocean, 0),
+        (NoisePreset::Wind, 1),
+        (NoisePreset::Rain, 2),
+    ] {
+        let mut sound = AmbientNoise::with_sample_rate(preset, SAMPLE_RATE);
+        let samples = collect_n(&mut sound, 4 * SAMPLE_RATE as usize);
+        assert_eq!(loudest_band(&samples, &bands), expected, "{preset:?}");
+    }
+}
+
+#[test]
+fn seed_changes_output() {
+    let mut first = AmbientNoise::new(NoisePreset::Wind);
+    let mut second = AmbientNoise::new(NoisePreset::Wind);
+    let mut third = AmbientNoise::new(NoisePreset::Wind);
+    third.set_seed(7);
+    let samples = collect_n(&mut first, 1000);
+    assert_eq!(samples, collect_n(&mut second, 1000));
+    assert_ne!(samples, collect_n(&mut third, 1000));
+    assert!(!first.may_block());
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+
+use super::*;
+
+fn constant(value: i16) -> Box<dyn Sound> {
+    let mut sound = ConstantValueSound::new(value);
+    sound.channel_count = 1;
+    Box::new(sound)
+}
+
+#[test]
+fn switches_at_next_beat_boundary() {
+    let (mut switch, handle) = BeatSwitch::new(vec![constant(1), constant(2)], 4).unwrap();
+    for _ in 0..5 {
+        assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(1));
+    }
+    // Mid way through the second beat
+    handle.select(1);
+    assert_eq!(handle.selected(), 1);
+    for _ in 5..8 {
+        assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(1));
+        assert_eq!(switch.current(), 0);
+    }
+    assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(switch.current(), 1);
+}
+
+#[test]
+fn loops_stay_in_time() {
+    let ramp = |offset: i16| -> Box<dyn Sound> {
+        let samples: Vec<i16> = (0..6).map(|i| offset + i).collect();
+        Box::new(MemorySound::from_samples(Arc::new(samples), 1, 1000))
+    };
+    let (mut switch, handle) = BeatSwitch::new(vec![ramp(0), ramp(100)], 2).unwrap();
+    assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(0));
+    handle.select(1);
+    assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(1));
+    // The second loop continues from the same position
+    assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(102));
+    assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(103));
+    assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(104));
+    assert_eq!(switch.next_sample().unwrap(), NextSample::Sample(105));
+    assert_eq!(switch.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn beat_length_from_bpm() {
+    let mut first = ConstantValueSound::new(0);
+    first.sample_rate = 44100;
+    let (switch, _handle) = BeatSwitch::with_bpm(vec![Box::new(first)], 120.0).unwrap();
+    assert_eq!(switch.beat_frames(), 22050);
+}
+
+#[test]
+fn mismatched_formats_are_an_error() {
+    let mut stereo = ConstantValueSound::new(0);
+    stereo.channel_count = 2;
+    assert!(BeatSwitch::new(vec![constant(0), Box::new(stereo)], 4).is_err());
+    assert!(BeatSwitch::new(Vec::new(), 4).is_err());
+    assert!(BeatSwitch::new(vec![constant(0)], 0).is_err());
+}
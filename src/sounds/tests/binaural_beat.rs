@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use super::*;
+
+/// The number of times `samples` crosses zero going upward.
+fn rising_zero_crossings(samples: &[i16]) -> usize {
+    samples.windows(2).filter(|w| w[0] < 0 && w[1] >= 0).count()
+}
+
+#[test]
+fn channels_have_configured_frequencies() {
+    let mut beat = BinauralBeat::with_sample_rate(200.0, 10.0, 8000);
+    beat.set_duration(Some(Duration::from_secs(1)));
+    assert_eq!(beat.channel_count(), 2);
+    assert_eq!(beat.sample_rate(), 8000);
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    while let NextSample::Sample(l) = beat.next_sample().unwrap() {
+        let NextSample::Sample(r) = beat.next_sample().unwrap() else {
+            panic!("finished mid frame");
+        };
+        left.push(l);
+        right.push(r);
+    }
+    assert_eq!(beat.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(left.len(), 8000);
+
+    // The first cycle starts at zero so is not counted
+    let left_frequency = rising_zero_crossings(&left) + 1;
+    let right_frequency = rising_zero_crossings(&right) + 1;
+    assert_eq!(left_frequency, 200);
+    assert_eq!(right_frequency, 210);
+    assert_eq!(right_frequency - left_frequency, 10);
+}
+
+#[test]
+fn frequency_change_is_phase_continuous() {
+    let mut beat = BinauralBeat::with_sample_rate(100.0, 0.0, 8000);
+    let mut previous = 0;
+    for i in 0..8000 {
+        if i == 1234 {
+            beat.set_frequencies(300.0, 0.0);
+        }
+        let NextSample::Sample(left) = beat.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        beat.next_sample().unwrap();
+        // The largest step of a full scale 300 Hz sine wave at 8000 Hz
+        assert!((left as i32 - previous as i32).abs() < 7800);
+        previous = left;
+    }
+}
+
+#[test]
+fn clone_plays_from_the_start() {
+    let mut beat = BinauralBeat::with_sample_rate(200.0, 10.0, 8000);
+    beat.set_duration(Some(Duration::from_millis(100)));
+    let mut clone = beat.try_clone_boxed().unwrap();
+    let expected = crate::tests::collect(&mut beat);
+    assert_eq!(expected.len(), 1600);
+    assert_eq!(crate::tests::collect(&mut clone), expected);
+    // Clones of a finished sound still play the whole duration
+    let mut clone = beat.try_clone_boxed().unwrap();
+    assert_eq!(crate::tests::collect(&mut clone), expected);
+}
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use super::*;
+use crate::tests::{wav_bytes, write_temp_file};
+
+fn write_wav(name: &str, channel_count: u16, sample_rate: u32, secs: u32, value: i16) -> PathBuf {
+    let samples = vec![value; (sample_rate * secs * channel_count as u32) as usize];
+    write_temp_file(name, &wav_bytes(channel_count, sample_rate, &samples))
+}
+
+#[test]
+fn plays_chunks_in_numeric_order() {
+    // Written out of order and unpadded so name order differs from numeric
+    // order.
+    let third = write_wav("chunked_plays_10.wav", 1, 8000, 1, 3000);
+    let first = write_wav("chunked_plays_1.wav", 1, 8000, 2, 1000);
+    let second = write_wav("chunked_plays_2.wav", 2, 16000, 1, 2000);
+    write_temp_file("chunked_plays_notes.txt", b"not a chunk");
+    let dir = first.parent().unwrap();
+
+    let mut sound = ChunkedDirSound::new(dir, "chunked_plays_%d.wav").unwrap();
+    assert_eq!(sound.paths(), [first, second, third]);
+    assert_eq!(sound.channel_count(), 1);
+    assert_eq!(sound.sample_rate(), 8000);
+    assert_eq!(sound.total_duration().unwrap(), Duration::from_secs(4));
+
+    let mut values = Vec::new();
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => match values.last_mut() {
+                Some((value, count)) if *value == s => *count += 1,
+                _ => values.push((s, 1)),
+            },
+            NextSample::MetadataChanged => {
+                assert_eq!(sound.channel_count(), 1);
+                assert_eq!(sound.sample_rate(), 8000);
+            }
+            NextSample::Paused => panic!("unexpected pause"),
+            NextSample::Finished => break,
+        }
+    }
+    let order: Vec<i16> = values.iter().map(|(value, _)| *value).collect();
+    assert_eq!(order, [1000, 2000, 3000], "{values:?}");
+    assert_eq!(values[0].1, 16000);
+    assert!(values[1].1 > 7900, "{values:?}");
+    assert_eq!(values[2].1, 8000);
+    assert_eq!(sound.current_position(), Duration::from_secs(4));
+}
+
+#[test]
+fn zero_padded_pattern() {
+    let first = write_wav("chunked_padded_0001.wav", 1, 8000, 1, 1000);
+    write_wav("chunked_padded_0002.wav", 1, 8000, 1, 2000);
+    // Too few digits for the pattern
+    write_wav("chunked_padded_3.wav", 1, 8000, 1, 3000);
+    let dir = first.parent().unwrap();
+
+    let mut sound = ChunkedDirSound::new(dir, "chunked_padded_%04d.wav").unwrap();
+    assert_eq!(sound.paths().len(), 2);
+    assert_eq!(sound.total_duration().unwrap(), Duration::from_secs(2));
+}
+
+#[test]
+fn invalid_pattern_or_no_matches_is_an_error() {
+    let path = write_wav("chunked_invalid_1.wav", 1, 8000, 1, 1000);
+    let dir = path.parent().unwrap();
+    assert!(ChunkedDirSound::new(dir, "chunked_invalid_1.wav").is_err());
+    assert!(ChunkedDirSound::new(dir, "chunked_invalid_%4d.wav").is_err());
+    assert!(ChunkedDirSound::new(dir, "chunked_missing_%d.wav").is_err());
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use super::*;
+use crate::tests::goertzel;
+
+const SAMPLE_RATE: u32 = 8000;
+
+/// The strongest of `candidates` in `samples`.
+fn detect(samples: &[i16], candidates: &[f32]) -> f32 {
+    *candidates
+        .iter()
+        .max_by(|a, b| {
+            goertzel(samples, **a, SAMPLE_RATE).total_cmp(&goertzel(samples, **b, SAMPLE_RATE))
+        })
+        .unwrap()
+}
+
+#[test]
+fn digits_have_correct_frequency_pairs() {
+    let mut dtmf = Dtmf::with_sample_rate("123", SAMPLE_RATE).unwrap();
+    dtmf.set_tone_duration(Duration::from_millis(50));
+    dtmf.set_gap_duration(Duration::from_millis(25));
+    assert_eq!(dtmf.channel_count(), 1);
+    assert_eq!(dtmf.sample_rate(), SAMPLE_RATE);
+    let mut samples = Vec::new();
+    while let NextSample::Sample(s) = dtmf.next_sample().unwrap() {
+        samples.push(s);
+    }
+    assert_eq!(dtmf.next_sample().unwrap(), NextSample::Finished);
+
+    // 3 tones of 400 frames with 2 gaps of 200 frames
+    assert_eq!(samples.len(), 3 * 400 + 2 * 200);
+    let expected_high = [1209.0, 1336.0, 1477.0];
+    for (i, high) in expected_high.iter().enumerate() {
+        let start = i * 600;
+        let tone = &samples[start..start + 400];
+        assert_eq!(detect(tone, &LOW_FREQUENCIES), 697.0);
+        assert_eq!(detect(tone, &HIGH_FREQUENCIES), *high);
+        if i < 2 {
+            assert!(samples[start + 400..start + 600].iter().all(|s| *s == 0));
+        }
+    }
+}
+
+#[test]
+fn all_digits_are_accepted() {
+    let mut dtmf = Dtmf::new("0123456789*#ABCDabcd").unwrap();
+    assert_eq!(dtmf.sample_rate(), 8000);
+    let mut num_samples = 0;
+    while let NextSample::Sample(_) = dtmf.next_sample().unwrap() {
+        num_samples += 1;
+    }
+    assert_eq!(num_samples, 20 * 800 + 19 * 800);
+}
+
+#[test]
+fn invalid_digit_is_an_error() {
+    assert!(Dtmf::new("12E").is_err());
+    assert!(Dtmf::with_sample_rate("1", 3999).is_err());
+}
+
+#[test]
+fn empty_string_finishes() {
+    let mut dtmf = Dtmf::new("").unwrap();
+    assert_eq!(dtmf.next_sample().unwrap(), NextSample::Finished);
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::*;
+use crate::tests::collect;
+
+#[test]
+fn grain_density_matches_configuration() {
+    let samples: Vec<i16> = (0..800).map(|i| ((i % 100) * 200 - 10000) as i16).collect();
+    let mut granular = Granular::new(Arc::new(samples), 1, 8000);
+    granular.set_density(40.0);
+    granular.set_grain_duration(Duration::from_millis(60));
+    granular.set_pitch_jitter(2.0);
+    granular.set_duration(Some(Duration::from_secs(2)));
+    let output = collect(&mut granular);
+    assert_eq!(output.len(), 16000);
+    assert!((79..=81).contains(&granular.grains_started()));
+    assert!(output.iter().all(|s| s.unsigned_abs() <= 10000));
+    assert!(output.iter().any(|s| *s != 0));
+}
+
+#[test]
+fn overlapping_grains_stay_within_source_level() {
+    let mut granular = Granular::new(Arc::new(vec![10000, -10000]), 2, 1000);
+    granular.set_density(200.0);
+    granular.set_grain_duration(Duration::from_millis(30));
+    granular.set_seed(7);
+    granular.set_duration(Some(Duration::from_secs(1)));
+    let output = collect(&mut granular);
+    assert_eq!(output.len(), 2000);
+    for frame in output.chunks(2) {
+        assert!(frame[0] <= 10000 && frame[0] >= 0);
+        assert_eq!(frame[0], -frame[1]);
+    }
+    // Once grains overlap the level is close to the source level
+    assert!(output[100..].iter().step_by(2).all(|s| *s > 4000));
+}
+
+#[test]
+fn clone_plays_from_the_start() {
+    let samples: Vec<i16> = (0..800).map(|i| ((i % 100) * 200 - 10000) as i16).collect();
+    let mut granular = Granular::new(Arc::new(samples), 1, 8000);
+    granular.set_pitch_jitter(2.0);
+    granular.set_seed(3);
+    granular.set_duration(Some(Duration::from_millis(500)));
+    let expected = collect(&mut granular);
+    assert_eq!(expected.len(), 4000);
+    let mut clone = granular.try_clone_boxed().unwrap();
+    assert_eq!(collect(&mut clone), expected);
+}
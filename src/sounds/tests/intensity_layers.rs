@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+
+use super::*;
+
+fn constant(value: i16) -> Box<dyn Sound> {
+    let mut sound = ConstantValueSound::new(value);
+    sound.channel_count = 1;
+    sound.sample_rate = 1000;
+    Box::new(sound)
+}
+
+fn next(layers: &mut IntensityLayers) -> i16 {
+    match layers.next_sample().unwrap() {
+        NextSample::Sample(s) => s,
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn layers_fade_in_and_out_at_thresholds() {
+    let (mut layers, handle) = IntensityLayers::new(
+        vec![
+            (constant(1000), 0.0),
+            (constant(2000), 0.5),
+            (constant(4000), 0.9),
+        ],
+        Duration::from_millis(4),
+    )
+    .unwrap();
+    assert_eq!(next(&mut layers), 1000);
+
+    // Below the second threshold nothing changes
+    handle.set_intensity(0.4);
+    assert_eq!(next(&mut layers), 1000);
+
+    handle.set_intensity(0.5);
+    let faded: Vec<i16> = (0..5).map(|_| next(&mut layers)).collect();
+    assert_eq!(faded, vec![1500, 2000, 2500, 3000, 3000]);
+
+    handle.set_intensity(1.0);
+    assert_eq!(handle.intensity(), 1.0);
+    let faded: Vec<i16> = (0..5).map(|_| next(&mut layers)).collect();
+    assert_eq!(faded, vec![4000, 5000, 6000, 7000, 7000]);
+    assert_eq!(layers.gains(), vec![1.0, 1.0, 1.0]);
+
+    // Back below both thresholds fades both layers out together
+    handle.set_intensity(0.0);
+    let faded: Vec<i16> = (0..5).map(|_| next(&mut layers)).collect();
+    assert_eq!(faded, vec![5500, 4000, 2500, 1000, 1000]);
+}
+
+#[test]
+fn silent_layers_stay_in_sync() {
+    let ramp = |offset: i16| -> Box<dyn Sound> {
+        let samples: Vec<i16> = (0..4).map(|i| offset + i).collect();
+        Box::new(MemorySound::from_samples(Arc::new(samples), 1, 1000))
+    };
+    let (mut layers, handle) =
+        IntensityLayers::new(vec![(ramp(0), 0.0), (ramp(100), 0.5)], Duration::ZERO).unwrap();
+    assert_eq!(next(&mut layers), 0);
+    assert_eq!(next(&mut layers), 1);
+    handle.set_intensity(0.7);
+    // The second layer continues from the same position
+    assert_eq!(next(&mut layers), 2 + 102);
+    assert_eq!(next(&mut layers), 3 + 103);
+    assert_eq!(layers.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn invalid_layers_are_an_error() {
+    assert!(IntensityLayers::new(Vec::new(), Duration::ZERO).is_err());
+    assert!(IntensityLayers::new(vec![(constant(0), 1.5)], Duration::ZERO).is_err());
+    let mut stereo = ConstantValueSound::new(0);
+    stereo.channel_count = 2;
+    stereo.sample_rate = 1000;
+    assert!(IntensityLayers::new(
+        vec![(constant(0), 0.0), (Box::new(stereo), 0.5)],
+        Duration::ZERO
+    )
+    .is_err());
+}
@@ -0,0 +1,85 @@
+use super::*;
+use crate::tests::collect;
+
+const SAMPLE_RATE: u32 = 16000;
+
+/// The average frequency of `samples` from the number of zero crossings.
+fn zero_crossing_frequency(samples: &[i16]) -> f32 {
+    let crossings = samples
+        .windows(2)
+        .filter(|w| (w[0] < 0) != (w[1] < 0))
+        .count();
+    crossings as f32 / 2.0 * SAMPLE_RATE as f32 / samples.len() as f32
+}
+
+#[test]
+fn silence_tails_are_exact() {
+    let mut sweep =
+        MeasurementSweep::with_sample_rate(100.0, 4000.0, Duration::from_secs(1), SAMPLE_RATE);
+    sweep.set_silence(Duration::from_millis(250), Duration::from_millis(500));
+    let samples = collect(&mut sweep);
+    assert_eq!(sweep.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(samples.len(), 4000 + 16000 + 8000);
+    assert!(samples[..4000].iter().all(|s| *s == 0));
+    assert!(samples[20000..].iter().all(|s| *s == 0));
+    // The sweep starts at phase 0 and is not silent at its edges
+    assert_eq!(samples[4000], 0);
+    assert_ne!(samples[4001], 0);
+    assert_ne!(samples[19999], 0);
+}
+
+#[test]
+fn sweep_covers_frequency_range() {
+    let mut sweep =
+        MeasurementSweep::with_sample_rate(100.0, 4000.0, Duration::from_secs(2), SAMPLE_RATE);
+    assert_eq!(sweep.sweep_frames(), 32000);
+    assert_eq!(sweep.frequency_at(0), 100.0);
+    assert!((sweep.frequency_at(32000) - 4000.0).abs() < 0.01);
+    let samples = collect(&mut sweep);
+    assert_eq!(samples.len(), 32000);
+    let peak = samples.iter().map(|s| s.unsigned_abs()).max().unwrap();
+    assert!(peak > 16000 && peak <= 16384, "{peak}");
+
+    // Measure in windows short enough that the frequency barely changes
+    for start in [0, 8000, 16000, 24000, 31000] {
+        let window = &samples[start..start + 1000];
+        let expected = sweep.frequency_at(start as u64 + 500);
+        let measured = zero_crossing_frequency(window);
+        assert!(
+            (measured - expected).abs() < expected * 0.05 + 16.0,
+            "at {start}: measured {measured}, expected {expected}"
+        );
+    }
+}
+
+#[test]
+fn inverse_filter_recovers_impulse() {
+    let sweep =
+        MeasurementSweep::with_sample_rate(50.0, 8000.0, Duration::from_millis(250), SAMPLE_RATE);
+    let n = sweep.sweep_frames() as usize;
+    let signal: Vec<f64> = (0..n as u64).map(|i| sweep.sweep_value(i)).collect();
+    let filter = sweep.inverse_filter();
+    assert_eq!(filter.len(), n);
+    let convolved: Vec<f64> = (0..2 * n - 1)
+        .map(|k| {
+            let lo = k.saturating_sub(n - 1);
+            let hi = k.min(n - 1);
+            (lo..=hi).map(|i| signal[i] * filter[k - i] as f64).sum()
+        })
+        .collect();
+    let (peak_idx, peak) = convolved
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .unwrap();
+    assert_eq!(peak_idx, n - 1);
+    assert!((peak - 1.0).abs() < 1e-6, "{peak}");
+    // Away from the peak the result is small
+    let side = convolved
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i.abs_diff(n - 1) > 20)
+        .map(|(_, v)| v.abs())
+        .fold(0.0, f64::max);
+    assert!(side < 0.1, "{side}");
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::{sounds::SoundList, NextSample, Sound};
 
 use super::*;
@@ -83,3 +85,138 @@ fn loop_forever() {
     assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
     assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
 }
+
+#[test]
+fn loop_toggled_through_trait_object() {
+    let mut sound: Box<dyn Sound> =
+        Box::new(MemorySound::from_samples(Arc::new(vec![1, 2]), 1, 1000));
+    sound.set_looping(true);
+    assert!(sound.is_looping());
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    sound.set_looping(false);
+    assert!(!sound.is_looping());
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn loop_click_marks_each_restart() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4, 5, 6]), 2, 1000);
+    sound.set_looping(true);
+    sound.set_loop_click(true);
+    let samples: Vec<i16> = (0..18)
+        .map(|_| match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => s,
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    let click = i16::MAX / 2;
+    let mut expected = vec![1, 2, 3, 4, 5, 6];
+    expected.extend([1 + click, 2 + click, 3, 4, 5, 6]);
+    expected.extend([1 + click, 2 + click, 3, 4, 5, 6]);
+    assert_eq!(samples, expected);
+
+    sound.set_loop_click(false);
+    for expected in [1, 2, 3, 4, 5, 6, 1, 2] {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+}
+
+#[test]
+fn scan_peak() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![100, -16384, 8000, 2]), 2, 1000);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(100));
+    assert!((sound.scan_peak() - 0.5).abs() < 0.001);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(-16384));
+}
+
+#[test]
+fn seek() {
+    let mut sound = MemorySound::from_samples(Arc::new((0..20).collect()), 2, 10);
+    assert_eq!(sound.seek_granularity(), Some(Duration::from_millis(100)));
+    let position = sound.seek(Duration::from_millis(300)).unwrap();
+    assert_eq!(position, Duration::from_millis(300));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(6));
+    let position = sound.seek(Duration::from_secs(5)).unwrap();
+    assert_eq!(position, Duration::from_secs(1));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn does_not_block() {
+    let sound: Box<dyn Sound> = Box::new(MemorySound::from_samples(Arc::new(vec![1]), 1, 1000));
+    assert!(!sound.may_block());
+}
+
+#[test]
+fn emitted_frame_index_follows_read_cursor() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![1, 2, 3, 4, 5, 6]), 2, 1000);
+    let mut indexes = Vec::new();
+    while let NextSample::Sample(_) = sound.next_sample().unwrap() {
+        indexes.push(sound.emitted_frame_index());
+    }
+    assert_eq!(indexes, [0, 1, 1, 2, 2, 3]);
+    sound.seek(Duration::from_millis(1)).unwrap();
+    assert_eq!(sound.emitted_frame_index(), 1);
+    sound.set_looping(true);
+    for _ in 0..4 {
+        sound.next_sample().unwrap();
+    }
+    assert_eq!(sound.emitted_frame_index(), 3);
+    sound.next_sample().unwrap();
+    assert_eq!(sound.emitted_frame_index(), 0);
+}
+
+#[test]
+fn progress_through_sound() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![0; 2000]), 2, 1000);
+    assert_eq!(sound.progress(), Some(0.0));
+    sound.skip(Duration::from_millis(500)).unwrap();
+    assert_eq!(sound.progress(), Some(0.5));
+    while let NextSample::Sample(_) = sound.next_sample().unwrap() {}
+    assert_eq!(sound.progress(), Some(1.0));
+
+    let empty = MemorySound::from_samples(Arc::new(vec![]), 2, 1000);
+    assert_eq!(empty.progress(), None);
+}
+
+#[test]
+fn set_end_finishes_at_position() {
+    let samples: Vec<i16> = (0..200).collect();
+    let mut sound = MemorySound::from_samples(Arc::new(samples), 2, 1000);
+    for expected in 0..20 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    sound.set_end(Some(Duration::from_millis(30)));
+    assert_eq!(sound.end(), Some(Duration::from_millis(30)));
+    for expected in 20..60 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+
+    // Clearing the end plays the rest
+    sound.set_end(None);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(60));
+
+    // Looping starts over at the end
+    sound.set_end(Some(Duration::from_millis(31)));
+    sound.set_looping(true);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(61));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+}
+
+#[test]
+fn samples_remaining_after_seek_and_end() {
+    let mut sound = MemorySound::from_samples(Arc::new((0..20).collect()), 2, 1000);
+    assert_eq!(sound.samples_remaining(), Some(20));
+    sound.next_sample().unwrap();
+    assert_eq!(sound.samples_remaining(), Some(19));
+    sound.seek(Duration::from_millis(4)).unwrap();
+    assert_eq!(sound.samples_remaining(), Some(12));
+    sound.set_end(Some(Duration::from_millis(6)));
+    assert_eq!(sound.samples_remaining(), Some(4));
+    sound.set_looping(true);
+    assert_eq!(sound.samples_remaining(), None);
+}
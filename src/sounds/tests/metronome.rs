@@ -0,0 +1,63 @@
+use super::*;
+use crate::tests::collect;
+
+const SAMPLE_RATE: u32 = 8000;
+
+fn peak(samples: &[i16]) -> i16 {
+    samples
+        .iter()
+        .map(|s| s.saturating_abs())
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn clicks_at_expected_intervals() {
+    // 70 BPM is not a whole number of frames per beat at 8 kHz
+    let mut metronome = Metronome::with_format(70.0, 1, SAMPLE_RATE);
+    metronome.set_bars(Some(3));
+    let samples = collect(&mut metronome);
+    assert_eq!(metronome.next_sample().unwrap(), NextSample::Finished);
+
+    let frames_per_beat = 60.0 * SAMPLE_RATE as f64 / 70.0;
+    let starts: Vec<usize> = (0..12)
+        .map(|beat| (beat as f64 * frames_per_beat).round() as usize)
+        .collect();
+    assert_eq!(samples.len(), (12.0 * frames_per_beat).round() as usize);
+    // 15ms clicks
+    let click_frames = 120;
+    for (beat, start) in starts.iter().enumerate() {
+        let click = &samples[*start..start + click_frames];
+        assert_eq!(click[0], 0, "clicks start at silence");
+        let expected_peak = if beat % 4 == 0 { 29000 } else { 16000 };
+        assert!(peak(click) > expected_peak, "beat {beat}: {}", peak(click));
+        let end = starts.get(beat + 1).copied().unwrap_or(samples.len());
+        assert!(
+            samples[start + click_frames..end].iter().all(|s| *s == 0),
+            "beat {beat}"
+        );
+    }
+}
+
+#[test]
+fn accents_can_be_disabled() {
+    let mut metronome = Metronome::with_format(120.0, 1, SAMPLE_RATE);
+    metronome.set_accent(false);
+    metronome.set_beats_per_bar(3);
+    metronome.set_bars(Some(1));
+    let samples = collect(&mut metronome);
+    assert_eq!(samples.len(), 3 * 4000);
+    let peaks: Vec<i16> = samples.chunks(4000).map(peak).collect();
+    assert!(peaks.iter().all(|p| *p == peaks[0]), "{peaks:?}");
+}
+
+#[test]
+fn every_channel_gets_the_same_value() {
+    let mut metronome = Metronome::with_format(120.0, 2, SAMPLE_RATE);
+    assert_eq!(metronome.channel_count(), 2);
+    metronome.set_bars(Some(1));
+    let samples = collect(&mut metronome);
+    assert_eq!(samples.len(), 2 * 4 * 4000);
+    assert!(samples.chunks(2).all(|frame| frame[0] == frame[1]));
+    assert!(peak(&samples) > 0);
+}
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use super::*;
+use crate::tests::{wav_bytes, write_temp_file};
+
+fn write_wav(name: &str, channel_count: u16, sample_rate: u32, secs: u32, value: i16) -> PathBuf {
+    let samples = vec![value; (sample_rate * secs * channel_count as u32) as usize];
+    write_temp_file(name, &wav_bytes(channel_count, sample_rate, &samples))
+}
+
+fn test_files(test_name: &str) -> Vec<PathBuf> {
+    vec![
+        write_wav(&format!("{test_name}-first.wav"), 1, 8000, 1, 1000),
+        write_wav(&format!("{test_name}-second.wav"), 2, 16000, 1, 2000),
+        write_wav(&format!("{test_name}-third.wav"), 1, 8000, 1, 3000),
+    ]
+}
+
+#[test]
+fn plays_files_in_sequence_with_common_format() {
+    let mut sound = MultiFileSound::new(test_files("plays")).unwrap();
+    assert_eq!(sound.channel_count(), 1);
+    assert_eq!(sound.sample_rate(), 8000);
+    let mut counts = std::collections::BTreeMap::new();
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => *counts.entry(s).or_insert(0) += 1,
+            NextSample::MetadataChanged => {
+                assert_eq!(sound.channel_count(), 1);
+                assert_eq!(sound.sample_rate(), 8000);
+            }
+            NextSample::Paused => panic!("unexpected pause"),
+            NextSample::Finished => break,
+        }
+    }
+    assert_eq!(counts[&1000], 8000);
+    assert!(counts[&2000] > 7900, "{counts:?}");
+    assert_eq!(counts[&3000], 8000);
+    assert_eq!(sound.current_position(), Duration::from_secs(3));
+}
+
+#[test]
+fn seek_into_third_file() {
+    let mut sound = MultiFileSound::new(test_files("seek_into")).unwrap();
+    let position = sound.seek(Duration::from_millis(2500)).unwrap();
+    let tolerance = Duration::from_millis(50);
+    assert!(
+        position.abs_diff(Duration::from_millis(2500)) < tolerance,
+        "{position:?}"
+    );
+    assert_eq!(sound.current_position(), position);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(3000));
+
+    for _ in 0..799 {
+        sound.next_sample().unwrap();
+    }
+    assert_eq!(
+        sound.current_position(),
+        position + Duration::from_millis(100)
+    );
+}
+
+#[test]
+fn seek_back_to_first_file() {
+    let mut sound = MultiFileSound::new(test_files("seek_back")).unwrap();
+    sound.seek(Duration::from_millis(2500)).unwrap();
+    let position = sound.seek(Duration::from_millis(200)).unwrap();
+    assert!(position < Duration::from_millis(250), "{position:?}");
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1000));
+}
+
+#[test]
+fn failed_seek_keeps_playing_current_file() {
+    let paths = test_files("failed_seek");
+    let mut sound = MultiFileSound::new(paths.clone()).unwrap();
+    sound.total_duration().unwrap();
+    std::fs::remove_file(&paths[1]).unwrap();
+    assert!(sound.seek(Duration::from_millis(1500)).is_err());
+    assert_eq!(sound.current_position(), Duration::ZERO);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1000));
+}
@@ -0,0 +1,93 @@
+use super::*;
+use crate::tests::goertzel;
+
+const SAMPLE_RATE: u32 = 22050;
+
+/// Play `sound` for `num_frames` and return the samples of each channel.
+fn collect_channels(sound: &mut NoiseBed, num_frames: usize) -> Vec<Vec<i16>> {
+    let channel_count = sound.channel_count() as usize;
+    let mut channels = vec![Vec::with_capacity(num_frames); channel_count];
+    for _ in 0..num_frames {
+        for channel in &mut channels {
+            match sound.next_sample().unwrap() {
+                NextSample::Sample(s) => channel.push(s),
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+    }
+    channels
+}
+
+/// The average power of the octave starting at `frequency`, averaged over
+/// short blocks to smooth out the randomness of the noise.
+fn octave_power(samples: &[i16], frequency: f32) -> f32 {
+    let block = 2048;
+    let frequencies: Vec<f32> = (0..16)
+        .map(|i| frequency * 2f32.powf(i as f32 / 16.0))
+        .collect();
+    let mut total = 0.0;
+    let mut count = 0;
+    for chunk in samples.chunks_exact(block) {
+        for f in &frequencies {
+            total += goertzel(chunk, *f, SAMPLE_RATE);
+            count += 1;
+        }
+    }
+    total / count as f32
+}
+
+fn correlation(a: &[i16], b: &[i16]) -> f64 {
+    let (mut ab, mut aa, mut bb) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (*x as f64, *y as f64);
+        ab += x * y;
+        aa += x * x;
+        bb += y * y;
+    }
+    ab / (aa * bb).sqrt()
+}
+
+#[test]
+fn channels_are_decorrelated() {
+    for color in [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+        let mut sound = NoiseBed::with_sample_rate(color, 2, SAMPLE_RATE);
+        let channels = collect_channels(&mut sound, SAMPLE_RATE as usize * 4);
+        let r = correlation(&channels[0], &channels[1]);
+        assert!(r.abs() < 0.1, "{color:?} correlation {r}");
+        assert!(channels[0].iter().any(|s| *s != 0));
+    }
+}
+
+#[test]
+fn spectrum_matches_color() {
+    // The power 4 octaves up relative to the power at 100 Hz is 0 dB for
+    // white, -12 dB for pink and -24 dB for brown
+    let ratio = |color| {
+        let mut sound = NoiseBed::with_sample_rate(color, 1, SAMPLE_RATE);
+        let samples = collect_channels(&mut sound, SAMPLE_RATE as usize * 4).remove(0);
+        octave_power(&samples, 100.0) / octave_power(&samples, 1600.0)
+    };
+    let white = ratio(NoiseColor::White);
+    assert!((0.5..2.0).contains(&white), "white {white}");
+    let pink = ratio(NoiseColor::Pink);
+    assert!((8.0..32.0).contains(&pink), "pink {pink}");
+    let brown = ratio(NoiseColor::Brown);
+    assert!(brown > 100.0, "brown {brown}");
+}
+
+#[test]
+fn seed_is_reproducible() {
+    let mut a = NoiseBed::with_sample_rate(NoiseColor::Pink, 2, SAMPLE_RATE);
+    let mut b = NoiseBed::with_sample_rate(NoiseColor::Pink, 2, SAMPLE_RATE);
+    a.set_seed(7);
+    b.set_seed(7);
+    assert_eq!(
+        collect_channels(&mut a, 1000),
+        collect_channels(&mut b, 1000)
+    );
+    b.set_seed(8);
+    assert_ne!(
+        collect_channels(&mut a, 1000),
+        collect_channels(&mut b, 1000)
+    );
+}
@@ -0,0 +1,204 @@
+use super::*;
+use crate::{
+    tests::{wav_bytes, write_temp_file},
+    NextSample,
+};
+
+const FROM_RATE: u32 = 48000;
+const TO_RATE: u32 = 16000;
+
+/// RMS of the output of resampling a 15 kHz tone to 16 kHz. Everything in the
+/// output is aliasing since the tone is above the new Nyquist frequency.
+fn aliasing_rms(quality: ResampleQuality) -> f64 {
+    let samples: Vec<i16> = (0..FROM_RATE)
+        .map(|i| {
+            let t = i as f64 / FROM_RATE as f64;
+            ((2.0 * std::f64::consts::PI * 15000.0 * t).sin() * 16000.0) as i16
+        })
+        .collect();
+    let path = write_temp_file(
+        &format!("open_file_resampled_{quality:?}.wav"),
+        &wav_bytes(1, FROM_RATE, &samples),
+    );
+    let mut sound = open_file_resampled(path, TO_RATE, quality).unwrap();
+    assert_eq!(sound.sample_rate(), TO_RATE);
+    assert_eq!(sound.channel_count(), 1);
+
+    let mut sum = 0.0;
+    let mut count = 0;
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => {
+                // Ignore the start and end where the filter sees silence
+                if count > 100 && count < TO_RATE as usize - 100 {
+                    sum += (s as f64 / 16000.0).powi(2);
+                }
+                count += 1;
+            }
+            NextSample::MetadataChanged => assert_eq!(sound.sample_rate(), TO_RATE),
+            NextSample::Paused => panic!("unexpected pause"),
+            NextSample::Finished => break,
+        }
+    }
+    assert!(count.abs_diff(TO_RATE as usize) < 10, "{count}");
+    (sum / (count - 200) as f64).sqrt()
+}
+
+#[test]
+fn sinc_has_less_aliasing_than_linear() {
+    let linear = aliasing_rms(ResampleQuality::Linear);
+    let sinc = aliasing_rms(ResampleQuality::default());
+    assert!(linear > 0.1, "{linear}");
+    assert!(sinc < linear / 10.0, "sinc {sinc} linear {linear}");
+}
+
+#[test]
+fn decode_range_of_ramp() {
+    // Every sample is its own index
+    let samples: Vec<i16> = (0..5000).collect();
+    let path = write_temp_file("decode_range_ramp.wav", &wav_bytes(1, 1000, &samples));
+    let (excerpt, sample_rate, channel_count) = decode_range(
+        path,
+        std::time::Duration::from_millis(2000),
+        std::time::Duration::from_millis(2500),
+    )
+    .unwrap();
+    assert_eq!(sample_rate, 1000);
+    assert_eq!(channel_count, 1);
+    assert_eq!(excerpt, (2000..2500).collect::<Vec<i16>>());
+}
+
+#[test]
+fn decode_range_past_end() {
+    let samples: Vec<i16> = (0..1000).collect();
+    let path = write_temp_file("decode_range_past_end.wav", &wav_bytes(1, 1000, &samples));
+    let (excerpt, _, _) = decode_range(
+        path,
+        std::time::Duration::from_millis(900),
+        std::time::Duration::from_millis(1500),
+    )
+    .unwrap();
+    assert_eq!(excerpt, (900..1000).collect::<Vec<i16>>());
+}
+
+#[cfg(feature = "hound-wav")]
+#[test]
+fn falls_back_to_wav_decoder_when_symphonia_fails() {
+    let mut bytes = wav_bytes(1, 8000, &[1, 2, 3]);
+    // A RIFF length of 0 as written by some streaming recorders is rejected
+    // by Symphonia but accepted by hound.
+    bytes[4..8].copy_from_slice(&0u32.to_le_bytes());
+    let path = write_temp_file("open_file_fallback_to_wav.dat", &bytes);
+    let mut sound = open_file(path).unwrap();
+    assert_eq!(sound.sample_rate(), 8000);
+    for expected in 1..=3 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+}
+
+#[test]
+fn wrong_extension_falls_back_to_symphonia() {
+    let path = write_temp_file(
+        "open_file_wav_named_qoa.qoa",
+        &wav_bytes(1, 8000, &[1, 2, 3]),
+    );
+    let mut sound = open_file(path).unwrap();
+    assert_eq!(sound.sample_rate(), 8000);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+}
+
+#[test]
+fn errors_are_aggregated_when_all_decoders_fail() {
+    let path = write_temp_file("open_file_garbage.bin", &[0x55; 1000]);
+    let Err(crate::Error::FormatError(e)) = open_file(path) else {
+        panic!("expected a FormatError");
+    };
+    let chain = e.downcast_ref::<DecodeChainError>().unwrap();
+    let decoders: Vec<&str> = chain.errors().iter().map(|(name, _)| *name).collect();
+    assert_eq!(decoders.first(), Some(&"symphonia"));
+    assert_eq!(decoders.last(), Some(&"qoaudio"));
+}
+
+#[test]
+fn aac_lc_m4a() {
+    // AAC-LC, 44100 Hz, mono
+    let asc = [0x12, 0x08];
+    let frames = vec![crate::tests::silent_aac_frame(); 10];
+    let path = write_temp_file(
+        "open_file_aac_lc.m4a",
+        &crate::tests::m4a_bytes(&asc, 1, 44100, &frames),
+    );
+    let mut sound = open_file(path).unwrap();
+    assert_eq!(sound.sample_rate(), 44100);
+    assert_eq!(sound.channel_count(), 1);
+    let mut num_samples = 0;
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => {
+                assert_eq!(s, 0);
+                num_samples += 1;
+            }
+            NextSample::MetadataChanged => (),
+            NextSample::Paused | NextSample::Finished => break,
+        }
+    }
+    assert_eq!(num_samples, 10 * 1024);
+}
+
+/// Stands in for a platform asset reader such as an Android `AssetManager`
+/// asset.
+#[cfg(feature = "symphonia")]
+struct AssetSource {
+    data: std::io::Cursor<Vec<u8>>,
+}
+
+#[cfg(feature = "symphonia")]
+impl std::io::Read for AssetSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+#[cfg(feature = "symphonia")]
+impl std::io::Seek for AssetSource {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.data.seek(pos)
+    }
+}
+
+#[cfg(feature = "symphonia")]
+impl crate::sounds::decoders::MediaSource for AssetSource {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.data.get_ref().len() as u64)
+    }
+}
+
+#[cfg(feature = "symphonia")]
+#[test]
+fn open_asset_decodes_custom_source() {
+    let samples: Vec<i16> = (0..1000).map(|i| i * 3 - 1500).collect();
+    for extension in [Some("wav"), None] {
+        let source = AssetSource {
+            data: std::io::Cursor::new(wav_bytes(2, 22050, &samples)),
+        };
+        let mut sound = open_asset(Box::new(source), extension).unwrap();
+        assert_eq!(sound.channel_count(), 2);
+        assert_eq!(sound.sample_rate(), 22050);
+        let mut decoded = Vec::new();
+        while let NextSample::Sample(s) = sound.next_sample().unwrap() {
+            decoded.push(s);
+        }
+        assert_eq!(decoded, samples);
+
+        // Seeking goes through the custom source
+        sound.seek(Duration::from_millis(20)).unwrap();
+        assert_eq!(
+            sound.next_sample().unwrap(),
+            NextSample::Sample(samples[2 * 441])
+        );
+    }
+}
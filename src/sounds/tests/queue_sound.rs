@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use crate::{NextSample, Sound};
+
+use super::*;
+
+#[test]
+fn push_and_drain() {
+    let (mut sound, handle) = QueueSound::new(2, 1000);
+    assert_eq!(sound.channel_count(), 2);
+    assert_eq!(sound.sample_rate(), 1000);
+    handle.push([1, 2, 3, 4]);
+    handle.push(vec![5, 6]);
+    assert_eq!(handle.len(), 6);
+    for expected in 1..=6 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert!(handle.is_empty());
+    assert!(!handle.take_starved());
+}
+
+#[test]
+fn starvation_plays_silence_and_is_signaled() {
+    let (mut sound, handle) = QueueSound::new(1, 1000);
+    handle.push([7]);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(7));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+    assert!(handle.take_starved());
+    assert!(!handle.take_starved());
+    assert_eq!(handle.starved_count(), 2);
+    handle.push([8]);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(8));
+}
+
+#[test]
+fn closed_finishes_after_draining() {
+    let (mut sound, handle) = QueueSound::new(1, 1000);
+    handle.push([1, 2]);
+    handle.close();
+    handle.push([3]);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+    assert!(!handle.take_starved());
+}
+
+#[test]
+fn buffered_duration_follows_pushed_and_pulled_samples() {
+    let (mut sound, handle) = QueueSound::new(2, 48000);
+    assert_eq!(handle.buffered_duration(), Duration::ZERO);
+    handle.push(vec![1; 4800]);
+    assert_eq!(handle.buffered_duration(), Duration::from_millis(50));
+    assert_eq!(sound.buffered_duration(), Duration::from_millis(50));
+    for _ in 0..960 {
+        sound.next_sample().unwrap();
+    }
+    assert_eq!(handle.buffered_duration(), Duration::from_millis(40));
+    let sound: Box<dyn Sound> = Box::new(sound);
+    assert_eq!(sound.buffered_duration(), Duration::from_millis(40));
+}
+
+#[test]
+fn underrun_stats_count_runs_of_silence() {
+    let (mut sound, handle) = QueueSound::new(2, 1000);
+    handle.push([1, 1]);
+    let mut pull = |count: usize| {
+        for _ in 0..count {
+            sound.next_sample().unwrap();
+        }
+    };
+    pull(2);
+    assert_eq!(handle.underrun_stats(), crate::UnderrunStats::default());
+    // Two underruns of 2 and 4 frames
+    pull(4);
+    handle.push([1, 1]);
+    pull(2 + 8);
+    let stats = handle.underrun_stats();
+    assert_eq!(stats.underruns, 2);
+    assert_eq!(stats.silent_samples, 12);
+    assert_eq!(stats.silence, Duration::from_millis(6));
+    assert_eq!(sound.underrun_stats(), stats);
+
+    handle.reset_underrun_stats();
+    assert_eq!(handle.underrun_stats(), crate::UnderrunStats::default());
+    assert_eq!(handle.starved_count(), 0);
+}
+
+#[test]
+fn underrun_mid_frame_keeps_channels_in_sync() {
+    let (mut sound, handle) = QueueSound::new(2, 1000);
+    // Only the left sample of the second frame arrives in time
+    handle.push([1, 2, 3]);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+    handle.push([4]);
+    // The rest of the silent frame is still silence
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(3));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(4));
+    assert_eq!(handle.starved_count(), 2);
+    assert_eq!(handle.underrun_stats().underruns, 1);
+
+    // A partial frame left when closing is dropped
+    handle.push([5, 6, 7]);
+    handle.close();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(5));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(6));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use super::*;
+
+/// A zeroed region aligned for the header atomics.
+fn region(capacity: usize) -> Vec<u64> {
+    vec![0; SharedMemoryWriter::region_len(capacity).div_ceil(8)]
+}
+
+#[test]
+fn writer_thread_plays_back_in_order() {
+    // Small enough that the ring buffer wraps many times
+    let mut memory = region(64);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    let mut writer = unsafe { SharedMemoryWriter::create(ptr, len, 2, 8000) }.unwrap();
+    let mut sound = unsafe { SharedMemorySound::open(ptr, len) }.unwrap();
+    assert_eq!(sound.channel_count(), 2);
+    assert_eq!(sound.sample_rate(), 8000);
+
+    // Never 0 so samples can be told apart from underrun silence
+    let expected: Vec<i16> = (1..=2000).collect();
+    let played = std::thread::scope(|scope| {
+        scope.spawn(|| {
+            // Chunks that split frames and wrap around the end of the buffer
+            for chunk in expected.chunks(37) {
+                let mut chunk = chunk;
+                while !chunk.is_empty() {
+                    let written = writer.write(chunk);
+                    chunk = &chunk[written..];
+                    std::thread::sleep(Duration::from_micros(50));
+                }
+            }
+            writer.finish();
+        });
+
+        let mut played = Vec::new();
+        loop {
+            let left = sound.next_sample().unwrap();
+            if left == NextSample::Finished {
+                break played;
+            }
+            let (NextSample::Sample(left), NextSample::Sample(right)) =
+                (left, sound.next_sample().unwrap())
+            else {
+                panic!("expected a whole frame");
+            };
+            if left == 0 {
+                assert_eq!(right, 0, "underrun silence must be a whole frame");
+            } else {
+                played.extend([left, right]);
+            }
+        }
+    });
+    assert_eq!(played, expected);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn underrun_returns_silent_frames() {
+    let mut memory = region(16);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    let mut writer = unsafe { SharedMemoryWriter::create(ptr, len, 2, 8000) }.unwrap();
+    let mut sound = unsafe { SharedMemorySound::open(ptr, len) }.unwrap();
+    assert_eq!(writer.available_space(), 16);
+
+    // Only half a frame is available
+    assert_eq!(writer.write(&[5]), 1);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(sound.underruns(), 2);
+    assert_eq!(
+        sound.underrun_stats(),
+        crate::UnderrunStats {
+            underruns: 1,
+            silent_samples: 4,
+            silence: Duration::from_micros(250),
+        }
+    );
+
+    assert_eq!(writer.write(&[6, 7]), 2);
+    writer.finish();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(5));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(6));
+    // The trailing partial frame is never played
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(writer.available_space(), 15);
+}
+
+#[test]
+fn writer_does_not_overwrite_unread_samples() {
+    let mut memory = region(4);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    let mut writer = unsafe { SharedMemoryWriter::create(ptr, len, 1, 8000) }.unwrap();
+    let mut sound = unsafe { SharedMemorySound::open(ptr, len) }.unwrap();
+    assert_eq!(writer.write(&[1, 2, 3, 4, 5, 6]), 4);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(writer.write(&[5, 6]), 1);
+    for expected in [2, 3, 4, 5] {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+}
+
+#[test]
+fn uninitialized_region_is_an_error() {
+    let mut memory = region(16);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    assert!(unsafe { SharedMemorySound::open(ptr, len) }.is_err());
+    // Misaligned
+    assert!(unsafe { SharedMemoryWriter::create(ptr.add(1), len - 8, 1, 8000) }.is_err());
+    assert!(unsafe { SharedMemoryWriter::create(ptr, len, 0, 8000) }.is_err());
+}
+
+#[test]
+fn buffered_duration_is_unread_frames() {
+    let mut memory = region(64);
+    let len = memory.len() * 8;
+    let ptr = memory.as_mut_ptr().cast::<u8>();
+    let mut writer = unsafe { SharedMemoryWriter::create(ptr, len, 2, 8000) }.unwrap();
+    let mut sound = unsafe { SharedMemorySound::open(ptr, len) }.unwrap();
+    assert_eq!(sound.buffered_duration(), Duration::ZERO);
+    assert_eq!(writer.write(&[1; 32]), 32);
+    assert_eq!(sound.buffered_duration(), Duration::from_millis(2));
+    for _ in 0..17 {
+        sound.next_sample().unwrap();
+    }
+    // Only whole frames are counted
+    assert_eq!(sound.buffered_duration(), Duration::from_micros(875));
+}
@@ -0,0 +1,86 @@
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use crate::sounds::MemorySound;
+use crate::tests::{ConstantValueSound, DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE};
+use crate::{NextSample, Sound};
+
+use super::*;
+
+#[test]
+fn pulls_in_batches() {
+    let mut sound = SharedSound::new(Box::new(ConstantValueSound::new(3)));
+    sound.set_batch_frames(4);
+    let shared = sound.shared();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(3));
+    // The rest of the batch was already pulled
+    *shared.lock().unwrap() = Box::new(ConstantValueSound::new(5));
+    let channel_count = sound.channel_count() as usize;
+    for _ in 1..4 * channel_count {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(3));
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(5));
+}
+
+#[test]
+fn specials_are_returned_in_order() {
+    let inner = MemorySound::from_samples(Arc::new(vec![1, 2, 3]), 1, 1000);
+    let mut sound = SharedSound::new(Box::new(inner));
+    for expected in 1..=3 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn metadata_changes_after_buffered_samples() {
+    let mut sound = SharedSound::new(Box::new(ConstantValueSound::new(1)));
+    sound.set_batch_frames(2);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    let mut inner = ConstantValueSound::new(1);
+    inner.set_sample_rate(1000);
+    *sound.shared().lock().unwrap() = Box::new(inner);
+    for _ in 1..2 * DEFAULT_CHANNEL_COUNT {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    }
+    assert_eq!(sound.sample_rate(), DEFAULT_SAMPLE_RATE);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(sound.sample_rate(), 1000);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+}
+
+#[test]
+fn controlled_from_another_thread() {
+    let mut sound = SharedSound::new(Box::new(ConstantValueSound::new(1)));
+    let shared = sound.shared();
+    let (start_tx, start_rx) = mpsc::channel::<()>();
+    let thread = std::thread::spawn(move || {
+        start_rx.recv().unwrap();
+        *shared.lock().unwrap() = Box::new(MemorySound::from_samples(
+            Arc::new(vec![2; 10]),
+            DEFAULT_CHANNEL_COUNT,
+            DEFAULT_SAMPLE_RATE,
+        ));
+    });
+
+    for _ in 0..1000 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    }
+    start_tx.send(()).unwrap();
+    let start = Instant::now();
+    let mut num_twos = 0;
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(1) => assert_eq!(num_twos, 0),
+            NextSample::Sample(2) => num_twos += 1,
+            NextSample::Finished => break,
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "inner sound was never replaced"
+        );
+    }
+    assert_eq!(num_twos, 10);
+    thread.join().unwrap();
+}
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{sounds::MemorySound, NextSample, Sound};
 
@@ -27,3 +28,51 @@ fn empty_gives_metadata_changed_on_next() {
     assert_eq!(list.next_sample().unwrap(), NextSample::Sample(6));
     assert_eq!(list.next_sample().unwrap(), NextSample::Finished);
 }
+
+/// A MemorySound encoded in blocks of 4 frames.
+struct Blocks(MemorySound);
+
+impl Sound for Blocks {
+    fn channel_count(&self) -> u16 {
+        self.0.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.0.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        self.0.next_sample()
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn frame_alignment(&self) -> Option<u64> {
+        Some(4)
+    }
+
+    fn set_end(&mut self, at: Option<Duration>) {
+        self.0.set_end(at)
+    }
+}
+
+#[test]
+fn add_trimmed_snaps_to_frame_alignment() {
+    let samples: Vec<i16> = (0..20).collect();
+    let mut list = SoundList::new();
+    let first = Blocks(MemorySound::from_samples(Arc::new(samples), 1, 44100));
+    let end = list.add_trimmed(
+        Box::new(first),
+        Duration::from_micros(7 * 1_000_000 / 44100),
+    );
+    let second = MemorySound::from_samples(Arc::new(vec![100]), 1, 44100);
+    list.add(Box::new(second));
+
+    assert_eq!(crate::utils::duration_to_num_samples(end, 1, 44100), 8);
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    for expected in 0..8 {
+        assert_eq!(list.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(list.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(list.next_sample().unwrap(), NextSample::Sample(100));
+}
@@ -1,4 +1,7 @@
+use std::sync::Arc;
+
 use super::*;
+use crate::sounds::{MemorySound, Silence};
 use crate::tests::{ConstantValueSound, DEFAULT_CHANNEL_COUNT, DEFAULT_SAMPLE_RATE};
 
 #[test]
@@ -29,3 +32,38 @@ fn two_sounds_add_together() {
     assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(12));
     assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(12));
 }
+
+#[test]
+fn mono_and_surround_mix_into_stereo() {
+    let mut mono = ConstantValueSound::new(100);
+    mono.channel_count = 1;
+    // Two 5.1 frames of L, R, C, LFE, SL, SR
+    let frame = [1000, 2000, 4000, 8000, 1600, 3200];
+    let surround = MemorySound::from_samples(Arc::new(frame.repeat(2)), 6, DEFAULT_SAMPLE_RATE);
+    let mut mixer = SoundMixer::new(2, DEFAULT_SAMPLE_RATE);
+    mixer.add(Box::new(mono));
+    mixer.add(Box::new(surround));
+
+    // L + (C + SL) / sqrt(2) and R + (C + SR) / sqrt(2). LFE is dropped.
+    let left = 100 + (1000.0 + 5600.0 * std::f32::consts::FRAC_1_SQRT_2) as i16;
+    let right = 100 + (2000.0 + 7200.0 * std::f32::consts::FRAC_1_SQRT_2) as i16;
+    for _ in 0..2 {
+        assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(left));
+        assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(right));
+    }
+    // Once the surround sound finishes the mono sound is still on both
+    // channels.
+    for _ in 0..4 {
+        assert_eq!(mixer.next_sample().unwrap(), NextSample::Sample(100));
+    }
+}
+
+#[test]
+fn sound_without_channels_can_not_be_added() {
+    let mut mixer = SoundMixer::new(2, DEFAULT_SAMPLE_RATE);
+    assert!(mixer
+        .try_add(Box::new(Silence::new(0, DEFAULT_SAMPLE_RATE)))
+        .is_err());
+    mixer.add(Box::new(Silence::new(0, DEFAULT_SAMPLE_RATE)));
+    assert_eq!(mixer.next_sample().unwrap(), NextSample::Finished);
+}
@@ -0,0 +1,53 @@
+use super::*;
+use crate::sounds::SineWav;
+use crate::tests::ConstantValueSound;
+
+#[test]
+fn pure_tone_energy_in_expected_bin() {
+    // 1000 Hz falls exactly on bin 32
+    let sine =
+        SineWav::with_sample_rate(1000.0, 16000).finish_after(std::time::Duration::from_secs(1));
+    let spectra = spectrogram(sine, 512, 256, WindowFunction::Hann).unwrap();
+    // Every complete block of the 16000 frames
+    assert_eq!(spectra.len(), (16000 - 512) / 256 + 1);
+    for magnitudes in &spectra {
+        assert_eq!(magnitudes.len(), 257);
+        let peak_bin = magnitudes
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap()
+            .0;
+        assert_eq!(peak_bin, 32);
+        assert!((magnitudes[32] - 1.0).abs() < 0.05, "{}", magnitudes[32]);
+        let total: f32 = magnitudes.iter().map(|m| m * m).sum();
+        let near_peak: f32 = magnitudes[31..=33].iter().map(|m| m * m).sum();
+        assert!(near_peak / total > 0.99, "{near_peak} {total}");
+    }
+}
+
+#[test]
+fn hop_larger_than_fft_size_skips_frames() {
+    let sine =
+        SineWav::with_sample_rate(1000.0, 16000).finish_after(std::time::Duration::from_secs(1));
+    let spectra = spectrogram(sine, 256, 1000, WindowFunction::Rectangular).unwrap();
+    assert_eq!(spectra.len(), 16);
+}
+
+#[test]
+fn infinite_sound_is_error() {
+    let result = spectrogram_with_limit(
+        ConstantValueSound::new(1),
+        64,
+        64,
+        WindowFunction::Hann,
+        33 * 100,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn invalid_sizes_are_errors() {
+    assert!(spectrogram(SineWav::new(100.0), 1, 1, WindowFunction::Hann).is_err());
+    assert!(spectrogram(SineWav::new(100.0), 16, 0, WindowFunction::Hann).is_err());
+}
@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+
+use super::*;
+
+/// A mono stem at 1000 Hz whose sample at frame `i` is `i * scale`.
+fn stem(num_frames: i16, scale: i16) -> Box<dyn Sound> {
+    let samples = (0..num_frames).map(|i| i * scale).collect();
+    Box::new(MemorySound::from_samples(Arc::new(samples), 1, 1000))
+}
+
+#[test]
+fn seek_keeps_stems_at_same_position() {
+    // The mix is `a + 100 * b` where a and b are the frame positions of the
+    // two stems
+    let mut group = SyncGroup::new(vec![stem(50, 1), stem(30, 100)]).unwrap();
+    for i in 0..5 {
+        assert_eq!(group.next_sample().unwrap(), NextSample::Sample(i * 101));
+    }
+    assert_eq!(
+        group.seek(Duration::from_millis(20)).unwrap(),
+        Duration::from_millis(20)
+    );
+    for i in 20..30 {
+        assert_eq!(group.next_sample().unwrap(), NextSample::Sample(i * 101));
+    }
+    // The shorter stem is silent until the group ends
+    for i in 30..50 {
+        assert_eq!(group.next_sample().unwrap(), NextSample::Sample(i));
+    }
+    assert_eq!(group.next_sample().unwrap(), NextSample::Finished);
+
+    // Seeking back restarts the finished stem
+    group.seek(Duration::from_millis(10)).unwrap();
+    assert_eq!(group.next_sample().unwrap(), NextSample::Sample(10 * 101));
+}
+
+/// Counts up in steps of 100 while `open` is set and is paused otherwise.
+struct Gated {
+    open: Arc<AtomicBool>,
+    next: i16,
+}
+
+impl Sound for Gated {
+    fn channel_count(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        1000
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if !self.open.load(Ordering::Relaxed) {
+            return Ok(NextSample::Paused);
+        }
+        self.next += 100;
+        Ok(NextSample::Sample(self.next - 100))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+#[test]
+fn paused_member_pauses_group() {
+    let open = Arc::new(AtomicBool::new(true));
+    let gated = Gated {
+        open: open.clone(),
+        next: 0,
+    };
+    let mut group = SyncGroup::new(vec![stem(10, 1), Box::new(gated)]).unwrap();
+    assert_eq!(group.next_sample().unwrap(), NextSample::Sample(0));
+    open.store(false, Ordering::Relaxed);
+    assert_eq!(group.next_sample().unwrap(), NextSample::Paused);
+    assert_eq!(group.next_sample().unwrap(), NextSample::Paused);
+    // The other stem did not advance while the group was paused
+    open.store(true, Ordering::Relaxed);
+    assert_eq!(group.next_sample().unwrap(), NextSample::Sample(101));
+    assert_eq!(group.next_sample().unwrap(), NextSample::Sample(202));
+    assert_eq!(group.members().len(), 2);
+}
+
+#[test]
+fn mismatched_formats_are_an_error() {
+    let mut stereo = ConstantValueSound::new(0);
+    stereo.channel_count = 2;
+    stereo.sample_rate = 1000;
+    assert!(SyncGroup::new(vec![stem(1, 1), Box::new(stereo)]).is_err());
+    assert!(SyncGroup::new(Vec::new()).is_err());
+}
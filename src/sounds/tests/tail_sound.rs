@@ -0,0 +1,78 @@
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use super::*;
+use crate::tests::{wav_bytes, write_temp_file};
+
+fn append(path: &Path, bytes: &[u8]) {
+    let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(bytes).unwrap();
+}
+
+fn sample_bytes(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+#[test]
+fn plays_appended_samples() {
+    let path = write_temp_file("tail_sound_appended.wav", &wav_bytes(2, 8000, &[1, 2]));
+    let (mut sound, handle) = TailSound::open_wav(&path).unwrap();
+    assert_eq!(sound.channel_count(), 2);
+    assert_eq!(sound.sample_rate(), 8000);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Paused);
+
+    // Half of a frame is not played until the rest is written
+    let frame = sample_bytes(&[3, 4]);
+    append(&path, &frame[..3]);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Paused);
+    append(&path, &frame[3..]);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(3));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(4));
+
+    append(&path, &sample_bytes(&[5, 6]));
+    handle.stop();
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(5));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(6));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn plays_while_written_from_another_thread() {
+    let path = write_temp_file("tail_sound_thread.raw", &[]);
+    let (mut sound, handle) = TailSound::open_raw(&path, 1, 8000, PcmFormat::S16LE).unwrap();
+    let writer_path = path.clone();
+    let writer = std::thread::spawn(move || {
+        for i in 0..100i16 {
+            // Write each sample a byte at a time
+            for byte in i.to_le_bytes() {
+                append(&writer_path, &[byte]);
+            }
+        }
+        handle.stop();
+    });
+
+    let start = Instant::now();
+    let mut received = Vec::new();
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => received.push(s),
+            NextSample::Paused => std::thread::sleep(Duration::from_micros(100)),
+            NextSample::Finished => break,
+            NextSample::MetadataChanged => unreachable!(),
+        }
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+    writer.join().unwrap();
+    assert_eq!(received, (0..100).collect::<Vec<i16>>());
+}
+
+#[test]
+fn non_pcm_wav_is_an_error() {
+    let mut bytes = wav_bytes(1, 8000, &[]);
+    // IEEE float
+    bytes[20] = 3;
+    let path = write_temp_file("tail_sound_float.wav", &bytes);
+    assert!(TailSound::open_wav(path).is_err());
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+
+const SAMPLE_RATE: u32 = 8000;
+
+fn clip(value: i16, duration: Duration, channel_count: u16, sample_rate: u32) -> Box<dyn Sound> {
+    let num_samples = utils::duration_to_num_samples(duration, channel_count, sample_rate);
+    let samples = vec![value; num_samples as usize];
+    Box::new(MemorySound::from_samples(
+        Arc::new(samples),
+        channel_count,
+        sample_rate,
+    ))
+}
+
+fn frames(timeline: &mut Timeline) -> Vec<Vec<i16>> {
+    let mut frames = Vec::new();
+    while let Ok(frame) = timeline.next_frame() {
+        frames.push(frame);
+    }
+    frames
+}
+
+#[test]
+fn second_clip_starts_at_exactly_one_second() {
+    let mut timeline = Timeline::with_entries(
+        2,
+        SAMPLE_RATE,
+        vec![
+            (
+                Duration::ZERO,
+                clip(100, Duration::from_millis(1200), 1, SAMPLE_RATE),
+            ),
+            // Different channel count and sample rate to the output
+            (
+                Duration::from_secs(1),
+                clip(1000, Duration::from_millis(500), 2, 2 * SAMPLE_RATE),
+            ),
+        ],
+    )
+    .unwrap();
+    assert_eq!(timeline.remaining(), 2);
+    let frames = frames(&mut timeline);
+    assert_eq!(timeline.remaining(), 0);
+    assert_eq!(timeline.next_sample().unwrap(), NextSample::Finished);
+
+    let second = SAMPLE_RATE as usize;
+    assert!(frames[..second].iter().all(|frame| frame == &[100, 100]));
+    assert_eq!(frames[second], [1100, 1100]);
+    let first_end = second + SAMPLE_RATE as usize / 5;
+    assert!(frames[second..first_end]
+        .iter()
+        .all(|frame| frame == &[1100, 1100]));
+    assert!(frames[first_end..]
+        .iter()
+        .all(|frame| frame == &[1000, 1000]));
+    // Finishes with the second clip
+    let end = second + SAMPLE_RATE as usize / 2;
+    assert!(frames.len().abs_diff(end) <= 1, "{}", frames.len());
+}
+
+#[test]
+fn gaps_are_silent_and_overlaps_saturate() {
+    let mut timeline = Timeline::new(1, SAMPLE_RATE);
+    let quarter = Duration::from_millis(250);
+    timeline
+        .add(quarter, clip(30000, quarter, 1, SAMPLE_RATE))
+        .unwrap();
+    timeline
+        .add(quarter, clip(30000, quarter, 1, SAMPLE_RATE))
+        .unwrap();
+    let frames = frames(&mut timeline);
+    let start = SAMPLE_RATE as usize / 4;
+    assert_eq!(frames.len(), 2 * start);
+    assert!(frames[..start].iter().all(|frame| frame == &[0]));
+    assert!(frames[start..].iter().all(|frame| frame == &[i16::MAX]));
+}
+
+#[test]
+fn empty_timeline_finishes() {
+    let mut timeline = Timeline::new(2, SAMPLE_RATE);
+    assert_eq!(timeline.next_sample().unwrap(), NextSample::Finished);
+    assert!(timeline
+        .add(Duration::ZERO, clip(1, Duration::ZERO, 0, SAMPLE_RATE))
+        .is_err());
+}
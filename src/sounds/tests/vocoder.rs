@@ -0,0 +1,80 @@
+use std::{f32::consts::PI, sync::Arc};
+
+use super::*;
+use crate::sounds::{MemorySound, SineWav};
+
+const SAMPLE_RATE: u32 = 8000;
+const CARRIER_FREQUENCY: f32 = 110.0;
+
+/// A band limited sawtooth which has energy at every multiple of
+/// CARRIER_FREQUENCY.
+fn carrier() -> MemorySound {
+    let samples = (0..SAMPLE_RATE * 2)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let value: f32 = (1..=30)
+                .map(|k| (2.0 * PI * CARRIER_FREQUENCY * k as f32 * t).sin() / k as f32)
+                .sum();
+            (value * 5000.0) as i16
+        })
+        .collect();
+    MemorySound::from_samples(Arc::new(samples), 1, SAMPLE_RATE)
+}
+
+/// Magnitude of `frequency` in `samples` using a Hann window.
+fn magnitude(samples: &[f32], frequency: f32) -> f32 {
+    let n = samples.len() as f32;
+    let (mut re, mut im) = (0.0, 0.0);
+    for (i, s) in samples.iter().enumerate() {
+        let window = 0.5 - 0.5 * (2.0 * PI * i as f32 / n).cos();
+        let phase = 2.0 * PI * frequency * i as f32 / SAMPLE_RATE as f32;
+        re += s * window * phase.cos();
+        im += s * window * phase.sin();
+    }
+    (re * re + im * im).sqrt()
+}
+
+fn collect_second_half(sound: &mut impl Sound) -> Vec<f32> {
+    let mut samples = Vec::new();
+    while let NextSample::Sample(s) = sound.next_sample().unwrap() {
+        samples.push(s as f32);
+    }
+    samples.split_off(samples.len() / 2)
+}
+
+#[test]
+fn follows_modulator_envelope_and_carrier_structure() {
+    let modulator = SineWav::with_sample_rate(1000.0, SAMPLE_RATE);
+    let mut vocoder = Vocoder::new(Box::new(modulator), Box::new(carrier()), 16);
+    assert_eq!(vocoder.band_count(), 16);
+    assert_eq!(vocoder.sample_rate(), SAMPLE_RATE);
+    assert_eq!(vocoder.channel_count(), 1);
+    let output = collect_second_half(&mut vocoder);
+    let carrier = collect_second_half(&mut carrier());
+
+    // 9th and 27th harmonics of the carrier
+    let near = CARRIER_FREQUENCY * 9.0;
+    let far = CARRIER_FREQUENCY * 27.0;
+    // The spectral envelope follows the modulator: the harmonic near the
+    // modulator's frequency is boosted relative to the carrier.
+    let carrier_ratio = magnitude(&carrier, near) / magnitude(&carrier, far);
+    let output_ratio = magnitude(&output, near) / magnitude(&output, far);
+    assert!(
+        output_ratio > carrier_ratio * 10.0,
+        "output {output_ratio} carrier {carrier_ratio}"
+    );
+
+    // The fine structure follows the carrier: energy is at the carrier's
+    // harmonic rather than the modulator's frequency or between harmonics.
+    let harmonic = magnitude(&output, near);
+    assert!(harmonic > magnitude(&output, 1000.0) * 10.0);
+    assert!(harmonic > magnitude(&output, near + CARRIER_FREQUENCY / 2.0) * 10.0);
+}
+
+#[test]
+fn silent_modulator_silences_output() {
+    let modulator = crate::sounds::Silence::new(1, SAMPLE_RATE);
+    let mut vocoder = Vocoder::new(Box::new(modulator), Box::new(carrier()), 8);
+    let output = collect_second_half(&mut vocoder);
+    assert!(output.iter().all(|s| *s == 0.0));
+}
@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use super::wrappers::{ChannelCountConverter, SampleRateConverter};
+use crate::{utils, NextSample, Sound};
+
+type TimelineSound = SampleRateConverter<ChannelCountConverter<Box<dyn Sound>>>;
+
+/// Play sounds starting at fixed positions on a timeline, e.g. for the music,
+/// dialog and effects of a cutscene.
+///
+/// Each entry starts at its scheduled time measured from the first frame of
+/// the timeline. Overlapping entries are summed, saturating at full scale,
+/// and silence is returned where no entry is playing. The timeline finishes
+/// once every entry has finished.
+///
+/// Every entry is converted to the output channel count and sample rate in
+/// the same way as [SoundMixer][super::SoundMixer]. If an entry returns an
+/// error it is logged and the entry is dropped but the rest keep playing. An
+/// entry that returns `Paused` is silent until it returns samples again.
+pub struct Timeline {
+    /// Entries that have not started yet with their start frame, sorted by
+    /// start.
+    pending: Vec<(u64, TimelineSound)>,
+    active: Vec<TimelineSound>,
+    output_channel_count: u16,
+    output_sample_rate: u32,
+    /// The frame being returned.
+    frame: u64,
+    next_channel_idx: u16,
+}
+
+impl Timeline {
+    /// An empty timeline that converts all entries to `output_channel_count`
+    /// and `output_sample_rate`.
+    pub fn new(output_channel_count: u16, output_sample_rate: u32) -> Timeline {
+        Timeline {
+            pending: Vec::new(),
+            active: Vec::new(),
+            output_channel_count,
+            output_sample_rate,
+            frame: 0,
+            next_channel_idx: 0,
+        }
+    }
+
+    /// A timeline of `entries`, each starting at the paired time.
+    ///
+    /// Returns an error if any sound can not be converted to the output
+    /// channel count (i.e. either has no channels).
+    pub fn with_entries(
+        output_channel_count: u16,
+        output_sample_rate: u32,
+        entries: Vec<(Duration, Box<dyn Sound>)>,
+    ) -> Result<Timeline, crate::Error> {
+        let mut timeline = Timeline::new(output_channel_count, output_sample_rate);
+        for (start, sound) in entries {
+            timeline.add(start, sound)?;
+        }
+        Ok(timeline)
+    }
+
+    /// Schedule `sound` to start at `start` from the beginning of the
+    /// timeline.
+    ///
+    /// If the timeline has already passed `start` the sound starts at the
+    /// next frame. Returns an error and drops `sound` if its channels can not
+    /// be converted to the output channel count.
+    pub fn add(&mut self, start: Duration, sound: Box<dyn Sound>) -> Result<(), crate::Error> {
+        let sound = SampleRateConverter::new(
+            ChannelCountConverter::try_new(sound, self.output_channel_count)?,
+            self.output_sample_rate,
+        );
+        let start_frame = utils::duration_to_num_samples(start, 1, self.output_sample_rate);
+        let idx = self
+            .pending
+            .partition_point(|(pending_start, _)| *pending_start <= start_frame);
+        self.pending.insert(idx, (start_frame, sound));
+        Ok(())
+    }
+
+    /// The number of entries that have not finished.
+    pub fn remaining(&self) -> usize {
+        self.pending.len() + self.active.len()
+    }
+
+    /// Move entries that start at the current frame to the active list.
+    fn start_due(&mut self) {
+        let due = self
+            .pending
+            .partition_point(|(start, _)| *start <= self.frame);
+        self.active
+            .extend(self.pending.drain(..due).map(|(_, sound)| sound));
+    }
+}
+
+impl Sound for Timeline {
+    fn channel_count(&self) -> u16 {
+        self.output_channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.output_sample_rate
+    }
+
+    fn on_start_of_batch(&mut self) {
+        for sound in &mut self.active {
+            sound.on_start_of_batch();
+        }
+    }
+
+    /// Guaranteed to not return an Error.
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.next_channel_idx == 0 {
+            self.start_due();
+        }
+
+        let mut output: i16 = 0;
+        let mut finished = Vec::new();
+        for (idx, sound) in self.active.iter_mut().enumerate() {
+            loop {
+                match sound.next_sample() {
+                    Ok(NextSample::Sample(s)) => {
+                        output = output.saturating_add(s);
+                        break;
+                    }
+                    // The converters keep the format the same so this only
+                    // marks the start of a frame
+                    Ok(NextSample::MetadataChanged) => (),
+                    Ok(NextSample::Paused) => break,
+                    Ok(NextSample::Finished) => {
+                        finished.push(idx);
+                        break;
+                    }
+                    Err(e) => {
+                        log::error!("dropping sound in Timeline which returned error: {}", e);
+                        finished.push(idx);
+                        break;
+                    }
+                }
+            }
+        }
+        for idx in finished.into_iter().rev() {
+            self.active.remove(idx);
+        }
+        if self.next_channel_idx == 0 && self.remaining() == 0 {
+            return Ok(NextSample::Finished);
+        }
+
+        self.next_channel_idx += 1;
+        if self.next_channel_idx == self.output_channel_count {
+            self.next_channel_idx = 0;
+            self.frame += 1;
+        }
+        Ok(NextSample::Sample(output))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/timeline.rs"]
+mod tests;
@@ -0,0 +1,201 @@
+use super::biquad::{Biquad, BiquadCoefficients};
+use super::wrappers::{ChannelCountConverter, SampleRateConverter};
+use crate::sound::NextSample;
+use crate::Sound;
+
+type Modulator = SampleRateConverter<ChannelCountConverter<Box<dyn Sound>>>;
+
+/// The lowest band center frequency in Hz.
+const MIN_FREQUENCY: f32 = 100.0;
+/// The highest band center frequency in Hz if allowed by the sample rate.
+const MAX_FREQUENCY: f32 = 8000.0;
+/// Time constant of the envelope follower in seconds.
+const ENVELOPE_TIME: f32 = 0.01;
+
+/// A channel vocoder: the spectral envelope of a modulator (e.g. a voice) is
+/// applied to a carrier (e.g. a synth).
+///
+/// Both sounds are split into `band_count` frequency bands with log spaced
+/// center frequencies from 100 Hz up to 8 kHz (or just below the Nyquist
+/// frequency). The carrier's signal in each band is scaled by the envelope of
+/// the modulator's signal in the same band and all bands are summed.
+///
+/// The output has the channel count and sample rate of the carrier. The
+/// modulator is converted to match. The vocoder returns `Paused` and
+/// `Finished` when the carrier does. While the modulator is paused or after
+/// it has finished it is treated as silence.
+pub struct Vocoder {
+    modulator: Modulator,
+    carrier: Box<dyn Sound>,
+    band_count: usize,
+    bands: Vec<Band>,
+    /// Remaining samples of the current output frame stored in reverse.
+    output_frame: Vec<i16>,
+    carrier_frame: Vec<i16>,
+    modulator_frame: Vec<i16>,
+    envelope_coefficient: f32,
+}
+
+impl Vocoder {
+    /// Create a vocoder with `band_count` bands.
+    ///
+    /// Panics if `band_count` is 0.
+    pub fn new(modulator: Box<dyn Sound>, carrier: Box<dyn Sound>, band_count: usize) -> Vocoder {
+        assert!(band_count > 0, "band_count must be at least 1");
+        let modulator = SampleRateConverter::new(
+            ChannelCountConverter::new(modulator, carrier.channel_count()),
+            carrier.sample_rate(),
+        );
+        let mut vocoder = Vocoder {
+            modulator,
+            carrier,
+            band_count,
+            bands: Vec::new(),
+            output_frame: Vec::new(),
+            carrier_frame: Vec::new(),
+            modulator_frame: Vec::new(),
+            envelope_coefficient: 0.0,
+        };
+        vocoder.init();
+        vocoder
+    }
+
+    /// The number of frequency bands.
+    pub fn band_count(&self) -> usize {
+        self.band_count
+    }
+
+    /// Create the filters for the current channel count and sample rate of the
+    /// carrier.
+    fn init(&mut self) {
+        let sample_rate = self.carrier.sample_rate() as f32;
+        let channel_count = self.carrier.channel_count() as usize;
+        let max_frequency = MAX_FREQUENCY.min(sample_rate * 0.45).max(MIN_FREQUENCY);
+        let ratio = if self.band_count > 1 {
+            (max_frequency / MIN_FREQUENCY).powf(1.0 / (self.band_count - 1) as f32)
+        } else {
+            2.0
+        };
+        // Neighbouring bands meet at the -3 dB points of a single biquad
+        let bandwidth_ratio = ratio.max(1.01);
+        let q = bandwidth_ratio.sqrt() / (bandwidth_ratio - 1.0);
+        self.bands = (0..self.band_count)
+            .map(|i| {
+                let frequency = MIN_FREQUENCY * ratio.powi(i as i32);
+                let coefficients = BiquadCoefficients::band_pass(frequency, q, sample_rate);
+                let filter = [Biquad::new(coefficients), Biquad::new(coefficients)];
+                Band {
+                    modulator_filters: vec![filter.clone(); channel_count],
+                    carrier_filters: vec![filter; channel_count],
+                    envelopes: vec![0.0; channel_count],
+                }
+            })
+            .collect();
+        self.envelope_coefficient = 1.0 - (-1.0 / (ENVELOPE_TIME * sample_rate)).exp();
+    }
+
+    /// Move the modulator to the current output format of the carrier.
+    fn rewrap_modulator(&mut self) {
+        let placeholder: Modulator = SampleRateConverter::new(
+            ChannelCountConverter::new(Box::new(super::Silence::new(1, 1000)), 1),
+            1000,
+        );
+        let modulator = std::mem::replace(&mut self.modulator, placeholder)
+            .into_inner()
+            .into_inner();
+        self.modulator = SampleRateConverter::new(
+            ChannelCountConverter::new(modulator, self.carrier.channel_count()),
+            self.carrier.sample_rate(),
+        );
+    }
+}
+
+impl Sound for Vocoder {
+    fn channel_count(&self) -> u16 {
+        self.carrier.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.carrier.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output_frame.pop() {
+            return Ok(NextSample::Sample(sample));
+        }
+
+        self.carrier_frame.clear();
+        match self.carrier.append_next_frame_to(&mut self.carrier_frame) {
+            Ok(()) => (),
+            Err(Ok(NextSample::MetadataChanged)) => {
+                self.init();
+                self.rewrap_modulator();
+                return Ok(NextSample::MetadataChanged);
+            }
+            Err(Ok(NextSample::Sample(_))) => unreachable!(),
+            Err(Ok(special)) => return Ok(special),
+            Err(Err(e)) => return Err(e),
+        }
+
+        self.modulator_frame.clear();
+        match self
+            .modulator
+            .append_next_frame_to(&mut self.modulator_frame)
+        {
+            Ok(()) => (),
+            Err(Ok(_)) => {
+                // Treat the modulator as silent until it has a full frame again
+                self.modulator_frame.clear();
+                self.modulator_frame.resize(self.carrier_frame.len(), 0);
+            }
+            Err(Err(e)) => return Err(e),
+        }
+
+        let channel_count = self.carrier_frame.len();
+        let mut output = vec![0.0f32; channel_count];
+        for band in &mut self.bands {
+            for (channel, out) in output.iter_mut().enumerate() {
+                let modulated = process(
+                    &mut band.modulator_filters[channel],
+                    self.modulator_frame[channel] as f32 / i16::MAX as f32,
+                );
+                let envelope = &mut band.envelopes[channel];
+                *envelope += self.envelope_coefficient * (modulated.abs() - *envelope);
+                let carrier = process(
+                    &mut band.carrier_filters[channel],
+                    self.carrier_frame[channel] as f32,
+                );
+                *out += carrier * *envelope;
+            }
+        }
+
+        self.output_frame.clear();
+        for sample in output.iter().rev() {
+            self.output_frame
+                .push(sample.clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+        }
+        Ok(NextSample::Sample(self.output_frame.pop().unwrap()))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.carrier.on_start_of_batch();
+        self.modulator.on_start_of_batch();
+    }
+}
+
+struct Band {
+    /// One filter per channel. Two biquads are cascaded for steeper slopes so
+    /// less of neighbouring bands leaks in.
+    modulator_filters: Vec<[Biquad; 2]>,
+    carrier_filters: Vec<[Biquad; 2]>,
+    envelopes: Vec<f32>,
+}
+
+fn process(filter: &mut [Biquad; 2], x: f32) -> f32 {
+    let y = filter[0].process(x);
+    filter[1].process(y)
+}
+
+#[cfg(test)]
+#[path = "./tests/vocoder.rs"]
+mod tests;
@@ -7,30 +7,110 @@
 
 mod adjustable_speed;
 mod adjustable_volume;
+mod agc;
+mod ambisonic_decode;
 #[cfg(feature = "async")]
 pub mod async_completion_notifier;
+mod async_resampler;
+mod auto_pan;
+mod bass_enhance;
+mod block_size;
+mod broadcast_processor;
 mod channel_count_converter;
+mod channel_delay;
+mod channel_mute;
 mod completion_notifier;
 mod controllable;
+#[cfg(feature = "convolution")]
+mod convolution_reverb;
+mod count_in;
+mod de_clip;
+mod dynamic_eq;
+mod fade_in;
 mod finish_after;
+mod frame_counter;
+mod history_tap;
+mod iir_filter;
+mod inject_metadata_change;
+mod latency_offset;
+mod loudness_contour;
+mod marker_track;
+mod multiband_compressor;
+mod on_finish;
 mod pausable;
+mod peekable;
+mod prebuffer;
+mod realtime_throttle;
+mod reverb;
+mod ring_mod;
+mod room_correction;
 mod sample_rate_converter;
+mod sidechain_gate;
+mod sinc_sample_rate_converter;
+mod skip_silence;
+#[cfg(feature = "spectrum")]
+mod spectrum_tap;
+mod sync_start;
+mod tail_fade;
+mod true_peak_limiter;
+mod wave_shaper;
 mod wrapper;
+mod zero_cross_gain;
 
 pub use adjustable_speed::AdjustableSpeed;
 pub use adjustable_speed::SetSpeed;
 pub use adjustable_volume::AdjustableVolume;
 pub use adjustable_volume::SetVolume;
+pub use agc::Agc;
+pub use ambisonic_decode::{AmbisonicDecode, AmbisonicHandle, AmbisonicOutput};
 #[cfg(feature = "async")]
 pub use async_completion_notifier::AsyncCompletionNotifier;
-pub use channel_count_converter::ChannelCountConverter;
+pub use async_resampler::{AsyncResampler, WorkerPriority, WorkerThreadHints};
+pub use auto_pan::AutoPan;
+pub use bass_enhance::BassEnhance;
+pub use block_size::BlockSize;
+pub use broadcast_processor::BroadcastProcessor;
+pub use channel_count_converter::{ChannelCountConverter, DownmixCoefficients};
+pub use channel_delay::ChannelDelay;
+pub use channel_mute::{ChannelMute, ChannelMuteHandle};
 pub use completion_notifier::CompletionNotifier;
 pub use controllable::{Controllable, Controller};
+#[cfg(feature = "convolution")]
+pub use convolution_reverb::ConvolutionReverb;
+pub use count_in::CountIn;
+pub use de_clip::DeClip;
+pub use dynamic_eq::{DynamicEq, DynamicEqBand};
+pub use fade_in::{FadeCurve, FadeIn};
 pub use finish_after::FinishAfter;
+pub use frame_counter::{FrameCounter, FrameCounterHandle};
+pub use history_tap::{HistoryHandle, HistoryTap};
+pub use iir_filter::IirFilter;
+pub use inject_metadata_change::InjectMetadataChange;
+pub use latency_offset::LatencyOffset;
+pub use loudness_contour::{LoudnessContour, LoudnessContourHandle};
+pub use marker_track::MarkerTrack;
+pub use multiband_compressor::MultibandCompressor;
+pub use on_finish::OnFinish;
 pub use pausable::Pausable;
 pub use pausable::SetPaused;
+pub use peekable::Peekable;
+pub use prebuffer::Prebuffer;
+pub use realtime_throttle::RealtimeThrottle;
+pub use reverb::Reverb;
+pub use ring_mod::{RingMod, Waveform};
+pub use room_correction::{ChannelCorrection, PeakingEq, RoomCorrection};
 pub use sample_rate_converter::SampleRateConverter;
+pub use sidechain_gate::SidechainGate;
+pub use sinc_sample_rate_converter::{ResampleQuality, SincSampleRateConverter};
+pub use skip_silence::SkipSilence;
+#[cfg(feature = "spectrum")]
+pub use spectrum_tap::{SpectrumHandle, SpectrumTap, WindowFunction};
+pub use sync_start::SyncStart;
+pub use tail_fade::TailFade;
+pub use true_peak_limiter::TruePeakLimiter;
+pub use wave_shaper::WaveShaper;
 pub use wrapper::Wrapper;
+pub use zero_cross_gain::ZeroCrossGain;
 
 /// A Sound which contains other sounds that can be added to it.
 pub trait AddSound {
@@ -80,6 +80,10 @@ where
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
 }
 
 impl<S> SetSpeed for AdjustableSpeed<S>
@@ -0,0 +1,145 @@
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Time constant of the short-term RMS measurement.
+const RMS_WINDOW_SECS: f32 = 0.05;
+/// Time constant of gain changes. Slow enough to not pump between syllables.
+const GAIN_SMOOTHING_SECS: f32 = 0.3;
+
+/// Automatic gain control. Continuously adjusts the volume of the inner sound
+/// so its short-term RMS level stays near a target level.
+///
+/// Intended for evening out voice levels (e.g. in voice chat). Levels are
+/// expressed as a fraction of full scale so 1.0 is the loudest possible level.
+///
+/// While the inner sound is quieter than the silence threshold the gain is
+/// held instead of increased so background noise in speech pauses is not
+/// boosted.
+pub struct Agc<S: Sound> {
+    inner: S,
+    target_rms: f32,
+    min_gain: f32,
+    max_gain: f32,
+    silence_threshold: f32,
+    gain: f32,
+    mean_square: f32,
+    rms_coefficient: f32,
+    gain_coefficient: f32,
+}
+
+impl<S> Agc<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` and aim for a short-term RMS of `target_rms`.
+    ///
+    /// Gain is limited to between 0.1 and 10.0 and levels below 0.001 are
+    /// considered silence. See the setters to change these.
+    pub fn new(inner: S, target_rms: f32) -> Self {
+        let mut agc = Agc {
+            inner,
+            target_rms,
+            min_gain: 0.1,
+            max_gain: 10.0,
+            silence_threshold: 0.001,
+            gain: 1.0,
+            mean_square: 0.0,
+            rms_coefficient: 0.0,
+            gain_coefficient: 0.0,
+        };
+        agc.update_coefficients();
+        agc
+    }
+
+    /// Return the gain currently being applied.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Set the RMS level to aim for as a fraction of full scale.
+    pub fn set_target_rms(&mut self, target_rms: f32) {
+        self.target_rms = target_rms;
+    }
+
+    /// Limit the gain to between `min_gain` and `max_gain`.
+    pub fn set_gain_range(&mut self, min_gain: f32, max_gain: f32) {
+        self.min_gain = min_gain;
+        self.max_gain = max_gain;
+        self.gain = self.gain.clamp(min_gain, max_gain);
+    }
+
+    /// Set the RMS level, as a fraction of full scale, below which the inner
+    /// sound is considered silent and the gain is held.
+    pub fn set_silence_threshold(&mut self, silence_threshold: f32) {
+        self.silence_threshold = silence_threshold;
+    }
+
+    fn update_coefficients(&mut self) {
+        let samples_per_sec = self.inner.sample_rate() as f32 * self.inner.channel_count() as f32;
+        self.rms_coefficient = 1.0 - (-1.0 / (RMS_WINDOW_SECS * samples_per_sec)).exp();
+        self.gain_coefficient = 1.0 - (-1.0 / (GAIN_SMOOTHING_SECS * samples_per_sec)).exp();
+    }
+}
+
+impl<S> Sound for Agc<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                let level = s as f32 / i16::MAX as f32;
+                self.mean_square += (level * level - self.mean_square) * self.rms_coefficient;
+                let rms = self.mean_square.sqrt();
+                if rms >= self.silence_threshold {
+                    let desired = (self.target_rms / rms).clamp(self.min_gain, self.max_gain);
+                    self.gain += (desired - self.gain) * self.gain_coefficient;
+                }
+                Ok(NextSample::Sample((s as f32 * self.gain) as i16))
+            }
+            NextSample::MetadataChanged => {
+                self.update_coefficients();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for Agc<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/agc.rs"]
+mod tests;
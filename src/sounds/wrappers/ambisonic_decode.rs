@@ -0,0 +1,267 @@
+use std::collections::VecDeque;
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Radius of an average head in meters.
+const HEAD_RADIUS: f32 = 0.0875;
+const SPEED_OF_SOUND: f32 = 343.0;
+/// Cutoff of the low pass filter approximating the shadow of the head.
+const HEAD_SHADOW_CUTOFF: f32 = 2000.0;
+/// Gain of the signal reaching an ear from the far side of the head.
+const HEAD_SHADOW_GAIN: f32 = 0.5;
+
+/// How [AmbisonicDecode] renders the sound field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmbisonicOutput {
+    /// Two virtual cardioid microphones pointing left and right. Suitable for
+    /// speakers.
+    #[default]
+    Stereo,
+    /// Four virtual speakers around the listener heard through a simple head
+    /// model (interaural time and level differences) for headphones.
+    Binaural,
+}
+
+/// Render a first-order Ambisonic B-format sound to stereo or binaural
+/// output for a listener that can turn their head.
+///
+/// The inner sound must have 4 channels in the traditional (FuMa) order and
+/// scaling: W (omnidirectional, scaled by 1/√2), X (front), Y (left) and Z
+/// (up). The listener orientation is changed at runtime with the
+/// [AmbisonicHandle] returned when the wrapper is created and is applied at
+/// the start of each frame.
+///
+/// Binaural output decodes to virtual speakers at 45° and 135° on each side.
+/// Each ear hears the speakers on its side directly and those on the other
+/// side delayed, attenuated and low pass filtered as if by the head. This gives a clear
+/// left/right image without head related transfer functions but does not
+/// distinguish front from back or up from down.
+pub struct AmbisonicDecode<S: Sound> {
+    inner: S,
+    output: AmbisonicOutput,
+    orientation: Arc<Orientation>,
+    /// The left and right samples of the current frame.
+    frame: [i16; 2],
+    next_channel_idx: u16,
+    /// Binaural state for the left and right ears.
+    ears: [Ear; 2],
+    itd_frames: usize,
+    shadow_coefficient: f32,
+}
+
+/// The signal from the far side of the head for one ear.
+#[derive(Default)]
+struct Ear {
+    delay: VecDeque<f32>,
+    low_pass: f32,
+}
+
+/// Controls the listener orientation of an [AmbisonicDecode].
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct AmbisonicHandle {
+    orientation: Arc<Orientation>,
+}
+
+/// Yaw, pitch and roll in radians stored as f32 bits.
+#[derive(Default)]
+struct Orientation([AtomicU32; 3]);
+
+impl Orientation {
+    fn get(&self) -> [f32; 3] {
+        self.0
+            .each_ref()
+            .map(|angle| f32::from_bits(angle.load(Ordering::Relaxed)))
+    }
+
+    fn set(&self, angles: [f32; 3]) {
+        for (atomic, angle) in self.0.iter().zip(angles) {
+            atomic.store(angle.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl<S> AmbisonicDecode<S>
+where
+    S: Sound,
+{
+    /// Render `inner` with the listener facing forward (along X).
+    ///
+    /// Returns an error if `inner` does not have 4 channels.
+    pub fn new(inner: S, output: AmbisonicOutput) -> Result<(Self, AmbisonicHandle), crate::Error> {
+        check_channel_count(&inner)?;
+        let orientation = Arc::new(Orientation::default());
+        let mut decode = AmbisonicDecode {
+            inner,
+            output,
+            orientation: orientation.clone(),
+            frame: [0; 2],
+            next_channel_idx: 0,
+            ears: Default::default(),
+            itd_frames: 0,
+            shadow_coefficient: 0.0,
+        };
+        decode.reset();
+        Ok((decode, AmbisonicHandle { orientation }))
+    }
+
+    /// Clear the binaural state and size it for the sample rate of the inner
+    /// sound.
+    fn reset(&mut self) {
+        let sample_rate = self.inner.sample_rate() as f32;
+        // Woodworth's formula for a source 45° to the side
+        let itd = HEAD_RADIUS / SPEED_OF_SOUND * (FRAC_PI_4 + FRAC_PI_4.sin());
+        self.itd_frames = (itd * sample_rate).round() as usize;
+        self.shadow_coefficient = (-std::f32::consts::TAU * HEAD_SHADOW_CUTOFF / sample_rate).exp();
+        for ear in &mut self.ears {
+            ear.delay = std::iter::repeat_n(0.0, self.itd_frames).collect();
+            ear.low_pass = 0.0;
+        }
+        self.next_channel_idx = 0;
+    }
+
+    /// Rotate the sound field `[w, x, y, z]` into the listener's frame.
+    fn rotate(&self, [w, x, y, z]: [f32; 4]) -> [f32; 4] {
+        let [yaw, pitch, roll] = self.orientation.get();
+        // Turning the head left turns the sound field right
+        let (sin, cos) = yaw.sin_cos();
+        let (x, y) = (x * cos + y * sin, y * cos - x * sin);
+        // Looking up moves sounds in front down
+        let (sin, cos) = pitch.sin_cos();
+        let (x, z) = (x * cos + z * sin, z * cos - x * sin);
+        // Tilting the head right moves sounds above to the left
+        let (sin, cos) = roll.sin_cos();
+        let (y, z) = (y * cos + z * sin, z * cos - y * sin);
+        [w, x, y, z]
+    }
+
+    /// Render a frame of B-format into left and right samples in
+    /// `-1.0..=1.0`.
+    fn render(&mut self, b_format: [f32; 4]) -> [f32; 2] {
+        let [w, x, y, _] = self.rotate(b_format);
+        let cardioid = |azimuth: f32| 0.5 * (SQRT_2 * w + x * azimuth.cos() + y * azimuth.sin());
+        match self.output {
+            AmbisonicOutput::Stereo => [cardioid(FRAC_PI_2), cardioid(-FRAC_PI_2)],
+            AmbisonicOutput::Binaural => {
+                let left = cardioid(FRAC_PI_4) + cardioid(3.0 * FRAC_PI_4);
+                let right = cardioid(-FRAC_PI_4) + cardioid(-3.0 * FRAC_PI_4);
+                // Each ear hears the other side through the head
+                let far = [right, left];
+                let mut ears = [0.0; 2];
+                for ((ear, near), (state, far)) in ears
+                    .iter_mut()
+                    .zip([left, right])
+                    .zip(self.ears.iter_mut().zip(far))
+                {
+                    state.delay.push_back(far);
+                    let delayed = state.delay.pop_front().unwrap_or(0.0);
+                    state.low_pass = delayed * (1.0 - self.shadow_coefficient)
+                        + state.low_pass * self.shadow_coefficient;
+                    // Normalized so a source in front has unity gain
+                    *ear = (near + HEAD_SHADOW_GAIN * state.low_pass) / (1.0 + HEAD_SHADOW_GAIN);
+                }
+                ears
+            }
+        }
+    }
+}
+
+fn check_channel_count(inner: &impl Sound) -> Result<(), crate::Error> {
+    if inner.channel_count() != 4 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "AmbisonicDecode requires 4 channels but got {}",
+                inner.channel_count()
+            ),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+impl AmbisonicHandle {
+    /// Set the direction the listener is facing in radians. Yaw turns the
+    /// head left, pitch tilts it up and roll tilts it to the right. They are
+    /// applied in that order.
+    pub fn set_orientation(&self, yaw: f32, pitch: f32, roll: f32) {
+        self.orientation.set([yaw, pitch, roll]);
+    }
+
+    /// The yaw, pitch and roll of the listener in radians.
+    pub fn orientation(&self) -> (f32, f32, f32) {
+        let [yaw, pitch, roll] = self.orientation.get();
+        (yaw, pitch, roll)
+    }
+}
+
+impl<S> Sound for AmbisonicDecode<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.next_channel_idx == 1 {
+            self.next_channel_idx = 0;
+            return Ok(NextSample::Sample(self.frame[1]));
+        }
+        let mut b_format = [0.0; 4];
+        for value in &mut b_format {
+            match self.inner.next_sample()? {
+                NextSample::Sample(s) => *value = s as f32 / i16::MAX as f32,
+                NextSample::MetadataChanged => {
+                    check_channel_count(&self.inner)?;
+                    self.reset();
+                    return Ok(NextSample::MetadataChanged);
+                }
+                next @ (NextSample::Paused | NextSample::Finished) => return Ok(next),
+            }
+        }
+        let rendered = self.render(b_format);
+        self.frame =
+            rendered.map(|v| (v * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+        self.next_channel_idx = 1;
+        Ok(NextSample::Sample(self.frame[0]))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for AmbisonicDecode<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/ambisonic_decode.rs"]
+mod tests;
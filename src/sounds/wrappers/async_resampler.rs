@@ -0,0 +1,421 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound, UnderrunStats};
+
+use super::{ResampleQuality, SampleRateConverter, SincSampleRateConverter};
+
+/// The number of frames resampled by the worker thread at a time.
+const CHUNK_FRAMES: usize = 256;
+/// How long the worker thread waits before pulling a paused sound again.
+const PAUSED_RETRY: Duration = Duration::from_millis(1);
+
+/// Convert a Sound to another sample rate on a background thread.
+///
+/// The inner sound is moved to a worker thread which resamples it ahead of
+/// playback into a buffer of up to `buffer` duration. Pulling samples only
+/// copies from that buffer and never waits for the worker so expensive
+/// resampling (e.g. [ResampleQuality::Sinc] with many taps) of many streams
+/// does not slow down the thread pulling the samples.
+///
+/// If the buffer is empty when a frame is needed (an underrun) a frame of
+/// silence is returned instead. Once the inner sound has finished `Finished`
+/// is returned after the buffer has been drained. Pauses of the inner sound
+/// are absorbed by the buffer. The worker thread stops when the
+/// AsyncResampler is dropped.
+pub struct AsyncResampler {
+    hints: WorkerThreadHints,
+    shared: Arc<Shared>,
+    channel_count: u16,
+    sample_rate: u32,
+    /// The chunk being returned and the position of its next sample.
+    chunk: Chunk,
+    chunk_pos: usize,
+    /// Samples of silence left in the current underrun frame.
+    silence_remaining: u16,
+    underrun_frames: u64,
+    /// The number of separate runs of underrun frames.
+    underruns: u64,
+    /// Whether the last frame returned was an underrun frame.
+    underrunning: bool,
+    finished: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Signaled when space is made in the buffer or the worker should stop.
+    changed: Condvar,
+}
+
+struct State {
+    chunks: VecDeque<Chunk>,
+    buffered_samples: usize,
+    stop: bool,
+}
+
+#[derive(Default)]
+struct Chunk {
+    samples: Vec<i16>,
+    /// What to return after `samples`.
+    end: Option<ChunkEnd>,
+}
+
+enum ChunkEnd {
+    MetadataChanged {
+        channel_count: u16,
+        sample_rate: u32,
+    },
+    Finished,
+    Error(crate::Error),
+}
+
+/// Best effort scheduling hints for the worker thread of an
+/// [AsyncResampler].
+///
+/// The priority and CPU affinity are only applied on Linux and Android and
+/// are ignored elsewhere. If the platform refuses a hint (e.g. a core that
+/// does not exist) it is logged and the worker runs without it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkerThreadHints {
+    /// The name of the thread shown by debuggers and profilers.
+    pub name: Option<String>,
+    /// The scheduling priority of the thread.
+    pub priority: WorkerPriority,
+    /// The indexes of the CPU cores the thread may run on. Empty allows
+    /// every core.
+    pub cpu_affinity: Vec<usize>,
+}
+
+/// The scheduling priority of a worker thread. See [WorkerThreadHints].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WorkerPriority {
+    /// The priority threads are created with.
+    #[default]
+    Normal,
+    /// Run after threads of normal priority, e.g. the audio output.
+    Low,
+    /// Only run when nothing else needs the CPU.
+    Lowest,
+}
+
+impl WorkerThreadHints {
+    /// Apply the priority and affinity to the calling thread.
+    fn apply(&self) {
+        let nice = match self.priority {
+            WorkerPriority::Normal => None,
+            WorkerPriority::Low => Some(10),
+            WorkerPriority::Lowest => Some(19),
+        };
+        if let Some(nice) = nice {
+            if !os::set_nice(nice) {
+                log::debug!("unable to lower AsyncResampler worker priority");
+            }
+        }
+        if !self.cpu_affinity.is_empty() && !os::set_affinity(&self.cpu_affinity) {
+            log::debug!(
+                "unable to set AsyncResampler worker affinity to {:?}",
+                self.cpu_affinity
+            );
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod os {
+    use std::os::raw::{c_int, c_uint, c_ulong};
+
+    /// The number of CPUs in the kernel's `cpu_set_t`.
+    const CPU_SETSIZE: usize = 1024;
+    const BITS_PER_WORD: usize = c_ulong::BITS as usize;
+
+    extern "C" {
+        fn setpriority(which: c_int, who: c_uint, prio: c_int) -> c_int;
+        fn sched_setaffinity(pid: c_int, cpusetsize: usize, mask: *const c_ulong) -> c_int;
+    }
+
+    /// Set the nice value of the calling thread. Linux applies it per thread
+    /// when `who` is 0.
+    pub(super) fn set_nice(nice: c_int) -> bool {
+        const PRIO_PROCESS: c_int = 0;
+        // Safety: plain system call without pointers
+        unsafe { setpriority(PRIO_PROCESS, 0, nice) == 0 }
+    }
+
+    /// Restrict the calling thread to `cpus`.
+    pub(super) fn set_affinity(cpus: &[usize]) -> bool {
+        let mut mask = [0 as c_ulong; CPU_SETSIZE / BITS_PER_WORD];
+        for cpu in cpus {
+            if *cpu >= CPU_SETSIZE {
+                return false;
+            }
+            mask[cpu / BITS_PER_WORD] |= 1 << (cpu % BITS_PER_WORD);
+        }
+        // Safety: the mask is valid for reads of its whole size
+        unsafe { sched_setaffinity(0, std::mem::size_of_val(&mask), mask.as_ptr()) == 0 }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod os {
+    pub(super) fn set_nice(_nice: i32) -> bool {
+        false
+    }
+
+    pub(super) fn set_affinity(_cpus: &[usize]) -> bool {
+        false
+    }
+}
+
+impl AsyncResampler {
+    /// Convert `inner` to `to_rate` using the resampler selected by `quality`
+    /// on a new thread, keeping up to `buffer` of output resampled ahead.
+    ///
+    /// An error is returned if `quality` is [ResampleQuality::Sinc] with
+    /// fewer than 2 taps, a sample rate is 0 or the worker thread can not be
+    /// spawned.
+    pub fn new<S>(
+        inner: S,
+        to_rate: u32,
+        quality: ResampleQuality,
+        buffer: Duration,
+    ) -> Result<Self, crate::Error>
+    where
+        S: Sound + 'static,
+    {
+        Self::with_worker_hints(
+            inner,
+            to_rate,
+            quality,
+            buffer,
+            WorkerThreadHints::default(),
+        )
+    }
+
+    /// Same as [new][AsyncResampler::new] but the worker thread is started
+    /// with `hints`, e.g. to keep it off the core the audio output runs on.
+    pub fn with_worker_hints<S>(
+        inner: S,
+        to_rate: u32,
+        quality: ResampleQuality,
+        buffer: Duration,
+        hints: WorkerThreadHints,
+    ) -> Result<Self, crate::Error>
+    where
+        S: Sound + 'static,
+    {
+        let converter: Box<dyn Sound> = match quality {
+            ResampleQuality::Linear => Box::new(SampleRateConverter::new(inner, to_rate)),
+            ResampleQuality::Sinc { taps } => {
+                Box::new(SincSampleRateConverter::new(inner, to_rate, taps)?)
+            }
+        };
+        let channel_count = converter.channel_count();
+        let sample_rate = converter.sample_rate();
+        // Always allow at least one chunk so the worker can make progress
+        let capacity = (utils::duration_to_num_samples(buffer, channel_count, sample_rate)
+            as usize)
+            .max(CHUNK_FRAMES * channel_count as usize);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                chunks: VecDeque::new(),
+                buffered_samples: 0,
+                stop: false,
+            }),
+            changed: Condvar::new(),
+        });
+        let worker_shared = shared.clone();
+        let mut builder = std::thread::Builder::new();
+        if let Some(name) = &hints.name {
+            builder = builder.name(name.clone());
+        }
+        let worker_hints = hints.clone();
+        builder.spawn(move || {
+            worker_hints.apply();
+            run_worker(converter, &worker_shared, capacity)
+        })?;
+        Ok(AsyncResampler {
+            hints,
+            shared,
+            channel_count,
+            sample_rate,
+            chunk: Chunk::default(),
+            chunk_pos: 0,
+            silence_remaining: 0,
+            underrun_frames: 0,
+            underruns: 0,
+            underrunning: false,
+            finished: false,
+        })
+    }
+
+    /// The hints the worker thread was started with.
+    pub fn worker_hints(&self) -> &WorkerThreadHints {
+        &self.hints
+    }
+
+    /// The number of frames of silence returned because the worker thread
+    /// had not resampled enough audio yet.
+    pub fn underrun_frames(&self) -> u64 {
+        self.underrun_frames
+    }
+
+    /// Take the next chunk from the worker without waiting for it.
+    fn take_chunk(&mut self) -> Option<Chunk> {
+        let mut state = self.shared.state.try_lock().ok()?;
+        let chunk = state.chunks.pop_front()?;
+        state.buffered_samples -= chunk.samples.len();
+        drop(state);
+        self.shared.changed.notify_one();
+        Some(chunk)
+    }
+}
+
+fn run_worker(mut sound: Box<dyn Sound>, shared: &Shared, capacity: usize) {
+    loop {
+        {
+            let mut state = shared.state.lock().unwrap();
+            while !state.stop && state.buffered_samples >= capacity {
+                state = shared.changed.wait(state).unwrap();
+            }
+            if state.stop {
+                return;
+            }
+        }
+
+        let channel_count = sound.channel_count().max(1) as usize;
+        let target = CHUNK_FRAMES * channel_count;
+        let mut chunk = Chunk::default();
+        // Chunks are only split on frame boundaries so an underrun never
+        // shifts the channels.
+        while chunk.samples.len() < target || chunk.samples.len() % channel_count != 0 {
+            match sound.next_sample() {
+                Ok(NextSample::Sample(s)) => chunk.samples.push(s),
+                Ok(NextSample::MetadataChanged) => {
+                    chunk.end = Some(ChunkEnd::MetadataChanged {
+                        channel_count: sound.channel_count(),
+                        sample_rate: sound.sample_rate(),
+                    });
+                    break;
+                }
+                Ok(NextSample::Paused) => {
+                    if chunk.samples.len().is_multiple_of(channel_count) {
+                        break;
+                    }
+                    std::thread::sleep(PAUSED_RETRY);
+                }
+                Ok(NextSample::Finished) => {
+                    chunk.end = Some(ChunkEnd::Finished);
+                    break;
+                }
+                Err(e) => {
+                    chunk.end = Some(ChunkEnd::Error(e));
+                    break;
+                }
+            }
+        }
+
+        let done = matches!(chunk.end, Some(ChunkEnd::Finished | ChunkEnd::Error(_)));
+        if chunk.samples.is_empty() && chunk.end.is_none() {
+            // Paused with nothing to hand over
+            std::thread::sleep(PAUSED_RETRY);
+            continue;
+        }
+        let mut state = shared.state.lock().unwrap();
+        state.buffered_samples += chunk.samples.len();
+        state.chunks.push_back(chunk);
+        if done {
+            return;
+        }
+    }
+}
+
+impl Sound for AsyncResampler {
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.silence_remaining > 0 {
+            self.silence_remaining -= 1;
+            return Ok(NextSample::Sample(0));
+        }
+        loop {
+            if let Some(sample) = self.chunk.samples.get(self.chunk_pos) {
+                self.chunk_pos += 1;
+                return Ok(NextSample::Sample(*sample));
+            }
+            match self.chunk.end.take() {
+                Some(ChunkEnd::MetadataChanged {
+                    channel_count,
+                    sample_rate,
+                }) => {
+                    self.channel_count = channel_count;
+                    self.sample_rate = sample_rate;
+                    return Ok(NextSample::MetadataChanged);
+                }
+                Some(ChunkEnd::Finished) => self.finished = true,
+                Some(ChunkEnd::Error(e)) => {
+                    self.finished = true;
+                    return Err(e);
+                }
+                None => (),
+            }
+            if self.finished {
+                return Ok(NextSample::Finished);
+            }
+            match self.take_chunk() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.chunk_pos = 0;
+                    self.underrunning = false;
+                }
+                None => {
+                    if !self.underrunning {
+                        self.underrunning = true;
+                        self.underruns += 1;
+                    }
+                    self.underrun_frames += 1;
+                    self.silence_remaining = self.channel_count.saturating_sub(1);
+                    return Ok(NextSample::Sample(0));
+                }
+            }
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {}
+
+    fn underrun_stats(&self) -> UnderrunStats {
+        let silent_samples = self.underrun_frames * self.channel_count as u64;
+        UnderrunStats {
+            underruns: self.underruns,
+            silent_samples,
+            silence: utils::num_samples_to_duration(
+                silent_samples,
+                self.channel_count,
+                self.sample_rate,
+            ),
+        }
+    }
+
+    fn may_block(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for AsyncResampler {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.stop = true;
+        }
+        self.shared.changed.notify_one();
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/async_resampler.rs"]
+mod tests;
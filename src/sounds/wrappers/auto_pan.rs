@@ -0,0 +1,142 @@
+use std::f64::consts::{FRAC_PI_4, SQRT_2, TAU};
+
+use crate::{NextSample, Sound};
+
+use super::{ChannelCountConverter, Wrapper};
+
+/// Continuously pan a sound between the left and right channels for an
+/// auto-pan or rotating speaker effect.
+///
+/// The inner sound is converted to stereo and a sine LFO moves the pan
+/// position between `-depth` (left) and `depth` (right). The first channel
+/// starts centered and moves right first. Constant power panning keeps the
+/// total power of the two channels constant: a centered sound is unchanged
+/// and a sound panned fully to one side is 3dB louder on that side.
+pub struct AutoPan<S: Sound> {
+    inner: ChannelCountConverter<S>,
+    rate: f32,
+    depth: f32,
+    /// The LFO phase in cycles in the range `0.0..1.0`.
+    phase: f64,
+    /// The phase advance per frame.
+    increment: f64,
+    /// The left and right gains for the current frame.
+    gains: [f64; 2],
+    next_channel_idx: u16,
+}
+
+impl<S> AutoPan<S>
+where
+    S: Sound,
+{
+    /// Pan `inner` back and forth `rate` times per second fully between the
+    /// left and right channels.
+    pub fn new(inner: S, rate: f32) -> Self {
+        let mut auto_pan = AutoPan {
+            inner: ChannelCountConverter::new(inner, 2),
+            rate,
+            depth: 1.0,
+            phase: 0.0,
+            increment: 0.0,
+            gains: [1.0, 1.0],
+            next_channel_idx: 0,
+        };
+        auto_pan.update_increment();
+        auto_pan
+    }
+
+    /// The number of times per second the sound moves from the center to the
+    /// right, to the left and back to the center.
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    /// Change the LFO rate. The LFO stays phase continuous.
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate;
+        self.update_increment();
+    }
+
+    /// How far the sound is panned from the center where 1.0 reaches fully
+    /// left and right and 0.0 leaves the sound centered.
+    pub fn depth(&self) -> f32 {
+        self.depth
+    }
+
+    /// Set the pan depth. Clamped to `0.0..=1.0`.
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth.clamp(0.0, 1.0);
+    }
+
+    fn update_increment(&mut self) {
+        self.increment = self.rate as f64 / self.inner.sample_rate() as f64;
+    }
+
+    fn update_gains(&mut self) {
+        let pan = self.depth as f64 * (self.phase * TAU).sin();
+        let angle = (pan + 1.0) * FRAC_PI_4;
+        self.gains = [SQRT_2 * angle.cos(), SQRT_2 * angle.sin()];
+    }
+}
+
+impl<S> Sound for AutoPan<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                if self.next_channel_idx == 0 {
+                    self.update_gains();
+                    self.phase = (self.phase + self.increment).rem_euclid(1.0);
+                }
+                let gain = self.gains[self.next_channel_idx as usize];
+                self.next_channel_idx = (self.next_channel_idx + 1) % 2;
+                Ok(NextSample::Sample((s as f64 * gain) as i16))
+            }
+            NextSample::MetadataChanged => {
+                self.next_channel_idx = 0;
+                self.update_increment();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for AutoPan<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        self.inner.inner()
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        self.inner.inner_mut()
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner.into_inner()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/auto_pan.rs"]
+mod tests;
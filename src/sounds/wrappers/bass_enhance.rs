@@ -0,0 +1,156 @@
+use crate::sounds::biquad::{Biquad, BiquadCoefficients};
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Add harmonics of the bass of the inner sound so the bass is still
+/// perceived on small speakers that can not reproduce it.
+///
+/// The bass below the crossover frequency is split off with a
+/// Linkwitz-Riley low pass filter and distorted to generate harmonics at
+/// integer multiples of its frequencies. The listener infers the missing
+/// fundamental from the harmonics. Full wave rectification generates the even
+/// harmonics and a squared term the odd ones. The harmonics are high passed at
+/// the crossover so only the added harmonics and none of the bass itself are
+/// mixed back in, scaled by the intensity.
+///
+/// By default the crossover is 120Hz and the intensity 0.5. Filter state is
+/// reset when the inner sound returns `MetadataChanged`. The output is
+/// clipped to the range of i16.
+pub struct BassEnhance<S: Sound> {
+    inner: S,
+    crossover: f32,
+    intensity: f32,
+    channels: Vec<ChannelState>,
+    next_channel_idx: usize,
+}
+
+#[derive(Clone)]
+struct ChannelState {
+    low: [Biquad; 2],
+    harmonics: [Biquad; 2],
+}
+
+impl<S> BassEnhance<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` adding harmonics of the bass below 120Hz.
+    pub fn new(inner: S) -> Self {
+        let mut enhance = BassEnhance {
+            inner,
+            crossover: 120.0,
+            intensity: 0.5,
+            channels: Vec::new(),
+            next_channel_idx: 0,
+        };
+        enhance.reset();
+        enhance
+    }
+
+    /// The frequency in Hz below which harmonics are generated.
+    pub fn crossover(&self) -> f32 {
+        self.crossover
+    }
+
+    /// Set the frequency in Hz below which harmonics are generated.
+    ///
+    /// It is limited to below the Nyquist frequency of the inner sound. The
+    /// filter state is reset.
+    pub fn set_crossover(&mut self, crossover: f32) {
+        self.crossover = crossover;
+        self.reset();
+    }
+
+    /// How loud the harmonics are mixed in.
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Set how loud the harmonics are mixed in. 0.0 adds no harmonics.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.max(0.0);
+    }
+
+    fn reset(&mut self) {
+        let sample_rate = self.inner.sample_rate() as f32;
+        let crossover = self.crossover.clamp(1.0, sample_rate / 2.0 * 0.99);
+        let low = Biquad::new(BiquadCoefficients::low_pass(crossover, sample_rate));
+        let high = Biquad::new(BiquadCoefficients::high_pass(crossover, sample_rate));
+        let state = ChannelState {
+            low: [low.clone(), low],
+            harmonics: [high.clone(), high],
+        };
+        self.channels = vec![state; self.inner.channel_count() as usize];
+        self.next_channel_idx = 0;
+    }
+
+    fn enhance(&mut self, sample: i16) -> i16 {
+        let channel_count = self.channels.len();
+        let Some(state) = self.channels.get_mut(self.next_channel_idx) else {
+            return sample;
+        };
+        self.next_channel_idx = (self.next_channel_idx + 1) % channel_count;
+        let x = sample as f32 / i16::MAX as f32;
+        let low = state.low[0].process(x);
+        let low = state.low[1].process(low);
+        let distorted = low.abs() + low * low.abs();
+        let harmonics = state.harmonics[0].process(distorted);
+        let harmonics = state.harmonics[1].process(harmonics);
+        let y = (x + harmonics * self.intensity) * i16::MAX as f32;
+        y.clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    }
+}
+
+impl<S> Sound for BassEnhance<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        Ok(match next {
+            NextSample::Sample(s) => NextSample::Sample(self.enhance(s)),
+            NextSample::MetadataChanged => {
+                self.reset();
+                next
+            }
+            NextSample::Paused | NextSample::Finished => next,
+        })
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S: Sound> Wrapper for BassEnhance<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/bass_enhance.rs"]
+mod tests;
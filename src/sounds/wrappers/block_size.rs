@@ -0,0 +1,173 @@
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Deliver the inner sound in whole blocks of a fixed number of frames for
+/// backends that require fixed size buffers.
+///
+/// A block is only started once all of its frames have been read from the
+/// inner sound so `Paused`, `MetadataChanged` and `Finished` are only
+/// returned between blocks. If the inner sound pauses part way through a
+/// block, `Paused` is returned until the rest of the block is available. If
+/// it finishes or changes its metadata part way through a block, the block is
+/// padded with silence. Otherwise samples are returned unchanged.
+///
+/// Blocks can be read with [append_next_block_to][BlockSize::append_next_block_to]
+/// or one sample at a time with `next_sample`.
+pub struct BlockSize<S: Sound> {
+    inner: S,
+    block_frames: usize,
+    channel_count: u16,
+    sample_rate: u32,
+    /// The block being read or returned.
+    block: Vec<i16>,
+    /// The index of the next sample of `block` to return if it is complete.
+    next_idx: usize,
+    /// `block` is complete and being returned.
+    complete: bool,
+    /// A MetadataChanged or Finished to return after `block`.
+    pending: Option<NextSample>,
+    frame: Vec<i16>,
+}
+
+impl<S> BlockSize<S>
+where
+    S: Sound,
+{
+    /// Deliver `inner` in blocks of `block_frames` frames.
+    ///
+    /// Panics if `block_frames` is 0.
+    pub fn new(inner: S, block_frames: usize) -> Self {
+        assert!(block_frames > 0, "block_frames must be at least 1");
+        BlockSize {
+            channel_count: inner.channel_count(),
+            sample_rate: inner.sample_rate(),
+            inner,
+            block_frames,
+            block: Vec::new(),
+            next_idx: 0,
+            complete: false,
+            pending: None,
+            frame: Vec::new(),
+        }
+    }
+
+    /// The number of frames in each block.
+    pub fn block_frames(&self) -> usize {
+        self.block_frames
+    }
+
+    /// Append the next block (`block_frames * channel_count` samples) to
+    /// `samples`.
+    ///
+    /// Like [append_next_frame_to][Sound::append_next_frame_to] this must
+    /// only be called at the start of a block otherwise only the rest of the
+    /// current block is appended. If something other than a sample is
+    /// encountered Err(Ok(NextSample)) is returned and nothing is appended.
+    /// Err(Ok(NextSample::Sample)) will never be returned. If an error is
+    /// encountered Err(Err(error::Error)) is returned.
+    pub fn append_next_block_to(
+        &mut self,
+        samples: &mut Vec<i16>,
+    ) -> Result<(), Result<NextSample, crate::Error>> {
+        match self.next_sample() {
+            Ok(NextSample::Sample(s)) => samples.push(s),
+            other => return Err(other),
+        }
+        samples.extend_from_slice(&self.block[self.next_idx..]);
+        self.next_idx = self.block.len();
+        Ok(())
+    }
+
+    /// Read frames until the block is complete. Returns false if the inner
+    /// sound paused first.
+    fn fill_block(&mut self) -> Result<bool, crate::Error> {
+        let block_len = self.block_frames * self.channel_count as usize;
+        while self.block.len() < block_len {
+            self.frame.clear();
+            match self.inner.append_next_frame_to(&mut self.frame) {
+                Ok(()) => self.block.extend_from_slice(&self.frame),
+                Err(Ok(NextSample::Paused)) => return Ok(false),
+                Err(Ok(special)) => {
+                    if !self.block.is_empty() {
+                        self.block.resize(block_len, 0);
+                    }
+                    self.pending = Some(special);
+                    break;
+                }
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        self.complete = !self.block.is_empty();
+        Ok(true)
+    }
+}
+
+impl<S> Sound for BlockSize<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.complete {
+            if let Some(sample) = self.block.get(self.next_idx) {
+                self.next_idx += 1;
+                return Ok(NextSample::Sample(*sample));
+            }
+            self.complete = false;
+            self.block.clear();
+            self.next_idx = 0;
+        }
+        match self.pending.take() {
+            Some(NextSample::MetadataChanged) => {
+                self.channel_count = self.inner.channel_count();
+                self.sample_rate = self.inner.sample_rate();
+                return Ok(NextSample::MetadataChanged);
+            }
+            Some(NextSample::Finished) => {
+                self.pending = Some(NextSample::Finished);
+                return Ok(NextSample::Finished);
+            }
+            _ => (),
+        }
+        if !self.fill_block()? {
+            return Ok(NextSample::Paused);
+        }
+        self.next_sample()
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for BlockSize<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/block_size.rs"]
+mod tests;
@@ -0,0 +1,203 @@
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// How quickly the gate opens once the level is above the threshold.
+const GATE_ATTACK_SECS: f32 = 0.001;
+/// How long the level must stay below the threshold before the gate closes.
+const GATE_HOLD_SECS: f32 = 0.05;
+/// How quickly the gate closes once the hold time has passed.
+const GATE_RELEASE_SECS: f32 = 0.1;
+/// Decay of the peak level followed by the gate.
+const GATE_ENVELOPE_SECS: f32 = 0.01;
+/// How quickly the limiter gain recovers after a peak.
+const LIMITER_RELEASE_SECS: f32 = 0.1;
+
+/// A noise gate followed by a peak limiter with broadcast defaults.
+///
+/// The gate silences each channel while its level stays below the gate
+/// threshold (-40 dBFS by default) so hiss and room noise between speech is
+/// removed. The limiter then keeps every sample at or below the ceiling
+/// (-1 dBFS by default) by reducing the gain instantly on peaks and
+/// recovering over 100ms.
+///
+/// The input gain is applied first so quiet sources can be pushed into the
+/// limiter. All processing is done in floating point before converting back
+/// to i16 and each channel has its own gate and limiter state.
+pub struct BroadcastProcessor<S: Sound> {
+    inner: S,
+    input_gain: f32,
+    gate_threshold: f32,
+    ceiling: f32,
+    channels: Vec<ChannelState>,
+    next_channel_idx: u16,
+    gate_attack_coefficient: f32,
+    gate_release_coefficient: f32,
+    gate_envelope_coefficient: f32,
+    gate_hold_frames: u32,
+    limiter_release_coefficient: f32,
+}
+
+struct ChannelState {
+    gate_envelope: f32,
+    gate_gain: f32,
+    /// Frames since the level was last above the gate threshold.
+    frames_below: u32,
+    limiter_gain: f32,
+}
+
+impl<S> BroadcastProcessor<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` with the default gate threshold, ceiling and no input
+    /// gain.
+    ///
+    /// The gate starts closed so leading noise is removed.
+    pub fn new(inner: S) -> Self {
+        let mut processor = BroadcastProcessor {
+            inner,
+            input_gain: 1.0,
+            gate_threshold: 0.01,
+            ceiling: 0.89,
+            channels: Vec::new(),
+            next_channel_idx: 0,
+            gate_attack_coefficient: 0.0,
+            gate_release_coefficient: 0.0,
+            gate_envelope_coefficient: 0.0,
+            gate_hold_frames: 0,
+            limiter_release_coefficient: 0.0,
+        };
+        processor.reset();
+        processor
+    }
+
+    /// Set the gain applied before the gate and limiter.
+    pub fn set_input_gain(&mut self, input_gain: f32) {
+        self.input_gain = input_gain.max(0.0);
+    }
+
+    /// Set the level, as a fraction of full scale, below which a channel is
+    /// silenced. 0.0 disables the gate.
+    pub fn set_gate_threshold(&mut self, gate_threshold: f32) {
+        self.gate_threshold = gate_threshold.clamp(0.0, 1.0);
+    }
+
+    /// Set the highest level, as a fraction of full scale, the limiter lets
+    /// through.
+    pub fn set_ceiling(&mut self, ceiling: f32) {
+        self.ceiling = ceiling.clamp(0.0, 1.0);
+    }
+
+    /// Clear the per channel state and recompute the time constants for the
+    /// format of the inner sound.
+    fn reset(&mut self) {
+        let sample_rate = self.inner.sample_rate() as f32;
+        let coefficient = |secs: f32| (-1.0 / (secs * sample_rate)).exp();
+        self.gate_attack_coefficient = coefficient(GATE_ATTACK_SECS);
+        self.gate_release_coefficient = coefficient(GATE_RELEASE_SECS);
+        self.gate_envelope_coefficient = coefficient(GATE_ENVELOPE_SECS);
+        self.gate_hold_frames = (GATE_HOLD_SECS * sample_rate) as u32;
+        self.limiter_release_coefficient = coefficient(LIMITER_RELEASE_SECS);
+        self.channels.clear();
+        self.channels
+            .resize_with(self.inner.channel_count() as usize, || ChannelState {
+                gate_envelope: 0.0,
+                gate_gain: 0.0,
+                frames_below: u32::MAX,
+                limiter_gain: 1.0,
+            });
+        self.next_channel_idx = 0;
+    }
+
+    fn process(&mut self, value: f32) -> f32 {
+        let Some(state) = self.channels.get_mut(self.next_channel_idx as usize) else {
+            return value;
+        };
+        let value = value * self.input_gain;
+        let level = value.abs();
+
+        state.gate_envelope = level.max(state.gate_envelope * self.gate_envelope_coefficient);
+        if state.gate_envelope >= self.gate_threshold {
+            state.frames_below = 0;
+        } else {
+            state.frames_below = state.frames_below.saturating_add(1);
+        }
+        let (target, coefficient) = if state.frames_below <= self.gate_hold_frames {
+            (1.0, self.gate_attack_coefficient)
+        } else {
+            (0.0, self.gate_release_coefficient)
+        };
+        state.gate_gain = target + (state.gate_gain - target) * coefficient;
+        let value = value * state.gate_gain;
+
+        // Recover towards unity then reduce instantly if this sample would
+        // still be over the ceiling
+        state.limiter_gain = 1.0 + (state.limiter_gain - 1.0) * self.limiter_release_coefficient;
+        let level = value.abs();
+        if level * state.limiter_gain > self.ceiling {
+            state.limiter_gain = self.ceiling / level;
+        }
+        value * state.limiter_gain
+    }
+}
+
+impl<S> Sound for BroadcastProcessor<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                let value = self.process(s as f32 / i16::MAX as f32);
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                }
+                Ok(NextSample::Sample((value * i16::MAX as f32) as i16))
+            }
+            NextSample::MetadataChanged => {
+                self.reset();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S: Sound> Wrapper for BroadcastProcessor<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/broadcast_processor.rs"]
+mod tests;
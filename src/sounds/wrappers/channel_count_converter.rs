@@ -4,16 +4,58 @@ use super::Wrapper;
 
 /// Convert a Sound to have a specified number of output channels.
 /// For example convert a mono sound to stereo or vice versa.
+///
+/// Mono, stereo, quad and 5.1 (`L, R, C, LFE, SL, SR`) are up-mixed and
+/// down-mixed between each other following the speaker rules of the
+/// [Web Audio API](https://developer.mozilla.org/en-US/docs/Web/API/Web_Audio_API/Basic_concepts_behind_Web_Audio_API#up-mixing_and_down-mixing).
+/// Any other combination is converted discretely: the first channels are
+/// copied and extra output channels are silent.
+///
+/// Down-mixing 5.1 to stereo can use other [DownmixCoefficients] and down-mixed
+/// channels can be normalized so they never clip (see
+/// [try_with_downmix][Self::try_with_downmix]). Otherwise samples that sum
+/// beyond full scale are clipped.
 pub struct ChannelCountConverter<S: Sound> {
     inner: S,
     to_count: u16,
+    downmix: DownmixCoefficients,
+    normalize: bool,
     converter_type: ConverterType,
 }
 
+/// The coefficients used by a [ChannelCountConverter] to down-mix 5.1
+/// (`L, R, C, LFE, SL, SR`) to stereo.
+///
+/// Only this conversion is affected. Every other conversion uses the Web
+/// Audio API rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DownmixCoefficients {
+    /// ITU-R BS.775: the center and each surround are added to their side at
+    /// -3 dB and LFE is dropped. This is the same as the Web Audio API.
+    #[default]
+    Itu,
+    /// A Dolby Pro Logic II compatible matrix: the center is added to both
+    /// sides at -3 dB and the surrounds are added to both sides with opposite
+    /// polarity (-1.2 dB to their own side and -6 dB to the other) so a
+    /// decoder can steer them back to the rear. LFE is dropped.
+    Dolby,
+}
+
 enum ConverterType {
     PassThrough,
-    MonoToStereo { last_sample: Option<i16> },
+    MonoToStereo {
+        last_sample: Option<i16>,
+    },
     StereoToMono,
+    Matrix {
+        /// `to_count` rows of `from_count` weights.
+        weights: Vec<f32>,
+        /// The input frame being converted.
+        input: Vec<f32>,
+        /// The converted frame currently being output.
+        frame: Vec<i16>,
+        next_idx: usize,
+    },
 }
 
 impl<S> ChannelCountConverter<S>
@@ -21,41 +63,94 @@ where
     S: Sound,
 {
     /// Wrap `inner` such that it will output `to_count` channels.
+    ///
+    /// Panics if `to_count` or the channel count of `inner` is 0. Use
+    /// [try_new][Self::try_new] to get an error instead.
     pub fn new(inner: S, to_count: u16) -> ChannelCountConverter<S> {
-        let converter_type = Self::get_type(inner.channel_count(), to_count);
+        match Self::try_new(inner, to_count) {
+            Ok(converter) => converter,
+            Err(e) => panic!("{}", e),
+        }
+    }
 
-        ChannelCountConverter {
+    /// Wrap `inner` such that it will output `to_count` channels.
+    ///
+    /// Returns an error if `to_count` or the channel count of `inner` is 0
+    /// since there is no way to convert to or from no channels.
+    pub fn try_new(inner: S, to_count: u16) -> Result<ChannelCountConverter<S>, crate::Error> {
+        Self::try_with_downmix(inner, to_count, DownmixCoefficients::default(), false)
+    }
+
+    /// Wrap `inner` such that it will output `to_count` channels, down-mixing
+    /// 5.1 to stereo with `downmix`.
+    ///
+    /// If `normalize` is true each output channel that mixes several input
+    /// channels is scaled down by the sum of their weights so it can not
+    /// exceed full scale. This makes the output quieter than the input even
+    /// when it would not have clipped.
+    ///
+    /// Returns an error if `to_count` or the channel count of `inner` is 0.
+    pub fn try_with_downmix(
+        inner: S,
+        to_count: u16,
+        downmix: DownmixCoefficients,
+        normalize: bool,
+    ) -> Result<ChannelCountConverter<S>, crate::Error> {
+        let converter_type = Self::get_type(inner.channel_count(), to_count, downmix, normalize)?;
+
+        Ok(ChannelCountConverter {
             inner,
             to_count,
+            downmix,
+            normalize,
             converter_type,
-        }
+        })
     }
 
-    fn get_type(from_count: u16, to_count: u16) -> ConverterType {
-        if from_count == to_count {
+    fn get_type(
+        from_count: u16,
+        to_count: u16,
+        downmix: DownmixCoefficients,
+        normalize: bool,
+    ) -> Result<ConverterType, crate::Error> {
+        if from_count == 0 || to_count == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "can not convert {} channels to {} channels",
+                    from_count, to_count
+                ),
+            )
+            .into());
+        }
+        Ok(if from_count == to_count {
             ConverterType::PassThrough
         } else if from_count == 1 && to_count == 2 {
             ConverterType::MonoToStereo { last_sample: None }
         } else if from_count == 2 && to_count == 1 {
             ConverterType::StereoToMono
         } else {
-            // Can implement more conversions like
-            // https://developer.mozilla.org/en-US/docs/Web/API/Web_Audio_API/Basic_concepts_behind_Web_Audio_API#up-mixing_and_down-mixing
-            todo!(
-                "ChannelCountConverter for {} to {} channels not implemented.",
-                from_count,
-                to_count
-            );
-        }
+            ConverterType::Matrix {
+                weights: mix_weights(from_count, to_count, downmix, normalize),
+                input: vec![0.0; from_count as usize],
+                frame: vec![0; to_count as usize],
+                next_idx: to_count as usize,
+            }
+        })
     }
 
     // We could save the metadata of the inner Source and only return MetadataChange
     // if the metadata change is something we can't handle (i.e. a Rate Change).
-    fn handle_possible_channel_count_change(&mut self, next: NextSample) {
+    fn handle_possible_channel_count_change(
+        &mut self,
+        next: NextSample,
+    ) -> Result<(), crate::Error> {
         if let NextSample::MetadataChanged = next {
             let from_count = self.inner.channel_count();
-            self.converter_type = Self::get_type(from_count, self.to_count);
+            self.converter_type =
+                Self::get_type(from_count, self.to_count, self.downmix, self.normalize)?;
         }
+        Ok(())
     }
 
     /// Unwrap the inner Sound.
@@ -83,7 +178,7 @@ where
         match &mut self.converter_type {
             ConverterType::PassThrough => {
                 let next = self.inner.next_sample()?;
-                self.handle_possible_channel_count_change(next);
+                self.handle_possible_channel_count_change(next)?;
                 Ok(next)
             }
             ConverterType::MonoToStereo {
@@ -102,13 +197,13 @@ where
                         NextSample::MetadataChanged => {} // handled below
                         NextSample::Paused | NextSample::Finished => {} // Just pass through
                     }
-                    self.handle_possible_channel_count_change(next);
+                    self.handle_possible_channel_count_change(next)?;
                     Ok(next)
                 }
             }
             ConverterType::StereoToMono => {
                 let next1 = self.inner.next_sample()?;
-                self.handle_possible_channel_count_change(next1);
+                self.handle_possible_channel_count_change(next1)?;
                 let sample1 = match next1 {
                     NextSample::Sample(s) => s,
                     NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
@@ -116,7 +211,7 @@ where
                     }
                 };
                 let next2 = self.inner.next_sample()?;
-                self.handle_possible_channel_count_change(next2);
+                self.handle_possible_channel_count_change(next2)?;
                 let sample2 = match next2 {
                     NextSample::Sample(s) => s,
                     NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => {
@@ -128,12 +223,131 @@ where
                 let avg = ((sample1 as i32 + sample2 as i32) / 2) as i16;
                 Ok(NextSample::Sample(avg))
             }
+            ConverterType::Matrix {
+                weights,
+                input,
+                frame,
+                next_idx,
+            } => {
+                if *next_idx < frame.len() {
+                    let sample = frame[*next_idx];
+                    *next_idx += 1;
+                    return Ok(NextSample::Sample(sample));
+                }
+                for value in input.iter_mut() {
+                    match self.inner.next_sample()? {
+                        NextSample::Sample(s) => *value = s as f32,
+                        next @ (NextSample::MetadataChanged
+                        | NextSample::Paused
+                        | NextSample::Finished) => {
+                            self.handle_possible_channel_count_change(next)?;
+                            return Ok(next);
+                        }
+                    }
+                }
+                for (out, row) in frame.iter_mut().zip(weights.chunks(input.len())) {
+                    let value: f32 = row.iter().zip(input.iter()).map(|(w, s)| w * s).sum();
+                    *out = value.clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+                }
+                *next_idx = 1;
+                Ok(NextSample::Sample(frame[0]))
+            }
         }
     }
 
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+/// The weight of each input channel in each output channel with `to_count`
+/// rows of `from_count` weights.
+fn mix_weights(
+    from_count: u16,
+    to_count: u16,
+    downmix: DownmixCoefficients,
+    normalize: bool,
+) -> Vec<f32> {
+    let mut weights = speaker_weights(from_count, to_count, downmix);
+    if normalize {
+        for row in weights.chunks_mut(from_count as usize) {
+            let sum: f32 = row.iter().map(|w| w.abs()).sum();
+            if sum > 1.0 {
+                row.iter_mut().for_each(|w| *w /= sum);
+            }
+        }
+    }
+    weights
+}
+
+fn speaker_weights(from_count: u16, to_count: u16, downmix: DownmixCoefficients) -> Vec<f32> {
+    const H: f32 = std::f32::consts::FRAC_1_SQRT_2;
+    // Pro Logic II surround weights: sin(60°) and sin(30°)
+    const S_NEAR: f32 = 0.8660254;
+    const S_FAR: f32 = 0.5;
+    #[rustfmt::skip]
+    let speaker: Option<&[f32]> = match (from_count, to_count) {
+        (1, 4) => Some(&[1.0, 1.0, 0.0, 0.0]),
+        (1, 6) => Some(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0]),
+        (2, 4) => Some(&[
+            1.0, 0.0,
+            0.0, 1.0,
+            0.0, 0.0,
+            0.0, 0.0,
+        ]),
+        (2, 6) => Some(&[
+            1.0, 0.0,
+            0.0, 1.0,
+            0.0, 0.0,
+            0.0, 0.0,
+            0.0, 0.0,
+            0.0, 0.0,
+        ]),
+        (4, 6) => Some(&[
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ]),
+        (4, 1) => Some(&[0.25, 0.25, 0.25, 0.25]),
+        (4, 2) => Some(&[
+            0.5, 0.0, 0.5, 0.0,
+            0.0, 0.5, 0.0, 0.5,
+        ]),
+        (6, 1) => Some(&[H, H, 1.0, 0.0, 0.5, 0.5]),
+        (6, 2) if downmix == DownmixCoefficients::Dolby => Some(&[
+            1.0, 0.0, H, 0.0, -S_NEAR, -S_FAR,
+            0.0, 1.0, H, 0.0, S_FAR, S_NEAR,
+        ]),
+        (6, 2) => Some(&[
+            1.0, 0.0, H, 0.0, H, 0.0,
+            0.0, 1.0, H, 0.0, 0.0, H,
+        ]),
+        (6, 4) => Some(&[
+            1.0, 0.0, H, 0.0, 0.0, 0.0,
+            0.0, 1.0, H, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+        ]),
+        _ => None,
+    };
+    if let Some(weights) = speaker {
+        return weights.to_vec();
+    }
+
+    // Discrete: copy the channels both have and leave the rest silent.
+    let (from_count, to_count) = (from_count as usize, to_count as usize);
+    let mut weights = vec![0.0; from_count * to_count];
+    for channel in 0..from_count.min(to_count) {
+        weights[channel * from_count + channel] = 1.0;
+    }
+    weights
 }
 
 impl<S: Sound> Wrapper for ChannelCountConverter<S> {
@@ -151,3 +365,7 @@ impl<S: Sound> Wrapper for ChannelCountConverter<S> {
         self.inner
     }
 }
+
+#[cfg(test)]
+#[path = "./tests/channel_count_converter.rs"]
+mod tests;
@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// Delay each channel of the inner sound independently by a whole number of
+/// samples, e.g. to realign channels captured with different latencies.
+///
+/// Each delayed channel starts with silence for the length of its delay.
+/// Channels without a delay given are not delayed. Once the inner sound
+/// finishes the delayed channels are played out until every channel has
+/// returned all of its samples, with silence on the channels that have
+/// already finished.
+///
+/// Delays given as a `Duration` are converted to samples using the sample
+/// rate of the inner sound. When the inner sound returns `MetadataChanged`
+/// the delays are applied again from the start in the new format and any
+/// samples still delayed from the old format are dropped.
+pub struct ChannelDelay<S: Sound> {
+    inner: S,
+    delays: Delays,
+    /// The samples of each channel waiting to be returned.
+    buffers: Vec<VecDeque<i16>>,
+    /// The frames of silence still to be pushed through after the inner sound
+    /// finished. `None` until it finishes.
+    tail_frames_remaining: Option<usize>,
+    next_channel_idx: usize,
+}
+
+enum Delays {
+    Samples(Vec<usize>),
+    Durations(Vec<Duration>),
+}
+
+impl<S> ChannelDelay<S>
+where
+    S: Sound,
+{
+    /// Delay each channel of `inner` by the number of samples at its index in
+    /// `delays`.
+    pub fn new(inner: S, delays: Vec<usize>) -> Self {
+        Self::with_delays(inner, Delays::Samples(delays))
+    }
+
+    /// Delay each channel of `inner` by the duration at its index in
+    /// `delays`, rounded down to a whole sample.
+    pub fn with_durations(inner: S, delays: Vec<Duration>) -> Self {
+        Self::with_delays(inner, Delays::Durations(delays))
+    }
+
+    fn with_delays(inner: S, delays: Delays) -> Self {
+        let mut channel_delay = ChannelDelay {
+            inner,
+            delays,
+            buffers: Vec::new(),
+            tail_frames_remaining: None,
+            next_channel_idx: 0,
+        };
+        channel_delay.reset();
+        channel_delay
+    }
+
+    /// The delay in samples of each channel in the current format.
+    pub fn delay_samples(&self) -> Vec<usize> {
+        (0..self.inner.channel_count() as usize)
+            .map(|channel| self.delay_of(channel))
+            .collect()
+    }
+
+    fn delay_of(&self, channel: usize) -> usize {
+        match &self.delays {
+            Delays::Samples(samples) => samples.get(channel).copied().unwrap_or(0),
+            Delays::Durations(durations) => durations
+                .get(channel)
+                .map(|duration| {
+                    utils::duration_to_num_samples(*duration, 1, self.inner.sample_rate()) as usize
+                })
+                .unwrap_or(0),
+        }
+    }
+
+    /// Fill each channel's buffer with its delay of silence.
+    fn reset(&mut self) {
+        self.buffers = (0..self.inner.channel_count() as usize)
+            .map(|channel| VecDeque::from(vec![0; self.delay_of(channel)]))
+            .collect();
+        self.tail_frames_remaining = None;
+        self.next_channel_idx = 0;
+    }
+
+    /// Push `sample` into the current channel's buffer and return the oldest
+    /// sample of that channel.
+    fn delay(&mut self, sample: i16) -> i16 {
+        let Some(buffer) = self.buffers.get_mut(self.next_channel_idx) else {
+            return sample;
+        };
+        buffer.push_back(sample);
+        let delayed = buffer.pop_front().unwrap_or(sample);
+        self.next_channel_idx += 1;
+        if self.next_channel_idx >= self.buffers.len() {
+            self.next_channel_idx = 0;
+        }
+        delayed
+    }
+}
+
+impl<S> Sound for ChannelDelay<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(remaining) = self.tail_frames_remaining {
+            if remaining == 0 {
+                return Ok(NextSample::Finished);
+            }
+            let sample = self.delay(0);
+            if self.next_channel_idx == 0 {
+                self.tail_frames_remaining = Some(remaining - 1);
+            }
+            return Ok(NextSample::Sample(sample));
+        }
+
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => Ok(NextSample::Sample(self.delay(s))),
+            NextSample::MetadataChanged => {
+                self.reset();
+                Ok(next)
+            }
+            NextSample::Paused => Ok(next),
+            NextSample::Finished => {
+                let longest = self.buffers.iter().map(VecDeque::len).max().unwrap_or(0);
+                if longest == 0 {
+                    return Ok(next);
+                }
+                self.tail_frames_remaining = Some(longest);
+                self.next_channel_idx = 0;
+                self.next_sample()
+            }
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for ChannelDelay<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/channel_delay.rs"]
+mod tests;
@@ -0,0 +1,213 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// The default length of the fade applied when a channel is muted or
+/// unmuted.
+const DEFAULT_FADE: Duration = Duration::from_millis(5);
+
+/// Mute individual channels of the inner sound.
+///
+/// Which channels are muted is controlled at runtime with the
+/// [ChannelMuteHandle] returned when the wrapper is created. Changes take
+/// effect at the start of the next frame and are faded over a short duration
+/// (5ms by default) so they do not click. Only the first 64 channels can be
+/// muted.
+///
+/// When the inner sound changes its channel count or sample rate any fade in
+/// progress is completed immediately.
+pub struct ChannelMute<S: Sound> {
+    inner: S,
+    mask: Arc<AtomicU64>,
+    fade_duration: Duration,
+    fade_frames: u64,
+    /// The number of frames each channel is into its fade in. 0 when fully
+    /// muted and `fade_frames` when fully unmuted.
+    levels: Vec<u64>,
+    next_channel_idx: usize,
+}
+
+/// Controls which channels a [ChannelMute] mutes.
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct ChannelMuteHandle {
+    mask: Arc<AtomicU64>,
+}
+
+impl<S> ChannelMute<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` with no channels muted.
+    pub fn new(inner: S) -> (Self, ChannelMuteHandle) {
+        let mask = Arc::new(AtomicU64::new(0));
+        let mut mute = ChannelMute {
+            inner,
+            mask: mask.clone(),
+            fade_duration: DEFAULT_FADE,
+            fade_frames: 0,
+            levels: Vec::new(),
+            next_channel_idx: 0,
+        };
+        mute.reset_levels();
+        (mute, ChannelMuteHandle { mask })
+    }
+
+    /// Return how long muting and unmuting a channel fades for.
+    pub fn fade_duration(&self) -> Duration {
+        self.fade_duration
+    }
+
+    /// Set how long muting and unmuting a channel fades for. A zero duration
+    /// mutes and unmutes instantly.
+    ///
+    /// Any fade in progress is completed immediately.
+    pub fn set_fade_duration(&mut self, fade_duration: Duration) {
+        self.fade_duration = fade_duration;
+        self.reset_levels();
+    }
+
+    /// Recompute the fade length and move every channel straight to its
+    /// target level.
+    fn reset_levels(&mut self) {
+        // A single frame fade mutes and unmutes instantly
+        self.fade_frames =
+            utils::duration_to_num_samples(self.fade_duration, 1, self.inner.sample_rate()).max(1);
+        let mask = self.mask.load(Ordering::Relaxed);
+        let fade_frames = self.fade_frames;
+        self.levels = (0..self.inner.channel_count() as usize)
+            .map(|ch| if is_muted(mask, ch) { 0 } else { fade_frames })
+            .collect();
+    }
+
+    /// Move each channel one frame closer to its target level.
+    fn step_levels(&mut self) {
+        let mask = self.mask.load(Ordering::Relaxed);
+        for (ch, level) in self.levels.iter_mut().enumerate() {
+            if is_muted(mask, ch) {
+                *level = level.saturating_sub(1);
+            } else if *level < self.fade_frames {
+                *level += 1;
+            }
+        }
+    }
+}
+
+fn is_muted(mask: u64, channel: usize) -> bool {
+    channel < 64 && mask & (1 << channel) != 0
+}
+
+impl ChannelMuteHandle {
+    /// Mute or unmute `channel`.
+    ///
+    /// Panics if `channel` is 64 or more.
+    pub fn set_muted(&self, channel: u16, muted: bool) {
+        assert!(channel < 64, "only the first 64 channels can be muted");
+        if muted {
+            self.mask.fetch_or(1 << channel, Ordering::Relaxed);
+        } else {
+            self.mask.fetch_and(!(1 << channel), Ordering::Relaxed);
+        }
+    }
+
+    /// Return if `channel` is muted.
+    pub fn is_muted(&self, channel: u16) -> bool {
+        is_muted(self.mask(), channel as usize)
+    }
+
+    /// Set which channels are muted. Bit `n` mutes channel `n`.
+    pub fn set_mask(&self, mask: u64) {
+        self.mask.store(mask, Ordering::Relaxed);
+    }
+
+    /// Return which channels are muted. Bit `n` is set if channel `n` is
+    /// muted.
+    pub fn mask(&self) -> u64 {
+        self.mask.load(Ordering::Relaxed)
+    }
+
+    /// Mute every channel except `channel`.
+    ///
+    /// Panics if `channel` is 64 or more.
+    pub fn solo(&self, channel: u16) {
+        assert!(channel < 64, "only the first 64 channels can be soloed");
+        self.set_mask(!(1 << channel));
+    }
+
+    /// Unmute all channels.
+    pub fn unmute_all(&self) {
+        self.set_mask(0);
+    }
+}
+
+impl<S> Sound for ChannelMute<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                if self.next_channel_idx == 0 {
+                    self.step_levels();
+                }
+                let ch = self.next_channel_idx;
+                self.next_channel_idx = (ch + 1) % self.levels.len().max(1);
+                let level = self.levels.get(ch).copied().unwrap_or(self.fade_frames);
+                if level >= self.fade_frames {
+                    Ok(next)
+                } else {
+                    let gain = level as f32 / self.fade_frames as f32;
+                    Ok(NextSample::Sample((s as f32 * gain) as i16))
+                }
+            }
+            NextSample::MetadataChanged => {
+                self.next_channel_idx = 0;
+                self.reset_levels();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for ChannelMute<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/channel_mute.rs"]
+mod tests;
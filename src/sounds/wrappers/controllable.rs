@@ -85,6 +85,10 @@ where
         }
         self.inner.on_start_of_batch();
     }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
 }
 
 impl<S> Wrapper for Controllable<S>
@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+use crate::{sounds::MemorySound, NextSample, Sound};
+
+use super::{SampleRateConverter, Wrapper};
+
+/// Convolve a Sound with an impulse response (IR) to apply reverb or any other
+/// linear filter.
+///
+/// Convolution is done per channel using overlap-add with FFTs. Blocks of the
+/// inner sound as long as the IR are read ahead of time so no latency is
+/// added. After the inner sound finishes, the tail of the reverb is played
+/// before `Finished` is returned.
+///
+/// If the IR has a different channel count than the inner sound: a mono IR is
+/// applied to every channel, a multi-channel IR on a mono sound is averaged
+/// to a single channel, and otherwise channel `n` uses IR channel
+/// `n % ir_channel_count`.
+///
+/// If the inner sound changes its channel count the reverb tail is dropped.
+/// The IR is not resampled if the inner sound's sample rate changes.
+pub struct ConvolutionReverb<S: Sound> {
+    inner: S,
+    /// The IR with one Vec per IR channel.
+    ir: Vec<Vec<f32>>,
+    mix: f32,
+    block_len: usize,
+    fft: Arc<dyn Fft<f32>>,
+    ifft: Arc<dyn Fft<f32>>,
+    /// The FFT of the IR for each channel of the inner sound.
+    ir_spectra: Vec<Vec<Complex<f32>>>,
+    /// Convolution output not yet returned for each channel. Index 0 is the
+    /// next frame.
+    accumulators: Vec<Vec<f32>>,
+    channel_count: u16,
+    /// Interleaved samples ready to be returned.
+    output: VecDeque<i16>,
+    /// What to return after `output` has been drained.
+    pending: Option<NextSample>,
+    scratch: Vec<Complex<f32>>,
+    input_frame: Vec<i16>,
+}
+
+impl<S> ConvolutionReverb<S>
+where
+    S: Sound,
+{
+    /// Convolve `inner` with an IR of interleaved samples in `-1.0..=1.0`.
+    ///
+    /// The IR should have the same sample rate as `inner`. Panics if
+    /// `ir_channel_count` is 0 or `ir` does not contain at least one frame.
+    pub fn new(inner: S, ir: &[f32], ir_channel_count: u16) -> ConvolutionReverb<S> {
+        assert!(ir_channel_count > 0, "ir_channel_count must be at least 1");
+        let ir_channel_count = ir_channel_count as usize;
+        assert!(
+            ir.len() >= ir_channel_count,
+            "ir must contain at least one frame"
+        );
+        let ir: Vec<Vec<f32>> = (0..ir_channel_count)
+            .map(|channel| {
+                ir.iter()
+                    .skip(channel)
+                    .step_by(ir_channel_count)
+                    .copied()
+                    .collect()
+            })
+            .collect();
+        let block_len = ir[0].len().next_power_of_two().max(64);
+        let mut planner = FftPlanner::new();
+        let mut reverb = ConvolutionReverb {
+            fft: planner.plan_fft_forward(block_len * 2),
+            ifft: planner.plan_fft_inverse(block_len * 2),
+            inner,
+            ir,
+            mix: 1.0,
+            block_len,
+            ir_spectra: Vec::new(),
+            accumulators: Vec::new(),
+            channel_count: 0,
+            output: VecDeque::new(),
+            pending: None,
+            scratch: Vec::new(),
+            input_frame: Vec::new(),
+        };
+        reverb.init();
+        reverb
+    }
+
+    /// Convolve `inner` with the IR read entirely from `ir`.
+    ///
+    /// `ir` is converted to the sample rate of `inner` before being read.
+    pub fn from_ir_sound(inner: S, ir: impl Sound) -> Result<ConvolutionReverb<S>, crate::Error> {
+        let ir = SampleRateConverter::new(ir, inner.sample_rate());
+        let ir_channel_count = ir.channel_count();
+        let mut ir = MemorySound::from_sound(ir)?;
+        let mut samples = Vec::new();
+        while let NextSample::Sample(s) = ir.next_sample()? {
+            samples.push(s as f32 / i16::MAX as f32);
+        }
+        if samples.len() < ir_channel_count as usize {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "impulse response contains no samples",
+            )
+            .into());
+        }
+        Ok(ConvolutionReverb::new(inner, &samples, ir_channel_count))
+    }
+
+    /// The ratio of the reverberated (wet) signal in the output from 0.0 (only
+    /// the dry inner sound) to 1.0 (only the wet signal). Defaults to 1.0.
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Set the ratio of the wet signal. See [mix][ConvolutionReverb::mix].
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    fn fft_len(&self) -> usize {
+        self.block_len * 2
+    }
+
+    /// Compute the IR spectrum for each channel of the inner sound.
+    fn init(&mut self) {
+        self.channel_count = self.inner.channel_count();
+        let channel_count = self.channel_count as usize;
+        let ir_channel_count = self.ir.len();
+        let ir_len = self.ir[0].len();
+        let fft_len = self.fft_len();
+        self.ir_spectra = (0..channel_count)
+            .map(|channel| {
+                let mut spectrum = vec![Complex::default(); fft_len];
+                if ir_channel_count == 1 || ir_channel_count == channel_count {
+                    let ir = &self.ir[if ir_channel_count == 1 { 0 } else { channel }];
+                    for (dest, s) in spectrum.iter_mut().zip(ir) {
+                        dest.re = *s;
+                    }
+                } else if channel_count == 1 {
+                    for (i, dest) in spectrum.iter_mut().take(ir_len).enumerate() {
+                        dest.re =
+                            self.ir.iter().map(|ir| ir[i]).sum::<f32>() / ir_channel_count as f32;
+                    }
+                } else {
+                    let ir = &self.ir[channel % ir_channel_count];
+                    for (dest, s) in spectrum.iter_mut().zip(ir) {
+                        dest.re = *s;
+                    }
+                }
+                self.fft.process(&mut spectrum);
+                spectrum
+            })
+            .collect();
+        self.accumulators = vec![vec![0.0; fft_len]; channel_count];
+    }
+
+    /// Read and convolve the next block of the inner sound into `output`.
+    fn process_block(&mut self) -> Result<(), crate::Error> {
+        let channel_count = self.channel_count as usize;
+        let mut dry = Vec::with_capacity(self.block_len * channel_count);
+        let mut next_special = None;
+        while dry.len() < self.block_len * channel_count {
+            self.input_frame.clear();
+            match self.inner.append_next_frame_to(&mut self.input_frame) {
+                Ok(()) => dry.extend_from_slice(&self.input_frame),
+                Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                Err(Ok(special)) => {
+                    next_special = Some(special);
+                    break;
+                }
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        let num_frames = dry.len() / channel_count;
+
+        let wet_frames = match next_special {
+            // Output the whole tail with this block
+            Some(NextSample::Finished) => num_frames + self.ir[0].len() - 1,
+            _ => num_frames,
+        };
+
+        let fft_len = self.fft_len();
+        let mut wet = vec![0.0; wet_frames * channel_count];
+        for channel in 0..channel_count {
+            let accumulator = &mut self.accumulators[channel];
+            if num_frames > 0 {
+                self.scratch.clear();
+                self.scratch.extend(
+                    dry.iter()
+                        .skip(channel)
+                        .step_by(channel_count)
+                        .map(|s| Complex::new(*s as f32, 0.0)),
+                );
+                self.scratch.resize(fft_len, Complex::default());
+                self.fft.process(&mut self.scratch);
+                for (s, h) in self.scratch.iter_mut().zip(&self.ir_spectra[channel]) {
+                    *s *= h;
+                }
+                self.ifft.process(&mut self.scratch);
+                for (acc, s) in accumulator.iter_mut().zip(&self.scratch) {
+                    *acc += s.re / fft_len as f32;
+                }
+            }
+            for (frame, value) in accumulator.iter().take(wet_frames).enumerate() {
+                wet[frame * channel_count + channel] = *value;
+            }
+            let consumed = wet_frames.min(fft_len);
+            accumulator.drain(..consumed);
+            accumulator.resize(fft_len, 0.0);
+        }
+
+        for (i, wet) in wet.iter().enumerate() {
+            let dry = dry.get(i).copied().unwrap_or(0) as f32;
+            let sample = dry * (1.0 - self.mix) + wet * self.mix;
+            self.output
+                .push_back(sample.clamp(i16::MIN as f32, i16::MAX as f32).round() as i16);
+        }
+
+        self.pending = next_special;
+        Ok(())
+    }
+}
+
+impl<S> Sound for ConvolutionReverb<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        // The inner sound may have already changed its channel count while
+        // reading ahead so this is only updated when MetadataChanged is
+        // returned.
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output.pop_front() {
+            return Ok(NextSample::Sample(sample));
+        }
+        if let Some(pending) = self.pending.take() {
+            match pending {
+                NextSample::MetadataChanged => {
+                    if self.inner.channel_count() != self.channel_count {
+                        self.init();
+                    }
+                }
+                // Keep returning Finished
+                NextSample::Finished => self.pending = Some(pending),
+                NextSample::Sample(_) | NextSample::Paused => (),
+            }
+            return Ok(pending);
+        }
+        self.process_block()?;
+        self.next_sample()
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::High)
+    }
+}
+
+impl<S: Sound> Wrapper for ConvolutionReverb<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/convolution_reverb.rs"]
+mod tests;
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use crate::sounds::Metronome;
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// Play a count-in of metronome clicks before the inner sound, e.g. so a
+/// musician recording along to a backing track knows when to start.
+///
+/// The count-in is one bar of [Metronome] clicks with `beats` beats at `bpm`
+/// with the first beat accented. It is generated in the channel count and
+/// sample rate the inner sound has when the count-in is created. The inner
+/// sound is not pulled until the count-in has finished, after which it plays
+/// unchanged.
+pub struct CountIn<S: Sound> {
+    inner: S,
+    count_in: Metronome,
+    counting: bool,
+    count_in_duration: Duration,
+}
+
+impl<S> CountIn<S>
+where
+    S: Sound,
+{
+    /// Play `beats` clicks at `bpm` beats per minute before `inner`. No
+    /// clicks are played if `beats` is 0.
+    ///
+    /// Panics if `bpm` is not positive.
+    pub fn new(inner: S, bpm: f64, beats: u32) -> Self {
+        let sample_rate = inner.sample_rate();
+        let mut count_in = Metronome::with_format(bpm, inner.channel_count(), sample_rate);
+        count_in.set_beats_per_bar(beats.max(1));
+        count_in.set_bars(Some(1));
+        let frames = (beats as f64 * 60.0 * sample_rate as f64 / bpm).round() as u64;
+        CountIn {
+            inner,
+            count_in,
+            counting: beats > 0,
+            count_in_duration: utils::num_samples_to_duration(frames, 1, sample_rate),
+        }
+    }
+
+    /// How much longer the count-in makes the sound.
+    pub fn count_in_duration(&self) -> Duration {
+        self.count_in_duration
+    }
+
+    /// Whether the count-in is still playing.
+    pub fn is_counting(&self) -> bool {
+        self.counting
+    }
+}
+
+impl<S> Sound for CountIn<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        if self.counting {
+            self.count_in.channel_count()
+        } else {
+            self.inner.channel_count()
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        if self.counting {
+            self.count_in.sample_rate()
+        } else {
+            self.inner.sample_rate()
+        }
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.counting {
+            match self.count_in.next_sample()? {
+                NextSample::Finished => {
+                    self.counting = false;
+                    if self.inner.channel_count() != self.count_in.channel_count()
+                        || self.inner.sample_rate() != self.count_in.sample_rate()
+                    {
+                        return Ok(NextSample::MetadataChanged);
+                    }
+                }
+                next => return Ok(next),
+            }
+        }
+        self.inner.next_sample()
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for CountIn<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/count_in.rs"]
+mod tests;
@@ -0,0 +1,284 @@
+use std::collections::VecDeque;
+
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// The shortest run of flat samples treated as clipping.
+const MIN_RUN: u64 = 3;
+/// The longest run of flat samples that is repaired. Longer runs are left
+/// alone since they are more likely to be intentional (e.g. a square wave).
+const MAX_RUN: u64 = 64;
+/// How far from the first sample of a run the other samples may be, as a
+/// fraction of full scale, for the run to count as flat.
+const FLAT_TOLERANCE: f32 = 0.002;
+/// How far the reconstruction must rise above a run, as a fraction of full
+/// scale, for the run to be repaired. A natural peak that happens to be flat
+/// is reconstructed at about its own level and is left alone.
+const MIN_OVERSHOOT: f64 = 0.01;
+/// Frames read ahead so a whole run and the samples around it are available.
+const LOOKAHEAD: u64 = MAX_RUN + 4;
+
+/// Detect clipping (flat tops) in the inner sound and replace it with a
+/// smooth reconstruction.
+///
+/// A run of 3 to 64 samples of a channel with the same sign, all at least the
+/// threshold (95% of full scale by default) and flat to within 0.2% of full
+/// scale is treated as clipped. It is replaced with a cubic through the two
+/// samples before and the two after the run if the cubic rises at least 1%
+/// of full scale above the run (i.e. the waveform was cut off). Where the reconstructed peak
+/// would exceed full scale, the part above the threshold is scaled down to
+/// fit so the top is rounded instead of flat. Repaired samples stay between
+/// the threshold and full scale and all other samples are returned
+/// unchanged, so loud content that is not flat is not altered.
+///
+/// Samples are read 68 frames ahead of those returned. This does not delay the
+/// sound but more samples are read from the inner sound on the first call and
+/// after a `MetadataChanged`.
+pub struct DeClip<S: Sound> {
+    inner: S,
+    threshold: f32,
+    /// Interleaved samples read but not returned yet.
+    buffer: VecDeque<i16>,
+    /// The frame index of the first frame in `buffer`.
+    buffer_start_frame: u64,
+    /// The number of samples of the first frame already returned.
+    returned_channels: usize,
+    /// The frame index of the next sample read from the inner sound.
+    next_read_frame: u64,
+    next_read_channel: usize,
+    channels: Vec<RunState>,
+    /// Returned after `buffer` has been drained.
+    pending: Option<NextSample>,
+}
+
+#[derive(Default, Clone)]
+struct RunState {
+    /// The first frame and sample of the current run.
+    start: Option<(u64, i16)>,
+    /// A run waiting for its second following sample, as start and end
+    /// (exclusive) frames.
+    to_repair: Option<(u64, u64)>,
+}
+
+impl<S> DeClip<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` with a threshold of 0.95.
+    pub fn new(inner: S) -> Self {
+        let mut de_clip = DeClip {
+            inner,
+            threshold: 0.95,
+            buffer: VecDeque::new(),
+            buffer_start_frame: 0,
+            returned_channels: 0,
+            next_read_frame: 0,
+            next_read_channel: 0,
+            channels: Vec::new(),
+            pending: None,
+        };
+        de_clip.reset();
+        de_clip
+    }
+
+    /// The fraction of full scale samples must reach to be treated as
+    /// clipped.
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Set the threshold. Clamped to `0.5..=1.0`.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold.clamp(0.5, 1.0);
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.buffer_start_frame = 0;
+        self.returned_channels = 0;
+        self.next_read_frame = 0;
+        self.next_read_channel = 0;
+        self.channels = vec![RunState::default(); self.inner.channel_count() as usize];
+    }
+
+    fn threshold_value(&self) -> i32 {
+        (self.threshold * i16::MAX as f32) as i32
+    }
+
+    fn sample_index(&self, frame: u64, channel: usize) -> usize {
+        (frame - self.buffer_start_frame) as usize * self.channels.len() + channel
+            - self.returned_channels
+    }
+
+    /// Track runs with the sample just added to the buffer.
+    fn process(&mut self, sample: i16) {
+        let frame = self.next_read_frame;
+        let channel = self.next_read_channel;
+        let threshold = self.threshold_value();
+        let tolerance = (FLAT_TOLERANCE * i16::MAX as f32) as i32;
+
+        if let Some((start, end)) = self.channels[channel].to_repair {
+            if frame == end + 1 {
+                self.repair(channel, start, end);
+                self.channels[channel].to_repair = None;
+            }
+        }
+
+        let value = sample as i32;
+        let state = &mut self.channels[channel];
+        let continues_run = state.start.is_some_and(|(_, first)| {
+            let first = first as i32;
+            first.signum() == value.signum() && (value - first).abs() <= tolerance
+        });
+        if continues_run {
+            return;
+        }
+        if let Some((start, _)) = state.start.take() {
+            let len = frame - start;
+            // The two samples before the run must still be available
+            if (MIN_RUN..=MAX_RUN).contains(&len)
+                && start >= self.buffer_start_frame + 2
+                && state.to_repair.is_none()
+            {
+                state.to_repair = Some((start, frame));
+            }
+        }
+        if value.abs() >= threshold {
+            state.start = Some((frame, sample));
+        }
+    }
+
+    /// Replace the run of `channel` from `start` to `end` (exclusive) with a
+    /// cubic through the two samples on either side.
+    fn repair(&mut self, channel: usize, start: u64, end: u64) {
+        let get = |de_clip: &Self, frame: u64| -> f64 {
+            de_clip.buffer[de_clip.sample_index(frame, channel)] as f64
+        };
+        let len = (end - start) as f64;
+        let xs = [-2.0, -1.0, len, len + 1.0];
+        let ys = [
+            get(self, start - 2),
+            get(self, start - 1),
+            get(self, end),
+            get(self, end + 1),
+        ];
+        let sign = get(self, start).signum();
+        let cubic = |x: f64| -> f64 {
+            (0..4)
+                .map(|i| {
+                    let basis: f64 = (0..4)
+                        .filter(|j| *j != i)
+                        .map(|j| (x - xs[j]) / (xs[i] - xs[j]))
+                        .product();
+                    ys[i] * basis
+                })
+                .sum()
+        };
+        // Work with positive values so both polarities are handled the same
+        let values: Vec<f64> = (0..end - start).map(|x| sign * cubic(x as f64)).collect();
+        let full_scale = i16::MAX as f64;
+        let knee = self.threshold_value() as f64;
+        let peak = values.iter().copied().fold(f64::MIN, f64::max);
+        let run_peak = (start..end)
+            .map(|frame| sign * get(self, frame))
+            .fold(f64::MIN, f64::max);
+        if peak - run_peak < MIN_OVERSHOOT * full_scale {
+            return;
+        }
+        for (frame, value) in (start..end).zip(values) {
+            let value = if peak > full_scale && value > knee {
+                knee + (value - knee) * (full_scale - knee) / (peak - knee)
+            } else {
+                value
+            };
+            let idx = self.sample_index(frame, channel);
+            self.buffer[idx] = (sign * value.clamp(knee, full_scale)) as i16;
+        }
+    }
+
+    fn pop(&mut self) -> Option<i16> {
+        let sample = self.buffer.pop_front()?;
+        self.returned_channels += 1;
+        if self.returned_channels >= self.channels.len() {
+            self.returned_channels = 0;
+            self.buffer_start_frame += 1;
+        }
+        Some(sample)
+    }
+}
+
+impl<S> Sound for DeClip<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        loop {
+            if self.pending.is_some() {
+                if let Some(sample) = self.pop() {
+                    return Ok(NextSample::Sample(sample));
+                }
+                let pending = self.pending.take().unwrap();
+                if pending == NextSample::MetadataChanged {
+                    self.reset();
+                }
+                return Ok(pending);
+            }
+            match self.inner.next_sample()? {
+                NextSample::Sample(s) => {
+                    self.buffer.push_back(s);
+                    self.process(s);
+                    self.next_read_channel += 1;
+                    if self.next_read_channel >= self.channels.len() {
+                        self.next_read_channel = 0;
+                        self.next_read_frame += 1;
+                    }
+                    let lookahead_samples = LOOKAHEAD as usize * self.channels.len();
+                    if self.buffer.len() > lookahead_samples {
+                        return Ok(NextSample::Sample(self.pop().unwrap()));
+                    }
+                }
+                NextSample::Paused => return Ok(NextSample::Paused),
+                next @ (NextSample::MetadataChanged | NextSample::Finished) => {
+                    self.pending = Some(next);
+                }
+            }
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for DeClip<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/de_clip.rs"]
+mod tests;
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use crate::sounds::biquad::{Biquad, BiquadCoefficients};
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// A band of a [DynamicEq] that is cut only while it is too loud.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DynamicEqBand {
+    /// The center frequency in Hz.
+    pub frequency: f32,
+    /// How narrow the band is. Higher values affect fewer frequencies.
+    pub q: f32,
+    /// The level of the band, as a fraction of full scale, above which it is
+    /// cut.
+    pub threshold: f32,
+    /// How much the band is compressed above its threshold, e.g. 4.0 means
+    /// every 4 dB above the threshold only raises the band 1 dB.
+    pub ratio: f32,
+}
+
+/// Cut frequency bands of the inner sound only while they are louder than
+/// their threshold, e.g. to tame harsh sibilance without dulling the rest of
+/// the time.
+///
+/// Each band is isolated with a band pass filter whose peak level is
+/// followed with a 10ms attack and 100ms release by default. While the
+/// followed level is above the threshold the band is compressed by its
+/// ratio by subtracting part of the band passed signal, which cuts the band
+/// like a peaking filter. Below the threshold the band is passed through
+/// unchanged. Bands are applied in order.
+///
+/// All filter and detector state is reset when the inner sound returns
+/// `MetadataChanged`. The output is clipped to the range of i16.
+pub struct DynamicEq<S: Sound> {
+    inner: S,
+    bands: Vec<DynamicEqBand>,
+    attack: Duration,
+    release: Duration,
+    attack_coefficient: f32,
+    release_coefficient: f32,
+    channels: Vec<Vec<BandState>>,
+    next_channel_idx: usize,
+}
+
+#[derive(Clone)]
+struct BandState {
+    band_pass: Biquad,
+    envelope: f32,
+}
+
+impl<S> DynamicEq<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` cutting each of `bands` while it is too loud.
+    ///
+    /// Frequencies are limited to below the Nyquist frequency of `inner`.
+    pub fn new(inner: S, bands: Vec<DynamicEqBand>) -> Self {
+        let mut eq = DynamicEq {
+            inner,
+            bands,
+            attack: Duration::from_millis(10),
+            release: Duration::from_millis(100),
+            attack_coefficient: 0.0,
+            release_coefficient: 0.0,
+            channels: Vec::new(),
+            next_channel_idx: 0,
+        };
+        eq.reset();
+        eq
+    }
+
+    /// The bands being cut.
+    pub fn bands(&self) -> &[DynamicEqBand] {
+        &self.bands
+    }
+
+    /// Set how quickly the detectors react to rising and falling levels.
+    pub fn set_attack_release(&mut self, attack: Duration, release: Duration) {
+        self.attack = attack;
+        self.release = release;
+        self.update_coefficients();
+    }
+
+    fn update_coefficients(&mut self) {
+        let sample_rate = self.inner.sample_rate() as f32;
+        let coefficient = |duration: Duration| {
+            let samples = duration.as_secs_f32() * sample_rate;
+            if samples > 0.0 {
+                (-1.0 / samples).exp()
+            } else {
+                0.0
+            }
+        };
+        self.attack_coefficient = coefficient(self.attack);
+        self.release_coefficient = coefficient(self.release);
+    }
+
+    /// Design the filters for the sample rate of the inner sound and clear
+    /// all state.
+    fn reset(&mut self) {
+        self.update_coefficients();
+        let sample_rate = self.inner.sample_rate() as f32;
+        let state: Vec<BandState> = self
+            .bands
+            .iter()
+            .map(|band| BandState {
+                band_pass: Biquad::new(BiquadCoefficients::band_pass(
+                    band.frequency.clamp(1.0, sample_rate / 2.0 * 0.99),
+                    band.q.max(f32::EPSILON),
+                    sample_rate,
+                )),
+                envelope: 0.0,
+            })
+            .collect();
+        self.channels = vec![state; self.inner.channel_count() as usize];
+        self.next_channel_idx = 0;
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let Some(state) = self.channels.get_mut(self.next_channel_idx) else {
+            return x;
+        };
+        let mut y = x;
+        for (band, settings) in state.iter_mut().zip(&self.bands) {
+            let band_passed = band.band_pass.process(y);
+            let level = band_passed.abs();
+            let coefficient = if level > band.envelope {
+                self.attack_coefficient
+            } else {
+                self.release_coefficient
+            };
+            band.envelope = level + (band.envelope - level) * coefficient;
+            if band.envelope > settings.threshold {
+                let compressed = settings.threshold
+                    * (band.envelope / settings.threshold).powf(1.0 / settings.ratio);
+                let gain = compressed / band.envelope;
+                y -= (1.0 - gain) * band_passed;
+            }
+        }
+        y
+    }
+}
+
+impl<S> Sound for DynamicEq<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                let y = self.process(s as f32 / i16::MAX as f32) * i16::MAX as f32;
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() as usize {
+                    self.next_channel_idx = 0;
+                }
+                Ok(NextSample::Sample(
+                    y.clamp(i16::MIN as f32, i16::MAX as f32) as i16,
+                ))
+            }
+            NextSample::MetadataChanged => {
+                self.reset();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S: Sound> Wrapper for DynamicEq<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/dynamic_eq.rs"]
+mod tests;
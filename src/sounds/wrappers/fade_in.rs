@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// The shape of the gain change applied over the length of a fade.
+///
+/// All curves start at a gain of 0.0 and end at a gain of 1.0 when fading in.
+/// A fade out uses the same curve with the progress reversed.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum FadeCurve {
+    /// Gain changes linearly. When crossfading, the combined loudness dips in
+    /// the middle of the fade.
+    #[default]
+    Linear,
+    /// Quarter sine wave. The summed power of a fade in and a fade out stays
+    /// constant so crossfades of uncorrelated sounds do not dip in loudness.
+    EqualPower,
+    /// Raised cosine (half of a Hann window). Starts and ends gently.
+    Cosine,
+}
+
+impl FadeCurve {
+    /// Return the gain multiplier for a fade in that is `progress` of the way
+    /// complete. `progress` is clamped to the range 0.0 to 1.0.
+    pub fn gain(&self, progress: f32) -> f32 {
+        let progress = progress.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => progress,
+            FadeCurve::EqualPower => (progress * std::f32::consts::FRAC_PI_2).sin(),
+            FadeCurve::Cosine => 0.5 - 0.5 * (progress * std::f32::consts::PI).cos(),
+        }
+    }
+}
+
+/// Gradually increase the volume of the inner sound from silence over a
+/// duration.
+///
+/// The gain is constant for all channels of a frame. Any time the inner sound
+/// is paused does not count against the duration.
+pub struct FadeIn<S: Sound> {
+    inner: S,
+    curve: FadeCurve,
+    frames_total: u64,
+    frames_done: u64,
+    next_channel_idx: u16,
+    current_channel_count: u16,
+    current_sample_rate: u32,
+}
+
+impl<S> FadeIn<S>
+where
+    S: Sound,
+{
+    /// Fade in `inner` over `duration` using `curve`.
+    pub fn new(inner: S, duration: Duration, curve: FadeCurve) -> Self {
+        let current_channel_count = inner.channel_count();
+        let current_sample_rate = inner.sample_rate();
+        FadeIn {
+            inner,
+            curve,
+            frames_total: utils::duration_to_num_samples(duration, 1, current_sample_rate),
+            frames_done: 0,
+            next_channel_idx: 0,
+            current_channel_count,
+            current_sample_rate,
+        }
+    }
+
+    /// Return the curve used for the fade.
+    pub fn curve(&self) -> FadeCurve {
+        self.curve
+    }
+
+    fn current_gain(&self) -> f32 {
+        if self.frames_done >= self.frames_total {
+            return 1.0;
+        }
+        self.curve
+            .gain(self.frames_done as f32 / self.frames_total as f32)
+    }
+}
+
+impl<S> Sound for FadeIn<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                if self.frames_done >= self.frames_total {
+                    return Ok(next);
+                }
+                let adjusted = (s as f32 * self.current_gain()) as i16;
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.current_channel_count {
+                    self.next_channel_idx = 0;
+                    self.frames_done += 1;
+                }
+                Ok(NextSample::Sample(adjusted))
+            }
+            NextSample::MetadataChanged => {
+                let new_sample_rate = self.inner.sample_rate();
+                if new_sample_rate != self.current_sample_rate {
+                    self.frames_total = utils::convert_num_samples(
+                        self.frames_total,
+                        1,
+                        self.current_sample_rate,
+                        1,
+                        new_sample_rate,
+                    );
+                    self.frames_done = utils::convert_num_samples(
+                        self.frames_done,
+                        1,
+                        self.current_sample_rate,
+                        1,
+                        new_sample_rate,
+                    );
+                }
+                self.current_channel_count = self.inner.channel_count();
+                self.current_sample_rate = new_sample_rate;
+                self.next_channel_idx = 0;
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for FadeIn<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/fade_in.rs"]
+mod tests;
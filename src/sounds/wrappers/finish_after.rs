@@ -100,6 +100,10 @@ where
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
 }
 
 pub fn num_samples(duration: Duration, num_channels: u16, num_samples: u32) -> u64 {
@@ -0,0 +1,138 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Count the frames returned by the inner sound so another thread (e.g. one
+/// syncing video) knows exactly how much audio has been delivered.
+///
+/// A frame is counted once all of its channels have been returned. The count
+/// is read with the [FrameCounterHandle] returned when the wrapper is created.
+/// Frames are counted at whatever sample rate the inner sound had when they
+/// were returned.
+///
+/// When seeking through this wrapper the count is set to the frame position
+/// the inner sound seeked to so `frames_played / sample_rate` stays the
+/// playback position. [reset][FrameCounterHandle::reset] sets it back to 0.
+pub struct FrameCounter<S: Sound> {
+    inner: S,
+    frames_played: Arc<AtomicU64>,
+    next_channel_idx: u16,
+}
+
+/// Reads the count of a [FrameCounter].
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct FrameCounterHandle {
+    frames_played: Arc<AtomicU64>,
+}
+
+impl<S> FrameCounter<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` with a count of 0.
+    pub fn new(inner: S) -> (Self, FrameCounterHandle) {
+        let frames_played = Arc::new(AtomicU64::new(0));
+        let counter = FrameCounter {
+            inner,
+            frames_played: frames_played.clone(),
+            next_channel_idx: 0,
+        };
+        (counter, FrameCounterHandle { frames_played })
+    }
+
+    /// The number of complete frames returned.
+    pub fn frames_played(&self) -> u64 {
+        self.frames_played.load(Ordering::Relaxed)
+    }
+}
+
+impl FrameCounterHandle {
+    /// The number of complete frames returned.
+    pub fn frames_played(&self) -> u64 {
+        self.frames_played.load(Ordering::Relaxed)
+    }
+
+    /// Set the count back to 0. A frame partially returned is still counted
+    /// when it completes.
+    pub fn reset(&self) {
+        self.frames_played.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<S> Sound for FrameCounter<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(_) => {
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                    self.frames_played.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            NextSample::MetadataChanged => self.next_channel_idx = 0,
+            NextSample::Paused | NextSample::Finished => (),
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+
+    fn seek(&mut self, seek_to: Duration) -> Result<Duration, crate::Error> {
+        let position = self.inner.seek(seek_to)?;
+        self.next_channel_idx = 0;
+        // Round since the position is usually a whole frame that was truncated
+        // to nanoseconds
+        let frames =
+            (position.as_nanos() * self.inner.sample_rate() as u128 + 500_000_000) / 1_000_000_000;
+        self.frames_played.store(frames as u64, Ordering::Relaxed);
+        Ok(position)
+    }
+
+    fn seek_granularity(&self) -> Option<Duration> {
+        self.inner.seek_granularity()
+    }
+}
+
+impl<S: Sound> Wrapper for FrameCounter<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/frame_counter.rs"]
+mod tests;
@@ -0,0 +1,186 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// Pass audio through unchanged while keeping the most recently played
+/// samples, e.g. for an instant replay or a scope display.
+///
+/// The last `duration` of whole frames is kept and can be copied out at any
+/// time with the [HistoryHandle] returned when the tap is created. When the
+/// inner sound changes its channel count or sample rate the history is
+/// cleared so it only ever holds samples of one format.
+///
+/// The handle is never waited on: frames played while a snapshot is being
+/// taken are added to the history on the next frame after it finishes.
+pub struct HistoryTap<S: Sound> {
+    inner: S,
+    duration: Duration,
+    /// The most samples kept, always a whole number of frames.
+    capacity: usize,
+    /// The frame currently being played.
+    frame: Vec<i16>,
+    /// Complete frames that could not be added because the handle was locked.
+    pending: VecDeque<i16>,
+    shared: Arc<Mutex<History>>,
+}
+
+/// Reads the history kept by a [HistoryTap].
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct HistoryHandle {
+    shared: Arc<Mutex<History>>,
+}
+
+struct History {
+    samples: VecDeque<i16>,
+    channel_count: u16,
+    sample_rate: u32,
+}
+
+impl<S> HistoryTap<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` keeping the last `duration` of it.
+    pub fn new(inner: S, duration: Duration) -> (Self, HistoryHandle) {
+        let shared = Arc::new(Mutex::new(History {
+            samples: VecDeque::new(),
+            channel_count: 0,
+            sample_rate: 0,
+        }));
+        let mut tap = HistoryTap {
+            inner,
+            duration,
+            capacity: 0,
+            frame: Vec::new(),
+            pending: VecDeque::new(),
+            shared: shared.clone(),
+        };
+        tap.reset();
+        (tap, HistoryHandle { shared })
+    }
+
+    /// Clear the history and size it for the current format of the inner
+    /// sound.
+    fn reset(&mut self) {
+        let channel_count = self.inner.channel_count();
+        let sample_rate = self.inner.sample_rate();
+        let frames = utils::duration_to_num_samples(self.duration, 1, sample_rate);
+        self.capacity = frames as usize * channel_count as usize;
+        self.frame.clear();
+        self.pending.clear();
+        let mut history = self.shared.lock().unwrap();
+        *history = History {
+            samples: VecDeque::with_capacity(self.capacity),
+            channel_count,
+            sample_rate,
+        };
+    }
+
+    fn push_frame(&mut self) {
+        match self.shared.try_lock() {
+            Ok(mut history) => {
+                history
+                    .samples
+                    .extend(self.pending.drain(..).chain(self.frame.drain(..)));
+                keep_last(&mut history.samples, self.capacity);
+            }
+            Err(_) => {
+                self.pending.extend(self.frame.drain(..));
+                keep_last(&mut self.pending, self.capacity);
+            }
+        }
+    }
+}
+
+/// Drop the oldest samples so at most `capacity` remain.
+fn keep_last(samples: &mut VecDeque<i16>, capacity: usize) {
+    let excess = samples.len().saturating_sub(capacity);
+    samples.drain(..excess);
+}
+
+impl HistoryHandle {
+    /// A copy of the kept samples, interleaved with the oldest first.
+    ///
+    /// Only whole frames are included.
+    pub fn snapshot(&self) -> Vec<i16> {
+        self.shared
+            .lock()
+            .unwrap()
+            .samples
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// The channel count of the samples in the history.
+    pub fn channel_count(&self) -> u16 {
+        self.shared.lock().unwrap().channel_count
+    }
+
+    /// The sample rate of the samples in the history.
+    pub fn sample_rate(&self) -> u32 {
+        self.shared.lock().unwrap().sample_rate
+    }
+}
+
+impl<S> Sound for HistoryTap<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                self.frame.push(s);
+                if self.frame.len() >= self.inner.channel_count() as usize {
+                    self.push_frame();
+                }
+            }
+            NextSample::MetadataChanged => self.reset(),
+            NextSample::Paused | NextSample::Finished => (),
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for HistoryTap<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/history_tap.rs"]
+mod tests;
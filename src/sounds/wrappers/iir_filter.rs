@@ -0,0 +1,156 @@
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Apply an infinite impulse response filter given by its coefficients to
+/// each channel of the inner sound.
+///
+/// The filter is
+/// `a[0]*y[n] = b[0]*x[n] + b[1]*x[n-1] + ... - a[1]*y[n-1] - a[2]*y[n-2] - ...`
+/// implemented in Direct Form II transposed. Coefficients are normalized so
+/// `a[0]` is 1.0. Each channel has its own state which is reset when the
+/// inner sound returns `MetadataChanged`. The output is clipped to the range
+/// of i16.
+///
+/// The coefficients are for the sample rate of the inner sound so they need
+/// to be recalculated if it changes.
+pub struct IirFilter<S: Sound> {
+    inner: S,
+    /// Feed forward coefficients normalized by a[0] and padded to the same
+    /// length as `a`.
+    b: Vec<f64>,
+    /// Feedback coefficients normalized by a[0].
+    a: Vec<f64>,
+    /// The delay line of each channel, `order` values per channel.
+    state: Vec<f64>,
+    next_channel_idx: usize,
+}
+
+impl<S> IirFilter<S>
+where
+    S: Sound,
+{
+    /// Filter `inner` with feed forward coefficients `b` and feedback
+    /// coefficients `a`.
+    ///
+    /// An error is returned if either is empty or `a[0]` is 0.0.
+    pub fn new(inner: S, b: &[f64], a: &[f64]) -> Result<Self, crate::Error> {
+        let mut filter = IirFilter {
+            inner,
+            b: Vec::new(),
+            a: Vec::new(),
+            state: Vec::new(),
+            next_channel_idx: 0,
+        };
+        filter.set_coefficients(b, a)?;
+        Ok(filter)
+    }
+
+    /// Replace the coefficients and reset the filter state.
+    ///
+    /// An error is returned and the coefficients are left unchanged if either
+    /// is empty or `a[0]` is 0.0.
+    pub fn set_coefficients(&mut self, b: &[f64], a: &[f64]) -> Result<(), crate::Error> {
+        let invalid = |msg: &'static str| -> crate::Error {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, msg).into()
+        };
+        if b.is_empty() {
+            return Err(invalid("b must have at least one coefficient"));
+        }
+        let Some(a0) = a.first().copied() else {
+            return Err(invalid("a must have at least one coefficient"));
+        };
+        if a0 == 0.0 {
+            return Err(invalid("a[0] must not be 0"));
+        }
+        let len = a.len().max(b.len());
+        self.b = b.iter().map(|c| c / a0).collect();
+        self.b.resize(len, 0.0);
+        self.a = a.iter().map(|c| c / a0).collect();
+        self.a.resize(len, 0.0);
+        self.reset();
+        Ok(())
+    }
+
+    /// Clear the filter state of all channels.
+    pub fn reset(&mut self) {
+        let order = self.a.len() - 1;
+        self.state.clear();
+        self.state
+            .resize(order * self.inner.channel_count() as usize, 0.0);
+    }
+
+    fn filter(&mut self, x: f64) -> f64 {
+        let order = self.a.len() - 1;
+        let state = &mut self.state[self.next_channel_idx * order..][..order];
+        let y = self.b[0] * x + state.first().copied().unwrap_or(0.0);
+        for i in 0..order {
+            let next = state.get(i + 1).copied().unwrap_or(0.0);
+            state[i] = self.b[i + 1] * x - self.a[i + 1] * y + next;
+        }
+        y
+    }
+}
+
+impl<S> Sound for IirFilter<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                let y = self.filter(s as f64);
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() as usize {
+                    self.next_channel_idx = 0;
+                }
+                Ok(NextSample::Sample(
+                    y.clamp(i16::MIN as f64, i16::MAX as f64) as i16,
+                ))
+            }
+            NextSample::MetadataChanged => {
+                self.next_channel_idx = 0;
+                self.reset();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for IirFilter<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/iir_filter.rs"]
+mod tests;
@@ -0,0 +1,104 @@
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// A debugging wrapper that returns a spurious `MetadataChanged` at a fixed
+/// interval without changing the channel count or sample rate.
+///
+/// Useful for verifying that a consumer or another wrapper correctly handles
+/// `MetadataChanged` at arbitrary points. The samples of the inner sound are
+/// passed through unmodified.
+///
+/// A `MetadataChanged` is returned once `interval` samples have passed since
+/// the last one. Since the sample after a `MetadataChanged` must be for the
+/// first channel, injection waits for the start of the next frame so the
+/// cadence is exact only if `interval` is a multiple of the channel count.
+/// A real `MetadataChanged` from the inner sound also restarts the interval.
+pub struct InjectMetadataChange<S: Sound> {
+    inner: S,
+    interval: u64,
+    samples_since_change: u64,
+    next_channel_idx: u16,
+}
+
+impl<S> InjectMetadataChange<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` and return a `MetadataChanged` every `interval` samples.
+    ///
+    /// Panics if `interval` is 0.
+    pub fn new(inner: S, interval: u64) -> Self {
+        assert!(interval > 0, "interval must be greater than 0");
+        InjectMetadataChange {
+            inner,
+            interval,
+            samples_since_change: 0,
+            next_channel_idx: 0,
+        }
+    }
+}
+
+impl<S> Sound for InjectMetadataChange<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.next_channel_idx == 0 && self.samples_since_change >= self.interval {
+            self.samples_since_change = 0;
+            return Ok(NextSample::MetadataChanged);
+        }
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(_) => {
+                self.samples_since_change += 1;
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                }
+            }
+            NextSample::MetadataChanged => {
+                self.samples_since_change = 0;
+                self.next_channel_idx = 0;
+            }
+            NextSample::Paused | NextSample::Finished => (),
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for InjectMetadataChange<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/inject_metadata_change.rs"]
+mod tests;
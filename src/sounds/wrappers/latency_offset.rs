@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// Shift the inner sound in time to compensate for latency elsewhere (e.g. to
+/// line audio up with video or external gear).
+///
+/// A delay prepends silence so the audio is heard later. An advance skips
+/// the start of the inner sound so the audio is heard earlier. The offset is
+/// converted to frames using the sample rate of the inner sound when the
+/// wrapper is created and can not be changed afterwards.
+///
+/// Positions and durations reported by the wrapper are on its own timeline:
+/// they include the prepended silence and exclude the skipped audio.
+pub struct LatencyOffset<S: Sound> {
+    inner: S,
+    delay_frames: u64,
+    advance_frames: u64,
+    /// Silent samples still to be returned before the inner sound.
+    delay_samples_remaining: u64,
+    /// Samples of the inner sound still to be skipped.
+    skip_samples_remaining: u64,
+    output_frames: u64,
+    next_channel_idx: u16,
+}
+
+impl<S> LatencyOffset<S>
+where
+    S: Sound,
+{
+    /// Delay `inner` by prepending `delay` of silence.
+    pub fn delay(inner: S, delay: Duration) -> Self {
+        let delay_frames = utils::duration_to_num_samples(delay, 1, inner.sample_rate());
+        let mut offset = Self::new(inner);
+        offset.delay_frames = delay_frames;
+        offset.delay_samples_remaining = delay_frames * offset.inner.channel_count() as u64;
+        offset
+    }
+
+    /// Advance `inner` by skipping its first `advance`.
+    pub fn advance(inner: S, advance: Duration) -> Self {
+        let advance_frames = utils::duration_to_num_samples(advance, 1, inner.sample_rate());
+        let mut offset = Self::new(inner);
+        offset.advance_frames = advance_frames;
+        offset.skip_samples_remaining = advance_frames * offset.inner.channel_count() as u64;
+        offset
+    }
+
+    fn new(inner: S) -> Self {
+        LatencyOffset {
+            inner,
+            delay_frames: 0,
+            advance_frames: 0,
+            delay_samples_remaining: 0,
+            skip_samples_remaining: 0,
+            output_frames: 0,
+            next_channel_idx: 0,
+        }
+    }
+
+    /// The net offset in frames. Positive when delayed and negative when
+    /// advanced.
+    pub fn offset_frames(&self) -> i64 {
+        self.delay_frames as i64 - self.advance_frames as i64
+    }
+
+    /// The position of the next sample measured from the start of this
+    /// wrapper.
+    pub fn current_position(&self) -> Duration {
+        utils::num_samples_to_duration(self.output_frames, 1, self.inner.sample_rate())
+    }
+
+    /// The duration of the output given the duration of the inner sound.
+    ///
+    /// This is `inner_duration` plus the delay or minus the advance.
+    pub fn total_duration(&self, inner_duration: Duration) -> Duration {
+        let sample_rate = self.inner.sample_rate();
+        (inner_duration + utils::num_samples_to_duration(self.delay_frames, 1, sample_rate))
+            .saturating_sub(utils::num_samples_to_duration(
+                self.advance_frames,
+                1,
+                sample_rate,
+            ))
+    }
+
+    fn count_sample(&mut self) {
+        self.next_channel_idx += 1;
+        if self.next_channel_idx >= self.inner.channel_count() {
+            self.next_channel_idx = 0;
+            self.output_frames += 1;
+        }
+    }
+}
+
+impl<S> Sound for LatencyOffset<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.delay_samples_remaining > 0 {
+            self.delay_samples_remaining -= 1;
+            self.count_sample();
+            return Ok(NextSample::Sample(0));
+        }
+        while self.skip_samples_remaining > 0 {
+            match self.inner.next_sample()? {
+                NextSample::Sample(_) => self.skip_samples_remaining -= 1,
+                NextSample::MetadataChanged => (),
+                NextSample::Paused => return Ok(NextSample::Paused),
+                NextSample::Finished => {
+                    self.skip_samples_remaining = 0;
+                    return Ok(NextSample::Finished);
+                }
+            }
+        }
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(_) => self.count_sample(),
+            NextSample::MetadataChanged => self.next_channel_idx = 0,
+            NextSample::Paused | NextSample::Finished => (),
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for LatencyOffset<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/latency_offset.rs"]
+mod tests;
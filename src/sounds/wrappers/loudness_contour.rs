@@ -0,0 +1,217 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::sounds::biquad::{Biquad, BiquadCoefficients, Shelf};
+use crate::{NextSample, Sound};
+
+use super::{SetPaused, SetSpeed, SetVolume};
+
+/// Corner frequency of the bass shelf in Hz.
+const LOW_SHELF_FREQUENCY: f32 = 150.0;
+/// Corner frequency of the treble shelf in Hz.
+const HIGH_SHELF_FREQUENCY: f32 = 8000.0;
+/// dB of bass boost per dB the volume is below the reference.
+const LOW_BOOST_PER_DB: f32 = 0.5;
+/// dB of treble boost per dB the volume is below the reference.
+const HIGH_BOOST_PER_DB: f32 = 0.2;
+const MAX_LOW_BOOST_DB: f32 = 15.0;
+const MAX_HIGH_BOOST_DB: f32 = 6.0;
+
+/// Adjust the volume of the inner sound while boosting bass and treble more
+/// the lower the volume is, so quiet playback keeps the same tonal balance.
+///
+/// Our hearing is less sensitive to low and high frequencies at low levels
+/// (the equal-loudness contours first measured by Fletcher and Munson). At
+/// the reference volume of 1.0 the sound is unchanged. Below it a low shelf
+/// at 150 Hz is boosted by half as many dB as the volume is reduced (up to 15
+/// dB) and a high shelf at 8 kHz by a fifth (up to 6 dB). Bass is never
+/// louder than at the reference volume.
+///
+/// The volume is set with the [LoudnessContourHandle] returned when the
+/// wrapper is created or with [SetVolume] so it can be used in place of
+/// [AdjustableVolume][super::AdjustableVolume]. Changes take effect at the
+/// start of the next frame.
+pub struct LoudnessContour<S: Sound> {
+    inner: S,
+    volume: Arc<AtomicU32>,
+    /// The volume the filters were last designed for.
+    applied_volume: f32,
+    /// The low and high shelf of each channel.
+    filters: Vec<[Biquad; 2]>,
+    next_channel_idx: usize,
+}
+
+/// Controls the volume of a [LoudnessContour].
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct LoudnessContourHandle {
+    volume: Arc<AtomicU32>,
+}
+
+impl<S> LoudnessContour<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` at a volume of `volume` where 1.0 is the reference level.
+    pub fn new(inner: S, volume: f32) -> (Self, LoudnessContourHandle) {
+        let volume = Arc::new(AtomicU32::new(volume.max(0.0).to_bits()));
+        let mut contour = LoudnessContour {
+            inner,
+            volume: volume.clone(),
+            applied_volume: 1.0,
+            filters: Vec::new(),
+            next_channel_idx: 0,
+        };
+        contour.reset();
+        (contour, LoudnessContourHandle { volume })
+    }
+
+    /// Return the current volume multiplier. 1.0 is the reference level.
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::Relaxed))
+    }
+
+    /// Get a reference to the wrapped inner Sound.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped inner Sound.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap and return the previously wrapped Sound.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Clear the filter state of all channels and design the filters for the
+    /// current volume and sample rate.
+    fn reset(&mut self) {
+        let coefficients = self.design();
+        self.filters.clear();
+        self.filters.resize(
+            self.inner.channel_count() as usize,
+            coefficients.map(Biquad::new),
+        );
+        self.next_channel_idx = 0;
+    }
+
+    /// The low and high shelf coefficients for the current volume.
+    fn design(&mut self) -> [BiquadCoefficients; 2] {
+        self.applied_volume = self.volume();
+        let sample_rate = self.inner.sample_rate() as f32;
+        let attenuation_db = -20.0 * self.applied_volume.max(1e-6).log10();
+        let attenuation_db = attenuation_db.max(0.0);
+        let low_db = (attenuation_db * LOW_BOOST_PER_DB).min(MAX_LOW_BOOST_DB);
+        let high_db = (attenuation_db * HIGH_BOOST_PER_DB).min(MAX_HIGH_BOOST_DB);
+        // Keep the treble shelf below Nyquist for low sample rates
+        let high_frequency = HIGH_SHELF_FREQUENCY.min(sample_rate * 0.4);
+        [
+            BiquadCoefficients::shelf(Shelf::Low, LOW_SHELF_FREQUENCY, low_db, sample_rate),
+            BiquadCoefficients::shelf(Shelf::High, high_frequency, high_db, sample_rate),
+        ]
+    }
+}
+
+impl LoudnessContourHandle {
+    /// Set the volume multiplier. 1.0 is the reference level where no
+    /// compensation is applied.
+    pub fn set_volume(&self, volume: f32) {
+        self.volume
+            .store(volume.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Return the current volume multiplier.
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::Relaxed))
+    }
+}
+
+impl<S> Sound for LoudnessContour<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                if self.next_channel_idx == 0 && self.volume() != self.applied_volume {
+                    // Keep the filter state so the change does not click
+                    let coefficients = self.design();
+                    for filters in &mut self.filters {
+                        for (filter, coefficients) in filters.iter_mut().zip(coefficients) {
+                            filter.set_coefficients(coefficients);
+                        }
+                    }
+                }
+                let Some([low, high]) = self.filters.get_mut(self.next_channel_idx) else {
+                    return Ok(next);
+                };
+                let y = high.process(low.process(s as f32)) * self.applied_volume;
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() as usize {
+                    self.next_channel_idx = 0;
+                }
+                Ok(NextSample::Sample(
+                    y.clamp(i16::MIN as f32, i16::MAX as f32) as i16,
+                ))
+            }
+            NextSample::MetadataChanged => {
+                self.reset();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S> SetVolume for LoudnessContour<S>
+where
+    S: Sound,
+{
+    fn set_volume(&mut self, multiplier: f32) {
+        self.volume
+            .store(multiplier.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl<S> SetPaused for LoudnessContour<S>
+where
+    S: Sound + SetPaused,
+{
+    fn set_paused(&mut self, paused: bool) {
+        self.inner.set_paused(paused)
+    }
+}
+
+impl<S> SetSpeed for LoudnessContour<S>
+where
+    S: Sound + SetSpeed,
+{
+    fn set_speed(&mut self, multiplier: f32) {
+        self.inner.set_speed(multiplier)
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/loudness_contour.rs"]
+mod tests;
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// Call a function at exact positions of the inner sound, e.g. for the beats
+/// of a rhythm game or to sync visuals.
+///
+/// Each marker is a position measured from the start of the inner sound and
+/// an event passed to the callback. The callback is called from the thread
+/// pulling samples just before the first sample of the marker's frame is
+/// returned so it should return quickly, e.g. by sending a message. Markers
+/// at the same position fire in the order given.
+///
+/// Seeking moves the markers with the inner sound: markers before the new
+/// position are skipped and markers after it fire again even if they already
+/// fired. Marker positions are converted to frames with the sample rate of
+/// the inner sound when the wrapper is created.
+pub struct MarkerTrack<S: Sound, E> {
+    inner: S,
+    /// The frame of each marker, sorted by frame.
+    markers: Vec<(u64, E)>,
+    callback: Box<dyn FnMut(&E) + Send>,
+    /// The index of the next marker to fire.
+    next_marker: usize,
+    /// The frame the next sample is part of.
+    frame: u64,
+    next_channel_idx: u16,
+}
+
+impl<S, E> MarkerTrack<S, E>
+where
+    S: Sound,
+    E: Send,
+{
+    /// Wrap `inner` calling `callback` with the event of each of `markers`
+    /// when its position is played.
+    pub fn new(
+        inner: S,
+        markers: Vec<(Duration, E)>,
+        callback: impl FnMut(&E) + Send + 'static,
+    ) -> Self {
+        let sample_rate = inner.sample_rate();
+        let mut markers: Vec<(u64, E)> = markers
+            .into_iter()
+            .map(|(position, event)| {
+                let frame = utils::duration_to_num_samples(position, 1, sample_rate);
+                (frame, event)
+            })
+            .collect();
+        markers.sort_by_key(|(frame, _)| *frame);
+        MarkerTrack {
+            inner,
+            markers,
+            callback: Box::new(callback),
+            next_marker: 0,
+            frame: 0,
+            next_channel_idx: 0,
+        }
+    }
+
+    /// The number of markers that have not fired since the start or the last
+    /// seek.
+    pub fn remaining(&self) -> usize {
+        self.markers.len() - self.next_marker
+    }
+}
+
+impl<S, E> Sound for MarkerTrack<S, E>
+where
+    S: Sound,
+    E: Send,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(_) => {
+                if self.next_channel_idx == 0 {
+                    while let Some((frame, event)) = self.markers.get(self.next_marker) {
+                        if *frame > self.frame {
+                            break;
+                        }
+                        (self.callback)(event);
+                        self.next_marker += 1;
+                    }
+                }
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                    self.frame += 1;
+                }
+            }
+            NextSample::MetadataChanged => {
+                if self.next_channel_idx != 0 {
+                    // The partial frame still counts as played
+                    self.frame += 1;
+                }
+                self.next_channel_idx = 0;
+            }
+            NextSample::Paused | NextSample::Finished => (),
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+
+    fn seek(&mut self, seek_to: Duration) -> Result<Duration, crate::Error> {
+        let position = self.inner.seek(seek_to)?;
+        self.next_channel_idx = 0;
+        // Round since the position is usually a whole frame that was truncated
+        // to nanoseconds
+        let frame =
+            (position.as_nanos() * self.inner.sample_rate() as u128 + 500_000_000) / 1_000_000_000;
+        self.frame = frame as u64;
+        self.next_marker = self
+            .markers
+            .partition_point(|(frame, _)| *frame < self.frame);
+        Ok(position)
+    }
+
+    fn seek_granularity(&self) -> Option<Duration> {
+        self.inner.seek_granularity()
+    }
+}
+
+impl<S: Sound, E: Send> Wrapper for MarkerTrack<S, E> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/marker_track.rs"]
+mod tests;
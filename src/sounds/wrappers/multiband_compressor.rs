@@ -0,0 +1,288 @@
+use std::time::Duration;
+
+use crate::sounds::biquad::{Biquad, BiquadCoefficients};
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Compress separate frequency bands of the inner sound independently, e.g.
+/// to control the bass without pumping the rest of the mix.
+///
+/// The sound is split into bands at each crossover frequency with
+/// Linkwitz-Riley 4th order filters. Lower bands are passed through matching
+/// all-pass filters so every band has the same phase and the bands sum back
+/// to a flat frequency response. Each band then has its own compressor with
+/// a threshold (as a fraction of full scale) and ratio. By default the
+/// threshold is 1.0 and the ratio 1.0 so no band is compressed.
+///
+/// The compressors follow the peak level of each channel with a 10ms attack
+/// and 100ms release by default. All filter and compressor state is reset
+/// when the inner sound returns `MetadataChanged`. The output is clipped to
+/// the range of i16.
+pub struct MultibandCompressor<S: Sound> {
+    inner: S,
+    crossovers: Vec<f32>,
+    bands: Vec<BandCompression>,
+    attack: Duration,
+    release: Duration,
+    attack_coefficient: f32,
+    release_coefficient: f32,
+    channels: Vec<ChannelState>,
+    next_channel_idx: usize,
+}
+
+#[derive(Clone, Copy)]
+struct BandCompression {
+    threshold: f32,
+    ratio: f32,
+}
+
+#[derive(Clone)]
+struct ChannelState {
+    crossovers: Vec<Crossover>,
+    /// The all-pass filters of each band for the crossovers above it.
+    all_passes: Vec<Vec<Biquad>>,
+    envelopes: Vec<f32>,
+}
+
+impl<S> MultibandCompressor<S>
+where
+    S: Sound,
+{
+    /// Split `inner` into bands at each of `crossovers` in Hz, giving
+    /// `crossovers.len() + 1` bands from lowest to highest.
+    ///
+    /// Returns an error if the crossovers are not increasing or not between
+    /// 0 Hz and the Nyquist frequency of `inner`.
+    pub fn new(inner: S, crossovers: &[f32]) -> Result<Self, crate::Error> {
+        let nyquist = inner.sample_rate() as f32 / 2.0;
+        let increasing = crossovers.windows(2).all(|pair| pair[0] < pair[1]);
+        let in_range = crossovers.iter().all(|f| *f > 0.0 && *f < nyquist);
+        if !increasing || !in_range {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "crossovers {:?} must be increasing and between 0 and {} Hz",
+                    crossovers, nyquist
+                ),
+            )
+            .into());
+        }
+        let mut compressor = MultibandCompressor {
+            inner,
+            crossovers: crossovers.to_vec(),
+            bands: vec![
+                BandCompression {
+                    threshold: 1.0,
+                    ratio: 1.0,
+                };
+                crossovers.len() + 1
+            ],
+            attack: Duration::from_millis(10),
+            release: Duration::from_millis(100),
+            attack_coefficient: 0.0,
+            release_coefficient: 0.0,
+            channels: Vec::new(),
+            next_channel_idx: 0,
+        };
+        compressor.reset();
+        Ok(compressor)
+    }
+
+    /// The number of bands.
+    pub fn band_count(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Set the level, as a fraction of full scale, above which `band` is
+    /// compressed.
+    ///
+    /// Panics if `band` is not less than [band_count][Self::band_count].
+    pub fn set_threshold(&mut self, band: usize, threshold: f32) {
+        self.bands[band].threshold = threshold.clamp(f32::EPSILON, 1.0);
+    }
+
+    /// Set how much `band` is compressed above its threshold, e.g. 4.0 means
+    /// every 4 dB above the threshold only raises the output 1 dB.
+    ///
+    /// Panics if `band` is not less than [band_count][Self::band_count].
+    pub fn set_ratio(&mut self, band: usize, ratio: f32) {
+        self.bands[band].ratio = ratio.max(1.0);
+    }
+
+    /// Set how quickly the compressors react to rising and falling levels.
+    pub fn set_attack_release(&mut self, attack: Duration, release: Duration) {
+        self.attack = attack;
+        self.release = release;
+        self.update_coefficients();
+    }
+
+    fn update_coefficients(&mut self) {
+        let sample_rate = self.inner.sample_rate() as f32;
+        let coefficient = |duration: Duration| {
+            let samples = duration.as_secs_f32() * sample_rate;
+            if samples > 0.0 {
+                (-1.0 / samples).exp()
+            } else {
+                0.0
+            }
+        };
+        self.attack_coefficient = coefficient(self.attack);
+        self.release_coefficient = coefficient(self.release);
+    }
+
+    /// Design the filters for the sample rate of the inner sound and clear
+    /// all state.
+    fn reset(&mut self) {
+        self.update_coefficients();
+        let sample_rate = self.inner.sample_rate() as f32;
+        let crossovers = self
+            .crossovers
+            .iter()
+            .map(|frequency| Crossover::new(*frequency, sample_rate))
+            .collect();
+        let all_passes = (0..self.bands.len())
+            .map(|band| {
+                self.crossovers
+                    .iter()
+                    .skip(band + 1)
+                    .map(|frequency| {
+                        Biquad::new(BiquadCoefficients::all_pass(*frequency, sample_rate))
+                    })
+                    .collect()
+            })
+            .collect();
+        let state = ChannelState {
+            crossovers,
+            all_passes,
+            envelopes: vec![0.0; self.bands.len()],
+        };
+        self.channels = vec![state; self.inner.channel_count() as usize];
+        self.next_channel_idx = 0;
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let Some(state) = self.channels.get_mut(self.next_channel_idx) else {
+            return x;
+        };
+        let mut rest = x;
+        let mut output = 0.0;
+        for (band, settings) in self.bands.iter().enumerate() {
+            let mut value = match state.crossovers.get_mut(band) {
+                Some(crossover) => {
+                    let (low, high) = crossover.split(rest);
+                    rest = high;
+                    low
+                }
+                None => rest,
+            };
+            for all_pass in &mut state.all_passes[band] {
+                value = all_pass.process(value);
+            }
+
+            let level = value.abs();
+            let envelope = &mut state.envelopes[band];
+            let coefficient = if level > *envelope {
+                self.attack_coefficient
+            } else {
+                self.release_coefficient
+            };
+            *envelope = level + (*envelope - level) * coefficient;
+            if *envelope > settings.threshold {
+                let compressed = settings.threshold
+                    * (*envelope / settings.threshold).powf(1.0 / settings.ratio);
+                value *= compressed / *envelope;
+            }
+            output += value;
+        }
+        output
+    }
+}
+
+impl<S> Sound for MultibandCompressor<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                let y = self.process(s as f32 / i16::MAX as f32) * i16::MAX as f32;
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() as usize {
+                    self.next_channel_idx = 0;
+                }
+                Ok(NextSample::Sample(
+                    y.clamp(i16::MIN as f32, i16::MAX as f32) as i16,
+                ))
+            }
+            NextSample::MetadataChanged => {
+                self.reset();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S: Sound> Wrapper for MultibandCompressor<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+/// Linkwitz-Riley 4th order low pass and high pass filters at the same
+/// frequency.
+#[derive(Clone)]
+struct Crossover {
+    low: [Biquad; 2],
+    high: [Biquad; 2],
+}
+
+impl Crossover {
+    fn new(frequency: f32, sample_rate: f32) -> Crossover {
+        let low = Biquad::new(BiquadCoefficients::low_pass(frequency, sample_rate));
+        let high = Biquad::new(BiquadCoefficients::high_pass(frequency, sample_rate));
+        Crossover {
+            low: [low.clone(), low],
+            high: [high.clone(), high],
+        }
+    }
+
+    /// Return the parts of `x` below and above the crossover frequency.
+    fn split(&mut self, x: f32) -> (f32, f32) {
+        let low = self.low[0].process(x);
+        let high = self.high[0].process(x);
+        (self.low[1].process(low), self.high[1].process(high))
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/multiband_compressor.rs"]
+mod tests;
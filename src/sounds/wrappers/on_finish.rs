@@ -0,0 +1,90 @@
+use super::Wrapper;
+use crate::NextSample;
+use crate::Sound;
+
+/// Call a function when the contained Sound has Finished.
+///
+/// The callback is called exactly once, from the thread pulling samples, the
+/// first time the inner sound returns `Finished`. It should return quickly
+/// since it runs in the audio path, e.g. by sending a message. If the Sound is
+/// dropped before it returned Finished the callback is dropped without being
+/// called. The contained Sound pausing or yielding an error does not count as
+/// completion.
+///
+/// See also [super::CompletionNotifier]
+pub struct OnFinish<S: Sound> {
+    inner: S,
+    callback: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl<S> OnFinish<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` so `callback` is called when `inner` has `Finished`.
+    pub fn new(inner: S, callback: impl FnOnce() + Send + 'static) -> Self {
+        OnFinish {
+            inner,
+            callback: Some(Box::new(callback)),
+        }
+    }
+
+    /// Whether the callback has been called.
+    pub fn has_finished(&self) -> bool {
+        self.callback.is_none()
+    }
+}
+
+impl<S> Sound for OnFinish<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        if let NextSample::Finished = next {
+            if let Some(callback) = self.callback.take() {
+                callback();
+            }
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch();
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S> Wrapper for OnFinish<S>
+where
+    S: Sound,
+{
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/on_finish.rs"]
+mod tests;
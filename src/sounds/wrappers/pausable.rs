@@ -1,4 +1,6 @@
-use crate::Sound;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
 
 use super::{SetSpeed, SetVolume};
 
@@ -9,9 +11,20 @@ pub trait SetPaused {
 }
 
 /// A wrapper to make a Sound pausable.
+///
+/// By default pausing takes effect immediately. With a fade duration set
+/// pausing fades the inner sound out over that duration before returning
+/// `Paused` and resuming fades it back in so neither transition clicks. The
+/// inner sound keeps playing during the fade out but is not advanced once it
+/// has completed.
 pub struct Pausable<S: Sound> {
     inner: S,
     paused: bool,
+    fade_duration: Duration,
+    /// Frames into the fade in. 0 once fully paused and `u64::MAX` when fully
+    /// playing.
+    level: u64,
+    next_channel_idx: u16,
 }
 
 impl<S> Pausable<S>
@@ -24,9 +37,20 @@ where
         Pausable {
             inner,
             paused: false,
+            fade_duration: Duration::ZERO,
+            level: u64::MAX,
+            next_channel_idx: 0,
         }
     }
 
+    /// Wrap `inner` and fade out over `fade_duration` when paused and fade
+    /// back in over `fade_duration` when unpaused.
+    pub fn with_fade(inner: S, fade_duration: Duration) -> Self {
+        let mut pausable = Pausable::new(inner);
+        pausable.fade_duration = fade_duration;
+        pausable
+    }
+
     /// Get a reference to the wrapped inner Sound.
     pub fn inner(&self) -> &S {
         &self.inner
@@ -55,16 +79,48 @@ where
         self.inner.sample_rate()
     }
 
-    fn next_sample(&mut self) -> Result<crate::NextSample, crate::Error> {
-        if self.paused {
-            return Ok(crate::NextSample::Paused);
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let fade_frames = self.fade_frames();
+        if self.paused && (fade_frames == 0 || self.level == 0) {
+            return Ok(NextSample::Paused);
+        }
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                let level = self.level.min(fade_frames);
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                    self.level = if self.paused {
+                        level - 1
+                    } else if level + 1 < fade_frames {
+                        level + 1
+                    } else {
+                        u64::MAX
+                    };
+                }
+                if level >= fade_frames {
+                    Ok(next)
+                } else {
+                    let gain = level as f32 / fade_frames as f32;
+                    Ok(NextSample::Sample((s as f32 * gain) as i16))
+                }
+            }
+            NextSample::MetadataChanged => {
+                self.next_channel_idx = 0;
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
         }
-        self.inner.next_sample()
     }
 
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
 }
 
 impl<S> Pausable<S>
@@ -75,6 +131,21 @@ where
     pub fn paused(&self) -> bool {
         self.paused
     }
+
+    /// Return how long pausing and resuming fade for.
+    pub fn fade_duration(&self) -> Duration {
+        self.fade_duration
+    }
+
+    /// Set how long pausing and resuming fade for. A zero duration pauses
+    /// and resumes immediately.
+    pub fn set_fade_duration(&mut self, fade_duration: Duration) {
+        self.fade_duration = fade_duration;
+    }
+
+    fn fade_frames(&self) -> u64 {
+        utils::duration_to_num_samples(self.fade_duration, 1, self.inner.sample_rate())
+    }
 }
 
 impl<S> SetPaused for Pausable<S>
@@ -83,6 +154,10 @@ where
 {
     fn set_paused(&mut self, paused: bool) {
         self.paused = paused;
+        if !paused && self.level == 0 {
+            // Start fading in from the first frame after resuming
+            self.level = 1;
+        }
     }
 }
 
@@ -0,0 +1,104 @@
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Look at the next value of the inner sound without consuming it, so a
+/// consumer can tell a format change is coming (see
+/// [next_is_metadata_change][Sound::next_is_metadata_change]) and resize its
+/// buffers before pulling samples in the new format.
+///
+/// Peeking pulls one value from the inner sound and holds it until the next
+/// call to `next_sample`, including any error. The channel count and sample
+/// rate reported keep their old values until the peeked `MetadataChanged`
+/// has been returned. Calling [into_inner][Wrapper::into_inner] drops any
+/// peeked value.
+pub struct Peekable<S: Sound> {
+    inner: S,
+    peeked: Option<Result<NextSample, crate::Error>>,
+    channel_count: u16,
+    sample_rate: u32,
+}
+
+impl<S> Peekable<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` to allow peeking.
+    pub fn new(inner: S) -> Self {
+        Peekable {
+            channel_count: inner.channel_count(),
+            sample_rate: inner.sample_rate(),
+            inner,
+            peeked: None,
+        }
+    }
+
+    /// Return what the next call to `next_sample` will return without
+    /// consuming it.
+    ///
+    /// If the inner sound returned an error a reference to it is returned
+    /// and the error itself is returned by `next_sample`.
+    pub fn peek(&mut self) -> Result<NextSample, &crate::Error> {
+        self.peeked
+            .get_or_insert_with(|| self.inner.next_sample())
+            .as_ref()
+            .copied()
+    }
+}
+
+impl<S> Sound for Peekable<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = match self.peeked.take() {
+            Some(next) => next?,
+            None => self.inner.next_sample()?,
+        };
+        if next == NextSample::MetadataChanged {
+            self.channel_count = self.inner.channel_count();
+            self.sample_rate = self.inner.sample_rate();
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+
+    fn next_is_metadata_change(&mut self) -> Option<bool> {
+        Some(matches!(self.peek(), Ok(NextSample::MetadataChanged)))
+    }
+}
+
+impl<S: Sound> Wrapper for Peekable<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/peekable.rs"]
+mod tests;
@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// Read `duration` of the inner sound ahead before starting playback so a
+/// slow first decode does not cause an underrun.
+///
+/// Until the pre-roll has been buffered `Paused` is returned. The buffer can
+/// also be filled before the sound is played by calling
+/// [fill][Prebuffer::fill] until [is_ready][Prebuffer::is_ready] returns
+/// true. The sound is also considered ready if the inner sound finishes or
+/// changes its metadata before `duration` has been read.
+///
+/// Once started, the buffer is topped up as samples are returned so the inner
+/// sound stays `duration` ahead and its pauses are absorbed by the buffer.
+/// This adds `duration` of latency to any changes made to the inner sound.
+pub struct Prebuffer<S: Sound> {
+    inner: S,
+    duration: Duration,
+    target_frames: u64,
+    channel_count: u16,
+    sample_rate: u32,
+    /// Interleaved samples that have been read ahead.
+    buffer: VecDeque<i16>,
+    /// A Finished or MetadataChanged to return once `buffer` is empty.
+    pending: Option<NextSample>,
+    ready: bool,
+    frame: Vec<i16>,
+}
+
+impl<S> Prebuffer<S>
+where
+    S: Sound,
+{
+    /// Buffer `duration` of `inner` before returning any samples.
+    pub fn new(inner: S, duration: Duration) -> Self {
+        let mut prebuffer = Prebuffer {
+            channel_count: inner.channel_count(),
+            sample_rate: inner.sample_rate(),
+            inner,
+            duration,
+            target_frames: 0,
+            buffer: VecDeque::new(),
+            pending: None,
+            ready: false,
+            frame: Vec::new(),
+        };
+        prebuffer.target_frames =
+            utils::duration_to_num_samples(duration, 1, prebuffer.sample_rate);
+        prebuffer
+    }
+
+    /// Return the amount of audio buffered before playback starts.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Return true once the pre-roll has been buffered and samples will be
+    /// returned.
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Read as much of the pre-roll as the inner sound has available without
+    /// returning any samples.
+    ///
+    /// Returns the new value of [is_ready][Prebuffer::is_ready].
+    pub fn fill(&mut self) -> Result<bool, crate::Error> {
+        if !self.ready {
+            self.read_ahead()?;
+            self.ready = self.pending.is_some()
+                || self.buffer.len() >= self.target_frames as usize * self.channel_count as usize;
+        }
+        Ok(self.ready)
+    }
+
+    /// Read frames until `target_frames` are buffered beyond the next frame
+    /// to return or the inner sound returns something other than a sample.
+    fn read_ahead(&mut self) -> Result<(), crate::Error> {
+        let target = self.target_frames as usize * self.channel_count as usize;
+        while self.pending.is_none() && self.buffer.len() <= target {
+            self.frame.clear();
+            match self.inner.append_next_frame_to(&mut self.frame) {
+                Ok(()) => self.buffer.extend(self.frame.iter()),
+                Err(Ok(NextSample::Paused)) => break,
+                Err(Ok(special)) => self.pending = Some(special),
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S> Sound for Prebuffer<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if !self.fill()? {
+            return Ok(NextSample::Paused);
+        }
+        self.read_ahead()?;
+        if let Some(sample) = self.buffer.pop_front() {
+            return Ok(NextSample::Sample(sample));
+        }
+        match self.pending.take() {
+            Some(NextSample::MetadataChanged) => {
+                self.channel_count = self.inner.channel_count();
+                self.sample_rate = self.inner.sample_rate();
+                self.target_frames =
+                    utils::duration_to_num_samples(self.duration, 1, self.sample_rate);
+                Ok(NextSample::MetadataChanged)
+            }
+            Some(NextSample::Finished) => {
+                self.pending = Some(NextSample::Finished);
+                Ok(NextSample::Finished)
+            }
+            _ => Ok(NextSample::Paused),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+
+    fn buffered_duration(&self) -> Duration {
+        utils::num_samples_to_duration(
+            self.buffer.len() as u64,
+            self.channel_count,
+            self.sample_rate,
+        )
+    }
+}
+
+impl<S: Sound> Wrapper for Prebuffer<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/prebuffer.rs"]
+mod tests;
@@ -0,0 +1,160 @@
+use std::time::{Duration, Instant};
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// The default for how far ahead of wall-clock time a [RealtimeThrottle] may
+/// run before it sleeps.
+pub const DEFAULT_MAX_LEAD: Duration = Duration::from_millis(5);
+
+/// Pace the pulling of samples from the inner sound to wall-clock time.
+///
+/// Useful when feeding a sound into a consumer that expects real-time delivery
+/// but has no hardware clock of its own. If the consumer pulls samples faster
+/// than the sample rate, `next_sample` sleeps the calling thread. The consumer
+/// is allowed to run up to `max_lead` ahead of real time so samples are
+/// delivered in bursts of roughly `max_lead` instead of sleeping for every
+/// frame.
+///
+/// This must not be used on a backend's renderer thread since it blocks.
+///
+/// The clock restarts whenever the inner sound returns Paused or Finished so
+/// time spent paused does not cause a burst of samples afterward. A sound
+/// with a sample rate of 0 is not throttled.
+pub struct RealtimeThrottle<S: Sound> {
+    inner: S,
+    max_lead: Duration,
+    clock_start: Option<Instant>,
+    frames_since_clock_start: u64,
+    next_channel_idx: u16,
+    current_sample_rate: u32,
+}
+
+impl<S> RealtimeThrottle<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` so it is pulled no faster than real time, allowing a lead
+    /// of [DEFAULT_MAX_LEAD].
+    pub fn new(inner: S) -> Self {
+        Self::with_max_lead(inner, DEFAULT_MAX_LEAD)
+    }
+
+    /// Wrap `inner` so it is pulled no faster than real time, allowing a lead
+    /// of `max_lead`.
+    pub fn with_max_lead(inner: S, max_lead: Duration) -> Self {
+        let current_sample_rate = inner.sample_rate();
+        RealtimeThrottle {
+            inner,
+            max_lead,
+            clock_start: None,
+            frames_since_clock_start: 0,
+            next_channel_idx: 0,
+            current_sample_rate,
+        }
+    }
+
+    /// Return how far ahead of real time samples may be pulled.
+    pub fn max_lead(&self) -> Duration {
+        self.max_lead
+    }
+
+    /// Set how far ahead of real time samples may be pulled.
+    pub fn set_max_lead(&mut self, max_lead: Duration) {
+        self.max_lead = max_lead;
+    }
+
+    fn elapsed_sound_time(&self) -> Duration {
+        // There is nothing to pace a sound without a sample rate to.
+        if self.current_sample_rate == 0 {
+            return Duration::ZERO;
+        }
+        utils::num_samples_to_duration(self.frames_since_clock_start, 1, self.current_sample_rate)
+    }
+
+    fn wait_for_next_frame(&mut self) {
+        let now = Instant::now();
+        let Some(clock_start) = self.clock_start else {
+            self.clock_start = Some(now);
+            return;
+        };
+        let due = clock_start + self.elapsed_sound_time();
+        if due > now + self.max_lead {
+            std::thread::sleep(due - now);
+        }
+    }
+
+    fn restart_clock(&mut self) {
+        self.clock_start = None;
+        self.frames_since_clock_start = 0;
+        self.next_channel_idx = 0;
+    }
+}
+
+impl<S> Sound for RealtimeThrottle<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.next_channel_idx == 0 {
+            self.wait_for_next_frame();
+        }
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(_) => {
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                    self.frames_since_clock_start += 1;
+                }
+            }
+            NextSample::MetadataChanged => {
+                // Keep the same clock but measure further progress in the new
+                // sample rate.
+                self.clock_start = self.clock_start.map(|s| s + self.elapsed_sound_time());
+                self.frames_since_clock_start = 0;
+                self.next_channel_idx = 0;
+                self.current_sample_rate = self.inner.sample_rate();
+            }
+            NextSample::Paused | NextSample::Finished => self.restart_clock(),
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for RealtimeThrottle<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/realtime_throttle.rs"]
+mod tests;
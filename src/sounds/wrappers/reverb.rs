@@ -0,0 +1,281 @@
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Comb filter delays in frames at 44,100 Hz from Freeverb.
+const COMB_TUNINGS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+/// Allpass filter delays in frames at 44,100 Hz from Freeverb.
+const ALLPASS_TUNINGS: [usize; 4] = [556, 441, 341, 225];
+/// Added to the delays of odd channels so channels are decorrelated.
+const STEREO_SPREAD: usize = 23;
+/// Input gain so the sum of the combs stays in range.
+const FIXED_GAIN: f32 = 0.015;
+const WET_SCALE: f32 = 3.0;
+const ALLPASS_FEEDBACK: f32 = 0.5;
+
+/// Algorithmic reverb using a Schroeder/Freeverb style network of filters,
+/// for when no impulse response is available for `ConvolutionReverb`.
+///
+/// Each channel is passed through 8 parallel low pass feedback comb filters
+/// followed by 4 series allpass filters. The delays are scaled to the sample
+/// rate and are slightly longer for odd channels so stereo output is wide.
+/// The room size sets how long the reverb lasts, the damping how quickly high
+/// frequencies die out and the mix the balance between the dry input and the
+/// reverb.
+///
+/// After the inner sound finishes the reverb tail is played until it is
+/// silent before `Finished` is returned. The filter state is cleared when the
+/// inner sound returns `MetadataChanged`.
+pub struct Reverb<S: Sound> {
+    inner: S,
+    room_size: f32,
+    damping: f32,
+    mix: f32,
+    channels: Vec<ChannelFilters>,
+    next_channel_idx: usize,
+    /// Whether the inner sound has finished and the tail is being played.
+    inner_finished: bool,
+    /// Whether every sample of the current frame of the tail was silent.
+    frame_silent: bool,
+    /// Consecutive silent frames of the tail.
+    silent_frames: usize,
+}
+
+struct ChannelFilters {
+    combs: Vec<Comb>,
+    allpasses: Vec<Allpass>,
+}
+
+struct Comb {
+    buffer: Vec<f32>,
+    pos: usize,
+    /// The state of the low pass filter in the feedback path.
+    filter_store: f32,
+}
+
+struct Allpass {
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl Comb {
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.pos];
+        self.filter_store = output * (1.0 - damping) + self.filter_store * damping;
+        self.buffer[self.pos] = input + self.filter_store * feedback;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        output
+    }
+}
+
+impl Allpass {
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.pos];
+        self.buffer[self.pos] = input + delayed * ALLPASS_FEEDBACK;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// Scale a Freeverb delay to `sample_rate`.
+fn scaled(frames: usize, sample_rate: u32) -> usize {
+    (frames as u64 * sample_rate as u64 / 44100).max(1) as usize
+}
+
+impl<S> Reverb<S>
+where
+    S: Sound,
+{
+    /// Add reverb to `inner` with a room size of 0.5, damping of 0.5 and a
+    /// mix of 0.3.
+    pub fn new(inner: S) -> Self {
+        let mut reverb = Reverb {
+            inner,
+            room_size: 0.5,
+            damping: 0.5,
+            mix: 0.3,
+            channels: Vec::new(),
+            next_channel_idx: 0,
+            inner_finished: false,
+            frame_silent: true,
+            silent_frames: 0,
+        };
+        reverb.reset();
+        reverb
+    }
+
+    /// How long the reverb lasts from 0.0 (a small room) to 1.0 (a very
+    /// large hall).
+    pub fn room_size(&self) -> f32 {
+        self.room_size
+    }
+
+    /// Set the room size. Clamped to `0.0..=1.0`.
+    pub fn set_room_size(&mut self, room_size: f32) {
+        self.room_size = room_size.clamp(0.0, 1.0);
+    }
+
+    /// How quickly high frequencies die out compared to low frequencies from
+    /// 0.0 (not at all) to 1.0.
+    pub fn damping(&self) -> f32 {
+        self.damping
+    }
+
+    /// Set the damping. Clamped to `0.0..=1.0`.
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.clamp(0.0, 1.0);
+    }
+
+    /// The wet/dry mix where 0.0 is only the input and 1.0 is only the
+    /// reverb.
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Set the wet/dry mix. Clamped to `0.0..=1.0`.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// Clear the filter state and size the delays for the channel count and
+    /// sample rate of the inner sound.
+    fn reset(&mut self) {
+        let sample_rate = self.inner.sample_rate();
+        self.channels = (0..self.inner.channel_count() as usize)
+            .map(|channel| {
+                let spread = if channel % 2 == 1 { STEREO_SPREAD } else { 0 };
+                ChannelFilters {
+                    combs: COMB_TUNINGS
+                        .iter()
+                        .map(|frames| Comb {
+                            buffer: vec![0.0; scaled(frames + spread, sample_rate)],
+                            pos: 0,
+                            filter_store: 0.0,
+                        })
+                        .collect(),
+                    allpasses: ALLPASS_TUNINGS
+                        .iter()
+                        .map(|frames| Allpass {
+                            buffer: vec![0.0; scaled(frames + spread, sample_rate)],
+                            pos: 0,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        self.next_channel_idx = 0;
+    }
+
+    /// The reverb of `input` in `-1.0..=1.0` for the next channel.
+    fn process(&mut self, input: f32) -> f32 {
+        // Freeverb's mapping of room size and damping
+        let feedback = self.room_size * 0.28 + 0.7;
+        let damping = self.damping * 0.4;
+        let channel = &mut self.channels[self.next_channel_idx];
+        let input = input * FIXED_GAIN;
+        let mut output: f32 = channel
+            .combs
+            .iter_mut()
+            .map(|comb| comb.process(input, feedback, damping))
+            .sum();
+        for allpass in &mut channel.allpasses {
+            output = allpass.process(output);
+        }
+        self.next_channel_idx += 1;
+        if self.next_channel_idx >= self.channels.len() {
+            self.next_channel_idx = 0;
+        }
+        output * WET_SCALE
+    }
+
+    fn next_tail_sample(&mut self) -> NextSample {
+        let longest_delay = self.channels[0].combs.iter().map(|c| c.buffer.len()).max();
+        if self.next_channel_idx == 0 {
+            if self.silent_frames >= longest_delay.unwrap_or(0) {
+                return NextSample::Finished;
+            }
+            self.frame_silent = true;
+        }
+        let sample = to_i16(self.process(0.0) * self.mix);
+        self.frame_silent &= sample == 0;
+        if self.next_channel_idx == 0 {
+            if self.frame_silent {
+                self.silent_frames += 1;
+            } else {
+                self.silent_frames = 0;
+            }
+        }
+        NextSample::Sample(sample)
+    }
+}
+
+fn to_i16(value: f32) -> i16 {
+    (value * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+impl<S> Sound for Reverb<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.inner_finished {
+            return Ok(self.next_tail_sample());
+        }
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                let dry = s as f32 / i16::MAX as f32;
+                let wet = self.process(dry);
+                Ok(NextSample::Sample(to_i16(
+                    dry * (1.0 - self.mix) + wet * self.mix,
+                )))
+            }
+            NextSample::MetadataChanged => {
+                self.reset();
+                Ok(next)
+            }
+            NextSample::Paused => Ok(next),
+            NextSample::Finished => {
+                self.inner_finished = true;
+                self.silent_frames = 0;
+                Ok(self.next_tail_sample())
+            }
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S: Sound> Wrapper for Reverb<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/reverb.rs"]
+mod tests;
@@ -0,0 +1,178 @@
+use std::f64::consts::TAU;
+
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// The shape of an oscillator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Waveform {
+    /// A pure tone.
+    #[default]
+    Sine,
+    /// Alternates between 1.0 and -1.0 for half of each cycle.
+    Square,
+    /// Rises linearly from -1.0 to 1.0 and back.
+    Triangle,
+    /// Rises from -1.0 to 1.0 then drops back.
+    Sawtooth,
+}
+
+impl Waveform {
+    /// The value at `phase` in cycles in the range `0.0..1.0`.
+    fn value(self, phase: f64) -> f64 {
+        match self {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+        }
+    }
+}
+
+/// Ring modulation: multiply every channel of the inner sound by an
+/// oscillator for metallic or robotic effects.
+///
+/// A sine oscillator of frequency `f` turns each input frequency `x` into
+/// `x + f` and `x - f`. The result is mixed with the unmodified input by the
+/// wet/dry mix (1.0, fully wet, by default).
+pub struct RingMod<S: Sound> {
+    inner: S,
+    frequency: f32,
+    waveform: Waveform,
+    mix: f32,
+    /// The oscillator phase in cycles in the range `0.0..1.0`.
+    phase: f64,
+    /// The phase advance per frame.
+    increment: f64,
+    /// The oscillator value for the current frame.
+    carrier: f64,
+    next_channel_idx: u16,
+}
+
+impl<S> RingMod<S>
+where
+    S: Sound,
+{
+    /// Modulate `inner` with an oscillator of `frequency` Hz and `waveform`.
+    pub fn new(inner: S, frequency: f32, waveform: Waveform) -> Self {
+        let mut ring_mod = RingMod {
+            inner,
+            frequency,
+            waveform,
+            mix: 1.0,
+            phase: 0.0,
+            increment: 0.0,
+            carrier: 0.0,
+            next_channel_idx: 0,
+        };
+        ring_mod.update_increment();
+        ring_mod
+    }
+
+    /// The oscillator frequency in Hz.
+    pub fn frequency(&self) -> f32 {
+        self.frequency
+    }
+
+    /// Change the oscillator frequency. The oscillator stays phase
+    /// continuous.
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+        self.update_increment();
+    }
+
+    /// The oscillator waveform.
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    /// Change the oscillator waveform.
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+    }
+
+    /// The wet/dry mix where 0.0 is only the input and 1.0 is only the
+    /// modulated signal.
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Set the wet/dry mix. Clamped to `0.0..=1.0`.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    fn update_increment(&mut self) {
+        self.increment = self.frequency as f64 / self.inner.sample_rate() as f64;
+    }
+}
+
+impl<S> Sound for RingMod<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                if self.next_channel_idx == 0 {
+                    self.carrier = self.waveform.value(self.phase);
+                    self.phase = (self.phase + self.increment).rem_euclid(1.0);
+                }
+                self.next_channel_idx = (self.next_channel_idx + 1) % self.inner.channel_count();
+                let mix = self.mix as f64;
+                let value = s as f64 * ((1.0 - mix) + mix * self.carrier);
+                Ok(NextSample::Sample(value as i16))
+            }
+            NextSample::MetadataChanged => {
+                self.next_channel_idx = 0;
+                self.update_increment();
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for RingMod<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/ring_mod.rs"]
+mod tests;
@@ -0,0 +1,178 @@
+use crate::sounds::biquad::{Biquad, BiquadCoefficients};
+use crate::{NextSample, Sound};
+
+use super::{ChannelDelay, Wrapper};
+
+/// The corrections measured for one speaker by a room calibration. See
+/// [RoomCorrection].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChannelCorrection {
+    /// Gain in decibels.
+    pub gain_db: f32,
+    /// How many samples the channel is delayed by.
+    pub delay_samples: usize,
+    /// Whether the polarity of the channel is inverted.
+    pub inverted: bool,
+    /// Peaking filters applied in order.
+    pub eq: Vec<PeakingEq>,
+}
+
+/// A peaking filter boosting or cutting around a frequency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeakingEq {
+    /// The center frequency in Hz.
+    pub frequency: f32,
+    /// The gain at the center frequency in decibels.
+    pub gain_db: f32,
+    /// How narrow the filter is. Higher values affect fewer frequencies.
+    pub q: f32,
+}
+
+/// Apply a room calibration profile to a multichannel sound, correcting the
+/// gain, delay, polarity and frequency response of each speaker.
+///
+/// The correction at each index of the profile is applied to the channel at
+/// the same index. Channels without a correction are passed through
+/// unchanged. Delays are applied with a [ChannelDelay] so each delayed
+/// channel starts with silence and is played out after the inner sound
+/// finishes.
+///
+/// The filters are recalculated for the new sample rate and their state is
+/// reset when the inner sound returns `MetadataChanged`. The output is
+/// clipped to the range of i16.
+pub struct RoomCorrection<S: Sound> {
+    inner: ChannelDelay<S>,
+    profile: Vec<ChannelCorrection>,
+    /// The gain multiplier of each channel in the profile including the
+    /// polarity.
+    gains: Vec<f32>,
+    filters: Vec<Vec<Biquad>>,
+    next_channel_idx: usize,
+}
+
+impl<S> RoomCorrection<S>
+where
+    S: Sound,
+{
+    /// Correct each channel of `inner` by the correction at its index in
+    /// `profile`.
+    pub fn new(inner: S, profile: Vec<ChannelCorrection>) -> Self {
+        let delays = profile.iter().map(|c| c.delay_samples).collect();
+        let gains = profile
+            .iter()
+            .map(|c| {
+                let gain = 10f32.powf(c.gain_db / 20.0);
+                if c.inverted {
+                    -gain
+                } else {
+                    gain
+                }
+            })
+            .collect();
+        let mut correction = RoomCorrection {
+            inner: ChannelDelay::new(inner, delays),
+            profile,
+            gains,
+            filters: Vec::new(),
+            next_channel_idx: 0,
+        };
+        correction.reset();
+        correction
+    }
+
+    /// The correction of each channel.
+    pub fn profile(&self) -> &[ChannelCorrection] {
+        &self.profile
+    }
+
+    fn reset(&mut self) {
+        let sample_rate = self.inner.sample_rate() as f32;
+        self.filters = self
+            .profile
+            .iter()
+            .map(|c| {
+                c.eq.iter()
+                    .map(|eq| {
+                        Biquad::new(BiquadCoefficients::peaking(
+                            eq.frequency.clamp(1.0, sample_rate / 2.0 * 0.99),
+                            eq.gain_db,
+                            eq.q.max(f32::EPSILON),
+                            sample_rate,
+                        ))
+                    })
+                    .collect()
+            })
+            .collect();
+        self.next_channel_idx = 0;
+    }
+
+    fn correct(&mut self, sample: i16) -> i16 {
+        let channel = self.next_channel_idx;
+        self.next_channel_idx += 1;
+        if self.next_channel_idx >= self.inner.channel_count() as usize {
+            self.next_channel_idx = 0;
+        }
+        let (Some(gain), Some(filters)) = (self.gains.get(channel), self.filters.get_mut(channel))
+        else {
+            return sample;
+        };
+        let mut y = sample as f32;
+        for filter in filters {
+            y = filter.process(y);
+        }
+        (y * gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    }
+}
+
+impl<S> Sound for RoomCorrection<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        Ok(match next {
+            NextSample::Sample(s) => NextSample::Sample(self.correct(s)),
+            NextSample::MetadataChanged => {
+                self.reset();
+                next
+            }
+            NextSample::Paused | NextSample::Finished => next,
+        })
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S: Sound> Wrapper for RoomCorrection<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        self.inner.inner()
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        self.inner.inner_mut()
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner.into_inner()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/room_correction.rs"]
+mod tests;
@@ -1,4 +1,4 @@
-use crate::{NextSample, Sound};
+use crate::{utils, NextSample, Sound};
 
 use super::Wrapper;
 
@@ -79,19 +79,7 @@ where
         assert!(from_rate >= 1);
         assert!(self.to_rate >= 1);
 
-        // finding greatest common divisor
-        let gcd = {
-            #[inline]
-            fn gcd(a: u32, b: u32) -> u32 {
-                if b == 0 {
-                    a
-                } else {
-                    gcd(b, a % b)
-                }
-            }
-
-            gcd(from_rate, self.to_rate)
-        };
+        let gcd = utils::gcd(from_rate, self.to_rate);
 
         // These will get filled on the first or next call to next_sample
         self.current_frame = Vec::new();
@@ -317,6 +305,10 @@ where
     fn on_start_of_batch(&mut self) {
         self.inner.on_start_of_batch()
     }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
 }
 
 impl<S: Sound> Wrapper for SampleRateConverter<S> {
@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use crate::sounds::UnsupportedMetadataChangeError;
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Lower the level of the inner sound while a key sound is loud, e.g. to
+/// make a pad pump in time with a kick drum.
+///
+/// The key is played in lockstep with the inner sound but not heard. Its
+/// peak level is followed with an attack (1ms by default) and release
+/// (150ms by default) and the inner sound is lowered in proportion to it:
+/// once the followed level reaches the threshold (0.5 of full scale by
+/// default) the inner sound is lowered by the full depth (1.0, silence, by
+/// default).
+///
+/// The key only needs the same sample rate as the inner sound since only its
+/// level is used. Once the key has finished or while it is paused the inner
+/// sound is released back to its full level. If either sound changes to a
+/// different sample rate an IoError of ErrorKind::Other with a
+/// UnsupportedMetadataChangeError is returned.
+pub struct SidechainGate<S: Sound> {
+    inner: S,
+    key: Box<dyn Sound>,
+    key_finished: bool,
+    depth: f32,
+    threshold: f32,
+    attack: Duration,
+    release: Duration,
+    attack_coefficient: f32,
+    release_coefficient: f32,
+    envelope: f32,
+    gain: f32,
+    next_channel_idx: u16,
+    key_frame: Vec<i16>,
+}
+
+impl<S> SidechainGate<S>
+where
+    S: Sound,
+{
+    /// Lower `inner` while `key` is loud.
+    ///
+    /// An error is returned if `key` does not have the same sample rate as
+    /// `inner`.
+    pub fn new(inner: S, key: Box<dyn Sound>) -> Result<Self, crate::Error> {
+        if key.sample_rate() != inner.sample_rate() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "the key must have the same sample rate as the inner sound",
+            )
+            .into());
+        }
+        let mut gate = SidechainGate {
+            inner,
+            key,
+            key_finished: false,
+            depth: 1.0,
+            threshold: 0.5,
+            attack: Duration::from_millis(1),
+            release: Duration::from_millis(150),
+            attack_coefficient: 0.0,
+            release_coefficient: 0.0,
+            envelope: 0.0,
+            gain: 1.0,
+            next_channel_idx: 0,
+            key_frame: Vec::new(),
+        };
+        gate.update_coefficients();
+        Ok(gate)
+    }
+
+    /// Set how much the inner sound is lowered at most. 1.0 lowers it to
+    /// silence, 0.0 leaves it unchanged.
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth.clamp(0.0, 1.0);
+    }
+
+    /// Set the key level as a fraction of full scale at which the inner
+    /// sound is lowered by the full depth.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold.max(f32::EPSILON);
+    }
+
+    /// Set how quickly the inner sound is lowered when the key gets louder
+    /// and raised when it gets quieter.
+    pub fn set_attack_release(&mut self, attack: Duration, release: Duration) {
+        self.attack = attack;
+        self.release = release;
+        self.update_coefficients();
+    }
+
+    /// The gain currently applied to the inner sound.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    fn update_coefficients(&mut self) {
+        let sample_rate = self.inner.sample_rate() as f32;
+        let coefficient = |duration: Duration| {
+            let frames = duration.as_secs_f32() * sample_rate;
+            if frames > 0.0 {
+                (-1.0 / frames).exp()
+            } else {
+                0.0
+            }
+        };
+        self.attack_coefficient = coefficient(self.attack);
+        self.release_coefficient = coefficient(self.release);
+    }
+
+    /// Pull the next key frame and update the gain from its level.
+    fn next_key_frame(&mut self) -> Result<(), crate::Error> {
+        let mut level = 0.0;
+        while !self.key_finished {
+            self.key_frame.clear();
+            match self.key.append_next_frame_to(&mut self.key_frame) {
+                Ok(()) => {
+                    let peak = self.key_frame.iter().map(|s| s.unsigned_abs()).max();
+                    level = peak.unwrap_or(0) as f32 / i16::MAX as f32;
+                    break;
+                }
+                Err(Ok(NextSample::MetadataChanged)) => {
+                    if self.key.sample_rate() != self.inner.sample_rate() {
+                        return Err(crate::Error::IoError(std::io::Error::other(
+                            UnsupportedMetadataChangeError {},
+                        )));
+                    }
+                }
+                Err(Ok(NextSample::Finished)) => self.key_finished = true,
+                Err(Ok(NextSample::Paused)) | Err(Ok(NextSample::Sample(_))) => break,
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        let coefficient = if level > self.envelope {
+            self.attack_coefficient
+        } else {
+            self.release_coefficient
+        };
+        self.envelope = level + (self.envelope - level) * coefficient;
+        self.gain = 1.0 - self.depth * (self.envelope / self.threshold).min(1.0);
+        Ok(())
+    }
+}
+
+impl<S> Sound for SidechainGate<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                if self.next_channel_idx == 0 {
+                    self.next_key_frame()?;
+                }
+                self.next_channel_idx += 1;
+                if self.next_channel_idx >= self.inner.channel_count() {
+                    self.next_channel_idx = 0;
+                }
+                Ok(NextSample::Sample((s as f32 * self.gain) as i16))
+            }
+            NextSample::MetadataChanged => {
+                if self.key.sample_rate() != self.inner.sample_rate() {
+                    return Err(crate::Error::IoError(std::io::Error::other(
+                        UnsupportedMetadataChangeError {},
+                    )));
+                }
+                self.next_channel_idx = 0;
+                Ok(next)
+            }
+            NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch();
+        self.key.on_start_of_batch();
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner
+            .estimated_cost()
+            .max(self.key.estimated_cost())
+            .max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for SidechainGate<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/sidechain_gate.rs"]
+mod tests;
@@ -0,0 +1,379 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use crate::sounds::UnsupportedMetadataChangeError;
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// The most filter phases whose weights are kept. Rates with more phases
+/// compute the weights of each output frame as it is needed.
+const MAX_CACHED_PHASES: u64 = 1024;
+
+/// How to trade CPU usage for quality when converting sample rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// Linear interpolation between neighbouring frames using
+    /// [SampleRateConverter][super::SampleRateConverter]. Very cheap but high
+    /// frequencies alias when converting to a lower sample rate.
+    Linear,
+    /// Windowed sinc interpolation using
+    /// [SincSampleRateConverter]. Each output frame is computed from `taps`
+    /// input frames (more when converting to a lower sample rate). More taps
+    /// give a sharper low pass filter at a higher CPU cost.
+    Sinc {
+        /// The number of input frames used for each output frame.
+        taps: u16,
+    },
+}
+
+impl ResampleQuality {
+    /// A good balance between CPU usage and quality. This is the default.
+    pub const BALANCED: ResampleQuality = ResampleQuality::Sinc { taps: 16 };
+}
+
+impl Default for ResampleQuality {
+    fn default() -> Self {
+        ResampleQuality::BALANCED
+    }
+}
+
+/// Convert a Sound from one sample rate to another using windowed sinc
+/// interpolation.
+///
+/// Higher quality but more expensive than
+/// [SampleRateConverter][super::SampleRateConverter]. When converting to a
+/// lower sample rate the frequencies above the new Nyquist frequency are
+/// filtered out instead of aliasing.
+///
+/// If the inner sound changes its channel count or sample rate, any input
+/// frames not yet used for output are dropped. If it changes its sample rate
+/// to 0 an IoError of ErrorKind::Other with a UnsupportedMetadataChangeError
+/// is returned.
+pub struct SincSampleRateConverter<S: Sound> {
+    inner: S,
+    to_rate: u32,
+    from_rate: u32,
+    channel_count: u16,
+    taps: u16,
+    /// Input frames (interleaved) still needed for upcoming output frames.
+    history: VecDeque<f32>,
+    /// The input frame index of the first frame in `history`.
+    first_frame_idx: u64,
+    /// The number of input frames pulled from `inner` since the last reset.
+    frames_read: u64,
+    /// The index of the next output frame since the last reset.
+    next_output_frame: u64,
+    inner_finished: bool,
+    /// Remaining samples of the current output frame stored in reverse.
+    output_frame: Vec<i16>,
+    input_frame: Vec<i16>,
+    /// The number of distinct fractional positions of output frames between
+    /// input frames.
+    phase_count: u64,
+    /// How many phases each output frame advances.
+    phase_step: u64,
+    /// The filter of each phase, or only the current one if there are more
+    /// than [MAX_CACHED_PHASES].
+    kernels: Vec<Kernel>,
+    /// The weights of every kernel.
+    weights: Vec<f64>,
+    /// The per channel sums of the frame being computed.
+    sums: Vec<f64>,
+}
+
+/// The filter weights of one phase.
+struct Kernel {
+    /// The input frame of the first weight relative to the input frame at or
+    /// before the output frame.
+    first: i64,
+    /// The range of the weights in `weights`.
+    start: usize,
+    len: usize,
+    weight_sum: f64,
+}
+
+impl<S> SincSampleRateConverter<S>
+where
+    S: Sound,
+{
+    /// Create a new SincSampleRateConverter with an output sample rate of
+    /// `to_rate` using `taps` input frames per output frame.
+    ///
+    /// An error is returned if `taps` is less than 2 or either sample rate is
+    /// 0.
+    pub fn new(
+        inner: S,
+        to_rate: u32,
+        taps: u16,
+    ) -> Result<SincSampleRateConverter<S>, crate::Error> {
+        if taps < 2 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "taps must be at least 2",
+            )
+            .into());
+        }
+        if to_rate == 0 || inner.sample_rate() == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "sample rates must be at least 1",
+            )
+            .into());
+        }
+        let mut new = SincSampleRateConverter {
+            from_rate: inner.sample_rate(),
+            channel_count: inner.channel_count(),
+            inner,
+            to_rate,
+            taps,
+            history: VecDeque::new(),
+            first_frame_idx: 0,
+            frames_read: 0,
+            next_output_frame: 0,
+            inner_finished: false,
+            output_frame: Vec::new(),
+            input_frame: Vec::new(),
+            phase_count: 1,
+            phase_step: 1,
+            kernels: Vec::new(),
+            weights: Vec::new(),
+            sums: Vec::new(),
+        };
+        new.reset()?;
+        Ok(new)
+    }
+
+    fn reset(&mut self) -> Result<(), crate::Error> {
+        self.from_rate = self.inner.sample_rate();
+        self.channel_count = self.inner.channel_count();
+        if self.from_rate == 0 {
+            return Err(crate::Error::IoError(std::io::Error::other(
+                UnsupportedMetadataChangeError {},
+            )));
+        }
+        self.history.clear();
+        self.first_frame_idx = 0;
+        self.frames_read = 0;
+        self.next_output_frame = 0;
+        self.inner_finished = false;
+        self.output_frame.clear();
+
+        let gcd = utils::gcd(self.from_rate, self.to_rate);
+        self.phase_count = (self.to_rate / gcd) as u64;
+        self.phase_step = (self.from_rate / gcd) as u64;
+        self.kernels.clear();
+        self.weights.clear();
+        if self.phase_count <= MAX_CACHED_PHASES {
+            for phase in 0..self.phase_count {
+                self.push_kernel(phase);
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the filter for output frames `phase / phase_count` of the way
+    /// from one input frame to the next and add it to `kernels`.
+    fn push_kernel(&mut self, phase: u64) {
+        let offset = phase as f64 / self.phase_count as f64;
+        let cutoff = self.cutoff();
+        let radius = self.radius();
+        let first = (offset - radius).floor() as i64 + 1;
+        let last = (offset + radius).ceil() as i64 - 1;
+        let start = self.weights.len();
+        let mut weight_sum = 0.0;
+        for frame_idx in first..=last {
+            let x = offset - frame_idx as f64;
+            let weight = cutoff * sinc(cutoff * x) * blackman(x / radius);
+            weight_sum += weight;
+            self.weights.push(weight);
+        }
+        self.kernels.push(Kernel {
+            first,
+            start,
+            len: self.weights.len() - start,
+            weight_sum,
+        });
+    }
+
+    /// The cutoff of the low pass filter relative to the input Nyquist
+    /// frequency.
+    fn cutoff(&self) -> f64 {
+        (self.to_rate as f64 / self.from_rate as f64).min(1.0)
+    }
+
+    /// Half the width of the filter in input frames.
+    fn radius(&self) -> f64 {
+        self.taps as f64 / 2.0 / self.cutoff()
+    }
+
+    /// Compute the next output frame into `output_frame`.
+    ///
+    /// Returns `None` when the frame is ready otherwise what should be
+    /// returned instead.
+    fn compute_frame(&mut self) -> Result<Option<NextSample>, crate::Error> {
+        let channel_count = self.channel_count as usize;
+        // The output frame is at input frame `base + phase / phase_count`
+        let position = self.next_output_frame * self.phase_step;
+        let base = position / self.phase_count;
+        let phase = position % self.phase_count;
+        let time = base as f64 + phase as f64 / self.phase_count as f64;
+        let radius = self.radius();
+
+        // Make sure every input frame within the filter has been read
+        let last_needed = (time + radius).floor() as u64;
+        while !self.inner_finished && self.frames_read <= last_needed {
+            self.input_frame.clear();
+            match self.inner.append_next_frame_to(&mut self.input_frame) {
+                Ok(()) => {
+                    self.history
+                        .extend(self.input_frame.iter().map(|s| *s as f32));
+                    self.frames_read += 1;
+                }
+                Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                Err(Ok(NextSample::MetadataChanged)) => {
+                    let channel_count_changed = self.inner.channel_count() != self.channel_count;
+                    self.reset()?;
+                    if channel_count_changed {
+                        return Ok(Some(NextSample::MetadataChanged));
+                    }
+                    return self.compute_frame();
+                }
+                Err(Ok(NextSample::Paused)) => return Ok(Some(NextSample::Paused)),
+                Err(Ok(NextSample::Finished)) => self.inner_finished = true,
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        if self.inner_finished && time >= self.frames_read as f64 {
+            return Ok(Some(NextSample::Finished));
+        }
+
+        // Drop input frames that are no longer needed
+        let first_needed = (time - radius).ceil().max(0.0) as u64;
+        while self.first_frame_idx < first_needed && !self.history.is_empty() {
+            self.history.drain(..channel_count);
+            self.first_frame_idx += 1;
+        }
+
+        let kernel_idx = if self.phase_count <= MAX_CACHED_PHASES {
+            phase as usize
+        } else {
+            self.kernels.clear();
+            self.weights.clear();
+            self.push_kernel(phase);
+            0
+        };
+        let kernel = &self.kernels[kernel_idx];
+        let weights = &self.weights[kernel.start..kernel.start + kernel.len];
+        self.sums.clear();
+        self.sums.resize(channel_count, 0.0);
+        let first = base as i64 + kernel.first;
+        for (frame_idx, weight) in (first..).zip(weights) {
+            if frame_idx < self.first_frame_idx as i64 {
+                continue;
+            }
+            let offset = (frame_idx as u64 - self.first_frame_idx) as usize * channel_count;
+            if offset >= self.history.len() {
+                continue;
+            }
+            for (channel, sum) in self.sums.iter_mut().enumerate() {
+                *sum += self.history[offset + channel] as f64 * weight;
+            }
+        }
+
+        self.output_frame.clear();
+        for sum in self.sums.iter().rev() {
+            let sample = if kernel.weight_sum != 0.0 {
+                sum / kernel.weight_sum
+            } else {
+                0.0
+            };
+            self.output_frame
+                .push(sample.clamp(i16::MIN as f64, i16::MAX as f64).round() as i16);
+        }
+        self.next_output_frame += 1;
+        Ok(None)
+    }
+
+    /// Unwrap the inner Sound.
+    ///
+    /// It is guaranteed that the inner Sound is at the start of a Frame.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Sound for SincSampleRateConverter<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.to_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output_frame.pop() {
+            return Ok(NextSample::Sample(sample));
+        }
+        if self.from_rate == self.to_rate {
+            let next = self.inner.next_sample()?;
+            if let NextSample::MetadataChanged = next {
+                self.reset()?;
+            }
+            return Ok(next);
+        }
+        if let Some(special) = self.compute_frame()? {
+            return Ok(special);
+        }
+        Ok(NextSample::Sample(self.output_frame.pop().unwrap()))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::High)
+    }
+}
+
+impl<S: Sound> Wrapper for SincSampleRateConverter<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+pub(super) fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// A Blackman window over `-1.0..=1.0`.
+pub(super) fn blackman(x: f64) -> f64 {
+    if x.abs() >= 1.0 {
+        0.0
+    } else {
+        0.42 + 0.5 * (PI * x).cos() + 0.08 * (2.0 * PI * x).cos()
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/sinc_sample_rate_converter.rs"]
+mod tests;
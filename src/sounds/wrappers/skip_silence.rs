@@ -0,0 +1,223 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// Time constant of the short-term RMS measurement.
+const RMS_WINDOW_SECS: f32 = 0.02;
+/// Silent audio kept from right before the end of a skipped region so the
+/// start of the following sound is not cut off while the RMS measurement
+/// catches up.
+const PREROLL_SECS: f32 = 0.02;
+
+/// Skip long regions of silence (e.g. dead air in a podcast).
+///
+/// The inner sound is considered silent while its short-term RMS level, as a
+/// fraction of full scale, is below a threshold (0.01 by default). Once
+/// silence has lasted `min_silence` the rest of the silent region is
+/// discarded by pulling samples from the inner sound as fast as possible so
+/// any Sound can be used. Shorter silences are played unchanged.
+///
+/// A `MetadataChanged` from the inner sound that does not change the channel
+/// count or sample rate is not returned.
+///
+/// By default none of a long silence is kept. Use
+/// [set_pad][SkipSilence::set_pad] to keep some of its start so sounds are
+/// not played back to back.
+pub struct SkipSilence<S: Sound> {
+    inner: S,
+    channel_count: u16,
+    sample_rate: u32,
+    min_silence: Duration,
+    pad: Duration,
+    threshold: f32,
+    mean_square: f32,
+    rms_coefficient: f32,
+    /// The number of consecutive silent frames.
+    silent_frames: u64,
+    /// Interleaved silent samples that are held back until it is known if
+    /// they are part of a long silence.
+    held: VecDeque<i16>,
+    skipping: bool,
+    skipped_frames: u64,
+    output: VecDeque<i16>,
+    frame: Vec<i16>,
+}
+
+impl<S> SkipSilence<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` and skip silences lasting longer than `min_silence`.
+    pub fn new(inner: S, min_silence: Duration) -> Self {
+        let mut skip_silence = SkipSilence {
+            channel_count: inner.channel_count(),
+            sample_rate: inner.sample_rate(),
+            inner,
+            min_silence,
+            pad: Duration::ZERO,
+            threshold: 0.01,
+            mean_square: 0.0,
+            rms_coefficient: 0.0,
+            silent_frames: 0,
+            held: VecDeque::new(),
+            skipping: false,
+            skipped_frames: 0,
+            output: VecDeque::new(),
+            frame: Vec::new(),
+        };
+        skip_silence.update_coefficient();
+        skip_silence
+    }
+
+    /// Keep the first `pad` of each skipped silence. Values longer than
+    /// `min_silence` are treated as `min_silence`.
+    pub fn set_pad(&mut self, pad: Duration) {
+        self.pad = pad;
+    }
+
+    /// Set the RMS level, as a fraction of full scale, below which the inner
+    /// sound is considered silent.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// The total duration of audio that has been skipped so far.
+    ///
+    /// Measured using the current sample rate.
+    pub fn skipped(&self) -> Duration {
+        utils::num_samples_to_duration(self.skipped_frames, 1, self.sample_rate)
+    }
+
+    fn update_coefficient(&mut self) {
+        let samples_per_sec = self.sample_rate as f32 * self.channel_count as f32;
+        self.rms_coefficient = 1.0 - (-1.0 / (RMS_WINDOW_SECS * samples_per_sec)).exp();
+    }
+
+    fn duration_to_frames(&self, duration: Duration) -> u64 {
+        utils::duration_to_num_samples(duration, 1, self.sample_rate)
+    }
+
+    /// Handle the frame in `self.frame`.
+    fn process_frame(&mut self) {
+        for sample in &self.frame {
+            let level = *sample as f32 / i16::MAX as f32;
+            self.mean_square += self.rms_coefficient * (level * level - self.mean_square);
+        }
+
+        if self.mean_square.sqrt() >= self.threshold {
+            self.silent_frames = 0;
+            self.skipping = false;
+            self.output.extend(self.held.drain(..));
+            self.output.extend(self.frame.iter());
+            return;
+        }
+
+        self.silent_frames += 1;
+        let min_silence_frames = self.duration_to_frames(self.min_silence);
+        let pad_frames = self.duration_to_frames(self.pad).min(min_silence_frames);
+        if self.skipping {
+            self.held.extend(self.frame.iter());
+            let preroll_len = (PREROLL_SECS * self.sample_rate as f32) as usize * self.frame.len();
+            while self.held.len() > preroll_len {
+                self.held.drain(..self.frame.len());
+                self.skipped_frames += 1;
+            }
+        } else if self.silent_frames <= pad_frames {
+            self.output.extend(self.frame.iter());
+        } else if self.silent_frames >= min_silence_frames {
+            self.skipping = true;
+            self.skipped_frames += (self.held.len() / self.frame.len()) as u64 + 1;
+            self.held.clear();
+        } else {
+            self.held.extend(self.frame.iter());
+        }
+    }
+}
+
+impl<S> Sound for SkipSilence<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        loop {
+            if let Some(sample) = self.output.pop_front() {
+                return Ok(NextSample::Sample(sample));
+            }
+            self.frame.clear();
+            match self.inner.append_next_frame_to(&mut self.frame) {
+                Ok(()) => self.process_frame(),
+                Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                Err(Ok(NextSample::MetadataChanged)) => {
+                    if self.inner.channel_count() == self.channel_count
+                        && self.inner.sample_rate() == self.sample_rate
+                    {
+                        // Keep measuring the silence across the change
+                        continue;
+                    }
+                    self.channel_count = self.inner.channel_count();
+                    self.sample_rate = self.inner.sample_rate();
+                    // Any held samples are silent so drop them instead of
+                    // returning them in the new format.
+                    self.held.clear();
+                    self.silent_frames = 0;
+                    self.skipping = false;
+                    self.update_coefficient();
+                    return Ok(NextSample::MetadataChanged);
+                }
+                Err(Ok(NextSample::Paused)) => return Ok(NextSample::Paused),
+                Err(Ok(NextSample::Finished)) => {
+                    if self.skipping {
+                        self.skipped_frames +=
+                            (self.held.len() / self.channel_count as usize) as u64;
+                        self.held.clear();
+                    }
+                    if self.held.is_empty() {
+                        return Ok(NextSample::Finished);
+                    }
+                    // Play a silence that ended up being too short to skip
+                    self.output.extend(self.held.drain(..));
+                }
+                Err(Err(e)) => return Err(e),
+            }
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for SkipSilence<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/skip_silence.rs"]
+mod tests;
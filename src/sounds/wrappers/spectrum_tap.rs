@@ -0,0 +1,222 @@
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex};
+
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// The window applied to each block before its FFT is computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowFunction {
+    /// No window. Best frequency resolution but the most leakage between
+    /// bins.
+    Rectangular,
+    /// A good general purpose window.
+    #[default]
+    Hann,
+    /// Less leakage than Hann at the cost of wider peaks.
+    Blackman,
+}
+
+impl WindowFunction {
+    pub(crate) fn coefficients(self, size: usize) -> Vec<f32> {
+        let n = size as f32;
+        (0..size)
+            .map(|i| {
+                let x = 2.0 * PI * i as f32 / n;
+                match self {
+                    WindowFunction::Rectangular => 1.0,
+                    WindowFunction::Hann => 0.5 - 0.5 * x.cos(),
+                    WindowFunction::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Pass audio through unchanged while computing its magnitude spectrum for
+/// visualizers.
+///
+/// All channels are averaged to mono and the FFT of the latest `size` frames
+/// is computed with a window function every `size / 2` frames. The result is
+/// read from the [SpectrumHandle] returned when the tap is created.
+///
+/// The FFT is computed on the thread pulling samples so larger sizes will add
+/// more work to some `next_sample` calls. The handle is never waited on: if
+/// it is locked when a new spectrum is ready that update is skipped.
+pub struct SpectrumTap<S: Sound> {
+    inner: S,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    window_sum: f32,
+    /// The last `size` mono samples with the oldest at `history_pos`.
+    history: Vec<f32>,
+    history_pos: usize,
+    /// Frames since the last FFT.
+    frames_since_fft: usize,
+    frame_sum: f32,
+    next_channel_idx: u16,
+    scratch: Vec<Complex<f32>>,
+    shared: Arc<Mutex<Spectrum>>,
+}
+
+/// Reads the spectrum computed by a [SpectrumTap].
+///
+/// Can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct SpectrumHandle {
+    shared: Arc<Mutex<Spectrum>>,
+}
+
+struct Spectrum {
+    magnitudes: Vec<f32>,
+    sample_rate: u32,
+    size: usize,
+}
+
+impl<S> SpectrumTap<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` computing FFTs of `size` frames.
+    ///
+    /// Panics if `size` is less than 2.
+    pub fn new(inner: S, size: usize, window: WindowFunction) -> (Self, SpectrumHandle) {
+        assert!(size >= 2, "size must be at least 2");
+        let window = window.coefficients(size);
+        let shared = Arc::new(Mutex::new(Spectrum {
+            magnitudes: vec![0.0; size / 2 + 1],
+            sample_rate: inner.sample_rate(),
+            size,
+        }));
+        let tap = SpectrumTap {
+            inner,
+            fft: FftPlanner::new().plan_fft_forward(size),
+            window_sum: window.iter().sum(),
+            window,
+            history: vec![0.0; size],
+            history_pos: 0,
+            frames_since_fft: 0,
+            frame_sum: 0.0,
+            next_channel_idx: 0,
+            scratch: Vec::with_capacity(size),
+            shared: shared.clone(),
+        };
+        (tap, SpectrumHandle { shared })
+    }
+
+    fn push_frame(&mut self, value: f32) {
+        let size = self.history.len();
+        self.history[self.history_pos] = value;
+        self.history_pos = (self.history_pos + 1) % size;
+        self.frames_since_fft += 1;
+        if self.frames_since_fft < size / 2 {
+            return;
+        }
+        self.frames_since_fft = 0;
+
+        self.scratch.clear();
+        let (newest, oldest) = self.history.split_at(self.history_pos);
+        self.scratch.extend(
+            oldest
+                .iter()
+                .chain(newest)
+                .zip(&self.window)
+                .map(|(s, w)| Complex::new(s * w, 0.0)),
+        );
+        self.fft.process(&mut self.scratch);
+
+        let Ok(mut spectrum) = self.shared.try_lock() else {
+            return;
+        };
+        // Scale so a full scale sine wave has a magnitude of 1.0
+        let scale = 2.0 / self.window_sum;
+        for (magnitude, bin) in spectrum.magnitudes.iter_mut().zip(&self.scratch) {
+            *magnitude = bin.norm() * scale;
+        }
+    }
+}
+
+impl SpectrumHandle {
+    /// The magnitude of each frequency bin of the latest spectrum where 1.0 is
+    /// a full scale sine wave.
+    ///
+    /// There are `size / 2 + 1` bins from 0 Hz to the Nyquist frequency. All
+    /// magnitudes are 0.0 until the first `size / 2` frames have been played.
+    pub fn magnitudes(&self) -> Vec<f32> {
+        self.shared.lock().unwrap().magnitudes.clone()
+    }
+
+    /// The center frequency in Hz of `bin`.
+    pub fn bin_frequency(&self, bin: usize) -> f32 {
+        let spectrum = self.shared.lock().unwrap();
+        bin as f32 * spectrum.sample_rate as f32 / spectrum.size as f32
+    }
+}
+
+impl<S> Sound for SpectrumTap<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                self.frame_sum += s as f32 / i16::MAX as f32;
+                self.next_channel_idx += 1;
+                let channel_count = self.inner.channel_count();
+                if self.next_channel_idx >= channel_count {
+                    let value = self.frame_sum / channel_count as f32;
+                    self.frame_sum = 0.0;
+                    self.next_channel_idx = 0;
+                    self.push_frame(value);
+                }
+            }
+            NextSample::MetadataChanged => {
+                self.frame_sum = 0.0;
+                self.next_channel_idx = 0;
+                self.shared.lock().unwrap().sample_rate = self.inner.sample_rate();
+            }
+            NextSample::Paused | NextSample::Finished => (),
+        }
+        Ok(next)
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S: Sound> Wrapper for SpectrumTap<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/spectrum_tap.rs"]
+mod tests;
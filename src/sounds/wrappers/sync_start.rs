@@ -0,0 +1,98 @@
+use std::time::Instant;
+
+use crate::{utils, NextSample, Sound};
+
+use super::Wrapper;
+
+/// Start the inner sound at a wall clock instant, e.g. so players on several
+/// machines with synchronized clocks start together.
+///
+/// When the first sample is requested the time left until `start` is
+/// converted to frames at the sample rate of the inner sound and that many
+/// frames of silence are returned before the inner sound is pulled. Since the
+/// consumer pulls samples at the sample rate the inner sound is heard at
+/// `start` plus the output latency, which is the same on machines with the
+/// same setup.
+///
+/// If `start` has already passed when the first sample is requested the
+/// inner sound plays immediately.
+pub struct SyncStart<S: Sound> {
+    inner: S,
+    start: Instant,
+    /// Silent samples still to be returned. `None` until the first sample is
+    /// requested.
+    silence_remaining: Option<u64>,
+}
+
+impl<S> SyncStart<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` so it starts at `start`.
+    pub fn new(inner: S, start: Instant) -> Self {
+        SyncStart {
+            inner,
+            start,
+            silence_remaining: None,
+        }
+    }
+
+    /// The instant the inner sound starts.
+    pub fn start(&self) -> Instant {
+        self.start
+    }
+}
+
+impl<S> Sound for SyncStart<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let silence_remaining = self.silence_remaining.get_or_insert_with(|| {
+            let wait = self.start.saturating_duration_since(Instant::now());
+            let frames = utils::duration_to_num_samples(wait, 1, self.inner.sample_rate());
+            frames * self.inner.channel_count() as u64
+        });
+        if *silence_remaining > 0 {
+            *silence_remaining -= 1;
+            return Ok(NextSample::Sample(0));
+        }
+        self.inner.next_sample()
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for SyncStart<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/sync_start.rs"]
+mod tests;
@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::{FadeCurve, Wrapper};
+
+/// Fade the end of the inner sound to silence so it does not click if it
+/// finishes mid-waveform.
+///
+/// The last `duration` of the inner sound is faded out. To know where the end
+/// is, `duration` worth of frames are read ahead of the samples being
+/// returned. No delay is added but samples are held while the inner sound is
+/// paused.
+///
+/// When the inner sound changes its channel count or sample rate the read
+/// ahead samples are returned without fading before `MetadataChanged`.
+pub struct TailFade<S: Sound> {
+    inner: S,
+    duration: Duration,
+    curve: FadeCurve,
+    fade_frames: u64,
+    channel_count: u16,
+    sample_rate: u32,
+    /// Interleaved samples that have been read ahead.
+    buffer: VecDeque<i16>,
+    /// The inner sound has finished and `buffer` is being faded out.
+    finishing: bool,
+    /// A MetadataChanged to return once `buffer` is empty.
+    metadata_changed: bool,
+    frame: Vec<i16>,
+}
+
+impl<S> TailFade<S>
+where
+    S: Sound,
+{
+    /// Fade out the last `duration` of `inner` using `curve`.
+    pub fn new(inner: S, duration: Duration, curve: FadeCurve) -> Self {
+        let mut tail_fade = TailFade {
+            channel_count: inner.channel_count(),
+            sample_rate: inner.sample_rate(),
+            inner,
+            duration,
+            curve,
+            fade_frames: 0,
+            buffer: VecDeque::new(),
+            finishing: false,
+            metadata_changed: false,
+            frame: Vec::new(),
+        };
+        tail_fade.fade_frames = utils::duration_to_num_samples(duration, 1, tail_fade.sample_rate);
+        tail_fade
+    }
+
+    /// Return the curve used for the fade.
+    pub fn curve(&self) -> FadeCurve {
+        self.curve
+    }
+
+    /// Return the length of the fade.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Read frames until `fade_frames` are buffered beyond the next frame to
+    /// return or the inner sound returns something other than a sample.
+    fn fill_buffer(&mut self) -> Result<Option<NextSample>, crate::Error> {
+        let target = self.fade_frames as usize * self.channel_count as usize;
+        while self.buffer.len() <= target {
+            self.frame.clear();
+            match self.inner.append_next_frame_to(&mut self.frame) {
+                Ok(()) => self.buffer.extend(self.frame.iter()),
+                Err(Ok(special)) => return Ok(Some(special)),
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+
+    fn pop_faded(&mut self) -> Option<i16> {
+        let channel_count = self.channel_count as usize;
+        // Including the frame of the sample being returned
+        let remaining_frames = self.buffer.len().div_ceil(channel_count);
+        let sample = self.buffer.pop_front()?;
+        let progress = if self.fade_frames == 0 {
+            0.0
+        } else {
+            (remaining_frames - 1) as f32 / self.fade_frames as f32
+        };
+        Some((sample as f32 * self.curve.gain(progress)) as i16)
+    }
+}
+
+impl<S> Sound for TailFade<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.channel_count
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if self.finishing {
+            return Ok(match self.pop_faded() {
+                Some(sample) => NextSample::Sample(sample),
+                None => NextSample::Finished,
+            });
+        }
+        if self.metadata_changed {
+            if let Some(sample) = self.buffer.pop_front() {
+                return Ok(NextSample::Sample(sample));
+            }
+            self.metadata_changed = false;
+            self.channel_count = self.inner.channel_count();
+            self.sample_rate = self.inner.sample_rate();
+            self.fade_frames = utils::duration_to_num_samples(self.duration, 1, self.sample_rate);
+            return Ok(NextSample::MetadataChanged);
+        }
+        match self.fill_buffer()? {
+            None | Some(NextSample::Sample(_)) => (),
+            Some(NextSample::Finished) => {
+                self.finishing = true;
+                return self.next_sample();
+            }
+            Some(NextSample::MetadataChanged) => {
+                self.metadata_changed = true;
+                return self.next_sample();
+            }
+            Some(NextSample::Paused) => return Ok(NextSample::Paused),
+        }
+        Ok(NextSample::Sample(self.buffer.pop_front().unwrap()))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost()
+    }
+}
+
+impl<S: Sound> Wrapper for TailFade<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/tail_fade.rs"]
+mod tests;
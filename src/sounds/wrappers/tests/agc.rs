@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use super::*;
+use crate::sounds::{Silence, SineWav, SoundList};
+
+const SAMPLE_RATE: u32 = 8000;
+
+fn rms_of_next(sound: &mut impl Sound, num_samples: usize) -> f32 {
+    let mut sum = 0.0;
+    for _ in 0..num_samples {
+        let NextSample::Sample(s) = sound.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        let level = s as f32 / i16::MAX as f32;
+        sum += level * level;
+    }
+    (sum / num_samples as f32).sqrt()
+}
+
+fn segment(amplitude: f32) -> Box<dyn Sound> {
+    Box::new(
+        SineWav::with_sample_rate(200.0, SAMPLE_RATE)
+            .with_adjustable_volume_of(amplitude)
+            .finish_after(Duration::from_secs(2)),
+    )
+}
+
+#[test]
+fn quiet_and_loud_converge_to_target() {
+    let target = 0.1;
+    let list = SoundList::from(vec![segment(0.03), segment(0.6)]);
+    let mut agc = list.with_auto_gain_control(target);
+
+    let segment_len = 2 * SAMPLE_RATE as usize;
+    let measure_len = SAMPLE_RATE as usize / 2;
+
+    rms_of_next(&mut agc, segment_len - measure_len);
+    let quiet = rms_of_next(&mut agc, measure_len);
+    assert!((quiet - target).abs() < target * 0.1, "quiet rms {quiet}");
+
+    assert_eq!(agc.next_sample().unwrap(), NextSample::MetadataChanged);
+    rms_of_next(&mut agc, segment_len - measure_len);
+    let loud = rms_of_next(&mut agc, measure_len);
+    assert!((loud - target).abs() < target * 0.1, "loud rms {loud}");
+}
+
+#[test]
+fn silence_holds_gain() {
+    let mut agc = Agc::new(Silence::new(1, SAMPLE_RATE), 0.1);
+    rms_of_next(&mut agc, SAMPLE_RATE as usize);
+    assert_eq!(agc.gain(), 1.0);
+}
@@ -0,0 +1,114 @@
+use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI};
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+
+const SAMPLE_RATE: u32 = 48000;
+
+/// A 500 Hz tone encoded into B-format from `azimuth` radians (0 is in
+/// front, positive is to the left) and `elevation` radians.
+fn encoded_tone(azimuth: f32, elevation: f32) -> MemorySound {
+    let samples: Vec<i16> = (0..SAMPLE_RATE / 10)
+        .flat_map(|i| {
+            let s = 0.5 * (2.0 * PI * 500.0 * i as f32 / SAMPLE_RATE as f32).sin();
+            let w = s * FRAC_1_SQRT_2;
+            let x = s * azimuth.cos() * elevation.cos();
+            let y = s * azimuth.sin() * elevation.cos();
+            let z = s * elevation.sin();
+            [w, x, y, z].map(|v| (v * i16::MAX as f32) as i16)
+        })
+        .collect();
+    MemorySound::from_samples(Arc::new(samples), 4, SAMPLE_RATE)
+}
+
+/// The energy of the left and right channels.
+fn channel_energy(sound: &mut impl Sound) -> (f64, f64) {
+    let (mut left, mut right) = (0.0, 0.0);
+    while let NextSample::Sample(l) = sound.next_sample().unwrap() {
+        let NextSample::Sample(r) = sound.next_sample().unwrap() else {
+            panic!("frame ended after the left channel");
+        };
+        left += (l as f64).powi(2);
+        right += (r as f64).powi(2);
+    }
+    (left, right)
+}
+
+#[test]
+fn stereo_places_source_on_correct_side() {
+    let (mut decode, _) =
+        AmbisonicDecode::new(encoded_tone(FRAC_PI_2, 0.0), AmbisonicOutput::Stereo).unwrap();
+    assert_eq!(decode.channel_count(), 2);
+    let (left, right) = channel_energy(&mut decode);
+    // A cardioid pointing away from the source picks up nothing
+    assert!(left > 0.0);
+    assert!(right < left * 1e-4, "{left} {right}");
+
+    let (mut decode, _) =
+        AmbisonicDecode::new(encoded_tone(-FRAC_PI_2, 0.0), AmbisonicOutput::Stereo).unwrap();
+    let (left, right) = channel_energy(&mut decode);
+    assert!(left < right * 1e-4, "{left} {right}");
+
+    // A source in front is centered
+    let (mut decode, _) =
+        AmbisonicDecode::new(encoded_tone(0.0, 0.0), AmbisonicOutput::Stereo).unwrap();
+    let (left, right) = channel_energy(&mut decode);
+    assert!((left / right - 1.0).abs() < 0.01, "{left} {right}");
+}
+
+#[test]
+fn turning_head_moves_source() {
+    let (mut decode, handle) =
+        AmbisonicDecode::new(encoded_tone(FRAC_PI_2, 0.0), AmbisonicOutput::Stereo).unwrap();
+    // Turning to face the source on the left centers it
+    handle.set_orientation(FRAC_PI_2, 0.0, 0.0);
+    assert_eq!(handle.orientation(), (FRAC_PI_2, 0.0, 0.0));
+    let (left, right) = channel_energy(&mut decode);
+    assert!((left / right - 1.0).abs() < 0.01, "{left} {right}");
+
+    // Turning around puts it on the right
+    let (mut decode, handle) =
+        AmbisonicDecode::new(encoded_tone(FRAC_PI_2, 0.0), AmbisonicOutput::Stereo).unwrap();
+    handle.set_orientation(PI, 0.0, 0.0);
+    let (left, right) = channel_energy(&mut decode);
+    assert!(left < right * 1e-4, "{left} {right}");
+
+    // Tilting the head right moves a source above to the left
+    let (mut decode, handle) =
+        AmbisonicDecode::new(encoded_tone(0.0, FRAC_PI_2), AmbisonicOutput::Stereo).unwrap();
+    handle.set_orientation(0.0, 0.0, FRAC_PI_2);
+    let (left, right) = channel_energy(&mut decode);
+    assert!(right < left * 1e-4, "{left} {right}");
+}
+
+#[test]
+fn binaural_places_source_on_correct_side() {
+    for (azimuth, left_louder) in [(FRAC_PI_2, true), (-FRAC_PI_2, false), (2.5, true)] {
+        let (mut decode, _) =
+            AmbisonicDecode::new(encoded_tone(azimuth, 0.0), AmbisonicOutput::Binaural).unwrap();
+        let (left, right) = channel_energy(&mut decode);
+        let (near, far) = if left_louder {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        // At least 3dB louder in the near ear
+        assert!(near > 2.0 * far, "{azimuth}: {left} {right}");
+        assert!(far > 0.0);
+    }
+}
+
+#[test]
+fn requires_four_channels() {
+    assert!(AmbisonicDecode::new(ConstantValueSound::new(0), AmbisonicOutput::Stereo).is_err());
+
+    let mut inner = ConstantValueSound::new(0);
+    inner.channel_count = 4;
+    let (mut decode, _) = AmbisonicDecode::new(inner, AmbisonicOutput::Stereo).unwrap();
+    assert_eq!(decode.next_sample().unwrap(), NextSample::Sample(0));
+    assert_eq!(decode.next_sample().unwrap(), NextSample::Sample(0));
+    decode.inner_mut().set_channel_count(2);
+    assert!(decode.next_sample().is_err());
+}
@@ -0,0 +1,149 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use super::*;
+use crate::sounds::MemorySound;
+use crate::tests::collect;
+
+/// Stereo 44.1 kHz sine sweep.
+fn source() -> MemorySound {
+    let samples: Vec<i16> = (0..4410)
+        .flat_map(|i| {
+            let t = i as f32 / 44100.0;
+            let left = (t * 2.0 * std::f32::consts::PI * (200.0 + 2000.0 * t)).sin();
+            [(left * 20000.0) as i16, (-left * 10000.0) as i16]
+        })
+        .collect();
+    MemorySound::from_samples(Arc::new(samples), 2, 44100)
+}
+
+#[test]
+fn matches_synchronous_resampler() {
+    let expected = collect(&mut SincSampleRateConverter::new(source(), 48000, 32).unwrap());
+
+    let mut resampler = AsyncResampler::new(
+        source(),
+        48000,
+        ResampleQuality::Sinc { taps: 32 },
+        Duration::from_secs(1),
+    )
+    .unwrap();
+    assert_eq!(resampler.channel_count(), 2);
+    assert_eq!(resampler.sample_rate(), 48000);
+    wait_for_worker(&resampler);
+    let output = collect(&mut resampler);
+    assert_eq!(resampler.underrun_frames(), 0);
+    assert_eq!(output.len(), expected.len());
+    assert!(output == expected);
+    assert_eq!(resampler.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn worker_hints_still_resample() {
+    let expected = collect(&mut SincSampleRateConverter::new(source(), 48000, 32).unwrap());
+
+    let hints = WorkerThreadHints {
+        name: Some("resampler".to_owned()),
+        priority: WorkerPriority::Lowest,
+        cpu_affinity: vec![0],
+    };
+    let mut resampler = AsyncResampler::with_worker_hints(
+        source(),
+        48000,
+        ResampleQuality::Sinc { taps: 32 },
+        Duration::from_secs(1),
+        hints.clone(),
+    )
+    .unwrap();
+    assert_eq!(resampler.worker_hints(), &hints);
+    wait_for_worker(&resampler);
+    assert!(collect(&mut resampler) == expected);
+    assert_eq!(resampler.underrun_frames(), 0);
+
+    // A core that can not exist is ignored
+    let mut resampler = AsyncResampler::with_worker_hints(
+        source(),
+        48000,
+        ResampleQuality::Sinc { taps: 32 },
+        Duration::from_secs(1),
+        WorkerThreadHints {
+            cpu_affinity: vec![usize::MAX],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    wait_for_worker(&resampler);
+    assert!(collect(&mut resampler) == expected);
+    assert_eq!(resampler.underrun_frames(), 0);
+}
+
+/// Wait until the worker has resampled all of the inner sound so it can be
+/// read without underruns.
+fn wait_for_worker(resampler: &AsyncResampler) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let state = resampler.shared.state.lock().unwrap();
+        if let Some(Chunk {
+            end: Some(ChunkEnd::Finished),
+            ..
+        }) = state.chunks.back()
+        {
+            return;
+        }
+        drop(state);
+        assert!(Instant::now() < deadline, "timed out");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// A sound whose `next_sample` blocks until `open` is set.
+struct Blocking {
+    open: Arc<AtomicBool>,
+}
+
+impl Sound for Blocking {
+    fn channel_count(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        8000
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        while !self.open.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        Ok(NextSample::Sample(1000))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+#[test]
+fn pulling_does_not_wait_for_worker() {
+    let open = Arc::new(AtomicBool::new(false));
+    let mut resampler = AsyncResampler::new(
+        Blocking { open: open.clone() },
+        16000,
+        ResampleQuality::BALANCED,
+        Duration::from_millis(50),
+    )
+    .unwrap();
+    assert!(!resampler.may_block());
+    let start = Instant::now();
+    for _ in 0..16000 {
+        assert_eq!(resampler.next_sample().unwrap(), NextSample::Sample(0));
+    }
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(resampler.underrun_frames(), 16000);
+    let stats = resampler.underrun_stats();
+    assert_eq!(stats.underruns, 1);
+    assert_eq!(stats.silence, Duration::from_secs(1));
+
+    open.store(true, Ordering::Relaxed);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while resampler.next_sample().unwrap() == NextSample::Sample(0) {
+        assert!(Instant::now() < deadline, "worker never produced samples");
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+use crate::tests::ConstantValueSound;
+
+const VALUE: i16 = 10000;
+
+/// The left and right gain of each frame.
+fn gains(sound: &mut impl Sound, num_frames: usize) -> Vec<(f64, f64)> {
+    let mut next = || match sound.next_sample().unwrap() {
+        NextSample::Sample(s) => s as f64 / VALUE as f64,
+        other => panic!("unexpected {other:?}"),
+    };
+    (0..num_frames).map(|_| (next(), next())).collect()
+}
+
+fn assert_near(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 0.001, "{actual} != {expected}");
+}
+
+#[test]
+fn gains_oscillate_at_rate() {
+    let mut inner = ConstantValueSound::new(VALUE);
+    inner.sample_rate = 1000;
+    let mut sound = AutoPan::new(inner, 2.0);
+    assert_eq!(sound.channel_count(), 2);
+    // 500 frames per cycle
+    let frames = gains(&mut sound, 1000);
+    for (left, right) in &frames {
+        // Constant power
+        assert_near(left * left + right * right, 2.0);
+    }
+    for cycle in [0, 500] {
+        // Centered, fully right, centered, fully left
+        assert_near(frames[cycle].0, 1.0);
+        assert_near(frames[cycle].1, 1.0);
+        assert_near(frames[cycle + 125].0, 0.0);
+        assert_near(frames[cycle + 125].1, std::f64::consts::SQRT_2);
+        assert_near(frames[cycle + 250].0, 1.0);
+        assert_near(frames[cycle + 375].0, std::f64::consts::SQRT_2);
+        assert_near(frames[cycle + 375].1, 0.0);
+    }
+    // The right channel leads
+    assert!(frames[50].1 > frames[50].0);
+    assert!(frames[300].0 > frames[300].1);
+}
+
+#[test]
+fn depth_limits_pan() {
+    let mut inner = ConstantValueSound::new(VALUE);
+    inner.sample_rate = 1000;
+    let mut sound = AutoPan::new(inner, 2.0);
+    sound.set_depth(0.5);
+    let frames = gains(&mut sound, 500);
+    let angle = 1.5 * std::f64::consts::FRAC_PI_4;
+    assert_near(frames[125].0, std::f64::consts::SQRT_2 * angle.cos());
+    assert_near(frames[125].1, std::f64::consts::SQRT_2 * angle.sin());
+
+    sound.set_depth(0.0);
+    for (left, right) in gains(&mut sound, 500) {
+        assert_near(left, 1.0);
+        assert_near(right, 1.0);
+    }
+}
+
+#[test]
+fn mono_is_upmixed() {
+    let mut inner = ConstantValueSound::new(VALUE);
+    inner.channel_count = 1;
+    inner.sample_rate = 1000;
+    let mut sound = AutoPan::new(inner, 2.0);
+    assert_eq!(sound.channel_count(), 2);
+    let frames = gains(&mut sound, 126);
+    assert_near(frames[125].0, 0.0);
+    assert_near(frames[125].1, std::f64::consts::SQRT_2);
+}
+
+#[test]
+fn rate_follows_sample_rate_change() {
+    let mut inner = ConstantValueSound::new(VALUE);
+    inner.sample_rate = 1000;
+    let mut sound = AutoPan::new(inner, 2.0);
+    // A quarter of a cycle to fully right
+    gains(&mut sound, 125);
+    sound.inner_mut().set_sample_rate(2000);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    // The phase continues from fully right and a quarter cycle is now 250
+    // frames
+    let frames = gains(&mut sound, 501);
+    assert_near(frames[0].1, std::f64::consts::SQRT_2);
+    assert_near(frames[250].0, 1.0);
+    assert_near(frames[250].1, 1.0);
+    assert_near(frames[500].0, std::f64::consts::SQRT_2);
+}
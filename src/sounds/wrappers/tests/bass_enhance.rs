@@ -0,0 +1,52 @@
+use std::f32::consts::PI;
+
+use super::*;
+use crate::sounds::SineWav;
+use crate::tests::collect_n;
+
+const SAMPLE_RATE: u32 = 8000;
+
+/// The amplitude of `frequency` in `samples` as a fraction of full scale.
+fn amplitude_at(samples: &[i16], frequency: f32) -> f32 {
+    let (mut re, mut im) = (0.0, 0.0);
+    for (n, s) in samples.iter().enumerate() {
+        let phase = 2.0 * PI * frequency * n as f32 / SAMPLE_RATE as f32;
+        let x = *s as f32 / i16::MAX as f32;
+        re += x * phase.cos();
+        im += x * phase.sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / samples.len() as f32
+}
+
+#[test]
+fn adds_harmonics_of_low_tone() {
+    let sine = SineWav::with_sample_rate(50.0, SAMPLE_RATE).with_adjustable_volume_of(0.5);
+    let mut enhance = BassEnhance::new(sine);
+    enhance.set_intensity(1.0);
+    // Skip the filters settling
+    collect_n(&mut enhance, SAMPLE_RATE as usize);
+    let output = collect_n(&mut enhance, SAMPLE_RATE as usize);
+
+    assert!((amplitude_at(&output, 50.0) - 0.5).abs() < 0.05);
+    for harmonic in [100.0, 150.0, 200.0] {
+        let amplitude = amplitude_at(&output, harmonic);
+        assert!(amplitude > 0.01, "{harmonic} Hz at {amplitude}");
+    }
+    // Nothing between the harmonics
+    for frequency in [75.0, 125.0, 175.0] {
+        let amplitude = amplitude_at(&output, frequency);
+        assert!(amplitude < 0.001, "{frequency} Hz at {amplitude}");
+    }
+}
+
+#[test]
+fn zero_intensity_is_unchanged() {
+    let sine = SineWav::with_sample_rate(50.0, SAMPLE_RATE).with_adjustable_volume_of(0.5);
+    let mut expected = SineWav::with_sample_rate(50.0, SAMPLE_RATE).with_adjustable_volume_of(0.5);
+    let mut enhance = BassEnhance::new(sine);
+    enhance.set_intensity(0.0);
+    assert_eq!(
+        collect_n(&mut enhance, 1000),
+        collect_n(&mut expected, 1000)
+    );
+}
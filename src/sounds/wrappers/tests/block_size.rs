@@ -0,0 +1,69 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sounds::wrappers::FinishAfter;
+use crate::sounds::{MemorySound, SoundList};
+use crate::tests::ConstantValueSound;
+
+use super::*;
+
+#[test]
+fn batches_are_whole_blocks() {
+    let source: Vec<i16> = (1..=21).collect();
+    let inner = MemorySound::from_samples(Arc::new(source.clone()), 1, 1000);
+    let mut sound = BlockSize::new(inner, 4);
+    let mut output = Vec::new();
+    let mut batch = Vec::new();
+    let end = loop {
+        batch.clear();
+        match sound.append_next_block_to(&mut batch) {
+            Ok(()) => {
+                assert_eq!(batch.len() % 4, 0);
+                output.extend_from_slice(&batch);
+            }
+            Err(end) => break end.unwrap(),
+        }
+    };
+    assert_eq!(end, NextSample::Finished);
+    assert_eq!(output.len(), 24);
+    assert_eq!(output[..21], source);
+    assert_eq!(output[21..], [0, 0, 0]);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn stereo_blocks_pad_whole_frames() {
+    let inner = MemorySound::from_samples(Arc::new(vec![1, -1, 2, -2, 3, -3]), 2, 1000);
+    let mut sound = BlockSize::new(inner, 2);
+    let mut samples = Vec::new();
+    while let NextSample::Sample(s) = sound.next_sample().unwrap() {
+        samples.push(s);
+    }
+    assert_eq!(samples, vec![1, -1, 2, -2, 3, -3, 0, 0]);
+}
+
+#[test]
+fn metadata_change_pads_block_first() {
+    let mut second = ConstantValueSound::new(7);
+    second.channel_count = 1;
+    second.sample_rate = 2000;
+    let first = MemorySound::from_samples(Arc::new(vec![1, 2, 3]), 1, 1000);
+    let list = SoundList::from(vec![
+        Box::new(first) as Box<dyn Sound>,
+        Box::new(FinishAfter::new(second, Duration::from_millis(2))),
+    ]);
+    let mut sound = BlockSize::new(list, 2);
+    let mut samples = Vec::new();
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => samples.push(s),
+            NextSample::MetadataChanged => {
+                assert_eq!(samples.len() % 2, 0);
+                assert_eq!(sound.sample_rate(), 2000);
+            }
+            NextSample::Paused => panic!("unexpected pause"),
+            NextSample::Finished => break,
+        }
+    }
+    assert_eq!(samples, vec![1, 2, 3, 0, 7, 7, 7, 7]);
+}
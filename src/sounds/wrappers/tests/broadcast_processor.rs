@@ -0,0 +1,73 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+
+const SAMPLE_RATE: u32 = 8000;
+
+/// Half a second of noise at 0.005 of full scale on both channels followed by
+/// half a second with a full scale sine wave on the left channel and the
+/// same noise on the right.
+fn noise_then_peaks() -> MemorySound {
+    let mut state = 1u32;
+    let mut noise = move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        ((state >> 16) as f32 / 32768.0 - 1.0) * 0.005
+    };
+    let num_frames = SAMPLE_RATE as usize / 2;
+    let mut samples = Vec::new();
+    for _ in 0..num_frames {
+        samples.push(noise());
+        samples.push(noise());
+    }
+    for i in 0..num_frames {
+        samples.push((2.0 * PI * 200.0 * i as f32 / SAMPLE_RATE as f32).sin());
+        samples.push(noise());
+    }
+    let samples = samples
+        .into_iter()
+        .map(|s| (s * i16::MAX as f32) as i16)
+        .collect();
+    MemorySound::from_samples(Arc::new(samples), 2, SAMPLE_RATE)
+}
+
+#[test]
+fn noise_is_gated_and_peaks_are_limited() {
+    let mut processor = BroadcastProcessor::new(noise_then_peaks());
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    while let Ok(frame) = processor.next_frame() {
+        left.push(frame[0] as f32 / i16::MAX as f32);
+        right.push(frame[1] as f32 / i16::MAX as f32);
+    }
+    let half = SAMPLE_RATE as usize / 2;
+    assert_eq!(left.len(), 2 * half);
+
+    // The noise never opens the gate
+    assert!(left[..half].iter().all(|s| s.abs() < 1e-4));
+    assert!(right.iter().all(|s| s.abs() < 1e-4));
+
+    // The sine wave opens the gate on its own channel and is held at the
+    // ceiling
+    let peak = left[half..]
+        .iter()
+        .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(peak <= 0.89, "{peak}");
+    assert!(peak > 0.85, "{peak}");
+    let tail = &left[left.len() - SAMPLE_RATE as usize / 10..];
+    let rms = (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt();
+    assert!(rms > 0.5, "{rms}");
+}
+
+#[test]
+fn input_gain_opens_gate() {
+    let mut processor = BroadcastProcessor::new(noise_then_peaks());
+    processor.set_input_gain(4.0);
+    processor.set_gate_threshold(0.015);
+    let mut right_peak = 0.0f32;
+    while let Ok(frame) = processor.next_frame() {
+        right_peak = right_peak.max((frame[1] as f32 / i16::MAX as f32).abs());
+    }
+    assert!(right_peak > 0.015, "{right_peak}");
+}
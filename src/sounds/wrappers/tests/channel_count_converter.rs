@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+use crate::tests::collect;
+
+/// A 5.1 sound of `frames`.
+fn surround(frames: &[[i16; 6]]) -> MemorySound {
+    let samples = frames.iter().flatten().copied().collect();
+    MemorySound::from_samples(Arc::new(samples), 6, 48000)
+}
+
+#[test]
+fn normalized_full_scale_downmix_does_not_clip() {
+    let frames = [[i16::MAX; 6], [i16::MIN + 1; 6], [i16::MAX / 2; 6]];
+    for downmix in [DownmixCoefficients::Itu, DownmixCoefficients::Dolby] {
+        let mut converter =
+            ChannelCountConverter::try_with_downmix(surround(&frames), 2, downmix, true).unwrap();
+        let output = collect(&mut converter);
+        assert_eq!(output.len(), 6);
+        // Halving the input halves the output so nothing was clipped
+        for (full, half) in output[..2].iter().zip(&output[4..]) {
+            assert!((*full as i32 / 2 - *half as i32).abs() <= 1, "{output:?}");
+        }
+        assert_eq!(output[0] as i32, -(output[2] as i32), "{output:?}");
+    }
+
+    // The default is clipped
+    let mut converter = ChannelCountConverter::new(surround(&frames), 2);
+    let output = collect(&mut converter);
+    assert_eq!(output[..4], [i16::MAX, i16::MAX, i16::MIN, i16::MIN]);
+}
+
+#[test]
+fn itu_and_dolby_coefficients() {
+    // Only the left surround
+    let frames = [[0, 0, 0, 0, 10000, 0]];
+    let mut itu = ChannelCountConverter::new(surround(&frames), 2);
+    assert_eq!(collect(&mut itu), [7071, 0]);
+
+    let mut dolby = ChannelCountConverter::try_with_downmix(
+        surround(&frames),
+        2,
+        DownmixCoefficients::Dolby,
+        false,
+    )
+    .unwrap();
+    assert_eq!(collect(&mut dolby), [-8660, 5000]);
+
+    // Normalizing does not change conversions that can not clip
+    let stereo = MemorySound::from_samples(Arc::new(vec![i16::MAX, i16::MIN]), 2, 48000);
+    let mut converter =
+        ChannelCountConverter::try_with_downmix(stereo, 6, DownmixCoefficients::Itu, true).unwrap();
+    assert_eq!(collect(&mut converter), [i16::MAX, i16::MIN, 0, 0, 0, 0]);
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::*;
+use crate::sounds::{MemorySound, SoundList};
+use crate::tests::collect;
+
+#[test]
+fn delays_only_the_right_channel() {
+    let input: Vec<i16> = (1..=20).collect();
+    let sound = MemorySound::from_samples(Arc::new(input.clone()), 2, 1000);
+    let mut delay = ChannelDelay::new(sound, vec![0, 3]);
+    assert_eq!(delay.delay_samples(), [0, 3]);
+    let output = collect(&mut delay);
+
+    let left: Vec<i16> = output.iter().step_by(2).copied().collect();
+    let right: Vec<i16> = output.iter().skip(1).step_by(2).copied().collect();
+    let input_left: Vec<i16> = input.iter().step_by(2).copied().collect();
+    let input_right: Vec<i16> = input.iter().skip(1).step_by(2).copied().collect();
+    assert_eq!(left[..10], input_left);
+    assert_eq!(left[10..], [0; 3]);
+    assert_eq!(right[..3], [0; 3]);
+    assert_eq!(right[3..], input_right);
+    assert_eq!(delay.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn duration_delays_follow_sample_rate() {
+    let first = MemorySound::from_samples(Arc::new(vec![1; 4]), 1, 1000);
+    let second = MemorySound::from_samples(Arc::new(vec![2; 8]), 2, 2000);
+    let mut list = SoundList::new();
+    list.add(Box::new(first));
+    list.add(Box::new(second));
+    let mut delay = ChannelDelay::with_durations(list, vec![Duration::from_millis(2)]);
+
+    assert_eq!(delay.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(delay.delay_samples(), [2]);
+    let mut first_output = Vec::new();
+    for _ in 0..4 {
+        first_output.push(delay.next_sample().unwrap());
+    }
+    assert_eq!(first_output, [0, 0, 1, 1].map(NextSample::Sample).to_vec());
+
+    // The rest of the first sound is dropped at the format change
+    assert_eq!(delay.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(delay.delay_samples(), [4, 0]);
+    let output = collect(&mut delay);
+    assert_eq!(output, [0, 2, 0, 2, 0, 2, 0, 2, 2, 0, 2, 0, 2, 0, 2, 0]);
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use super::*;
+use crate::tests::ConstantValueSound;
+
+fn next_frame<S: Sound>(sound: &mut S) -> Vec<i16> {
+    let mut frame = Vec::new();
+    sound.append_next_frame_to(&mut frame).ok().unwrap();
+    frame
+}
+
+fn new_sound(channel_count: u16) -> (ChannelMute<ConstantValueSound>, ChannelMuteHandle) {
+    let mut inner = ConstantValueSound::new(10000);
+    inner.channel_count = channel_count;
+    inner.sample_rate = 1000;
+    let (mut sound, handle) = ChannelMute::new(inner);
+    sound.set_fade_duration(Duration::from_millis(4));
+    (sound, handle)
+}
+
+#[test]
+fn muted_channel_ramps_to_silence() {
+    let (mut sound, handle) = new_sound(3);
+    assert_eq!(next_frame(&mut sound), [10000, 10000, 10000]);
+
+    handle.set_muted(1, true);
+    assert!(handle.is_muted(1));
+    assert_eq!(handle.mask(), 0b010);
+    assert_eq!(next_frame(&mut sound), [10000, 7500, 10000]);
+    assert_eq!(next_frame(&mut sound), [10000, 5000, 10000]);
+    assert_eq!(next_frame(&mut sound), [10000, 2500, 10000]);
+    assert_eq!(next_frame(&mut sound), [10000, 0, 10000]);
+    assert_eq!(next_frame(&mut sound), [10000, 0, 10000]);
+
+    handle.set_muted(1, false);
+    assert_eq!(next_frame(&mut sound), [10000, 2500, 10000]);
+    assert_eq!(next_frame(&mut sound), [10000, 5000, 10000]);
+    assert_eq!(next_frame(&mut sound), [10000, 7500, 10000]);
+    assert_eq!(next_frame(&mut sound), [10000, 10000, 10000]);
+}
+
+#[test]
+fn change_mid_frame_applies_from_next_frame() {
+    let (mut sound, handle) = new_sound(2);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(10000));
+    handle.set_muted(1, true);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(10000));
+    assert_eq!(next_frame(&mut sound), [10000, 7500]);
+}
+
+#[test]
+fn solo_mutes_all_other_channels() {
+    let (mut sound, handle) = new_sound(3);
+    sound.set_fade_duration(Duration::ZERO);
+    handle.solo(2);
+    assert!(handle.is_muted(0) && handle.is_muted(1) && !handle.is_muted(2));
+    assert_eq!(next_frame(&mut sound), [0, 0, 10000]);
+    handle.unmute_all();
+    assert_eq!(next_frame(&mut sound), [10000, 10000, 10000]);
+}
+
+#[test]
+fn metadata_change_completes_fade() {
+    let (mut sound, handle) = new_sound(2);
+    handle.set_mask(0b01);
+    assert_eq!(next_frame(&mut sound), [7500, 10000]);
+    sound.inner_mut().set_channel_count(3);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(next_frame(&mut sound), [0, 10000, 10000]);
+}
@@ -0,0 +1,112 @@
+use std::{sync::Arc, time::Instant};
+
+use super::*;
+use crate::sounds::{MemorySound, SineWav};
+use crate::tests::collect;
+
+const IR: [f32; 5] = [1.0, 0.5, -0.25, 0.1, -0.05];
+
+#[test]
+fn impulse_outputs_ir() {
+    let mut impulse = vec![0; 100];
+    impulse[0] = 16000;
+    let inner = MemorySound::from_samples(Arc::new(impulse), 1, 1000);
+    let mut reverb = ConvolutionReverb::new(inner, &IR, 1);
+    let output = collect(&mut reverb);
+    assert_eq!(output.len(), 100 + IR.len() - 1);
+    for (i, s) in output.iter().enumerate() {
+        let expected = IR.get(i).copied().unwrap_or(0.0) * 16000.0;
+        assert!(
+            (*s as f32 - expected).abs() <= 1.0,
+            "{i}: {s} != {expected}"
+        );
+    }
+    assert_eq!(reverb.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn mono_ir_on_stereo_input() {
+    // Impulse on the left channel at frame 0 and the right channel at frame 2
+    let mut impulse = vec![0; 20];
+    impulse[0] = 10000;
+    impulse[5] = -10000;
+    let inner = MemorySound::from_samples(Arc::new(impulse), 2, 1000);
+    let mut reverb = ConvolutionReverb::new(inner, &IR, 1);
+    assert_eq!(reverb.channel_count(), 2);
+    let output = collect(&mut reverb);
+    for (frame, ir) in IR.iter().enumerate() {
+        assert!((output[frame * 2] as f32 - ir * 10000.0).abs() <= 1.0);
+        assert!((output[(frame + 2) * 2 + 1] as f32 + ir * 10000.0).abs() <= 1.0);
+    }
+}
+
+#[test]
+fn dry_mix_passes_input_through() {
+    let samples: Vec<i16> = (0..50).map(|i| i * 100).collect();
+    let inner = MemorySound::from_samples(Arc::new(samples.clone()), 1, 1000);
+    let mut reverb = ConvolutionReverb::new(inner, &IR, 1);
+    reverb.set_mix(0.0);
+    let output = collect(&mut reverb);
+    assert_eq!(&output[..samples.len()], &samples[..]);
+}
+
+#[test]
+fn ir_from_sound() {
+    let ir = MemorySound::from_samples(Arc::new(vec![i16::MAX, 0, i16::MAX / 2]), 1, 1000);
+    let inner = MemorySound::from_samples(Arc::new(vec![1000, 0, 0, 0]), 1, 1000);
+    let mut reverb = ConvolutionReverb::from_ir_sound(inner, ir).unwrap();
+    assert_eq!(collect(&mut reverb), vec![1000, 0, 500, 0, 0, 0]);
+}
+
+#[test]
+#[ignore = "wall-clock benchmark, run with --ignored in release mode"]
+fn faster_than_real_time() {
+    const SAMPLE_RATE: u32 = 44100;
+    let ir: Vec<f32> = (0..SAMPLE_RATE * 2)
+        .map(|i| (-(i as f32) / 5000.0).exp() * if i % 2 == 0 { 0.5 } else { -0.5 })
+        .collect();
+    let duration = std::time::Duration::from_secs(10);
+    let inner = SineWav::with_sample_rate(440.0, SAMPLE_RATE).finish_after(duration);
+    let mut reverb = ConvolutionReverb::new(
+        crate::sounds::wrappers::ChannelCountConverter::new(inner, 2),
+        &ir,
+        2,
+    );
+    let start = Instant::now();
+    let num_samples = collect(&mut reverb).len();
+    let elapsed = start.elapsed();
+    assert!(num_samples >= 2 * 10 * SAMPLE_RATE as usize);
+    assert!(elapsed < duration, "took {elapsed:?}");
+}
+
+#[cfg(feature = "symphonia")]
+#[test]
+fn reverb_raises_estimated_cost() {
+    use crate::sounds::decoders::SymphoniaDecoder;
+    use crate::CostHint;
+
+    let bytes = crate::tests::wav_bytes(1, 1000, &[0; 100]);
+    let decoder = SymphoniaDecoder::new(Box::new(std::io::Cursor::new(bytes)), None).unwrap();
+    assert_eq!(decoder.estimated_cost(), CostHint::Moderate);
+    let reverb = ConvolutionReverb::new(decoder, &IR, 1).with_adjustable_volume();
+    let memory = MemorySound::from_samples(Arc::new(vec![0; 100]), 1, 1000);
+    assert_eq!(memory.estimated_cost(), CostHint::Trivial);
+    assert_eq!(reverb.estimated_cost(), CostHint::High);
+    assert!(reverb.estimated_cost() > memory.estimated_cost());
+    let boxed: Box<dyn Sound> = Box::new(reverb);
+    assert_eq!(boxed.estimated_cost(), CostHint::High);
+}
+
+#[test]
+fn wrapped_reverb_keeps_estimated_cost() {
+    use crate::CostHint;
+
+    let inner = MemorySound::from_samples(Arc::new(vec![0; 100]), 1, 1000);
+    let reverb = ConvolutionReverb::new(inner, &IR, 1);
+    let wrapped = crate::sounds::wrappers::ChannelCountConverter::new(reverb, 2)
+        .with_auto_gain_control(0.1)
+        .skip_silence(std::time::Duration::from_millis(10))
+        .throttled_to_realtime()
+        .on_finished(|| {});
+    assert_eq!(wrapped.estimated_cost(), CostHint::High);
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+
+const SAMPLE_RATE: u32 = 8000;
+
+#[test]
+fn clicks_precede_inner() {
+    let inner = MemorySound::from_samples(Arc::new(vec![7; 200]), 2, SAMPLE_RATE);
+    // 600 BPM is a beat every 800 frames
+    let mut count_in = CountIn::new(inner, 600.0, 4);
+    assert_eq!(count_in.count_in_duration(), Duration::from_millis(400));
+
+    let mut frames = Vec::new();
+    while let Ok(frame) = count_in.next_frame() {
+        assert_eq!(frame[0], frame[1]);
+        frames.push(frame[0]);
+    }
+    assert!(!count_in.is_counting());
+    assert_eq!(frames.len(), 3200 + 100);
+    assert!(frames[3200..].iter().all(|s| *s == 7));
+
+    // The start of each run of clicking
+    let mut click_starts = Vec::new();
+    let mut last_click = None;
+    for (i, s) in frames[..3200].iter().enumerate() {
+        if *s != 0 {
+            if last_click.is_none_or(|last| i - last > 200) {
+                click_starts.push(i);
+            }
+            last_click = Some(i);
+        }
+    }
+    assert_eq!(click_starts.len(), 4, "{click_starts:?}");
+    for (beat, start) in click_starts.iter().enumerate() {
+        assert!(start.abs_diff(beat * 800) <= 1, "{click_starts:?}");
+    }
+}
+
+#[test]
+fn no_beats_plays_inner_immediately() {
+    let inner = MemorySound::from_samples(Arc::new(vec![7, 8]), 1, SAMPLE_RATE);
+    let mut count_in = CountIn::new(inner, 120.0, 0);
+    assert_eq!(count_in.count_in_duration(), Duration::ZERO);
+    assert_eq!(count_in.next_sample().unwrap(), NextSample::Sample(7));
+    assert_eq!(count_in.next_sample().unwrap(), NextSample::Sample(8));
+    assert_eq!(count_in.next_sample().unwrap(), NextSample::Finished);
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+
+/// A 441 Hz sine (100 frames per period) of `amplitude` times full scale
+/// clipped to the range of i16.
+fn sine(amplitude: f64, num_frames: usize) -> Vec<i16> {
+    (0..num_frames)
+        .map(|i| {
+            let value = amplitude * (i as f64 * std::f64::consts::TAU / 100.0).sin();
+            (value * i16::MAX as f64).clamp(i16::MIN as f64, i16::MAX as f64) as i16
+        })
+        .collect()
+}
+
+fn de_clip(samples: &[i16], channel_count: u16) -> Vec<i16> {
+    let inner = MemorySound::from_samples(Arc::new(samples.to_vec()), channel_count, 44100);
+    let mut sound = DeClip::new(inner);
+    let mut output = Vec::new();
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::Finished => break,
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+    output
+}
+
+#[test]
+fn flat_tops_are_rounded() {
+    let clipped = sine(1.5, 1000);
+    let output = de_clip(&clipped, 1);
+    assert_eq!(output.len(), clipped.len());
+
+    let is_clipped = |s: i16| s == i16::MAX || s == i16::MIN;
+    let mut runs = 0;
+    let mut idx = 0;
+    while idx < clipped.len() {
+        if !is_clipped(clipped[idx]) {
+            // Unclipped samples are untouched
+            assert_eq!(output[idx], clipped[idx], "sample {idx}");
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        while idx < clipped.len() && is_clipped(clipped[idx]) {
+            idx += 1;
+        }
+        if start == 0 || idx == clipped.len() {
+            continue;
+        }
+        runs += 1;
+        // Work with the positive half
+        let sign = clipped[start].signum() as i32;
+        let run: Vec<i32> = output[start..idx]
+            .iter()
+            .map(|s| sign * *s as i32)
+            .collect();
+        // No longer flat but a rounded top peaking in the middle
+        let distinct = run.iter().collect::<std::collections::HashSet<_>>().len();
+        assert!(distinct > run.len() / 2, "{run:?}");
+        let peak_idx = (0..run.len()).max_by_key(|i| run[*i]).unwrap();
+        assert!(peak_idx.abs_diff(run.len() / 2) <= 1, "{run:?}");
+        assert!(
+            run.windows(3).all(|w| w[0] + w[2] <= 2 * w[1] + 2),
+            "{run:?}"
+        );
+        assert!(run.iter().all(|s| *s >= (0.95 * i16::MAX as f64) as i32));
+    }
+    assert!(runs >= 15, "{runs}");
+}
+
+#[test]
+fn loud_unclipped_content_is_untouched() {
+    let loud = sine(0.999, 1000);
+    assert_eq!(de_clip(&loud, 1), loud);
+}
+
+#[test]
+fn channels_are_repaired_independently() {
+    let clipped = sine(1.5, 500);
+    let quiet = sine(0.5, 500);
+    let interleaved: Vec<i16> = clipped
+        .iter()
+        .zip(&quiet)
+        .flat_map(|(a, b)| [*a, *b])
+        .collect();
+    let output = de_clip(&interleaved, 2);
+    let left: Vec<i16> = output.iter().step_by(2).copied().collect();
+    let right: Vec<i16> = output.iter().skip(1).step_by(2).copied().collect();
+    assert_eq!(left, de_clip(&clipped, 1));
+    assert_eq!(right, quiet);
+}
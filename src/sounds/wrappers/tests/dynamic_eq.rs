@@ -0,0 +1,86 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+
+const SAMPLE_RATE: u32 = 16000;
+const WINDOW: usize = 1600;
+const LOW: f32 = 200.0;
+const HIGH: f32 = 3000.0;
+
+/// The amplitude of `frequency` in `samples`, which must contain a whole
+/// number of its cycles.
+fn amplitude_at(samples: &[f32], frequency: f32) -> f32 {
+    let (mut re, mut im) = (0.0, 0.0);
+    for (i, s) in samples.iter().enumerate() {
+        let phase = 2.0 * PI * frequency * i as f32 / SAMPLE_RATE as f32;
+        re += s * phase.cos();
+        im += s * phase.sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / samples.len() as f32
+}
+
+/// A steady low tone with a high tone that is loud in windows 4 and 5 and
+/// quiet otherwise.
+fn input() -> MemorySound {
+    let samples = (0..WINDOW * 10)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let high = if (4..6).contains(&(i / WINDOW)) {
+                0.5
+            } else {
+                0.05
+            };
+            let value = 0.3 * (2.0 * PI * LOW * t).sin() + high * (2.0 * PI * HIGH * t).sin();
+            (value * i16::MAX as f32) as i16
+        })
+        .collect();
+    MemorySound::from_samples(Arc::new(samples), 1, SAMPLE_RATE)
+}
+
+#[test]
+fn cuts_band_only_while_too_loud() {
+    let band = DynamicEqBand {
+        frequency: HIGH,
+        q: 2.0,
+        threshold: 0.1,
+        ratio: 8.0,
+    };
+    let mut eq = DynamicEq::new(input(), vec![band]);
+    assert_eq!(eq.bands(), &[band]);
+    let mut output = Vec::new();
+    while let NextSample::Sample(s) = eq.next_sample().unwrap() {
+        output.push(s as f32 / i16::MAX as f32);
+    }
+    assert_eq!(output.len(), WINDOW * 10);
+
+    for (window, samples) in output.chunks(WINDOW).enumerate().skip(1) {
+        let low = amplitude_at(samples, LOW);
+        assert!((low - 0.3).abs() < 0.3 * 0.05, "window {window}: {low}");
+        let high = amplitude_at(samples, HIGH);
+        match window {
+            4 | 5 => assert!(high < 0.25, "window {window}: {high}"),
+            // Releasing
+            6 | 7 => (),
+            _ => assert!((high - 0.05).abs() < 0.05 * 0.05, "window {window}: {high}"),
+        }
+    }
+}
+
+#[test]
+fn reset_clears_detectors() {
+    let band = DynamicEqBand {
+        frequency: HIGH,
+        q: 2.0,
+        threshold: 0.1,
+        ratio: 8.0,
+    };
+    let mut eq = DynamicEq::new(input(), vec![band]);
+    for _ in 0..WINDOW * 5 {
+        eq.next_sample().unwrap();
+    }
+    assert!(eq.channels[0][0].envelope > 0.1);
+    eq.reset();
+    assert_eq!(eq.channels[0][0].envelope, 0.0);
+}
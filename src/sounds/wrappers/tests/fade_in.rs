@@ -0,0 +1,35 @@
+use super::*;
+use crate::tests::ConstantValueSound;
+
+#[test]
+fn linear_ramps_up_then_passes_through() {
+    let mut inner = ConstantValueSound::new(1000);
+    inner.sample_rate = 1000;
+    let mut sound = inner.fade_in(Duration::from_millis(4));
+    let expected = [0, 0, 250, 250, 500, 500, 750, 750, 1000, 1000, 1000];
+    for value in expected {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(value));
+    }
+}
+
+#[test]
+fn equal_power_crossfade_keeps_power_constant() {
+    for step in 0..=100 {
+        let progress = step as f32 / 100.0;
+        let fade_in = FadeCurve::EqualPower.gain(progress);
+        let fade_out = FadeCurve::EqualPower.gain(1.0 - progress);
+        let power = fade_in * fade_in + fade_out * fade_out;
+        assert!((power - 1.0).abs() < 1e-5, "power {power} at {progress}");
+    }
+    let linear_midpoint = FadeCurve::Linear.gain(0.5);
+    let linear_power = 2.0 * linear_midpoint * linear_midpoint;
+    assert!((linear_power - 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn cosine_is_symmetric() {
+    assert_eq!(FadeCurve::Cosine.gain(0.0), 0.0);
+    assert!((FadeCurve::Cosine.gain(0.5) - 0.5).abs() < 1e-5);
+    assert!((FadeCurve::Cosine.gain(1.0) - 1.0).abs() < 1e-5);
+    assert!(FadeCurve::Cosine.gain(0.1) < FadeCurve::Linear.gain(0.1));
+}
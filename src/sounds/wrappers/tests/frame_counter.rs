@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::*;
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+
+#[test]
+fn counts_complete_frames() {
+    let mut inner = ConstantValueSound::new(5);
+    inner.channel_count = 3;
+    let (mut counter, handle) = FrameCounter::new(inner);
+    for _ in 0..3 * 100 {
+        assert_eq!(counter.next_sample().unwrap(), NextSample::Sample(5));
+    }
+    assert_eq!(handle.frames_played(), 100);
+    assert_eq!(counter.frames_played(), 100);
+
+    // A partial frame is not counted
+    counter.next_sample().unwrap();
+    counter.next_sample().unwrap();
+    assert_eq!(handle.frames_played(), 100);
+    counter.next_sample().unwrap();
+    assert_eq!(handle.frames_played(), 101);
+
+    let reader = handle.clone();
+    std::thread::spawn(move || assert_eq!(reader.frames_played(), 101))
+        .join()
+        .unwrap();
+    handle.reset();
+    assert_eq!(counter.frames_played(), 0);
+}
+
+#[test]
+fn channel_count_change_restarts_frame() {
+    let (mut counter, handle) = FrameCounter::new(ConstantValueSound::new(5));
+    counter.next_sample().unwrap();
+    counter.inner_mut().set_channel_count(1);
+    assert_eq!(counter.next_sample().unwrap(), NextSample::MetadataChanged);
+    counter.next_sample().unwrap();
+    assert_eq!(handle.frames_played(), 1);
+}
+
+#[test]
+fn seek_sets_count_to_position() {
+    let samples: Vec<i16> = (0..2000).collect();
+    let inner = MemorySound::from_samples(Arc::new(samples), 2, 44100);
+    let (mut counter, handle) = FrameCounter::new(inner);
+    for _ in 0..20 {
+        counter.next_sample().unwrap();
+    }
+    assert_eq!(handle.frames_played(), 10);
+
+    let frame = 777;
+    counter
+        .seek(Duration::from_nanos(frame * 1_000_000_000 / 44100 + 1))
+        .unwrap();
+    assert_eq!(handle.frames_played(), frame);
+    assert_eq!(
+        counter.next_sample().unwrap(),
+        NextSample::Sample(2 * frame as i16)
+    );
+    counter.next_sample().unwrap();
+    assert_eq!(handle.frames_played(), frame + 1);
+}
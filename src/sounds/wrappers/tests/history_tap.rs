@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::*;
+use crate::sounds::{MemorySound, SoundList};
+
+#[test]
+fn snapshot_is_most_recent_samples_in_order() {
+    let input: Vec<i16> = (0..200).collect();
+    let sound = MemorySound::from_samples(Arc::new(input.clone()), 2, 1000);
+    // 20 frames of 2 channels
+    let (mut tap, handle) = HistoryTap::new(sound, Duration::from_millis(20));
+    assert!(handle.snapshot().is_empty());
+
+    for expected in &input[..11] {
+        assert_eq!(tap.next_sample().unwrap(), NextSample::Sample(*expected));
+    }
+    // Only whole frames are kept
+    assert_eq!(handle.snapshot(), input[..10]);
+
+    while let NextSample::Sample(_) = tap.next_sample().unwrap() {}
+    assert_eq!(handle.snapshot(), input[160..]);
+    assert_eq!(handle.channel_count(), 2);
+    assert_eq!(handle.sample_rate(), 1000);
+}
+
+#[test]
+fn format_change_clears_history() {
+    let mut list = SoundList::new();
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![1; 10]),
+        2,
+        1000,
+    )));
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![2, 3, 4]),
+        1,
+        2000,
+    )));
+    let (mut tap, handle) = HistoryTap::new(list, Duration::from_secs(1));
+    while tap.next_sample().unwrap() != NextSample::MetadataChanged || handle.channel_count() != 1 {
+        assert!(handle.snapshot().len() <= 10);
+    }
+    assert!(handle.snapshot().is_empty());
+    assert_eq!(handle.sample_rate(), 2000);
+    while let NextSample::Sample(_) | NextSample::MetadataChanged = tap.next_sample().unwrap() {}
+    assert_eq!(handle.snapshot(), [2, 3, 4]);
+}
@@ -0,0 +1,84 @@
+use std::f64::consts::PI;
+
+use crate::sounds::SineWav;
+use crate::tests::ConstantValueSound;
+
+use super::*;
+
+const SAMPLE_RATE: u32 = 8000;
+
+/// Coefficients of a second order Butterworth low-pass filter from the
+/// bilinear transform.
+fn butterworth_low_pass(cutoff: f64) -> ([f64; 3], [f64; 3]) {
+    let k = (PI * cutoff / SAMPLE_RATE as f64).tan();
+    let norm = 1.0 / (1.0 + 2f64.sqrt() * k + k * k);
+    let b0 = k * k * norm;
+    let b = [b0, 2.0 * b0, b0];
+    let a = [
+        1.0,
+        2.0 * (k * k - 1.0) * norm,
+        (1.0 - 2f64.sqrt() * k + k * k) * norm,
+    ];
+    (b, a)
+}
+
+/// The peak output amplitude of the filter for a full scale sine wave of
+/// `frequency` as a fraction of full scale once it has settled.
+fn gain_at(frequency: f32, b: &[f64], a: &[f64]) -> f64 {
+    let sine = SineWav::with_sample_rate(frequency, SAMPLE_RATE);
+    let mut filter = IirFilter::new(sine, b, a).unwrap();
+    for _ in 0..SAMPLE_RATE {
+        filter.next_sample().unwrap();
+    }
+    let mut peak = 0;
+    for _ in 0..SAMPLE_RATE {
+        let NextSample::Sample(s) = filter.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        peak = peak.max(s.unsigned_abs());
+    }
+    peak as f64 / i16::MAX as f64
+}
+
+#[test]
+fn low_pass_frequency_response() {
+    let (b, a) = butterworth_low_pass(1000.0);
+    assert!((gain_at(100.0, &b, &a) - 1.0).abs() < 0.01);
+    // -3dB at the cutoff
+    assert!((gain_at(1000.0, &b, &a) - 0.5f64.sqrt()).abs() < 0.01);
+    assert!(gain_at(3000.0, &b, &a) < 0.05);
+}
+
+#[test]
+fn coefficients_are_normalized_by_a0() {
+    let mut dc = ConstantValueSound::new(1000);
+    dc.channel_count = 1;
+    // 2*y[n] - y[n-1] = x[n] normalizes to y[n] = 0.5*x[n] + 0.5*y[n-1]
+    let mut filter = IirFilter::new(dc, &[1.0], &[2.0, -1.0]).unwrap();
+    assert_eq!(filter.next_sample().unwrap(), NextSample::Sample(500));
+    assert_eq!(filter.next_sample().unwrap(), NextSample::Sample(750));
+    assert_eq!(filter.next_sample().unwrap(), NextSample::Sample(875));
+}
+
+#[test]
+fn channels_have_separate_state() {
+    let mut dc = ConstantValueSound::new(1000);
+    dc.channel_count = 2;
+    let mut filter = IirFilter::new(dc, &[0.5], &[1.0, -0.5]).unwrap();
+    for expected in [500, 500, 750, 750] {
+        assert_eq!(filter.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    filter.inner_mut().set_channel_count(1);
+    assert_eq!(filter.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(filter.next_sample().unwrap(), NextSample::Sample(500));
+}
+
+#[test]
+fn invalid_coefficients() {
+    let dc = ConstantValueSound::new(0);
+    assert!(IirFilter::new(dc, &[1.0], &[0.0]).is_err());
+    let dc = ConstantValueSound::new(0);
+    assert!(IirFilter::new(dc, &[], &[1.0]).is_err());
+    let dc = ConstantValueSound::new(0);
+    assert!(IirFilter::new(dc, &[1.0], &[]).is_err());
+}
@@ -0,0 +1,32 @@
+use super::*;
+use crate::tests::Sawtooth;
+
+#[test]
+fn injected_at_cadence_without_changing_samples() {
+    let mut sound = InjectMetadataChange::new(Sawtooth::new(2, 1000), 4);
+    let mut reference = Sawtooth::new(2, 1000);
+    for _ in 0..5 {
+        for _ in 0..4 {
+            assert_eq!(
+                sound.next_sample().unwrap(),
+                reference.next_sample().unwrap()
+            );
+        }
+        assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+        assert_eq!(sound.channel_count(), 2);
+        assert_eq!(sound.sample_rate(), 1000);
+    }
+}
+
+#[test]
+fn waits_for_start_of_frame() {
+    let mut sound = InjectMetadataChange::new(Sawtooth::new(2, 1000), 3);
+    for _ in 0..4 {
+        assert!(matches!(
+            sound.next_sample().unwrap(),
+            NextSample::Sample(_)
+        ));
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+use crate::tests::collect;
+
+/// A stereo sound at 1000 Hz of 100 frames where both samples of frame `n`
+/// are `n + 1`.
+fn counting_sound() -> MemorySound {
+    let samples: Vec<i16> = (1..=100).flat_map(|i| [i, i]).collect();
+    MemorySound::from_samples(Arc::new(samples), 2, 1000)
+}
+
+#[test]
+fn delay_prepends_silence() {
+    let mut sound = LatencyOffset::delay(counting_sound(), Duration::from_millis(10));
+    assert_eq!(sound.offset_frames(), 10);
+    assert_eq!(
+        sound.total_duration(Duration::from_millis(100)),
+        Duration::from_millis(110)
+    );
+    let samples = collect(&mut sound);
+    assert_eq!(samples.len(), 2 * 110);
+    assert!(samples[..20].iter().all(|s| *s == 0));
+    assert_eq!(samples[20..24], [1, 1, 2, 2]);
+    assert_eq!(*samples.last().unwrap(), 100);
+    assert_eq!(sound.current_position(), Duration::from_millis(110));
+}
+
+#[test]
+fn advance_skips_start() {
+    let mut sound = LatencyOffset::advance(counting_sound(), Duration::from_millis(10));
+    assert_eq!(sound.offset_frames(), -10);
+    assert_eq!(
+        sound.total_duration(Duration::from_millis(100)),
+        Duration::from_millis(90)
+    );
+    assert_eq!(sound.current_position(), Duration::ZERO);
+    let samples = collect(&mut sound);
+    assert_eq!(samples.len(), 2 * 90);
+    assert_eq!(samples[..4], [11, 11, 12, 12]);
+    assert_eq!(sound.current_position(), Duration::from_millis(90));
+}
+
+#[test]
+fn advance_past_end_finishes() {
+    let mut sound = LatencyOffset::advance(counting_sound(), Duration::from_secs(1));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(
+        sound.total_duration(Duration::from_millis(100)),
+        Duration::ZERO
+    );
+}
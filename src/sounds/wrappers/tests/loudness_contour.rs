@@ -0,0 +1,69 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+use std::time::Duration;
+
+use super::*;
+use crate::sounds::SineWav;
+
+const SAMPLE_RATE: u32 = 44100;
+
+/// The RMS of the next `num_frames` samples as a fraction of full scale.
+fn rms_of_next(sound: &mut impl Sound, num_frames: usize) -> f32 {
+    let mut sum = 0.0;
+    for _ in 0..num_frames {
+        let NextSample::Sample(s) = sound.next_sample().unwrap() else {
+            panic!("expected sample");
+        };
+        sum += (s as f32 / i16::MAX as f32).powi(2);
+    }
+    (sum / num_frames as f32).sqrt()
+}
+
+/// The boost in dB applied on top of `volume` to a sine wave at `frequency`.
+fn boost_db(volume: f32, frequency: f32) -> f32 {
+    let sine = SineWav::with_sample_rate(frequency, SAMPLE_RATE).with_adjustable_volume_of(0.5);
+    let (mut contour, _) = LoudnessContour::new(sine, volume);
+    // Let the filters settle
+    rms_of_next(&mut contour, SAMPLE_RATE as usize / 4);
+    let rms = rms_of_next(&mut contour, SAMPLE_RATE as usize / 2);
+    20.0 * (rms / (0.5 * FRAC_1_SQRT_2 * volume)).log10()
+}
+
+#[test]
+fn low_boost_increases_as_volume_decreases() {
+    let volumes = [1.0, 0.5, 0.2, 0.05];
+    let low: Vec<f32> = volumes.iter().map(|v| boost_db(*v, 40.0)).collect();
+    assert!(low[0].abs() < 0.1, "{low:?}");
+    for pair in low.windows(2) {
+        assert!(pair[1] > pair[0] + 1.0, "{low:?}");
+    }
+    // Half the 26 dB reduction
+    assert!((low[3] - 13.0).abs() < 1.0, "{low:?}");
+
+    // Mid frequencies are left close to the plain volume
+    for volume in volumes {
+        let mid = boost_db(volume, 1000.0);
+        assert!(mid.abs() < 1.5, "{volume}: {mid}");
+        assert!(boost_db(volume, 15000.0) >= mid);
+    }
+}
+
+#[test]
+fn volume_follows_handle_and_set_volume() {
+    let sine = SineWav::with_sample_rate(40.0, SAMPLE_RATE).finish_after(Duration::from_secs(2));
+    let (mut contour, handle) = LoudnessContour::new(sine, 1.0);
+    let full = rms_of_next(&mut contour, SAMPLE_RATE as usize / 2);
+
+    handle.set_volume(0.1);
+    assert_eq!(contour.volume(), 0.1);
+    rms_of_next(&mut contour, SAMPLE_RATE as usize / 4);
+    let quiet = rms_of_next(&mut contour, SAMPLE_RATE as usize / 4);
+    // -20 dB of volume with 10 dB of bass boost
+    let quiet_db = 20.0 * (quiet / full).log10();
+    assert!((quiet_db + 10.0).abs() < 1.0, "{quiet_db}");
+
+    contour.set_volume(1.0);
+    assert_eq!(handle.volume(), 1.0);
+    rms_of_next(&mut contour, SAMPLE_RATE as usize / 4);
+    let restored = rms_of_next(&mut contour, SAMPLE_RATE as usize / 4);
+    assert!((restored / full - 1.0).abs() < 0.01, "{restored} {full}");
+}
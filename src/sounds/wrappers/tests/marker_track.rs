@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex};
+
+use super::*;
+use crate::sounds::MemorySound;
+
+const SAMPLE_RATE: u32 = 1000;
+
+/// One second of stereo where every sample is its frame index.
+fn source() -> MemorySound {
+    let samples = (0..SAMPLE_RATE as i16).flat_map(|i| [i, i]).collect();
+    MemorySound::from_samples(Arc::new(samples), 2, SAMPLE_RATE)
+}
+
+/// A marker track of `source` with the markers, and the events fired so far.
+fn track(
+    markers: &[(u64, &'static str)],
+) -> (
+    MarkerTrack<MemorySound, &'static str>,
+    Arc<Mutex<Vec<&'static str>>>,
+) {
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let markers = markers
+        .iter()
+        .map(|(ms, event)| (Duration::from_millis(*ms), *event))
+        .collect();
+    let callback_fired = fired.clone();
+    let track = MarkerTrack::new(source(), markers, move |event| {
+        callback_fired.lock().unwrap().push(*event)
+    });
+    (track, fired)
+}
+
+/// Play until finished returning each event with the value of the sample
+/// returned just after it fired.
+fn play(
+    track: &mut MarkerTrack<MemorySound, &'static str>,
+    fired: &Mutex<Vec<&'static str>>,
+) -> Vec<(&'static str, i16)> {
+    let mut events = Vec::new();
+    while let NextSample::Sample(s) = track.next_sample().unwrap() {
+        events.extend(fired.lock().unwrap().drain(..).map(|event| (event, s)));
+    }
+    events
+}
+
+#[test]
+fn markers_fire_at_exact_frames() {
+    // Given out of order
+    let (mut track, fired) = track(&[
+        (250, "b"),
+        (0, "start"),
+        (10, "a"),
+        (250, "c"),
+        (999, "end"),
+    ]);
+    assert_eq!(track.remaining(), 5);
+    let events = play(&mut track, &fired);
+    assert_eq!(
+        events,
+        [
+            ("start", 0),
+            ("a", 10),
+            ("b", 250),
+            ("c", 250),
+            ("end", 999)
+        ]
+    );
+    assert_eq!(track.remaining(), 0);
+}
+
+#[test]
+fn markers_fire_before_first_channel() {
+    let (mut track, fired) = track(&[(2, "a")]);
+    for _ in 0..4 {
+        track.next_sample().unwrap();
+    }
+    assert!(fired.lock().unwrap().is_empty());
+    assert_eq!(track.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(*fired.lock().unwrap(), ["a"]);
+}
+
+#[test]
+fn seeking_skips_and_rearms_markers() {
+    let (mut track, fired) = track(&[(100, "a"), (300, "b"), (600, "c")]);
+    for _ in 0..2 * 200 {
+        track.next_sample().unwrap();
+    }
+    assert_eq!(*fired.lock().unwrap(), ["a"]);
+    fired.lock().unwrap().clear();
+
+    // Past "b"
+    track.seek(Duration::from_millis(400)).unwrap();
+    assert_eq!(track.remaining(), 1);
+    for _ in 0..2 * 300 {
+        track.next_sample().unwrap();
+    }
+    assert_eq!(*fired.lock().unwrap(), ["c"]);
+    fired.lock().unwrap().clear();
+
+    // Back to exactly "a" so it fires again
+    track.seek(Duration::from_millis(100)).unwrap();
+    assert_eq!(track.remaining(), 3);
+    let events = play(&mut track, &fired);
+    assert_eq!(events, [("a", 100), ("b", 300), ("c", 600)]);
+}
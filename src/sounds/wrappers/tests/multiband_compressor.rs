@@ -0,0 +1,90 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::{MemorySound, SineWav};
+use crate::tests::{collect_n, ConstantValueSound};
+
+const SAMPLE_RATE: u32 = 44100;
+
+/// The amplitude of `frequency` in `samples` as a fraction of full scale.
+fn amplitude_at(samples: &[i16], frequency: f32) -> f32 {
+    let (mut re, mut im) = (0.0, 0.0);
+    for (n, s) in samples.iter().enumerate() {
+        let phase = 2.0 * PI * frequency * n as f32 / SAMPLE_RATE as f32;
+        let x = *s as f32 / i16::MAX as f32;
+        re += x * phase.cos();
+        im += x * phase.sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / samples.len() as f32
+}
+
+#[test]
+fn compresses_only_the_low_band() {
+    // A 60 Hz tone switching between loud and quiet every half second over a
+    // steady 5 kHz tone
+    let section = SAMPLE_RATE as usize / 2;
+    let input: Vec<i16> = (0..section * 4)
+        .map(|n| {
+            let t = n as f32 / SAMPLE_RATE as f32;
+            let low_level = if (n / section).is_multiple_of(2) {
+                0.4
+            } else {
+                0.04
+            };
+            let x = low_level * (2.0 * PI * 60.0 * t).sin() + 0.2 * (2.0 * PI * 5000.0 * t).sin();
+            (x * i16::MAX as f32) as i16
+        })
+        .collect();
+    let sound = MemorySound::from_samples(Arc::new(input.clone()), 1, SAMPLE_RATE);
+    let mut compressor = MultibandCompressor::new(sound, &[500.0]).unwrap();
+    assert_eq!(compressor.band_count(), 2);
+    compressor.set_threshold(0, 0.04);
+    compressor.set_ratio(0, 10.0);
+    let output = collect_n(&mut compressor, input.len());
+
+    // Measure the second half of each section once the compressor settled
+    let window = |samples: &[i16], idx: usize| {
+        samples[idx * section + section / 2..(idx + 1) * section].to_vec()
+    };
+    for (loud, quiet) in [(0, 1), (2, 3)] {
+        let input_ratio =
+            amplitude_at(&window(&input, loud), 60.0) / amplitude_at(&window(&input, quiet), 60.0);
+        let output_ratio = amplitude_at(&window(&output, loud), 60.0)
+            / amplitude_at(&window(&output, quiet), 60.0);
+        assert!((input_ratio - 10.0).abs() < 0.5, "{input_ratio}");
+        assert!(output_ratio < 2.0, "{output_ratio}");
+    }
+    for idx in 0..4 {
+        let before = amplitude_at(&window(&input, idx), 5000.0);
+        let after = amplitude_at(&window(&output, idx), 5000.0);
+        assert!(
+            (after / before - 1.0).abs() < 0.02,
+            "{idx}: {before} {after}"
+        );
+    }
+}
+
+#[test]
+fn bands_sum_back_to_flat_response() {
+    for frequency in [50.0, 200.0, 1000.0, 2000.0, 4000.0, 10000.0] {
+        let sine = SineWav::with_sample_rate(frequency, SAMPLE_RATE).with_adjustable_volume_of(0.5);
+        let mut compressor = MultibandCompressor::new(sine, &[200.0, 2000.0]).unwrap();
+        assert_eq!(compressor.band_count(), 3);
+        // Let the filters settle
+        collect_n(&mut compressor, SAMPLE_RATE as usize / 4);
+        let output = collect_n(&mut compressor, SAMPLE_RATE as usize / 2);
+        let amplitude = amplitude_at(&output, frequency);
+        assert!((amplitude - 0.5).abs() < 0.005, "{frequency}: {amplitude}");
+    }
+}
+
+#[test]
+fn rejects_invalid_crossovers() {
+    let sound = || ConstantValueSound::new(1000);
+    assert!(MultibandCompressor::new(sound(), &[]).is_ok());
+    assert!(MultibandCompressor::new(sound(), &[2000.0, 200.0]).is_err());
+    assert!(MultibandCompressor::new(sound(), &[200.0, 200.0]).is_err());
+    assert!(MultibandCompressor::new(sound(), &[0.0]).is_err());
+    assert!(MultibandCompressor::new(sound(), &[30000.0]).is_err());
+}
@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::sounds::wrappers::AddSound;
+use crate::sounds::{MemorySound, SoundList, SoundMixer};
+
+use super::*;
+
+fn clip_counting_finishes() -> (OnFinish<MemorySound>, Arc<AtomicUsize>) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let callback_count = count.clone();
+    let clip = MemorySound::from_samples(Arc::new(vec![1, 2, 3]), 1, 1000);
+    let sound = clip.on_finished(move || {
+        callback_count.fetch_add(1, Ordering::SeqCst);
+    });
+    (sound, count)
+}
+
+#[test]
+fn callback_fires_once_at_end() {
+    let (mut sound, count) = clip_counting_finishes();
+    for expected in 1..=3 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+    assert!(!sound.has_finished());
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert!(sound.has_finished());
+}
+
+#[test]
+fn callback_fires_inside_mixer_and_list() {
+    let (in_mixer, mixer_count) = clip_counting_finishes();
+    let mut mixer = SoundMixer::new(1, 1000);
+    mixer.add(Box::new(in_mixer));
+    while let NextSample::Sample(_) = mixer.next_sample().unwrap() {}
+    assert_eq!(mixer_count.load(Ordering::SeqCst), 1);
+
+    let (in_list, list_count) = clip_counting_finishes();
+    let mut list = SoundList::new();
+    list.add(Box::new(in_list));
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![4, 5]),
+        1,
+        1000,
+    )));
+    let mut samples = Vec::new();
+    loop {
+        match list.next_sample().unwrap() {
+            NextSample::Sample(s) => {
+                samples.push(s);
+                let expected = if samples.len() > 3 { 1 } else { 0 };
+                assert_eq!(list_count.load(Ordering::SeqCst), expected);
+            }
+            NextSample::MetadataChanged => (),
+            NextSample::Paused | NextSample::Finished => break,
+        }
+    }
+    assert_eq!(samples, vec![1, 2, 3, 4, 5]);
+    assert_eq!(list_count.load(Ordering::SeqCst), 1);
+}
@@ -1,5 +1,10 @@
 use super::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sounds::MemorySound;
 use crate::tests::ConstantValueSound;
+use crate::NextSample;
 
 #[test]
 fn set_paused_and_unpause() {
@@ -17,3 +22,67 @@ fn set_paused_and_unpause() {
         crate::NextSample::Sample(1000)
     );
 }
+
+#[test]
+fn fades_out_on_pause_and_in_on_resume() {
+    let mut inner = ConstantValueSound::new(10000);
+    inner.channel_count = 1;
+    inner.sample_rate = 1000;
+    let mut sound = inner.pausable_with_fade(Duration::from_millis(10));
+    assert_eq!(sound.fade_duration(), Duration::from_millis(10));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(10000));
+
+    sound.set_paused(true);
+    let expected_out: Vec<_> = (1..=10).rev().map(|i| i * 1000).collect();
+    let fade_out: Vec<_> = (0..10)
+        .map(|_| match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => s,
+            other => panic!("expected a sample, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(fade_out, expected_out);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Paused);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Paused);
+
+    sound.set_paused(false);
+    let fade_in: Vec<_> = (0..12)
+        .map(|_| match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => s,
+            other => panic!("expected a sample, got {other:?}"),
+        })
+        .collect();
+    let mut expected_in: Vec<_> = (1..=10).map(|i| i * 1000).collect();
+    expected_in.extend([10000, 10000]);
+    assert_eq!(fade_in, expected_in);
+}
+
+#[test]
+fn fade_applies_per_frame_and_inner_does_not_advance_while_paused() {
+    // Stereo frames whose left sample counts up from 1
+    let samples: Vec<i16> = (1..=100).flat_map(|i| [i * 100, -i * 100]).collect();
+    let inner = MemorySound::from_samples(Arc::new(samples), 2, 1000);
+    let mut sound = inner.pausable_with_fade(Duration::from_millis(4));
+    let next_frame = |sound: &mut Pausable<MemorySound>| {
+        let mut frame = Vec::new();
+        sound.append_next_frame_to(&mut frame).map(|()| frame).ok()
+    };
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![100, -100]);
+
+    // Pausing mid-frame still finishes the frame at the same gain
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(200));
+    sound.set_paused(true);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(-200));
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![225, -225]);
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![200, -200]);
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![125, -125]);
+    assert_eq!(next_frame(&mut sound), None);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Paused);
+
+    sound.set_paused(false);
+    // Resumes with frame 6 which is where the fade out stopped
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![150, -150]);
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![350, -350]);
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![600, -600]);
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![900, -900]);
+    assert_eq!(next_frame(&mut sound).unwrap(), vec![1000, -1000]);
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::{MemorySound, SoundList};
+
+#[test]
+fn predicts_metadata_change_one_pull_ahead() {
+    let mut list = SoundList::new();
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![1, 2, 3, 4]),
+        2,
+        44100,
+    )));
+    list.add(Box::new(MemorySound::from_samples(
+        Arc::new(vec![5, 6, 7]),
+        1,
+        8000,
+    )));
+    let mut peekable = Peekable::new(list);
+
+    let mut pulls = Vec::new();
+    loop {
+        let predicted = peekable.next_is_metadata_change().unwrap();
+        let before = (peekable.channel_count(), peekable.sample_rate());
+        let next = peekable.next_sample().unwrap();
+        assert_eq!(predicted, next == NextSample::MetadataChanged);
+        pulls.push((predicted, before, next));
+        if next == NextSample::Finished {
+            break;
+        }
+    }
+    let stereo = (2, 44100);
+    let mono = (1, 8000);
+    assert_eq!(
+        pulls,
+        [
+            // SoundList announces the format of its first sound
+            (true, stereo, NextSample::MetadataChanged),
+            (false, stereo, NextSample::Sample(1)),
+            (false, stereo, NextSample::Sample(2)),
+            (false, stereo, NextSample::Sample(3)),
+            (false, stereo, NextSample::Sample(4)),
+            // The old format is still reported until the change is pulled
+            (true, stereo, NextSample::MetadataChanged),
+            (false, mono, NextSample::Sample(5)),
+            (false, mono, NextSample::Sample(6)),
+            (false, mono, NextSample::Sample(7)),
+            (false, mono, NextSample::Finished),
+        ]
+    );
+}
+
+#[test]
+fn peek_does_not_consume() {
+    let mut peekable = Peekable::new(MemorySound::from_samples(Arc::new(vec![1, 2]), 1, 8000));
+    assert_eq!(peekable.peek().unwrap(), NextSample::Sample(1));
+    assert_eq!(peekable.peek().unwrap(), NextSample::Sample(1));
+    assert_eq!(peekable.next_is_metadata_change(), Some(false));
+    assert_eq!(peekable.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(peekable.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(peekable.peek().unwrap(), NextSample::Finished);
+    assert_eq!(peekable.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn boxed_sounds_forward_prediction() {
+    let mut sound: Box<dyn Sound> = Box::new(MemorySound::from_samples(Arc::new(vec![1]), 1, 8000));
+    // Only Peekable looks ahead
+    assert_eq!(sound.next_is_metadata_change(), None);
+
+    let mut list = SoundList::new();
+    list.add(sound);
+    let mut boxed: Box<dyn Sound> = Box::new(Peekable::new(list));
+    assert_eq!(boxed.next_is_metadata_change(), Some(true));
+    assert_eq!(boxed.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(boxed.next_is_metadata_change(), Some(false));
+}
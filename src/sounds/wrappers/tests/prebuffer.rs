@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sounds::MemorySound;
+
+use super::*;
+
+/// Counts up from 1 but only produces a sample on every other call.
+struct SlowDecoder {
+    produced: i16,
+    pause_next: bool,
+}
+
+impl Sound for SlowDecoder {
+    fn channel_count(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        1000
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        self.pause_next = !self.pause_next;
+        if !self.pause_next {
+            return Ok(NextSample::Paused);
+        }
+        self.produced += 1;
+        Ok(NextSample::Sample(self.produced))
+    }
+
+    fn on_start_of_batch(&mut self) {}
+}
+
+#[test]
+fn buffers_duration_before_first_sample() {
+    let inner = SlowDecoder {
+        produced: 0,
+        pause_next: false,
+    };
+    let mut sound = inner.prebuffered(Duration::from_millis(10));
+    let first = loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Paused => assert!(!sound.is_ready()),
+            NextSample::Sample(s) => break s,
+            other => panic!("unexpected {:?}", other),
+        }
+    };
+    assert!(sound.is_ready());
+    assert_eq!(first, 1);
+    assert!(sound.inner().produced >= 10);
+
+    // Pauses of the inner sound are absorbed by the buffer
+    for expected in 2..=30 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+}
+
+#[test]
+fn fill_before_playing() {
+    let inner = SlowDecoder {
+        produced: 0,
+        pause_next: false,
+    };
+    let mut sound = Prebuffer::new(inner, Duration::from_millis(5));
+    let mut calls = 0;
+    while !sound.fill().unwrap() {
+        calls += 1;
+    }
+    assert!(calls > 0);
+    assert_eq!(sound.inner().produced, 5);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+}
+
+#[test]
+fn short_sound_is_ready_when_finished() {
+    let inner = MemorySound::from_samples(Arc::new(vec![1, 2, 3]), 1, 1000);
+    let mut sound = inner.prebuffered(Duration::from_millis(100));
+    assert!(sound.fill().unwrap());
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(2));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(3));
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn buffered_duration_is_read_ahead() {
+    let samples = Arc::new(vec![1; 200]);
+    let mut sound = Prebuffer::new(
+        MemorySound::from_samples(samples, 2, 1000),
+        Duration::from_millis(10),
+    );
+    assert_eq!(sound.buffered_duration(), Duration::ZERO);
+    assert!(sound.fill().unwrap());
+    // One frame beyond the pre-roll is read so it stays ahead
+    assert_eq!(sound.buffered_duration(), Duration::from_millis(11));
+    sound.next_sample().unwrap();
+    sound.next_sample().unwrap();
+    // Topped up before a frame is returned so the pre-roll remains
+    assert_eq!(sound.buffered_duration(), Duration::from_millis(10));
+}
@@ -0,0 +1,62 @@
+use super::*;
+use crate::{sounds::wrappers::SetPaused, tests::ConstantValueSound};
+
+#[test]
+fn one_second_takes_one_second() {
+    let mut inner = ConstantValueSound::new(1000);
+    inner.sample_rate = 8000;
+    let mut sound = inner
+        .finish_after(Duration::from_secs(1))
+        .throttled_to_realtime();
+    let max_lead = sound.max_lead();
+    let start = Instant::now();
+    let mut num_samples = 0;
+    while let NextSample::Sample(_) = sound.next_sample().unwrap() {
+        num_samples += 1;
+        // A busy machine can make the throttle fall behind but it must never
+        // run further ahead of real time than max_lead.
+        let sound_time = Duration::from_secs_f64(num_samples as f64 / 2.0 / 8000.0);
+        let lead = sound_time.saturating_sub(start.elapsed());
+        assert!(
+            lead <= max_lead + Duration::from_millis(1),
+            "{lead:?} ahead after {num_samples} samples"
+        );
+    }
+    assert_eq!(num_samples, 8000 * 2);
+    assert!(
+        start.elapsed() >= Duration::from_secs(1) - max_lead,
+        "took {:?}",
+        start.elapsed()
+    );
+}
+
+#[test]
+fn zero_sample_rate_is_not_throttled() {
+    let mut inner = ConstantValueSound::new(1000);
+    inner.sample_rate = 0;
+    let mut sound = inner.throttled_to_realtime();
+    let start = Instant::now();
+    for _ in 0..1000 {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(1000));
+    }
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn paused_restarts_clock() {
+    let mut inner = ConstantValueSound::new(1000);
+    inner.sample_rate = 1000;
+    let mut sound = inner.pausable().throttled_to_realtime();
+    sound.next_sample().unwrap();
+    sound.set_paused(true);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Paused);
+    std::thread::sleep(Duration::from_millis(50));
+    sound.set_paused(false);
+    // If the clock had not restarted the 50 ms paused could be played
+    // immediately.
+    let start = Instant::now();
+    for _ in 0..(2 * 40) {
+        sound.next_sample().unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(30));
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+
+const SAMPLE_RATE: u32 = 44100;
+
+/// The output of a reverb with only the wet signal for a single impulse,
+/// up to when it finishes.
+fn impulse_response(room_size: f32, channel_count: u16) -> Vec<i16> {
+    let mut samples = vec![0; channel_count as usize];
+    samples[0] = i16::MAX;
+    let impulse = MemorySound::from_samples(Arc::new(samples), channel_count, SAMPLE_RATE);
+    let mut reverb = Reverb::new(impulse);
+    reverb.set_room_size(room_size);
+    reverb.set_mix(1.0);
+    let mut output = Vec::new();
+    loop {
+        match reverb.next_sample().unwrap() {
+            NextSample::Sample(s) => output.push(s),
+            NextSample::Finished => break,
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(output.len() < 20 * SAMPLE_RATE as usize, "tail never ends");
+    }
+    output
+}
+
+fn energy(samples: &[i16]) -> f64 {
+    samples.iter().map(|s| (*s as f64).powi(2)).sum()
+}
+
+#[test]
+fn impulse_has_decaying_diffuse_tail() {
+    let response = impulse_response(0.5, 1);
+    let seconds = response.len() as f32 / SAMPLE_RATE as f32;
+    // A feedback of 0.84 on delays of about 30ms decays 60dB in about 1.2s
+    // and the tail continues until it is below 1 LSB.
+    assert!((0.8..3.0).contains(&seconds), "{seconds}s");
+
+    let window = SAMPLE_RATE as usize / 10;
+    let energies: Vec<f64> = response.chunks(window).map(energy).collect();
+    // Nothing comes out before the shortest comb delay
+    assert!(response[..200].iter().all(|s| *s == 0));
+    assert!(energies[1] > 0.0);
+    // Decays over time
+    for pair in energies[1..].windows(3).step_by(3) {
+        assert!(pair[2] < pair[0], "{energies:?}");
+    }
+    // Diffuse: the echoes have blended into a dense tail
+    let tail = &response[SAMPLE_RATE as usize / 5..][..window];
+    let non_zero = tail.iter().filter(|s| **s != 0).count();
+    assert!(non_zero > window * 9 / 10, "{non_zero}");
+}
+
+#[test]
+fn larger_room_lasts_longer() {
+    let small = impulse_response(0.2, 1);
+    let large = impulse_response(0.9, 1);
+    assert!(large.len() > 2 * small.len());
+}
+
+#[test]
+fn channels_are_decorrelated() {
+    let mut samples = vec![0; 2];
+    samples[0] = i16::MAX;
+    samples[1] = i16::MAX;
+    let impulse = MemorySound::from_samples(Arc::new(samples), 2, SAMPLE_RATE);
+    let mut reverb = Reverb::new(impulse);
+    reverb.set_mix(1.0);
+    let mut output = Vec::new();
+    while let NextSample::Sample(s) = reverb.next_sample().unwrap() {
+        output.push(s);
+    }
+    assert!(output.len().is_multiple_of(2));
+    let (left, right): (Vec<i16>, Vec<i16>) =
+        output.chunks(2).map(|frame| (frame[0], frame[1])).unzip();
+    assert_ne!(left, right);
+    assert!(energy(&left) > 0.0 && energy(&right) > 0.0);
+}
+
+#[test]
+fn dry_mix_passes_input() {
+    let mut reverb = Reverb::new(ConstantValueSound::new(1000));
+    reverb.set_mix(0.0);
+    for _ in 0..100 {
+        assert_eq!(reverb.next_sample().unwrap(), NextSample::Sample(1000));
+    }
+}
+
+#[test]
+fn metadata_change_clears_state() {
+    let mut inner = ConstantValueSound::new(10000);
+    inner.channel_count = 1;
+    let mut reverb = Reverb::new(inner);
+    reverb.set_mix(1.0);
+    for _ in 0..SAMPLE_RATE {
+        reverb.next_sample().unwrap();
+    }
+    reverb.inner_mut().value = 0;
+    reverb.inner_mut().set_channel_count(2);
+    assert_eq!(reverb.next_sample().unwrap(), NextSample::MetadataChanged);
+    for _ in 0..1000 {
+        assert_eq!(reverb.next_sample().unwrap(), NextSample::Sample(0));
+    }
+}
@@ -0,0 +1,77 @@
+use std::f32::consts::PI;
+
+use super::*;
+use crate::sounds::SineWav;
+use crate::tests::{collect_n, ConstantValueSound};
+
+const SAMPLE_RATE: u32 = 8000;
+
+/// Amplitude of `frequency` in `samples` relative to full scale using the
+/// Goertzel algorithm.
+fn amplitude(samples: &[i16], frequency: f32) -> f32 {
+    let coefficient = 2.0 * (2.0 * PI * frequency / SAMPLE_RATE as f32).cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for sample in samples {
+        let s = *sample as f32 / i16::MAX as f32 + coefficient * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    let power = s1 * s1 + s2 * s2 - coefficient * s1 * s2;
+    2.0 * power.sqrt() / samples.len() as f32
+}
+
+#[test]
+fn sine_carrier_produces_sum_and_difference() {
+    let tone = SineWav::with_sample_rate(1000.0, SAMPLE_RATE);
+    let mut sound = RingMod::new(tone, 300.0, Waveform::Sine);
+    let samples = collect_n(&mut sound, SAMPLE_RATE as usize);
+    // Each sideband has half the amplitude of the input
+    for sideband in [700.0, 1300.0] {
+        let a = amplitude(&samples, sideband);
+        assert!((a - 0.5).abs() < 0.02, "{sideband} Hz: {a}");
+    }
+    for absent in [300.0, 1000.0] {
+        let a = amplitude(&samples, absent);
+        assert!(a < 0.01, "{absent} Hz: {a}");
+    }
+}
+
+#[test]
+fn mix_keeps_dry_signal() {
+    let tone = SineWav::with_sample_rate(1000.0, SAMPLE_RATE);
+    let mut sound = RingMod::new(tone, 300.0, Waveform::Sine);
+    sound.set_mix(0.5);
+    let samples = collect_n(&mut sound, SAMPLE_RATE as usize);
+    assert!((amplitude(&samples, 1000.0) - 0.5).abs() < 0.02);
+    assert!((amplitude(&samples, 700.0) - 0.25).abs() < 0.02);
+    assert!((amplitude(&samples, 1300.0) - 0.25).abs() < 0.02);
+}
+
+#[test]
+fn frequency_follows_sample_rate_change() {
+    let mut inner = ConstantValueSound::new(10000);
+    inner.channel_count = 2;
+    inner.sample_rate = 1000;
+    let mut sound = RingMod::new(inner, 250.0, Waveform::Square);
+    // 4 frames per period: 2 high then 2 low on both channels
+    let samples = collect_n(&mut sound, 16);
+    let expected: Vec<i16> = [10000, 10000, -10000, -10000]
+        .iter()
+        .flat_map(|s| [*s, *s])
+        .cycle()
+        .take(16)
+        .collect();
+    assert_eq!(samples, expected);
+
+    sound.inner_mut().set_sample_rate(2000);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    // Now 8 frames per period
+    let samples = collect_n(&mut sound, 32);
+    let expected: Vec<i16> = [10000; 8]
+        .into_iter()
+        .chain([-10000; 8])
+        .cycle()
+        .take(32)
+        .collect();
+    assert_eq!(samples, expected);
+}
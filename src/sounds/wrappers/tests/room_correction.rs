@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::{MemorySound, SineWav};
+use crate::tests::collect;
+
+#[test]
+fn applies_gain_delay_and_polarity() {
+    let inner = MemorySound::from_samples(
+        Arc::new(vec![1000, 1000, 1000, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+        3,
+        1000,
+    );
+    let profile = vec![
+        ChannelCorrection {
+            gain_db: -6.0206,
+            ..Default::default()
+        },
+        ChannelCorrection {
+            delay_samples: 2,
+            inverted: true,
+            ..Default::default()
+        },
+    ];
+    let mut correction = RoomCorrection::new(inner, profile);
+    assert_eq!(correction.profile().len(), 2);
+    assert_eq!(
+        collect(&mut correction),
+        vec![
+            500, 0, 1000, //
+            0, 0, 0, //
+            0, -1000, 0, //
+            0, 0, 0, //
+            // The delayed channel is played out
+            0, 0, 0, //
+            0, 0, 0, //
+        ]
+    );
+}
+
+#[test]
+fn eq_boosts_center_frequency() {
+    let sample_rate = 8000;
+    let sine = SineWav::with_sample_rate(1000.0, sample_rate).with_adjustable_volume_of(0.25);
+    let profile = vec![ChannelCorrection {
+        eq: vec![PeakingEq {
+            frequency: 1000.0,
+            gain_db: 6.0206,
+            q: 1.0,
+        }],
+        ..Default::default()
+    }];
+    let mut correction = RoomCorrection::new(sine, profile);
+    let samples: Vec<i16> = (0..sample_rate)
+        .map(|_| match correction.next_sample().unwrap() {
+            NextSample::Sample(s) => s,
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    // Skip the filter settling
+    let peak = samples[1000..]
+        .iter()
+        .map(|s| s.unsigned_abs())
+        .max()
+        .unwrap();
+    let expected = i16::MAX as f32 * 0.5;
+    assert!((peak as f32 - expected).abs() < expected * 0.02, "{peak}");
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+
+const SAMPLE_RATE: u32 = 1000;
+
+fn main_sound() -> ConstantValueSound {
+    let mut main = ConstantValueSound::new(10000);
+    main.channel_count = 2;
+    main.sample_rate = SAMPLE_RATE;
+    main
+}
+
+/// A kick of 5 loud frames every 100 frames.
+fn kick_pattern() -> Box<dyn Sound> {
+    let mut samples = vec![0; 100];
+    samples[..5].fill(30000);
+    let mut key = MemorySound::from_samples(Arc::new(samples), 1, SAMPLE_RATE);
+    key.set_looping(true);
+    Box::new(key)
+}
+
+#[test]
+fn gain_dips_on_each_key_hit() {
+    let mut gate = SidechainGate::new(main_sound(), kick_pattern()).unwrap();
+    gate.set_attack_release(Duration::from_millis(1), Duration::from_millis(20));
+    for _ in 0..4 {
+        let mut frames = Vec::new();
+        for _ in 0..100 {
+            let mut frame = Vec::new();
+            gate.append_next_frame_to(&mut frame).unwrap();
+            assert_eq!(frame[0], frame[1]);
+            frames.push(frame[0]);
+        }
+        // Lowered during the kick
+        assert!(frames[1..5].iter().all(|s| *s < 1000), "{frames:?}");
+        // And back up before the next one
+        assert!(frames[80..].iter().all(|s| *s > 9500), "{frames:?}");
+    }
+}
+
+#[test]
+fn depth_limits_the_dip() {
+    let mut gate = SidechainGate::new(main_sound(), kick_pattern()).unwrap();
+    gate.set_depth(0.5);
+    let mut frame = Vec::new();
+    for _ in 0..5 {
+        frame.clear();
+        gate.append_next_frame_to(&mut frame).unwrap();
+    }
+    assert!((gate.gain() - 0.5).abs() < 0.01);
+    assert!((frame[0] - 5000).abs() < 100);
+}
+
+#[test]
+fn mismatched_sample_rate_is_an_error() {
+    let mut key = ConstantValueSound::new(0);
+    key.sample_rate = SAMPLE_RATE * 2;
+    assert!(SidechainGate::new(main_sound(), Box::new(key)).is_err());
+}
@@ -0,0 +1,95 @@
+use super::*;
+use crate::{
+    sounds::wrappers::SetPaused,
+    tests::{ConstantValueSound, Sawtooth},
+};
+
+#[test]
+fn no_conversion_passes_through() {
+    let mut converted = SincSampleRateConverter::new(Sawtooth::new(2, 1000), 1000, 16).unwrap();
+    for expected in [0, 0, 1, 1, 2, 2] {
+        assert_eq!(
+            converted.next_sample().unwrap(),
+            NextSample::Sample(expected)
+        );
+    }
+}
+
+#[test]
+fn constant_value_is_preserved() {
+    // 1031 Hz has too many phases for the weights to be cached
+    for to_rate in [250, 3000, 1031] {
+        let mut inner = ConstantValueSound::new(1000);
+        inner.sample_rate = 1000;
+        let mut converted = SincSampleRateConverter::new(inner, to_rate, 16).unwrap();
+        assert_eq!(converted.sample_rate(), to_rate);
+        assert_eq!(converted.channel_count(), 2);
+        // Skip the start where the filter overlaps the implicit silence
+        for _ in 0..400 {
+            converted.next_sample().unwrap();
+        }
+        for _ in 0..100 {
+            let NextSample::Sample(s) = converted.next_sample().unwrap() else {
+                panic!("expected sample");
+            };
+            assert!((s - 1000).abs() <= 1, "{s}");
+        }
+    }
+}
+
+#[test]
+fn paused_and_finished() {
+    let mut inner = ConstantValueSound::new(1000);
+    inner.sample_rate = 1000;
+    let mut converted = SincSampleRateConverter::new(
+        inner
+            .finish_after(std::time::Duration::from_millis(100))
+            .pausable(),
+        500,
+        8,
+    )
+    .unwrap();
+    converted.inner_mut().set_paused(true);
+    assert_eq!(converted.next_sample().unwrap(), NextSample::Paused);
+    converted.inner_mut().set_paused(false);
+    let mut count = 0;
+    while let NextSample::Sample(_) = converted.next_sample().unwrap() {
+        count += 1;
+    }
+    assert_eq!(count, 100);
+    assert_eq!(converted.next_sample().unwrap(), NextSample::Finished);
+}
+
+#[test]
+fn channel_count_change() {
+    let mut inner = ConstantValueSound::new(1000);
+    inner.sample_rate = 1000;
+    let mut converted = SincSampleRateConverter::new(inner, 500, 8).unwrap();
+    converted.next_sample().unwrap();
+    converted.next_sample().unwrap();
+    converted.inner_mut().set_channel_count(1);
+    assert_eq!(
+        converted.next_sample().unwrap(),
+        NextSample::MetadataChanged
+    );
+    assert_eq!(converted.channel_count(), 1);
+}
+
+#[test]
+fn too_few_taps_is_error() {
+    assert!(SincSampleRateConverter::new(Sawtooth::new(2, 1000), 500, 1).is_err());
+    assert!(SincSampleRateConverter::new(Sawtooth::new(2, 1000), 0, 16).is_err());
+    assert!(SincSampleRateConverter::new(Sawtooth::new(2, 0), 500, 16).is_err());
+}
+
+#[test]
+fn sample_rate_change_to_zero_is_error() {
+    let mut inner = ConstantValueSound::new(1000);
+    inner.sample_rate = 1000;
+    let mut converted = SincSampleRateConverter::new(inner, 500, 8).unwrap();
+    converted.next_sample().unwrap();
+    converted.inner_mut().set_sample_rate(0);
+    // Samples computed before the change are returned first
+    let error = (0..100).find_map(|_| converted.next_sample().err());
+    assert!(error.is_some());
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use super::*;
+use crate::sounds::{Silence, SineWav, SoundList};
+
+const SAMPLE_RATE: u32 = 8000;
+
+fn tone(duration: Duration) -> Box<dyn Sound> {
+    Box::new(
+        SineWav::with_sample_rate(440.0, SAMPLE_RATE)
+            .with_adjustable_volume_of(0.5)
+            .finish_after(duration),
+    )
+}
+
+fn silence(duration: Duration) -> Box<dyn Sound> {
+    Box::new(Silence::new(1, SAMPLE_RATE).finish_after(duration))
+}
+
+fn count_samples(sound: &mut impl Sound) -> usize {
+    let mut count = 0;
+    loop {
+        match sound.next_sample().unwrap() {
+            NextSample::Sample(_) => count += 1,
+            NextSample::MetadataChanged => (),
+            NextSample::Paused => panic!("unexpected pause"),
+            NextSample::Finished => return count,
+        }
+    }
+}
+
+#[test]
+fn long_gap_is_skipped() {
+    let list = SoundList::from(vec![
+        tone(Duration::from_secs(1)),
+        silence(Duration::from_secs(5)),
+        tone(Duration::from_secs(1)),
+    ]);
+    let mut sound = list.skip_silence(Duration::from_millis(500));
+    sound.set_pad(Duration::from_millis(200));
+    let played = count_samples(&mut sound) as f32 / SAMPLE_RATE as f32;
+    // Both tones and the pad are played. The RMS measurement takes a moment
+    // to drop below the threshold after the first tone.
+    assert!((2.2..2.4).contains(&played), "played {played}");
+    let skipped = sound.skipped().as_secs_f32();
+    assert!((4.6..4.8).contains(&skipped), "skipped {skipped}");
+    assert!((played + skipped - 7.0).abs() < 0.01);
+}
+
+#[test]
+fn short_gap_is_kept() {
+    let list = SoundList::from(vec![
+        tone(Duration::from_secs(1)),
+        silence(Duration::from_millis(300)),
+        tone(Duration::from_secs(1)),
+    ]);
+    let mut sound = list.skip_silence(Duration::from_millis(500));
+    assert_eq!(count_samples(&mut sound), 2 * 8000 + 2400);
+    assert_eq!(sound.skipped(), Duration::ZERO);
+}
@@ -0,0 +1,55 @@
+use crate::sounds::SineWav;
+use crate::tests::ConstantValueSound;
+
+use super::*;
+
+#[test]
+fn pure_tone_peaks_in_expected_bin() {
+    // 1000 Hz falls exactly on bin 32
+    let (mut tap, handle) = SpectrumTap::new(
+        SineWav::with_sample_rate(1000.0, 16000),
+        512,
+        WindowFunction::Hann,
+    );
+    assert!(handle.magnitudes().iter().all(|m| *m == 0.0));
+    for _ in 0..2048 {
+        tap.next_sample().unwrap();
+    }
+    let magnitudes = handle.magnitudes();
+    assert_eq!(magnitudes.len(), 257);
+    let peak_bin = magnitudes
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .unwrap()
+        .0;
+    assert_eq!(peak_bin, 32);
+    assert_eq!(handle.bin_frequency(peak_bin), 1000.0);
+    assert!((magnitudes[32] - 1.0).abs() < 0.05, "{}", magnitudes[32]);
+    assert!(magnitudes[40] < 0.001);
+}
+
+#[test]
+fn audio_is_unchanged() {
+    let (mut tap, _handle) = SpectrumTap::new(ConstantValueSound::new(7), 16, Default::default());
+    for _ in 0..100 {
+        assert_eq!(tap.next_sample().unwrap(), NextSample::Sample(7));
+    }
+}
+
+#[test]
+fn constant_value_only_has_dc() {
+    // A constant value only has energy at 0 Hz
+    let (mut tap, handle) = SpectrumTap::new(
+        ConstantValueSound::new(i16::MAX / 2),
+        64,
+        WindowFunction::Rectangular,
+    );
+    for _ in 0..64 * 2 {
+        tap.next_sample().unwrap();
+    }
+    let magnitudes = handle.magnitudes();
+    // DC is scaled by 2 like every other bin
+    assert!((magnitudes[0] - 1.0).abs() < 0.01, "{}", magnitudes[0]);
+    assert!(magnitudes[1..].iter().all(|m| *m < 0.001));
+}
@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant};
+
+use super::*;
+use crate::tests::ConstantValueSound;
+
+/// The number of samples returned before the first non-silent one.
+fn leading_silence(sound: &mut impl Sound) -> usize {
+    let mut count = 0;
+    while sound.next_sample().unwrap() == NextSample::Sample(0) {
+        count += 1;
+    }
+    count
+}
+
+#[test]
+fn silence_until_start() {
+    let inner = ConstantValueSound {
+        value: 5,
+        channel_count: 2,
+        sample_rate: 48000,
+        metadata_changed: false,
+    };
+    let before = Instant::now();
+    let start = before + Duration::from_millis(200);
+    let mut sound = SyncStart::new(inner, start);
+    assert_eq!(sound.start(), start);
+    let silence = leading_silence(&mut sound);
+    let elapsed = before.elapsed();
+
+    // 200ms of stereo frames minus the time taken to request the first sample
+    let max = 2 * 48000 / 5;
+    let min = 2 * ((Duration::from_millis(200) - elapsed).as_secs_f64() * 48000.0) as usize;
+    assert!(silence <= max, "{silence}");
+    assert!(silence >= min - 2, "{silence} {min}");
+    assert_eq!(silence % 2, 0);
+}
+
+#[test]
+fn past_start_plays_immediately() {
+    let mut sound = SyncStart::new(ConstantValueSound::new(5), Instant::now());
+    assert_eq!(leading_silence(&mut sound), 0);
+}
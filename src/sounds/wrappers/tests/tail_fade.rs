@@ -0,0 +1,59 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sounds::wrappers::FinishAfter;
+use crate::sounds::{MemorySound, SoundList};
+use crate::tests::{collect, ConstantValueSound};
+
+use super::*;
+
+#[test]
+fn dc_source_ramps_to_zero_before_finished() {
+    let dc = MemorySound::from_samples(Arc::new(vec![10000; 100]), 1, 1000);
+    let mut sound = dc.with_tail_fade(Duration::from_millis(10));
+    let samples = collect(&mut sound);
+    assert_eq!(sound.next_sample().unwrap(), NextSample::Finished);
+
+    assert_eq!(samples.len(), 100);
+    assert!(samples[..90].iter().all(|s| *s == 10000));
+    let tail = &samples[90..];
+    assert_eq!(tail[0], 9000);
+    assert!(tail.windows(2).all(|w| w[1] < w[0]));
+    assert_eq!(*tail.last().unwrap(), 0);
+}
+
+#[test]
+fn channels_of_a_frame_have_the_same_gain() {
+    let dc = MemorySound::from_samples(Arc::new([1000, -1000].repeat(20)), 2, 1000);
+    let mut sound = TailFade::new(dc, Duration::from_millis(5), FadeCurve::Cosine);
+    let samples = collect(&mut sound);
+    assert_eq!(samples.len(), 40);
+    for frame in samples.chunks(2) {
+        assert_eq!(frame[0], -frame[1]);
+    }
+    assert_eq!(samples[38..], [0, 0]);
+}
+
+#[test]
+fn sound_shorter_than_fade() {
+    let dc = MemorySound::from_samples(Arc::new(vec![1000; 3]), 1, 1000);
+    let mut sound = dc.with_tail_fade(Duration::from_millis(10));
+    assert_eq!(collect(&mut sound), vec![200, 100, 0]);
+}
+
+#[test]
+fn metadata_change_returns_buffered_samples_first() {
+    let mut second = ConstantValueSound::new(7);
+    second.channel_count = 1;
+    let first = MemorySound::from_samples(Arc::new(vec![1, 2, 3]), 1, 1000);
+    let list = SoundList::from(vec![
+        Box::new(first) as Box<dyn Sound>,
+        Box::new(FinishAfter::new(second, Duration::from_millis(10))),
+    ]);
+    let mut sound = list.with_tail_fade(Duration::from_millis(2));
+    for expected in [1, 2, 3] {
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(expected));
+    }
+    assert_eq!(sound.next_sample().unwrap(), NextSample::MetadataChanged);
+    assert_eq!(sound.sample_rate(), crate::tests::DEFAULT_SAMPLE_RATE);
+}
@@ -0,0 +1,77 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+use crate::tests::collect;
+
+const SAMPLE_RATE: u32 = 48000;
+
+/// The true peak of mono `samples` as a fraction of full scale, measured by
+/// oversampling 16 times with a long windowed sinc.
+fn true_peak(samples: &[i16]) -> f64 {
+    const RADIUS: i64 = 32;
+    let mut peak = 0.0f64;
+    for idx in 0..samples.len() as i64 {
+        for point in 0..16 {
+            let time = idx as f64 + point as f64 / 16.0;
+            let mut value = 0.0;
+            for frame in idx - RADIUS + 1..=idx + RADIUS {
+                if frame < 0 || frame >= samples.len() as i64 {
+                    continue;
+                }
+                let x = time - frame as f64;
+                value += samples[frame as usize] as f64 * sinc(x) * blackman(x / RADIUS as f64);
+            }
+            peak = peak.max(value.abs() / i16::MAX as f64);
+        }
+    }
+    peak
+}
+
+/// A sine at a quarter of the sample rate with its peaks halfway between
+/// samples so every sample is at 0.707 of the true peak `amplitude`.
+fn quarter_rate_sine(amplitude: f64, num_frames: usize) -> Vec<i16> {
+    (0..num_frames)
+        .map(|n| {
+            let value = amplitude * (PI / 2.0 * n as f64 + PI / 4.0).sin();
+            (value * i16::MAX as f64).round() as i16
+        })
+        .collect()
+}
+
+#[test]
+fn inter_sample_peaks_stay_below_ceiling() {
+    // Silence then a burst whose samples are below the ceiling but whose
+    // true peak is above full scale
+    let mut input = vec![0; 2000];
+    input.extend(quarter_rate_sine(1.2, 4000));
+    input.extend(vec![0; 2000]);
+    let sample_peak = input.iter().map(|s| s.unsigned_abs()).max().unwrap();
+    let ceiling = 10f64.powf(-1.0 / 20.0);
+    assert!((sample_peak as f64 / i16::MAX as f64) < ceiling);
+    assert!(true_peak(&input) > 1.15);
+
+    let sound = MemorySound::from_samples(Arc::new(input.clone()), 1, SAMPLE_RATE);
+    let mut limiter = TruePeakLimiter::new(sound);
+    let output = collect(&mut limiter);
+    assert_eq!(output.len(), input.len());
+    let peak = true_peak(&output);
+    // Allow for rounding to i16
+    assert!(peak <= ceiling + 0.001, "{peak} {ceiling}");
+    // The burst is limited instead of silenced
+    assert!(peak > ceiling - 0.05, "{peak}");
+}
+
+#[test]
+fn quiet_sound_is_unchanged() {
+    let input: Vec<i16> = quarter_rate_sine(0.5, 1000)
+        .into_iter()
+        .flat_map(|s| [s, -s])
+        .collect();
+    let sound = MemorySound::from_samples(Arc::new(input.clone()), 2, SAMPLE_RATE);
+    let mut limiter = TruePeakLimiter::new(sound);
+    limiter.set_ceiling(0.6);
+    assert_eq!(limiter.ceiling(), 0.6);
+    assert_eq!(collect(&mut limiter), input);
+}
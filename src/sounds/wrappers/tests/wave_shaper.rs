@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::sounds::MemorySound;
+
+/// A tanh soft clip sampled at 65 points.
+fn soft_clip() -> Vec<f32> {
+    (0..65)
+        .map(|i| (3.0 * (i as f32 / 32.0 - 1.0)).tanh())
+        .collect()
+}
+
+fn shaped(levels: &[f32], drive: f32, output_level: f32) -> Vec<f32> {
+    let samples = levels
+        .iter()
+        .map(|level| (level * i16::MAX as f32) as i16)
+        .collect();
+    let sound = MemorySound::from_samples(Arc::new(samples), 1, 8000);
+    let mut shaper = WaveShaper::new(sound, soft_clip()).unwrap();
+    shaper.set_drive(drive);
+    shaper.set_output_level(output_level);
+    let mut out = Vec::new();
+    while let NextSample::Sample(s) = shaper.next_sample().unwrap() {
+        out.push(s as f32 / i16::MAX as f32);
+    }
+    out
+}
+
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-3, "{actual:?} != {expected:?}");
+    }
+}
+
+#[test]
+fn output_follows_curve() {
+    let curve = soft_clip();
+    // -1.0, 0.0, exactly on entries and halfway between the two entries at
+    // 0.5 and 0.53125
+    let out = shaped(&[-1.0, 0.0, 0.25, 0.5, 0.515625], 1.0, 1.0);
+    let halfway = (curve[48] + curve[49]) / 2.0;
+    assert_close(&out, &[curve[0], curve[32], curve[40], curve[48], halfway]);
+    // The curve is soft clipping
+    assert!(out[3] > 0.5 && out[3] < 1.0);
+}
+
+#[test]
+fn drive_saturates_at_ends_of_curve() {
+    let curve = soft_clip();
+    let out = shaped(&[-0.75, -0.25, 0.25, 0.75], 4.0, 0.5);
+    // Driven past the domain of the curve and clamped to its ends
+    assert_close(
+        &out,
+        &[
+            0.5 * curve[0],
+            0.5 * curve[0],
+            0.5 * curve[64],
+            0.5 * curve[64],
+        ],
+    );
+}
+
+#[test]
+fn curve_needs_two_entries() {
+    let sound = MemorySound::from_samples(Arc::new(vec![0]), 1, 8000);
+    assert!(WaveShaper::new(sound.clone(), vec![1.0]).is_err());
+    let mut shaper = WaveShaper::new(sound, vec![-1.0, 1.0]).unwrap();
+    assert!(shaper.set_curve(Vec::new()).is_err());
+    // A two point line from -1 to 1 leaves the sound unchanged
+    assert_eq!(shaper.next_sample().unwrap(), NextSample::Sample(0));
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use crate::sounds::MemorySound;
+use crate::tests::ConstantValueSound;
+
+use super::*;
+
+fn next(sound: &mut impl Sound) -> i16 {
+    match sound.next_sample().unwrap() {
+        NextSample::Sample(s) => s,
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn gain_changes_at_next_zero_crossing() {
+    // 100Hz so a zero crossing every 40 samples
+    let samples: Vec<i16> = (0..160)
+        .map(|i| ((i as f32 / 80.0 * std::f32::consts::TAU).sin() * 10000.0).round() as i16)
+        .collect();
+    let mut sound = ZeroCrossGain::new(MemorySound::from_samples(
+        Arc::new(samples.clone()),
+        1,
+        8000,
+    ));
+    for expected in &samples[..10] {
+        assert_eq!(next(&mut sound), *expected);
+    }
+    sound.set_volume(0.5);
+    assert_eq!(sound.volume(), 0.5);
+    // Unchanged until the signal crosses zero
+    for expected in &samples[10..40] {
+        assert_eq!(next(&mut sound), *expected);
+    }
+    assert_eq!(samples[40], 0);
+    for expected in &samples[40..] {
+        assert_eq!(next(&mut sound), (*expected as f32 * 0.5) as i16);
+    }
+}
+
+#[test]
+fn ramps_after_timeout_without_zero_crossing() {
+    let mut inner = ConstantValueSound::new(1000);
+    inner.channel_count = 1;
+    inner.sample_rate = 1000;
+    let mut sound = ZeroCrossGain::new(inner);
+    sound.set_timeout(Duration::from_millis(20));
+    assert_eq!(next(&mut sound), 1000);
+    sound.set_volume(0.5);
+    for _ in 0..20 {
+        assert_eq!(next(&mut sound), 1000);
+    }
+    for expected in [900, 800, 700, 600, 500, 500] {
+        assert!((next(&mut sound) - expected).abs() <= 1);
+    }
+    assert_eq!(next(&mut sound), 500);
+}
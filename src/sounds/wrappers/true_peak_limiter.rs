@@ -0,0 +1,307 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{NextSample, Sound};
+
+use super::sinc_sample_rate_converter::{blackman, sinc};
+use super::Wrapper;
+
+/// How many times the sample rate peaks are detected at.
+const OVERSAMPLING: usize = 4;
+/// Half the number of input frames used to interpolate each oversampled
+/// point.
+const RADIUS: usize = 8;
+/// How long the gain takes to ramp down before a peak.
+const ATTACK_SECS: f32 = 0.0015;
+
+/// Limit the true peak level of the inner sound, including the peaks between
+/// samples that appear once the sound is converted to analog or resampled.
+///
+/// Every channel is oversampled 4 times with windowed sinc interpolation to
+/// find the peaks between samples. The gain is lowered smoothly over 1.5ms
+/// before each peak so the oversampled signal stays at or below the ceiling
+/// (-1 dBTP by default) and recovers afterwards over the release time (100ms
+/// by default). The same gain is applied to every channel so the stereo image
+/// does not shift.
+///
+/// Samples are pulled from the inner sound ahead of the ones returned to see
+/// peaks coming so the wrapper adds no delay. If the inner sound changes its
+/// channel count or sample rate the samples pulled ahead are dropped.
+pub struct TruePeakLimiter<S: Sound> {
+    inner: S,
+    ceiling: f32,
+    release: Duration,
+    release_coefficient: f32,
+    channel_count: usize,
+    /// The frames the gain ramps down over before a peak.
+    attack_frames: usize,
+    /// The interpolation weights of the input frames around each oversampled
+    /// point between two frames.
+    kernels: Vec<Vec<f32>>,
+    /// Input frames (interleaved) still needed.
+    history: VecDeque<f32>,
+    /// The input frame index of the first frame in `history`.
+    first_frame_idx: usize,
+    /// The number of input frames pulled from `inner` since the last reset.
+    frames_read: usize,
+    inner_finished: bool,
+    /// The true peak of every channel at each frame and up to the next frame,
+    /// for the frames around the upcoming output frames.
+    peaks: VecDeque<f32>,
+    /// The frame index of the first value in `peaks`.
+    first_peak_idx: usize,
+    /// The gain required before smoothing for the last `attack_frames`
+    /// output frames.
+    envelopes: VecDeque<f32>,
+    envelope: f32,
+    /// The index of the next output frame since the last reset.
+    next_output_frame: usize,
+    /// Remaining samples of the current output frame stored in reverse.
+    output_frame: Vec<i16>,
+    input_frame: Vec<i16>,
+}
+
+impl<S> TruePeakLimiter<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` limiting it to -1 dBTP.
+    pub fn new(inner: S) -> Self {
+        let kernels = (1..OVERSAMPLING)
+            .map(|point| {
+                let offset = point as f64 / OVERSAMPLING as f64;
+                // Input frames from RADIUS - 1 before to RADIUS after
+                let weights: Vec<f64> = (0..2 * RADIUS)
+                    .map(|idx| {
+                        let x = offset - (idx as f64 - (RADIUS as f64 - 1.0));
+                        sinc(x) * blackman(x / RADIUS as f64)
+                    })
+                    .collect();
+                let sum: f64 = weights.iter().sum();
+                weights.iter().map(|w| (w / sum) as f32).collect()
+            })
+            .collect();
+        let mut limiter = TruePeakLimiter {
+            inner,
+            ceiling: 10f32.powf(-1.0 / 20.0),
+            release: Duration::from_millis(100),
+            release_coefficient: 0.0,
+            channel_count: 0,
+            attack_frames: 0,
+            kernels,
+            history: VecDeque::new(),
+            first_frame_idx: 0,
+            frames_read: 0,
+            inner_finished: false,
+            peaks: VecDeque::new(),
+            first_peak_idx: 0,
+            envelopes: VecDeque::new(),
+            envelope: 1.0,
+            next_output_frame: 0,
+            output_frame: Vec::new(),
+            input_frame: Vec::new(),
+        };
+        limiter.reset();
+        limiter
+    }
+
+    /// Return the highest true peak level as a fraction of full scale.
+    pub fn ceiling(&self) -> f32 {
+        self.ceiling
+    }
+
+    /// Set the highest true peak level as a fraction of full scale.
+    pub fn set_ceiling(&mut self, ceiling: f32) {
+        self.ceiling = ceiling.clamp(f32::EPSILON, 1.0);
+    }
+
+    /// Set how quickly the gain recovers after a peak.
+    pub fn set_release(&mut self, release: Duration) {
+        self.release = release;
+        self.update_release_coefficient();
+    }
+
+    fn update_release_coefficient(&mut self) {
+        let frames = self.release.as_secs_f32() * self.inner.sample_rate() as f32;
+        self.release_coefficient = if frames > 0.0 {
+            (-1.0 / frames).exp()
+        } else {
+            0.0
+        };
+    }
+
+    fn reset(&mut self) {
+        self.channel_count = self.inner.channel_count() as usize;
+        self.attack_frames = ((ATTACK_SECS * self.inner.sample_rate() as f32) as usize).max(1);
+        self.update_release_coefficient();
+        self.history.clear();
+        self.first_frame_idx = 0;
+        self.frames_read = 0;
+        self.inner_finished = false;
+        self.peaks.clear();
+        self.first_peak_idx = 0;
+        self.envelopes.clear();
+        self.envelope = 1.0;
+        self.next_output_frame = 0;
+        self.output_frame.clear();
+    }
+
+    /// The sample of `channel` at input frame `frame_idx`, or silence outside
+    /// the sound.
+    fn sample(&self, frame_idx: isize, channel: usize) -> f32 {
+        if frame_idx < self.first_frame_idx as isize {
+            return 0.0;
+        }
+        let offset = (frame_idx as usize - self.first_frame_idx) * self.channel_count + channel;
+        self.history.get(offset).copied().unwrap_or(0.0)
+    }
+
+    /// The highest absolute level of any channel at `frame_idx` and at the
+    /// oversampled points up to the next frame.
+    fn true_peak(&self, frame_idx: usize) -> f32 {
+        let mut peak = 0.0f32;
+        for channel in 0..self.channel_count {
+            peak = peak.max(self.sample(frame_idx as isize, channel).abs());
+            let first = frame_idx as isize - (RADIUS as isize - 1);
+            for kernel in &self.kernels {
+                let value: f32 = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, weight)| weight * self.sample(first + idx as isize, channel))
+                    .sum();
+                peak = peak.max(value.abs());
+            }
+        }
+        peak
+    }
+
+    /// Compute the next output frame into `output_frame`.
+    ///
+    /// Returns `None` when the frame is ready otherwise what should be
+    /// returned instead.
+    fn compute_frame(&mut self) -> Result<Option<NextSample>, crate::Error> {
+        let frame_idx = self.next_output_frame;
+        // The gain of this frame depends on the peaks up to `last_peak` which
+        // are interpolated from the frames up to RADIUS after it
+        let last_peak = frame_idx + self.attack_frames + RADIUS;
+        while !self.inner_finished && self.frames_read <= last_peak + RADIUS {
+            self.input_frame.clear();
+            match self.inner.append_next_frame_to(&mut self.input_frame) {
+                Ok(()) => {
+                    self.history
+                        .extend(self.input_frame.iter().map(|s| *s as f32 / i16::MAX as f32));
+                    self.frames_read += 1;
+                }
+                Err(Ok(NextSample::Sample(_))) => unreachable!(),
+                Err(Ok(NextSample::MetadataChanged)) => {
+                    self.reset();
+                    return Ok(Some(NextSample::MetadataChanged));
+                }
+                Err(Ok(NextSample::Paused)) => return Ok(Some(NextSample::Paused)),
+                Err(Ok(NextSample::Finished)) => self.inner_finished = true,
+                Err(Err(e)) => return Err(e),
+            }
+        }
+        if self.inner_finished && frame_idx >= self.frames_read {
+            return Ok(Some(NextSample::Finished));
+        }
+
+        while self.first_peak_idx + self.peaks.len() <= last_peak {
+            let peak = self.true_peak(self.first_peak_idx + self.peaks.len());
+            self.peaks.push_back(peak);
+        }
+        // Keep the peaks within RADIUS before this frame since they are
+        // interpolated from frames around it
+        while self.first_peak_idx + RADIUS < frame_idx {
+            self.peaks.pop_front();
+            self.first_peak_idx += 1;
+        }
+        let peak = self.peaks.iter().copied().fold(0.0, f32::max);
+        let required = if peak > self.ceiling {
+            self.ceiling / peak
+        } else {
+            1.0
+        };
+        self.envelope = if required < self.envelope {
+            required
+        } else {
+            required + (self.envelope - required) * self.release_coefficient
+        };
+        self.envelopes.push_back(self.envelope);
+        if self.envelopes.len() > self.attack_frames {
+            self.envelopes.pop_front();
+        }
+        // Averaging ramps the gain down over the attack while still reaching
+        // the required gain by the peak since every envelope averaged covers
+        // it
+        let gain = self.envelopes.iter().sum::<f32>() / self.envelopes.len() as f32;
+
+        self.output_frame.clear();
+        for channel in (0..self.channel_count).rev() {
+            let sample = self.sample(frame_idx as isize, channel) * gain * i16::MAX as f32;
+            self.output_frame
+                .push(sample.clamp(i16::MIN as f32, i16::MAX as f32).round() as i16);
+        }
+        self.next_output_frame += 1;
+
+        // Drop input frames that are no longer needed for output or
+        // interpolation
+        let first_needed = (frame_idx + 1).min((last_peak + 1).saturating_sub(RADIUS - 1));
+        while self.first_frame_idx < first_needed && !self.history.is_empty() {
+            self.history.drain(..self.channel_count);
+            self.first_frame_idx += 1;
+        }
+        Ok(None)
+    }
+}
+
+impl<S> Sound for TruePeakLimiter<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        if let Some(sample) = self.output_frame.pop() {
+            return Ok(NextSample::Sample(sample));
+        }
+        if let Some(special) = self.compute_frame()? {
+            return Ok(special);
+        }
+        Ok(NextSample::Sample(self.output_frame.pop().unwrap()))
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Moderate)
+    }
+}
+
+impl<S: Sound> Wrapper for TruePeakLimiter<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/true_peak_limiter.rs"]
+mod tests;
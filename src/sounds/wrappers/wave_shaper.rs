@@ -0,0 +1,143 @@
+use crate::{NextSample, Sound};
+
+use super::Wrapper;
+
+/// Map every sample of the inner sound through a transfer curve, for
+/// distortion and saturation effects.
+///
+/// The curve is a lookup table covering inputs from -1.0 (the first entry)
+/// to 1.0 (the last entry) in equal steps, where 1.0 is full scale. Inputs
+/// between entries are linearly interpolated. Each sample is multiplied by
+/// the drive before the lookup, clamped to -1.0..=1.0 so louder inputs use
+/// the ends of the curve, and the output of the curve is multiplied by the
+/// output level.
+///
+/// Every channel goes through the same curve independently. The output is
+/// clipped to the range of i16.
+pub struct WaveShaper<S: Sound> {
+    inner: S,
+    curve: Vec<f32>,
+    drive: f32,
+    output_level: f32,
+}
+
+impl<S> WaveShaper<S>
+where
+    S: Sound,
+{
+    /// Shape `inner` with `curve` at a drive and output level of 1.0.
+    ///
+    /// Returns an error if `curve` has fewer than 2 entries.
+    pub fn new(inner: S, curve: Vec<f32>) -> Result<Self, crate::Error> {
+        let mut shaper = WaveShaper {
+            inner,
+            curve: Vec::new(),
+            drive: 1.0,
+            output_level: 1.0,
+        };
+        shaper.set_curve(curve)?;
+        Ok(shaper)
+    }
+
+    /// Replace the curve.
+    ///
+    /// Returns an error and leaves the curve unchanged if `curve` has fewer
+    /// than 2 entries.
+    pub fn set_curve(&mut self, curve: Vec<f32>) -> Result<(), crate::Error> {
+        if curve.len() < 2 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "curve must have at least 2 entries",
+            )
+            .into());
+        }
+        self.curve = curve;
+        Ok(())
+    }
+
+    /// Return the gain applied before the curve.
+    pub fn drive(&self) -> f32 {
+        self.drive
+    }
+
+    /// Set the gain applied before the curve. Higher values push more of the
+    /// signal into the ends of the curve.
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive = drive.max(0.0);
+    }
+
+    /// Return the gain applied after the curve.
+    pub fn output_level(&self) -> f32 {
+        self.output_level
+    }
+
+    /// Set the gain applied after the curve, e.g. to make up for the level
+    /// added by the drive.
+    pub fn set_output_level(&mut self, output_level: f32) {
+        self.output_level = output_level.max(0.0);
+    }
+
+    /// Look up `x` in `-1.0..=1.0` on the curve.
+    fn shape(&self, x: f32) -> f32 {
+        let last = self.curve.len() - 1;
+        let pos = (x.clamp(-1.0, 1.0) + 1.0) * 0.5 * last as f32;
+        let idx = (pos as usize).min(last - 1);
+        let frac = pos - idx as f32;
+        self.curve[idx] + (self.curve[idx + 1] - self.curve[idx]) * frac
+    }
+}
+
+impl<S> Sound for WaveShaper<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        match next {
+            NextSample::Sample(s) => {
+                let x = s as f32 / i16::MAX as f32 * self.drive;
+                let y = self.shape(x) * self.output_level * i16::MAX as f32;
+                Ok(NextSample::Sample(
+                    y.clamp(i16::MIN as f32, i16::MAX as f32) as i16,
+                ))
+            }
+            NextSample::MetadataChanged | NextSample::Paused | NextSample::Finished => Ok(next),
+        }
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S: Sound> Wrapper for WaveShaper<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.inner
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/wave_shaper.rs"]
+mod tests;
@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use crate::{utils, NextSample, Sound};
+
+use super::{SetPaused, SetSpeed, SetVolume};
+
+/// How long the gain ramps for once a channel has not crossed zero in time.
+const RAMP: Duration = Duration::from_millis(5);
+
+/// A wrapper that adjusts the volume of the inner sound, waiting for each
+/// channel to cross zero before applying a new volume so the change does not
+/// click.
+///
+/// A new volume is applied to a channel at the first sample at or past a zero
+/// crossing. A channel that does not cross zero within the timeout (20ms by
+/// default, a half cycle of a 25Hz tone) ramps to the new volume over 5ms
+/// instead, e.g. for DC offsets or silence that is not exactly zero.
+pub struct ZeroCrossGain<S: Sound> {
+    inner: S,
+    volume: f32,
+    timeout: Duration,
+    timeout_frames: u64,
+    ramp_frames: u64,
+    channels: Vec<ChannelGain>,
+    next_channel_idx: usize,
+}
+
+struct ChannelGain {
+    gain: f32,
+    previous: i16,
+    /// Frames waited for a zero crossing since the volume was changed.
+    waited_frames: u64,
+    /// How much the gain changes each frame once ramping, 0.0 while waiting
+    /// for a zero crossing.
+    ramp_step: f32,
+}
+
+impl<S> ZeroCrossGain<S>
+where
+    S: Sound,
+{
+    /// Wrap `inner` such that its volume can be adjusted without clicks.
+    ///
+    /// The value is set to 1.0 so not adjustment is made.
+    pub fn new(inner: S) -> Self {
+        Self::new_with_volume(inner, 1.0)
+    }
+
+    /// Wrap `inner` such that its volume can be adjusted without clicks and
+    /// set an initial adjustment which is applied immediately.
+    pub fn new_with_volume(inner: S, volume: f32) -> Self {
+        let mut zero_cross_gain = ZeroCrossGain {
+            inner,
+            volume,
+            timeout: Duration::from_millis(20),
+            timeout_frames: 0,
+            ramp_frames: 0,
+            channels: Vec::new(),
+            next_channel_idx: 0,
+        };
+        zero_cross_gain.reset();
+        zero_cross_gain
+    }
+
+    /// Return the most recently set volume multiplier. Channels may not have
+    /// reached it yet.
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Set how long a channel waits for a zero crossing before ramping to a
+    /// new volume.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+        self.timeout_frames = utils::duration_to_num_samples(timeout, 1, self.inner.sample_rate());
+    }
+
+    /// Get a reference to the wrapped inner Sound.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped inner Sound.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap and return the previously wrapped Sound.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Apply the volume to every channel for the current format of the inner
+    /// sound.
+    fn reset(&mut self) {
+        let sample_rate = self.inner.sample_rate();
+        self.timeout_frames = utils::duration_to_num_samples(self.timeout, 1, sample_rate);
+        self.ramp_frames = utils::duration_to_num_samples(RAMP, 1, sample_rate).max(1);
+        self.channels = (0..self.inner.channel_count())
+            .map(|_| ChannelGain {
+                gain: self.volume,
+                previous: 0,
+                waited_frames: 0,
+                ramp_step: 0.0,
+            })
+            .collect();
+        self.next_channel_idx = 0;
+    }
+
+    fn apply(&mut self, sample: i16) -> i16 {
+        let channel_count = self.channels.len();
+        let Some(channel) = self.channels.get_mut(self.next_channel_idx) else {
+            return sample;
+        };
+        self.next_channel_idx = (self.next_channel_idx + 1) % channel_count;
+        let previous = std::mem::replace(&mut channel.previous, sample);
+        if channel.gain != self.volume {
+            let crossed = (previous <= 0 && sample >= 0) || (previous >= 0 && sample <= 0);
+            if channel.ramp_step > 0.0 {
+                channel.gain = if channel.gain < self.volume {
+                    (channel.gain + channel.ramp_step).min(self.volume)
+                } else {
+                    (channel.gain - channel.ramp_step).max(self.volume)
+                };
+            } else if crossed {
+                channel.gain = self.volume;
+            } else {
+                channel.waited_frames += 1;
+                if channel.waited_frames >= self.timeout_frames {
+                    channel.ramp_step =
+                        (self.volume - channel.gain).abs() / self.ramp_frames as f32;
+                }
+            }
+        }
+        (sample as f32 * channel.gain) as i16
+    }
+}
+
+impl<S> Sound for ZeroCrossGain<S>
+where
+    S: Sound,
+{
+    fn channel_count(&self) -> u16 {
+        self.inner.channel_count()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn next_sample(&mut self) -> Result<NextSample, crate::Error> {
+        let next = self.inner.next_sample()?;
+        Ok(match next {
+            NextSample::Sample(s) => NextSample::Sample(self.apply(s)),
+            NextSample::MetadataChanged => {
+                self.reset();
+                next
+            }
+            NextSample::Paused | NextSample::Finished => next,
+        })
+    }
+
+    fn on_start_of_batch(&mut self) {
+        self.inner.on_start_of_batch()
+    }
+
+    fn estimated_cost(&self) -> crate::CostHint {
+        self.inner.estimated_cost().max(crate::CostHint::Low)
+    }
+}
+
+impl<S> SetVolume for ZeroCrossGain<S>
+where
+    S: Sound,
+{
+    fn set_volume(&mut self, new: f32) {
+        self.volume = new;
+        for channel in &mut self.channels {
+            channel.waited_frames = 0;
+            channel.ramp_step = 0.0;
+        }
+    }
+}
+
+impl<S> SetPaused for ZeroCrossGain<S>
+where
+    S: Sound + SetPaused,
+{
+    fn set_paused(&mut self, paused: bool) {
+        self.inner.set_paused(paused)
+    }
+}
+
+impl<S> SetSpeed for ZeroCrossGain<S>
+where
+    S: Sound + SetSpeed,
+{
+    fn set_speed(&mut self, multiplier: f32) {
+        self.inner.set_speed(multiplier)
+    }
+}
+
+#[cfg(test)]
+#[path = "./tests/zero_cross_gain.rs"]
+mod tests;
@@ -59,6 +59,39 @@ impl ConstantValueSound {
     }
 }
 
+/// Read samples from `sound` until it returns anything else.
+pub fn collect(sound: &mut impl Sound) -> Vec<i16> {
+    let mut samples = Vec::new();
+    while let NextSample::Sample(s) = sound.next_sample().unwrap() {
+        samples.push(s);
+    }
+    samples
+}
+
+/// Read exactly `num_samples` samples from `sound`, panicking if it returns
+/// anything else.
+pub fn collect_n(sound: &mut impl Sound, num_samples: usize) -> Vec<i16> {
+    (0..num_samples)
+        .map(|_| match sound.next_sample().unwrap() {
+            NextSample::Sample(s) => s,
+            other => panic!("expected sample, got {other:?}"),
+        })
+        .collect()
+}
+
+/// The power of `frequency` in `samples` measured with the Goertzel
+/// algorithm, relative to full scale.
+pub fn goertzel(samples: &[i16], frequency: f32, sample_rate: u32) -> f32 {
+    let coefficient = 2.0 * (std::f32::consts::TAU * frequency / sample_rate as f32).cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for sample in samples {
+        let s = *sample as f32 / i16::MAX as f32 + coefficient * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    s1 * s1 + s2 * s2 - coefficient * s1 * s2
+}
+
 /// Start at 0, increment by 1 until MAX value then jump to MIN value and
 /// increment by 1 again
 pub struct Sawtooth {
@@ -100,3 +133,194 @@ impl Sound for Sawtooth {
 
     fn on_start_of_batch(&mut self) {}
 }
+
+/// Encode `samples` as a 16 bit PCM WAV file.
+pub fn wav_bytes(channel_count: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&channel_count.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate * channel_count as u32 * 2).to_le_bytes());
+    bytes.extend_from_slice(&(channel_count * 2).to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+/// Write `bytes` to a file named `name` in a temporary directory for this test
+/// run and return its path. Use a unique name per test since tests run in
+/// parallel.
+pub fn write_temp_file(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("awedio-tests-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+/// An AAC raw data block containing a single channel element with no spectral
+/// data (i.e. silence) followed by the end element.
+pub fn silent_aac_frame() -> Vec<u8> {
+    // SCE id (3 bits), element tag (4), global gain (8), ics_info: reserved
+    // (1), long window (2), window shape (1), max_sfb 0 (6), no predictor (1),
+    // no pulse/tns/gain control (3) then END id (3).
+    let bits: u32 = (100 << 17) | 0b111;
+    bits.to_be_bytes().to_vec()
+}
+
+fn mp4_box(name: &[u8; 4], contents: &[&[u8]]) -> Vec<u8> {
+    let len: usize = contents.iter().map(|c| c.len()).sum();
+    let mut bytes = ((len + 8) as u32).to_be_bytes().to_vec();
+    bytes.extend_from_slice(name);
+    for content in contents {
+        bytes.extend_from_slice(content);
+    }
+    bytes
+}
+
+fn mp4_full_box(name: &[u8; 4], flags: u32, contents: &[&[u8]]) -> Vec<u8> {
+    let header = flags.to_be_bytes();
+    let mut all = vec![&header[..]];
+    all.extend_from_slice(contents);
+    mp4_box(name, &all)
+}
+
+fn mp4_descriptor(tag: u8, contents: &[&[u8]]) -> Vec<u8> {
+    let len: usize = contents.iter().map(|c| c.len()).sum();
+    let mut bytes = vec![tag, len as u8];
+    for content in contents {
+        bytes.extend_from_slice(content);
+    }
+    bytes
+}
+
+/// Build an M4A file containing an AAC track with the AudioSpecificConfig
+/// `asc` and the raw AAC `frames` of 1024 samples each.
+///
+/// `sample_rate` is the rate stored in the container.
+pub fn m4a_bytes(asc: &[u8], channel_count: u16, sample_rate: u32, frames: &[Vec<u8>]) -> Vec<u8> {
+    let u16b = |v: u16| v.to_be_bytes();
+    let u32b = |v: u32| v.to_be_bytes();
+    let num_frames = frames.len() as u32;
+    let duration = num_frames * 1024;
+
+    let ftyp = mp4_box(b"ftyp", &[b"M4A ", &u32b(0), b"M4A mp42isom"]);
+    let frame_data: Vec<u8> = frames.concat();
+    let mdat = mp4_box(b"mdat", &[&frame_data]);
+    let data_offset = ftyp.len() as u32 + 8;
+
+    let matrix: Vec<u8> = [0x10000u32, 0, 0, 0, 0x10000, 0, 0, 0, 0x40000000]
+        .iter()
+        .flat_map(|v| v.to_be_bytes())
+        .collect();
+    let mvhd = mp4_full_box(
+        b"mvhd",
+        0,
+        &[
+            &u32b(0),
+            &u32b(0),
+            &u32b(sample_rate),
+            &u32b(duration),
+            &u32b(0x10000),
+            &u16b(0x100),
+            &[0; 10],
+            &matrix,
+            &[0; 24],
+            &u32b(2),
+        ],
+    );
+    let tkhd = mp4_full_box(
+        b"tkhd",
+        3,
+        &[
+            &u32b(0),
+            &u32b(0),
+            &u32b(1),
+            &u32b(0),
+            &u32b(duration),
+            &[0; 8],
+            &u16b(0),
+            &u16b(0),
+            &u16b(0x100),
+            &u16b(0),
+            &matrix,
+            &u32b(0),
+            &u32b(0),
+        ],
+    );
+    let mdhd = mp4_full_box(
+        b"mdhd",
+        0,
+        &[
+            &u32b(0),
+            &u32b(0),
+            &u32b(sample_rate),
+            &u32b(duration),
+            &u16b(0x55c4),
+            &u16b(0),
+        ],
+    );
+    let hdlr = mp4_full_box(b"hdlr", 0, &[&u32b(0), b"soun", &[0; 12], &[0]]);
+    let smhd = mp4_full_box(b"smhd", 0, &[&u16b(0), &u16b(0)]);
+    let dref = mp4_full_box(b"dref", 0, &[&u32b(1), &mp4_full_box(b"url ", 1, &[])]);
+    let dinf = mp4_box(b"dinf", &[&dref]);
+
+    let decoder_specific_info = mp4_descriptor(0x05, &[asc]);
+    let decoder_config = mp4_descriptor(
+        0x04,
+        &[
+            // MPEG-4 audio, audio stream
+            &[0x40, 0x15],
+            &[0; 3],
+            &u32b(0),
+            &u32b(0),
+            &decoder_specific_info,
+        ],
+    );
+    let sl_config = mp4_descriptor(0x06, &[&[0x02]]);
+    let es_descriptor = mp4_descriptor(0x03, &[&u16b(1), &[0], &decoder_config, &sl_config]);
+    let esds = mp4_full_box(b"esds", 0, &[&es_descriptor]);
+    let mp4a = mp4_box(
+        b"mp4a",
+        &[
+            &[0; 6],
+            &u16b(1),
+            &[0; 8],
+            &u16b(channel_count),
+            &u16b(16),
+            &u16b(0),
+            &u16b(0),
+            &u32b(sample_rate << 16),
+            &esds,
+        ],
+    );
+    let stsd = mp4_full_box(b"stsd", 0, &[&u32b(1), &mp4a]);
+    let stts = mp4_full_box(b"stts", 0, &[&u32b(1), &u32b(num_frames), &u32b(1024)]);
+    let stsc = mp4_full_box(
+        b"stsc",
+        0,
+        &[&u32b(1), &u32b(1), &u32b(num_frames), &u32b(1)],
+    );
+    let sizes: Vec<u8> = frames
+        .iter()
+        .flat_map(|f| (f.len() as u32).to_be_bytes())
+        .collect();
+    let stsz = mp4_full_box(b"stsz", 0, &[&u32b(0), &u32b(num_frames), &sizes]);
+    let stco = mp4_full_box(b"stco", 0, &[&u32b(1), &u32b(data_offset)]);
+    let stbl = mp4_box(b"stbl", &[&stsd, &stts, &stsc, &stsz, &stco]);
+    let minf = mp4_box(b"minf", &[&smhd, &dinf, &stbl]);
+    let mdia = mp4_box(b"mdia", &[&mdhd, &hdlr, &minf]);
+    let trak = mp4_box(b"trak", &[&tkhd, &mdia]);
+    let moov = mp4_box(b"moov", &[&mvhd, &trak]);
+
+    [ftyp, mdat, moov].concat()
+}
@@ -1,21 +1,69 @@
+use std::sync::Arc;
+
+use crate::sounds::MemorySound;
 use crate::tests::Sawtooth;
 
 use super::*;
+use crate::sounds::wrappers::AddSound;
 
 #[test]
 fn test_skip() {
     {
-        let mut sound = Sawtooth::new(1, std::u16::MAX as u32);
+        let mut sound = Sawtooth::new(1, u16::MAX as u32);
         sound.skip(Duration::from_millis(500)).unwrap();
-        assert_eq!(
-            sound.next_sample().unwrap(),
-            NextSample::Sample(std::i16::MAX)
-        );
+        assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(i16::MAX));
     }
     {
-        let mut sound = Sawtooth::new(1, std::u16::MAX as u32);
+        let mut sound = Sawtooth::new(1, u16::MAX as u32);
         sound.skip(Duration::from_millis(1000)).unwrap();
         assert_eq!(sound.next_sample().unwrap(), NextSample::Sample(-1));
     }
     // TODO test metadata changed
 }
+
+#[test]
+fn next_samples_f32_stops_at_finished() {
+    let mut sound = MemorySound::from_samples(Arc::new(vec![0, 16384, -32768]), 1, 1000);
+    let mut out = [9.0; 4];
+    assert_eq!(sound.next_samples_f32(&mut out).unwrap(), 3);
+    assert_eq!(out, [0.0, 0.5, -1.0, 9.0]);
+    assert_eq!(sound.next_samples_f32(&mut out).unwrap(), 0);
+}
+
+#[test]
+fn try_clone_boxed_plays_independent_copies() {
+    let samples: Vec<i16> = (1..=8).map(|i| i * 100).collect();
+    let mut template: Box<dyn Sound> =
+        Box::new(MemorySound::from_samples(Arc::new(samples), 1, 8000));
+    // Partially played templates still clone from the start
+    assert_eq!(template.next_sample().unwrap(), NextSample::Sample(100));
+
+    let first = template.try_clone_boxed().unwrap();
+    let mut second = template.try_clone_boxed().unwrap();
+    assert_eq!(second.next_sample().unwrap(), NextSample::Sample(100));
+    assert_eq!(template.next_sample().unwrap(), NextSample::Sample(200));
+
+    let mut mixer = crate::sounds::SoundMixer::new(1, 8000);
+    mixer.add(first);
+    mixer.add(second);
+    let mut mixed = Vec::new();
+    for _ in 0..8 {
+        match mixer.next_sample().unwrap() {
+            NextSample::Sample(s) => mixed.push(s),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+    // The second copy is one sample ahead and finishes first
+    assert_eq!(mixed, [300, 500, 700, 900, 1100, 1300, 1500, 800]);
+}
+
+#[test]
+fn try_clone_boxed_of_generators() {
+    let mut dtmf: Box<dyn Sound> = Box::new(crate::sounds::Dtmf::new("1").unwrap());
+    let expected: Vec<_> = (0..100).map(|_| dtmf.next_sample().unwrap()).collect();
+    let mut clone = dtmf.try_clone_boxed().unwrap();
+    let cloned: Vec<_> = (0..100).map(|_| clone.next_sample().unwrap()).collect();
+    assert_eq!(cloned, expected);
+
+    assert!(Sawtooth::new(1, 8000).try_clone_boxed().is_none());
+}
@@ -29,4 +29,52 @@ fn test_duration_to_num_samples() {
         duration_to_num_samples(Duration::from_secs(1_000_000), 6, 44100),
         264_600_000_000
     );
+    assert_eq!(duration_to_num_samples(Duration::MAX, 2, 48000), u64::MAX);
+}
+
+#[test]
+fn test_num_samples_to_duration() {
+    assert_eq!(
+        num_samples_to_duration(88200, 2, 44100),
+        Duration::from_secs(1)
+    );
+    assert_eq!(
+        num_samples_to_duration(96, 2, 48000),
+        Duration::from_millis(1)
+    );
+    // Truncates partial frames
+    assert_eq!(
+        num_samples_to_duration(97, 2, 48000),
+        Duration::from_millis(1)
+    );
+}
+
+#[test]
+fn num_samples_to_duration_round_trips() {
+    for sample_rate in [8000, 22050, 44100, 48000, 96000] {
+        for num_samples in [1, 2, 3, 441, 1152, 44099, 44101, 10_000_019] {
+            let duration = num_samples_to_duration(num_samples * 2, 2, sample_rate);
+            assert_eq!(
+                duration_to_num_samples(duration, 2, sample_rate),
+                num_samples * 2,
+                "{num_samples} at {sample_rate}"
+            );
+        }
+    }
+    // 1/44100 of a second is 22675.7ns
+    assert_eq!(
+        num_samples_to_duration(1, 1, 44100),
+        Duration::from_nanos(22676)
+    );
+}
+
+#[test]
+fn num_samples_to_duration_of_large_timestamp_does_not_overflow() {
+    let frames = u64::MAX - 1;
+    let duration = num_samples_to_duration(frames, 1, 44100);
+    assert_eq!(duration.as_secs(), frames / 44100);
+    assert_eq!(
+        duration.subsec_nanos() as u64,
+        ((frames % 44100) * 1_000_000_000).div_ceil(44100)
+    );
 }
@@ -23,18 +23,54 @@ pub fn convert_num_samples(
 }
 
 /// Return the number of samples that happen within `duration` amount of time
-/// (truncates).
+/// (truncates). Saturates at `u64::MAX`.
 pub fn duration_to_num_samples(duration: Duration, channel_count: u16, sample_rate: u32) -> u64 {
-    convert_num_samples(
-        duration
-            .as_micros()
-            .try_into()
-            .expect("duration in microseconds is too large to fit into a u64"),
-        1,
-        1_000_000,
-        channel_count,
-        sample_rate,
-    )
+    (duration.as_nanos() * channel_count as u128 * sample_rate as u128 / 1_000_000_000)
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+/// Return the amount of time the whole frames within `num_samples` take to
+/// play. Partial frames are truncated.
+///
+/// The time is rounded up to the next nanosecond so that
+/// [duration_to_num_samples] of the result returns the whole frames.
+pub fn num_samples_to_duration(num_samples: u64, channel_count: u16, sample_rate: u32) -> Duration {
+    let frames = num_samples / channel_count.max(1) as u64;
+    let sample_rate = sample_rate.max(1) as u64;
+    let nanos = ((frames % sample_rate) * 1_000_000_000).div_ceil(sample_rate);
+    Duration::new(frames / sample_rate, nanos as u32)
+}
+
+/// The greatest common divisor of `a` and `b`.
+pub(crate) fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// A small, fast xorshift64 pseudo random number generator for noise and
+/// jitter. Not suitable for anything needing good randomness.
+pub(crate) struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    /// A generator starting from `seed`.
+    pub(crate) fn new(seed: u64) -> Xorshift64 {
+        // xorshift must not have a state of 0
+        Xorshift64 { state: seed.max(1) }
+    }
+
+    /// A random value in `-1.0..1.0`.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1_u64 << 52) as f64 - 1.0
+    }
 }
 
 #[cfg(test)]